@@ -9,7 +9,7 @@ use regex::Regex;
 use serde_json::json;
 
 use pact_models::generators::{Generator, GeneratorCategory, Generators};
-use pact_models::matchingrules::MatchingRules;
+use pact_models::matchingrules::{MatchingRule, MatchingRules, RuleLogic};
 use pact_models::bodies::OptionalBody;
 use pact_models::expression_parser::DataType;
 use pact_models::path_exp::DocPath;
@@ -175,6 +175,52 @@ pub trait HttpPartBuilder {
     self
   }
 
+  /// Specify a binary body with a content type. The `Content-Type` header will also be set
+  /// if it has not already been specified.
+  ///
+  /// ```
+  /// use pact_consumer::prelude::*;
+  /// use pact_consumer::builders::ResponseBuilder;
+  ///
+  /// ResponseBuilder::default().binary_body("application/octet-stream", vec![1, 2, 3]);
+  /// ```
+  fn binary_body<B: Into<Vec<u8>>>(&mut self, content_type: &str, body: B) -> &mut Self {
+    let body = body.into();
+    {
+      let (headers, _) = self.headers_and_matching_rules_mut();
+      if !headers.keys().any(|k| k.eq_ignore_ascii_case("content-type")) {
+        headers.insert("Content-Type".to_string(), vec![content_type.to_string()]);
+      }
+    }
+    {
+      let (body_ref, _) = self.body_and_matching_rules_mut();
+      *body_ref = OptionalBody::Present(body.into(), content_type.parse().ok(), None);
+    }
+    self
+  }
+
+  /// Specify a binary body with a content type, and add a content type matcher for it. The
+  /// content type matcher will match any body which has the same content type (determined by
+  /// inspecting the bytes of the body), regardless of the actual contents.
+  ///
+  /// ```
+  /// use pact_consumer::prelude::*;
+  /// use pact_consumer::builders::ResponseBuilder;
+  ///
+  /// ResponseBuilder::default().binary_body_matching_content_type("image/gif", vec![
+  ///   0x47, 0x49, 0x46, 0x38, 0x39, 0x61
+  /// ]);
+  /// ```
+  fn binary_body_matching_content_type<B: Into<Vec<u8>>>(&mut self, content_type: &str, body: B) -> &mut Self {
+    self.binary_body(content_type, body);
+    {
+      let (_, rules) = self.body_and_matching_rules_mut();
+      rules.add_category("body").add_rule(DocPath::root(),
+        MatchingRule::ContentType(content_type.to_string()), RuleLogic::And);
+    }
+    self
+  }
+
     /// Specify the body as `JsonPattern`, possibly including special matching
     /// rules.
     ///
@@ -196,6 +242,27 @@ pub trait HttpPartBuilder {
         }
         self
     }
+
+    /// Specify a generator for the body value at the given path (i.e. `$.createdAt`). The mock
+    /// server will replace the value with a generated one (like the current date) when returning
+    /// a response. Panics if the path is not a valid path expression.
+    ///
+    /// ```
+    /// use pact_consumer::prelude::*;
+    /// use pact_consumer::*;
+    /// use pact_consumer::builders::ResponseBuilder;
+    /// use pact_models::generators::Generator;
+    ///
+    /// ResponseBuilder::default()
+    ///     .json_body(json_pattern!({ "createdAt": "2021-01-01" }))
+    ///     .body_generator("$.createdAt", Generator::Date(None));
+    /// ```
+    fn body_generator(&mut self, path: &str, generator: Generator) -> &mut Self {
+      let path = DocPath::new(path)
+        .unwrap_or_else(|err| panic!("'{}' is not a valid path expression - {}", path, err));
+      self.generators().add_generator_with_subcategory(&GeneratorCategory::BODY, path, generator);
+      self
+    }
 }
 
 #[tokio::test]
@@ -49,6 +49,14 @@ impl InteractionBuilder {
         self
     }
 
+  /// Specify a "provider state" with parameters for this interaction. The parameters are passed
+  /// to the provider state handler when the pact is verified. This can be called multiple times
+  /// to add more than one provider state.
+  pub fn given_with_params<G: Into<String>>(&mut self, given: G, params: HashMap<String, Value>) -> &mut Self {
+    self.provider_states.push(ProviderState { name: given.into(), params });
+    self
+  }
+
   /// Adds a text comment to this interaction. This allows to specify just a bit more information
   /// about the interaction. It has no functional impact, but can be displayed in the broker HTML
   /// page, and potentially in the test output.
@@ -210,6 +210,34 @@ impl MessageInteractionBuilder {
     })
   }
 
+  /// Specify the message contents, with an optional content type.
+  ///
+  /// ```
+  /// use pact_consumer::builders::MessageInteractionBuilder;
+  ///
+  /// MessageInteractionBuilder::new("hello message", "core/interaction/message")
+  ///   .body("Hello", Some("text/plain".to_string()));
+  /// ```
+  pub fn body<B: Into<Bytes>>(&mut self, body: B, content_type: Option<String>) -> &mut Self {
+    self.message_contents.body = OptionalBody::Present(body.into(),
+      content_type.map(|ct| ct.as_str().into()), None);
+    self
+  }
+
+  /// Adds a metadata value to the message.
+  ///
+  /// ```
+  /// use pact_consumer::builders::MessageInteractionBuilder;
+  ///
+  /// MessageInteractionBuilder::new("hello message", "core/interaction/message")
+  ///   .metadata("destination", "greetings");
+  /// ```
+  pub fn metadata<K: Into<String>, V: Into<Value>>(&mut self, key: K, value: V) -> &mut Self {
+    self.message_contents.metadata.get_or_insert_with(HashMap::new)
+      .insert(key.into(), value.into());
+    self
+  }
+
   /// Specify the body as `JsonPattern`, possibly including special matching
   /// rules.
   ///
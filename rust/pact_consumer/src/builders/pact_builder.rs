@@ -1,10 +1,12 @@
 use std::future::Future;
+use std::env;
 use std::path::PathBuf;
 
 use async_trait::async_trait;
 use pact_models::{Consumer, Provider};
 use pact_models::interaction::Interaction;
-use pact_models::pact::Pact;
+use pact_models::pact::{Pact, write_pact};
+use pact_models::PactSpecification;
 use pact_models::sync_pact::RequestResponsePact;
 use pact_models::v4::async_message::AsynchronousMessage;
 use pact_models::v4::pact::V4Pact;
@@ -156,6 +158,10 @@ impl PactBuilder {
 
   /// Add a new Asynchronous message `Interaction` to the `Pact`. Needs to return a clone of the builder
   /// that is passed in.
+  ///
+  /// Panics:
+  /// Message interactions can only be added to V4 specification pacts, which can contain both
+  /// HTTP and message interactions. Use `PactBuilder::new_v4` to create the builder.
   pub async fn message_interaction<D, F, O>(&mut self, description: D, interaction_type: D, build_fn: F) -> &mut Self
     where
       D: Into<String>,
@@ -193,6 +199,24 @@ impl PactBuilder {
     self.push_interaction(&interaction.build())
   }
 
+  /// Writes the Pact out to the output directory (or the directory set with the `PACT_OUTPUT_DIR`
+  /// environment variable, defaulting to `target/pacts`). This is required for message pacts, as
+  /// there is no mock server to write them when the test completes. Returns the path of the
+  /// written file. Existing pact files are merged with unless `PACT_OVERWRITE` is set to `true`.
+  pub fn write_pact(&self) -> anyhow::Result<PathBuf> {
+    let output_dir = self.output_dir.clone().unwrap_or_else(|| {
+      PathBuf::from(env::var("PACT_OUTPUT_DIR").unwrap_or_else(|_| "target/pacts".to_owned()))
+    });
+    let path = output_dir.join(self.pact.default_file_name());
+    let overwrite = env::var("PACT_OVERWRITE").unwrap_or_else(|_| "false".to_owned()) == "true";
+    let specification = match self.pact.specification_version() {
+      PactSpecification::Unknown => PactSpecification::V3,
+      version => version
+    };
+    write_pact(self.pact.boxed(), path.as_path(), specification, overwrite)?;
+    Ok(path)
+  }
+
   /// Returns an iterator over the asynchronous messages in the Pact
   pub fn messages(&self) -> MessageIterator<AsynchronousMessage> {
     send_metrics(MetricEvent::ConsumerTestRun {
@@ -204,6 +228,13 @@ impl PactBuilder {
     asynchronous_messages_iter(self.pact.as_v4_pact().unwrap())
   }
 
+  /// Start a mock server running in a background thread, bound to the given port. Use this
+  /// when the client under test must be configured with a fixed URL. Returns an error if the
+  /// port is already in use.
+  pub fn start_mock_server_on_port(&self, port: u16) -> anyhow::Result<ValidatingMockServer> {
+    ValidatingMockServer::start_on_port(self.build(), self.output_dir.clone(), port)
+  }
+
   /// Returns an iterator over the synchronous req/res messages in the Pact
   pub fn synchronous_messages(&self) -> MessageIterator<SynchronousMessage> {
     send_metrics(MetricEvent::ConsumerTestRun {
@@ -19,7 +19,7 @@ use pact_models::content_types::ContentType;
 use pact_models::expression_parser::DataType;
 use pact_models::generators::{Generator, GeneratorCategory, Generators};
 use pact_models::http_parts::HttpPart;
-use pact_models::json_utils::body_from_json;
+use pact_models::json_utils::{body_from_json, json_to_string};
 use pact_models::matchingrules::{Category, MatchingRules};
 use pact_models::path_exp::DocPath;
 use pact_models::request::Request;
@@ -143,6 +143,46 @@ impl RequestBuilder {
         self
     }
 
+    /// Specify a query parameter using a JSON pattern, such as the ones created with the `like!`
+    /// macro. The example value is converted to a string and the matching rules are recorded
+    /// against the query parameter.
+    ///
+    /// ```
+    /// use pact_consumer::*;
+    /// use pact_consumer::builders::RequestBuilder;
+    ///
+    /// RequestBuilder::default()
+    ///     .query_param_matching("id", like!("100"))
+    ///     .query_param_matching("id", like!("200"));
+    /// ```
+    ///
+    /// To pass multiple parameters with the same name, call `query_param_matching` more
+    /// than once with the same `key`.
+    pub fn query_param_matching<K, P>(&mut self, key: K, value: P) -> &mut Self
+    where
+        K: Into<String>,
+        P: Pattern<Matches = Value>,
+    {
+        let key = key.into();
+
+        self.request
+            .query
+            .get_defaulting()
+            .entry(key.clone())
+            .or_insert_with(Default::default)
+            .push(json_to_string(&value.to_example()));
+
+        let mut path = DocPath::root();
+        path.push_field(key);
+
+        value.extract_matching_rules(
+            path,
+            self.request.matching_rules.add_category("query"),
+        );
+
+        self
+    }
+
     /// Build the specified `Request` object.
     pub fn build(&self) -> Request {
          self.request.as_v3_request()
@@ -340,6 +380,27 @@ async fn query_param_pattern() {
     assert_requests_do_not_match!(bad, pattern);
 }
 
+#[tokio::test]
+async fn query_param_like_pattern() {
+    let pattern = PactBuilder::new("C", "P")
+        .interaction("I", "", |mut i| {
+            i.request
+                .query_param_matching("id", like!("100"))
+                .query_param_matching("id", like!("200"));
+            futures::future::ready(i)
+        })
+        .await
+        .build();
+    let good = PactBuilder::new("C", "P")
+        .interaction("I", "", |mut i| {
+            i.request.query_param("id", "300").query_param("id", "400");
+            futures::future::ready(i)
+        })
+        .await
+        .build();
+    assert_requests_match!(good, pattern);
+}
+
 #[tokio::test]
 async fn query_param_with_underscore() {
     let pattern = PactBuilder::new("C", "P")
@@ -74,6 +74,24 @@ impl ResponseBuilder {
         self.status(404)
     }
 
+    /// Add a trailing header, which the mock server sends after the response body. Trailers are
+    /// only supported by the V4 format, and are only sent when the mock server has HTTP/2 enabled.
+    ///
+    /// ```
+    /// use pact_consumer::builders::ResponseBuilder;
+    /// use pact_consumer::prelude::*;
+    ///
+    /// let response = ResponseBuilder::default().trailer("grpc-status", "0").build_v4();
+    /// assert_eq!(response.trailers.unwrap()["grpc-status"], vec!["0".to_string()]);
+    /// ```
+    pub fn trailer<N: Into<String>, V: Into<String>>(&mut self, name: N, value: V) -> &mut Self {
+        self.response.trailers.get_or_insert_with(HashMap::new)
+            .entry(name.into())
+            .or_default()
+            .push(value.into());
+        self
+    }
+
     /// Build the specified `Response` object.
     pub fn build(&self) -> Response {
         self.response.as_v3_response()
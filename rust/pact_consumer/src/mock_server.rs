@@ -8,6 +8,7 @@ use std::{
 };
 use std::sync::{Arc, Mutex};
 
+use anyhow::anyhow;
 use async_trait::async_trait;
 use log::{debug, warn};
 use url::Url;
@@ -68,11 +69,27 @@ impl ValidatingMockServer {
   /// Panics:
   /// Will panic if the provided Pact can not be sent to the background thread.
   pub fn start(pact: Box<dyn Pact + Send + Sync>, output_dir: Option<PathBuf>) -> ValidatingMockServer {
+    ValidatingMockServer::start_on_port(pact, output_dir, 0)
+      .expect("Could not start the mock server")
+  }
+
+  /// Create a new mock server which handles requests as described in the
+  /// pact, and runs in a background thread. The mock server will be bound to the given port
+  /// (a port of zero will bind to a random free port). Returns an error if the mock server
+  /// could not be bound to the port (i.e. it is already in use).
+  ///
+  /// Panics:
+  /// Will panic if the provided Pact can not be sent to the background thread.
+  pub fn start_on_port(
+    pact: Box<dyn Pact + Send + Sync>,
+    output_dir: Option<PathBuf>,
+    port: u16
+  ) -> anyhow::Result<ValidatingMockServer> {
     debug!("Starting mock server from pact {:?}", pact);
     // Spawn new runtime in thread to prevent reactor execution context conflict
     let (pact_tx, pact_rx) = std::sync::mpsc::channel::<Box<dyn Pact + Send + Sync>>();
     pact_tx.send(pact).expect("INTERNAL ERROR: Could not pass pact into mock server thread");
-    let (mock_server, done_rx) = std::thread::spawn(|| {
+    let (mock_server, done_rx) = std::thread::spawn(move || {
       let runtime = tokio::runtime::Builder::new_current_thread()
         .enable_all()
         .build()
@@ -82,12 +99,10 @@ impl ValidatingMockServer {
         mock_server::MockServer::new(
           Uuid::new_v4().to_string(),
           pact_rx.recv().unwrap(),
-          ([0, 0, 0, 0], 0).into(),
+          ([0, 0, 0, 0], port).into(),
           MockServerConfig::default()
-        )
-          .await
-          .unwrap()
-      });
+        ).await
+      })?;
 
       // Start the actual thread the runtime will run on
       let (done_tx, done_rx) = std::sync::mpsc::channel::<()>();
@@ -103,10 +118,11 @@ impl ValidatingMockServer {
         })
         .expect("thread spawn");
 
-      (mock_server, done_rx)
+      Ok((mock_server, done_rx))
     })
     .join()
-    .unwrap();
+    .unwrap()
+    .map_err(|err: String| anyhow!("Could not start the mock server on port {} - {}", port, err))?;
 
     let (description, url_str) = {
       let ms = mock_server.lock().unwrap();
@@ -116,13 +132,13 @@ impl ValidatingMockServer {
       );
       (description, ms.url())
     };
-    ValidatingMockServer {
+    Ok(ValidatingMockServer {
       description,
       url: url_str.parse().expect("invalid mock server URL"),
       mock_server,
       done_rx,
       output_dir
-    }
+    })
   }
 
   /// Create a new mock server which handles requests as described in the
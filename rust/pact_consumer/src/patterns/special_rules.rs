@@ -93,7 +93,7 @@ fn like_into() {
 #[macro_export]
 macro_rules! like {
     ($($json_pattern:tt)+) => {
-        $crate::patterns::Like::new(json_pattern!($($json_pattern)+))
+        $crate::patterns::Like::<$crate::patterns::JsonPattern>::new(json_pattern!($($json_pattern)+))
     }
 }
 
@@ -126,3 +126,38 @@ async fn test_req_res_message_client() {
     expect!(processed.state).to(be_equal_to("VA"));
   }
 }
+
+// This is a test for writing out a pact that has both HTTP and message interactions
+#[tokio::test]
+async fn test_writing_a_pact_with_message_interactions() {
+  let _ = env_logger::builder().is_test(true).try_init();
+
+  let output_dir = std::env::temp_dir().join(format!("pact_consumer_messages_{}", std::process::id()));
+  let mut pact_builder = PactBuilder::new_v4("message-writing-consumer", "message-writing-provider");
+  pact_builder.output_dir(&output_dir);
+
+  pact_builder.interaction("a request for a greeting", "", |mut i| async move {
+    i.request.path("/greeting");
+    i
+  })
+    .await
+    .message_interaction("a greeting message", "core/interaction/message", |mut i| async move {
+      i.body("{\"greeting\":\"hello\"}", Some("application/json".to_string()));
+      i.metadata("destination", "greetings");
+      i
+    })
+    .await;
+
+  let path = pact_builder.write_pact().unwrap();
+  let json: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+  std::fs::remove_dir_all(&output_dir).unwrap_or(());
+
+  let interactions = json["interactions"].as_array().unwrap();
+  expect!(interactions.len()).to(be_equal_to(2));
+  let message = interactions.iter()
+    .find(|interaction| interaction["type"] == "Asynchronous/Messages")
+    .unwrap();
+  expect!(message["description"].clone()).to(be_equal_to(serde_json::json!("a greeting message")));
+  expect!(message["contents"]["content"].clone()).to(be_equal_to(serde_json::json!({ "greeting": "hello" })));
+  expect!(message["metadata"]["destination"].clone()).to(be_equal_to(serde_json::json!("greetings")));
+}
@@ -5,13 +5,15 @@ use std::{
 };
 use std::path::PathBuf;
 
+use chrono::{Local, NaiveDate};
 use expectest::prelude::*;
+use pact_models::generators::Generator;
 use pact_models::pact::ReadWritePact;
 use pact_models::sync_pact::RequestResponsePact;
 use rand::prelude::*;
 use reqwest::Client;
 
-use pact_consumer::{json_pattern, json_pattern_internal};
+use pact_consumer::{json_pattern, json_pattern_internal, like};
 use pact_consumer::prelude::*;
 
 /// This is supposed to be a doctest in mod, but it's breaking there, so
@@ -150,3 +152,176 @@ async fn duplicate_interactions() {
   let written_pact = RequestResponsePact::read_pact(path.as_path()).unwrap();
   expect!(written_pact.interactions.len()).to(be_equal_to(1));
 }
+
+#[tokio::test]
+async fn query_param_with_like_matcher() {
+  let _ = env_logger::builder().is_test(true).try_init();
+
+  let output_dir = output_dir("target/pact_dir_query_param");
+
+  {
+    let mock_service = PactBuilder::new("query consumer", "query provider")
+      .interaction("a request with a query parameter", "", |mut i| async move {
+        i.request
+          .path("/items")
+          .query_param_matching("id", like!("100"));
+        i.response.ok();
+        i.clone()
+      })
+      .await
+      .output_dir(&output_dir)
+      .start_mock_server();
+
+    let response = Client::new()
+      .get(mock_service.path("/items?id=200"))
+      .send()
+      .await
+      .expect("could not fetch URL");
+    expect!(response.status().as_u16()).to(be_equal_to(200));
+  }
+
+  let path = output_dir.join("query consumer-query provider.json");
+  let json: serde_json::Value = serde_json::from_str(&fs::read_to_string(path).unwrap()).unwrap();
+  let request = &json["interactions"][0]["request"];
+  expect!(request["query"]["id"][0].as_str()).to(be_some().value("100"));
+  expect!(request["matchingRules"]["query"]["$.id"]["matchers"][0]["match"].as_str())
+    .to(be_some().value("type"));
+}
+
+const PNG_BYTES: [u8; 70] = [
+  0x89, 0x50, 0x4e, 0x47, 0x0d, 0x0a, 0x1a, 0x0a, 0x00, 0x00, 0x00, 0x0d, 0x49, 0x48, 0x44, 0x52,
+  0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0x01, 0x08, 0x06, 0x00, 0x00, 0x00, 0x1f, 0x15, 0xc4,
+  0x89, 0x00, 0x00, 0x00, 0x0d, 0x49, 0x44, 0x41, 0x54, 0x78, 0xda, 0x63, 0x64, 0x60, 0xf8, 0x5f,
+  0x0f, 0x00, 0x02, 0x87, 0x01, 0x80, 0xeb, 0x47, 0xba, 0x92, 0x00, 0x00, 0x00, 0x00, 0x49, 0x45,
+  0x4e, 0x44, 0xae, 0x42, 0x60, 0x82
+];
+
+#[tokio::test]
+async fn binary_response_body_with_content_type_matcher() {
+  let _ = env_logger::builder().is_test(true).try_init();
+
+  let output_dir = output_dir("target/pact_dir_binary_body");
+
+  {
+    let mock_service = PactBuilder::new("image consumer", "image provider")
+      .interaction("a request for an image", "", |mut i| async move {
+        i.request.path("/image.png");
+        i.response
+          .ok()
+          .binary_body_matching_content_type("image/png", PNG_BYTES.to_vec());
+        i.clone()
+      })
+      .await
+      .output_dir(&output_dir)
+      .start_mock_server();
+
+    let response = reqwest::get(mock_service.path("/image.png")).await
+      .expect("could not fetch URL");
+    let content_type = response.headers().get("content-type")
+      .map(|ct| ct.to_str().unwrap().to_string());
+    expect!(content_type).to(be_some().value("image/png"));
+    let body = response.bytes().await.expect("could not read response body");
+    expect!(body.as_ref()).to(be_equal_to(&PNG_BYTES[..]));
+  }
+
+  let path = output_dir.join("image consumer-image provider.json");
+  let json: serde_json::Value = serde_json::from_str(&fs::read_to_string(path).unwrap()).unwrap();
+  let response = &json["interactions"][0]["response"];
+  expect!(response["matchingRules"]["body"]["$"]["matchers"][0].clone())
+    .to(be_equal_to(serde_json::json!({ "match": "contentType", "value": "image/png" })));
+}
+
+#[tokio::test]
+async fn provider_states_with_parameters() {
+  let _ = env_logger::builder().is_test(true).try_init();
+
+  let output_dir = output_dir("target/pact_dir_state_params");
+
+  let pact = PactBuilder::new("state consumer", "state provider")
+    .interaction("a request for a user", "", |mut i| async move {
+      i.given_with_params("a user exists", maplit::hashmap!{
+        "id".to_string() => serde_json::json!(100),
+        "name".to_string() => serde_json::json!("Fred")
+      });
+      i.given_with_params("the user has an order", maplit::hashmap!{
+        "orderId".to_string() => serde_json::json!("A1")
+      });
+      i.request.path("/users/100");
+      i.response.ok();
+      i.clone()
+    })
+    .await
+    .output_dir(&output_dir)
+    .write_pact()
+    .unwrap();
+
+  let json: serde_json::Value = serde_json::from_str(&fs::read_to_string(pact).unwrap()).unwrap();
+  expect!(json["interactions"][0]["providerStates"].clone()).to(be_equal_to(serde_json::json!([
+    { "name": "a user exists", "params": { "id": 100, "name": "Fred" } },
+    { "name": "the user has an order", "params": { "orderId": "A1" } }
+  ])));
+}
+
+#[tokio::test]
+async fn response_trailers_are_sent_to_the_client() {
+  let _ = env_logger::builder().is_test(true).try_init();
+
+  let mock_service = PactBuilder::new_v4("trailer consumer", "trailer provider")
+    .interaction("a request for a stream", "", |mut i| async move {
+      i.request.path("/stream");
+      i.response
+        .ok()
+        .body("streamed")
+        .trailer("grpc-status", "0");
+      i.clone()
+    })
+    .await
+    .start_mock_server();
+
+  let client = hyper::Client::builder().http2_only(true).build_http::<hyper::Body>();
+  let uri = mock_service.path("/stream").as_str().parse().unwrap();
+  let mut body = client.get(uri).await.expect("could not fetch URL").into_body();
+  let data = hyper::body::HttpBody::data(&mut body).await.unwrap().unwrap();
+  let trailers = hyper::body::HttpBody::trailers(&mut body).await.unwrap().unwrap();
+
+  expect!(data.as_ref()).to(be_equal_to("streamed".as_bytes()));
+  expect!(trailers.get("grpc-status").unwrap().to_str().unwrap()).to(be_equal_to("0"));
+}
+
+#[test]
+fn start_mock_server_on_a_fixed_port() {
+  let port = std::net::TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap().port();
+  let pact = PactBuilder::new("FixedPortConsumer", "FixedPortProvider");
+
+  let mock_server = pact.start_mock_server_on_port(port).unwrap();
+  expect!(mock_server.url().port()).to(be_some().value(port));
+
+  let result = pact.start_mock_server_on_port(port);
+  expect!(result.is_err()).to(be_true());
+  expect!(result.err().unwrap().to_string().starts_with(&format!("Could not start the mock server on port {}", port))).to(be_true());
+}
+
+#[tokio::test]
+async fn response_body_values_are_generated_by_the_mock_server() {
+  let _ = env_logger::builder().is_test(true).try_init();
+
+  let mock_service = PactBuilder::new("generator consumer", "generator provider")
+    .interaction("a request for an order", "", |mut i| async move {
+      i.request.path("/orders/1");
+      i.response
+        .ok()
+        .json_body(json_pattern!({ "id": 1, "createdAt": "2000-01-01" }))
+        .body_generator("$.createdAt", Generator::Date(None));
+      i.clone()
+    })
+    .await
+    .start_mock_server();
+
+  let response = reqwest::get(mock_service.path("/orders/1")).await
+    .expect("could not fetch URL");
+  let body: serde_json::Value = response.json().await.expect("could not read response body");
+  expect!(body["id"].clone()).to(be_equal_to(serde_json::json!(1)));
+  let created_at = NaiveDate::parse_from_str(body["createdAt"].as_str().unwrap(), "%Y-%m-%d")
+    .expect("createdAt is not a valid date");
+  expect!((Local::today().naive_local() - created_at).num_days().abs() <= 1).to(be_true());
+}
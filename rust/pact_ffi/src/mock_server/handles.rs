@@ -7,6 +7,7 @@ use std::path::PathBuf;
 use std::ptr::null_mut;
 use std::str::from_utf8;
 use std::sync::Mutex;
+use std::sync::atomic::{AtomicU16, Ordering};
 
 use anyhow::{anyhow, Context};
 use bytes::Bytes;
@@ -61,6 +62,10 @@ lazy_static! {
   static ref PACT_HANDLES: Mutex<HashMap<u16, RefCell<PactHandleInner>>> = Mutex::new(hashmap![]);
 }
 
+/// Next ID to allocate to a Pact handle. IDs are never reused, so a freed handle can't be
+/// resurrected by a newer Pact taking over its slot.
+static PACT_HANDLE_ID: AtomicU16 = AtomicU16::new(1);
+
 #[repr(transparent)]
 #[derive(Debug, Clone, Copy)]
 /// Wraps a Pact model struct
@@ -91,7 +96,7 @@ impl PactHandle {
   /// Creates a new handle to a Pact model
   pub fn new(consumer: &str, provider: &str) -> Self {
     let mut handles = PACT_HANDLES.lock().unwrap();
-    let id = (handles.len() + 1) as u16;
+    let id = PACT_HANDLE_ID.fetch_add(1, Ordering::SeqCst);
     let mut pact = V4Pact {
       consumer: Consumer { name: consumer.to_string() },
       provider: Provider { name: provider.to_string() },
@@ -108,10 +113,11 @@ impl PactHandle {
     }
   }
 
-  /// Invokes the closure with the inner Pact model
+  /// Invokes the closure with the inner Pact model. Returns `None` if the handle is not valid.
   pub(crate) fn with_pact<R>(&self, f: &dyn Fn(u16, &mut PactHandleInner) -> R) -> Option<R> {
+    let index = self.pact_ref.checked_sub(1)?;
     let mut handles = PACT_HANDLES.lock().unwrap();
-    handles.get_mut(&self.pact_ref).map(|inner| f(self.pact_ref - 1, &mut inner.borrow_mut()))
+    handles.get_mut(&self.pact_ref).map(|inner| f(index, &mut inner.borrow_mut()))
   }
 }
 
@@ -126,24 +132,26 @@ impl InteractionHandle {
     }
   }
 
-  /// Invokes the closure with the inner Pact model
+  /// Invokes the closure with the inner Pact model. Returns `None` if the handle is not valid.
   pub fn with_pact<R>(&self, f: &dyn Fn(u16, &mut PactHandleInner) -> R) -> Option<R> {
-    let mut handles = PACT_HANDLES.lock().unwrap();
     let index = (self.interaction_ref >> 16) as u16;
-    handles.get_mut(&index).map(|inner| f(index - 1, &mut inner.borrow_mut()))
+    let pact_index = index.checked_sub(1)?;
+    let mut handles = PACT_HANDLES.lock().unwrap();
+    handles.get_mut(&index).map(|inner| f(pact_index, &mut inner.borrow_mut()))
   }
 
-  /// Invokes the closure with the inner Interaction model
+  /// Invokes the closure with the inner Interaction model. Returns `None` if the handle is not
+  /// valid or does not refer to an interaction in the Pact.
   pub fn with_interaction<R>(&self, f: &dyn Fn(u16, bool, &mut dyn V4Interaction) -> R) -> Option<R> {
-    let mut handles = PACT_HANDLES.lock().unwrap();
     let index = (self.interaction_ref >> 16) as u16;
-    let interaction = (self.interaction_ref & 0x0000FFFF) as u16;
+    let interaction = ((self.interaction_ref & 0x0000FFFF) as u16).checked_sub(1)?;
+    let mut handles = PACT_HANDLES.lock().unwrap();
     handles.get_mut(&index).map(|inner| {
       let inner_mut = &mut *inner.borrow_mut();
       let interactions = &mut inner_mut.pact.interactions;
-      match interactions.get_mut((interaction - 1) as usize) {
+      match interactions.get_mut(interaction as usize) {
         Some(inner_i) => {
-          Some(f(interaction - 1, inner_mut.mock_server_started, inner_i.as_mut()))
+          Some(f(interaction, inner_mut.mock_server_started, inner_i.as_mut()))
         },
         None => None
       }
@@ -171,7 +179,7 @@ impl MessagePactHandle {
   /// Creates a new handle to a Pact model
   pub fn new(consumer: &str, provider: &str) -> Self {
     let mut handles = PACT_HANDLES.lock().unwrap();
-    let id = (handles.len() + 1) as u16;
+    let id = PACT_HANDLE_ID.fetch_add(1, Ordering::SeqCst);
     let mut pact = V4Pact {
       consumer: Consumer { name: consumer.to_string() },
       provider: Provider { name: provider.to_string() },
@@ -188,13 +196,14 @@ impl MessagePactHandle {
     }
   }
 
-  /// Invokes the closure with the inner model
+  /// Invokes the closure with the inner model. Returns `None` if the handle is not valid.
   pub fn with_pact<R>(&self, f: &dyn Fn(u16, &mut V4Pact, PactSpecification) -> R) -> Option<R> {
+    let index = self.pact_ref.checked_sub(1)?;
     let mut handles = PACT_HANDLES.lock().unwrap();
     handles.get_mut(&self.pact_ref).map(|inner| {
       let mut ref_mut = inner.borrow_mut();
       let specification = ref_mut.specification_version;
-      f(self.pact_ref - 1, &mut ref_mut.pact, specification)
+      f(index, &mut ref_mut.pact, specification)
     })
   }
 }
@@ -220,29 +229,31 @@ impl MessageHandle {
     }
   }
 
-  /// Invokes the closure with the inner model
+  /// Invokes the closure with the inner model. Returns `None` if the handle is not valid.
   pub fn with_pact<R>(&self, f: &dyn Fn(u16, &mut V4Pact, PactSpecification) -> R) -> Option<R> {
+    let index = (self.interaction_ref >> 16) as u16;
+    let pact_index = index.checked_sub(1)?;
     let mut handles = PACT_HANDLES.lock().unwrap();
-    let index = self.interaction_ref as u16;
     handles.get_mut(&index).map(|inner| {
       let mut ref_mut = inner.borrow_mut();
       let specification = ref_mut.specification_version;
-      f(index - 1, & mut ref_mut.pact, specification)
+      f(pact_index, & mut ref_mut.pact, specification)
     })
   }
 
-  /// Invokes the closure with the inner Interaction model
+  /// Invokes the closure with the inner Interaction model. Returns `None` if the handle is not
+  /// valid or does not refer to a message in the Pact.
   pub fn with_message<R>(&self, f: &dyn Fn(u16, &mut dyn V4Interaction, PactSpecification) -> R) -> Option<R> {
-    let mut handles = PACT_HANDLES.lock().unwrap();
     let index = (self.interaction_ref >> 16) as u16;
-    let interaction = self.interaction_ref as u16;
+    let interaction = (self.interaction_ref as u16).checked_sub(1)?;
+    let mut handles = PACT_HANDLES.lock().unwrap();
     handles.get_mut(&index).map(|inner| {
       let mut ref_mut = inner.borrow_mut();
       let specification = ref_mut.specification_version;
-      ref_mut.pact.interactions.get_mut((interaction - 1) as usize)
+      ref_mut.pact.interactions.get_mut(interaction as usize)
         .map(|inner_i| {
           if inner_i.is_message() {
-            Some(f(interaction - 1, inner_i.as_mut(), specification))
+            Some(f(interaction, inner_i.as_mut(), specification))
           } else {
             error!("Interaction {:#x} is not a message interaction, it is {}", self.interaction_ref, inner_i.type_of());
             None
@@ -1345,6 +1356,10 @@ mod tests {
     pactffi_free_pact_handle,
     pactffi_new_async_message,
     pactffi_new_interaction,
+    pactffi_upon_receiving,
+    InteractionHandle,
+    MessageHandle,
+    MessagePactHandle,
     PactHandle
   };
 
@@ -1381,4 +1396,72 @@ mod tests {
 
     pactffi_free_pact_handle(pact_handle);
   }
+
+  #[test]
+  fn freed_pact_handles_can_not_be_used() {
+    let pact_handle = PactHandle::new("TestC", "TestP");
+    let description = CString::new("first interaction").unwrap();
+    let i_handle = pactffi_new_interaction(pact_handle, description.as_ptr());
+
+    expect!(pactffi_free_pact_handle(pact_handle)).to(be_equal_to(0));
+    expect!(pactffi_free_pact_handle(pact_handle)).to(be_equal_to(1));
+
+    expect!(pact_handle.with_pact(&|_, _| ())).to(be_none());
+    expect!(i_handle.with_pact(&|_, _| ())).to(be_none());
+    expect!(i_handle.with_interaction(&|_, _, _| ())).to(be_none());
+    expect!(pactffi_upon_receiving(i_handle, description.as_ptr())).to(be_false());
+
+    let pact_handle2 = PactHandle::new("TestC2", "TestP2");
+    expect!(pact_handle2.pact_ref).to_not(be_equal_to(pact_handle.pact_ref));
+    expect!(pact_handle.with_pact(&|_, _| ())).to(be_none());
+    pactffi_free_pact_handle(pact_handle2);
+  }
+
+  #[test]
+  fn zero_handles_return_none() {
+    let pact_handle = PactHandle { pact_ref: 0 };
+    expect!(pact_handle.with_pact(&|_, _| ())).to(be_none());
+
+    let i_handle = InteractionHandle { interaction_ref: 0 };
+    expect!(i_handle.with_pact(&|_, _| ())).to(be_none());
+    expect!(i_handle.with_interaction(&|_, _, _| ())).to(be_none());
+
+    let message_pact_handle = MessagePactHandle { pact_ref: 0 };
+    expect!(message_pact_handle.with_pact(&|_, _, _| ())).to(be_none());
+
+    let m_handle = MessageHandle { interaction_ref: 0 };
+    expect!(m_handle.with_pact(&|_, _, _| ())).to(be_none());
+    expect!(m_handle.with_message(&|_, _, _| ())).to(be_none());
+  }
+
+  #[test]
+  fn zero_interaction_index_returns_none() {
+    let pact_handle = PactHandle::new("TestC", "TestP");
+    let description = CString::new("first interaction").unwrap();
+    pactffi_new_interaction(pact_handle, description.as_ptr());
+
+    let i_handle = InteractionHandle::new(pact_handle, 0);
+    expect!(i_handle.with_pact(&|_, _| ())).to(be_some());
+    expect!(i_handle.with_interaction(&|_, _, _| ())).to(be_none());
+
+    pactffi_free_pact_handle(pact_handle);
+  }
+
+  #[test]
+  fn out_of_range_handles_return_none() {
+    let pact_handle = PactHandle::new("TestC", "TestP");
+    let description = CString::new("first interaction").unwrap();
+    pactffi_new_interaction(pact_handle, description.as_ptr());
+
+    let i_handle = InteractionHandle::new(pact_handle, 100);
+    expect!(i_handle.with_interaction(&|_, _, _| ())).to(be_none());
+
+    let invalid_pact_handle = PactHandle { pact_ref: u16::MAX };
+    expect!(invalid_pact_handle.with_pact(&|_, _| ())).to(be_none());
+    let i_handle = InteractionHandle::new(invalid_pact_handle, 1);
+    expect!(i_handle.with_pact(&|_, _| ())).to(be_none());
+    expect!(i_handle.with_interaction(&|_, _, _| ())).to(be_none());
+
+    pactffi_free_pact_handle(pact_handle);
+  }
 }
@@ -225,7 +225,11 @@ pub extern fn pactffi_create_mock_server_for_pact(pact: handles::PactHandle, add
 
     if let Ok(Ok(addr)) = str::from_utf8(addr_c_str.to_bytes()).map(|s| s.parse::<std::net::SocketAddr>()) {
       pact.with_pact(&move |_, inner| {
-        let config = MockServerConfig { cors_preflight: true, pact_specification: inner.specification_version };
+        let config = MockServerConfig {
+          cors_preflight: true,
+          pact_specification: inner.specification_version,
+          .. MockServerConfig::default()
+        };
         let server_result = match &tls_config {
           Some(tls_config) => pact_mock_server::start_tls_mock_server_with_config(
             Uuid::new_v4().to_string(), inner.pact.boxed(), addr, tls_config, config),
@@ -69,7 +69,8 @@ impl VerifierHandle {
       protocol: scheme,
       host,
       port: if port == 0 { None } else { Some(port) },
-      path
+      path,
+      .. ProviderInfo::default()
     }
   }
 
@@ -171,7 +172,8 @@ impl VerifierHandle {
     self.state_change = Arc::new(HttpRequestProviderStateExecutor {
       state_change_url,
       state_change_teardown,
-      state_change_body
+      state_change_body,
+      .. HttpRequestProviderStateExecutor::default()
     })
   }
 
@@ -193,6 +195,7 @@ impl VerifierHandle {
       provider_tags,
       disable_ssl_verification,
       request_timeout,
+      state_change_timeout: request_timeout,
       .. VerificationOptions::default()
     }
   }
@@ -101,6 +101,7 @@ fn consumer_tags_to_selectors(tags: Vec<&str>) -> Vec<pact_verifier::ConsumerVer
       main_branch: None,
       environment: None,
       matching_branch: None,
+      fallback_branch: None,
     }
   }).collect()
 }
@@ -215,9 +216,12 @@ async fn handle_matches(matches: &clap::ArgMatches<'_>) -> Result<(), i32> {
     let provider_state_executor = Arc::new(HttpRequestProviderStateExecutor {
       state_change_url: matches.value_of("state-change-url").map(|s| s.to_string()),
       state_change_body: !matches.is_present("state-change-as-query"),
-      state_change_teardown: matches.is_present("state-change-teardown")
+      state_change_teardown: matches.is_present("state-change-teardown"),
+      .. HttpRequestProviderStateExecutor::default()
     });
 
+    let request_timeout = matches.value_of("request-timeout")
+      .map(|t| t.parse::<u64>().unwrap_or(5000)).unwrap_or(5000);
     let options = VerificationOptions {
       publish: matches.is_present("publish"),
       provider_version: matches.value_of("provider-version").map(|v| v.to_string()),
@@ -227,8 +231,9 @@ async fn handle_matches(matches: &clap::ArgMatches<'_>) -> Result<(), i32> {
         .map_or_else(Vec::new, |tags| tags.map(|tag| tag.to_string()).collect()),
       provider_branch: matches.value_of("provider-branch").map(|v| v.to_string()),
       disable_ssl_verification: matches.is_present("disable-ssl-verification"),
-      request_timeout: matches.value_of("request-timeout")
-        .map(|t| t.parse::<u64>().unwrap_or(5000)).unwrap_or(5000)
+      request_timeout,
+      state_change_timeout: request_timeout,
+      .. VerificationOptions::default()
     };
 
     for s in &source {
@@ -61,6 +61,7 @@ pub fn match_octet_stream(
         expected: Some(expected),
         actual: Some(actual),
         mismatch: format!("No matcher found for category 'body' and path '{}'", path),
+        rule: None,
       })
     } else {
       let results = matchers.rules.iter().map(|rule|
@@ -73,6 +74,7 @@ pub fn match_octet_stream(
               expected: Some(expected.clone()),
               actual: Some(actual.clone()),
               mismatch: err.to_string(),
+              rule: None,
             })
           }
         },
@@ -85,6 +87,7 @@ pub fn match_octet_stream(
                   expected: Some(expected.clone()),
                   actual: Some(actual.clone()),
                   mismatch: err.to_string(),
+                  rule: None,
                 })
               }
             }
@@ -99,6 +102,7 @@ pub fn match_octet_stream(
       actual: Some(actual.clone()),
       mismatch: format!("Expected binary data of {} bytes but received {} bytes",
                         expected.len(), actual.len()),
+      rule: None,
     });
   }
 
@@ -153,7 +157,8 @@ pub fn match_mime_multipart(
         path: "$".into(),
         expected: expected.body().value(),
         actual: actual.body().value(),
-        mismatch: format!("Failed to parse the expected body as a MIME multipart body: '{}'", e)
+        mismatch: format!("Failed to parse the expected body as a MIME multipart body: '{}'", e),
+        rule: None
       });
     }
     if let Err(e) = actual_parts {
@@ -161,7 +166,8 @@ pub fn match_mime_multipart(
         path: "$".into(),
         expected: expected.body().value(),
         actual: actual.body().value(),
-        mismatch: format!("Failed to parse the actual body as a MIME multipart body: '{}'", e)
+        mismatch: format!("Failed to parse the actual body as a MIME multipart body: '{}'", e),
+        rule: None
       });
     }
   } else {
@@ -182,7 +188,8 @@ pub fn match_mime_multipart(
             path: "$".into(),
             expected: Some(Bytes::from(name.clone())),
             actual: None,
-            mismatch: format!("Expected a MIME part '{}' but was missing", name)});
+            mismatch: format!("Expected a MIME part '{}' but was missing", name),
+            rule: None});
         }
       }
     }
@@ -214,7 +221,7 @@ fn match_mime_part(
         Mismatch::BodyMismatch { path: "$".into(),
           expected: Some(Bytes::from(key.clone())),
           actual: None,
-          mismatch: format!("Expected a MIME field '{}' but was file", key)}
+          mismatch: format!("Expected a MIME field '{}' but was file", key), rule: None}
       ])
     },
     (MimePart::File(_), MimePart::Field(_)) => {
@@ -222,7 +229,7 @@ fn match_mime_part(
         Mismatch::BodyMismatch { path: "$".into(),
           expected: Some(Bytes::from(key.clone())),
           actual: None,
-          mismatch: format!("Expected a MIME file '{}' but was field", key)}
+          mismatch: format!("Expected a MIME file '{}' but was field", key), rule: None}
       ])
     }
   }
@@ -250,7 +257,8 @@ fn match_field(
         path: path.to_string(),
         expected: Some(Bytes::from(expected.as_bytes().to_vec())),
         actual: Some(Bytes::from(actual.as_bytes().to_vec())),
-        mismatch: message.clone()
+        mismatch: message.clone(),
+        rule: None
       }
     }).collect()
   })
@@ -324,7 +332,8 @@ fn match_file(
         path: path.to_string(),
         expected: None,
         actual: None,
-        mismatch: format!("MIME part '{}': {}", key, err)
+        mismatch: format!("MIME part '{}': {}", key, err),
+        rule: None
       }).collect()
     })
   } else if expected.content_type == actual.content_type {
@@ -333,7 +342,8 @@ fn match_file(
         path: path.to_string(),
         expected: None,
         actual: None,
-        mismatch: format!("MIME part '{}': {}", key, err)
+        mismatch: format!("MIME part '{}': {}", key, err),
+        rule: None
       }]
     )
   } else {
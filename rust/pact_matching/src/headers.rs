@@ -9,7 +9,7 @@ use pact_models::headers::PARAMETERISED_HEADERS;
 use pact_models::matchingrules::MatchingRule;
 use pact_models::path_exp::DocPath;
 
-use crate::{matchers, MatchingContext, Mismatch};
+use crate::{HeaderMatchConfig, matchers, MatchingContext, Mismatch};
 use crate::matchers::Matches;
 
 fn strip_whitespace<'a, T: FromIterator<&'a str>>(val: &'a str, split_by: &'a str) -> T {
@@ -62,8 +62,12 @@ pub(crate) fn match_header_value(
   context: &dyn MatchingContext
 ) -> Result<(), Vec<Mismatch>> {
   let path = DocPath::root().join(key);
-  let expected: String = strip_whitespace(expected, ",");
-  let actual: String = strip_whitespace(actual, ",");
+  let (expected, actual): (String, String) = if context.is_single_value_header(key) {
+    // These headers can have commas in their values, so must not be split
+    (expected.trim().to_string(), actual.trim().to_string())
+  } else {
+    (strip_whitespace(expected, ","), strip_whitespace(actual, ","))
+  };
 
   let matcher_result = if context.matcher_is_defined(&path) {
     matchers::match_values(&path, &context.select_best_matcher(&path), &expected, &actual)
@@ -115,15 +119,40 @@ fn match_header_maps(
   result
 }
 
-/// Matches the actual headers to the expected ones.
+fn unexpected_headers(
+  expected: &HashMap<String, Vec<String>>,
+  actual: &HashMap<String, Vec<String>>,
+  config: &HeaderMatchConfig
+) -> HashMap<String, Vec<Mismatch>> {
+  match config {
+    HeaderMatchConfig::AllowUnexpectedHeaders => hashmap!{},
+    HeaderMatchConfig::NoUnexpectedHeaders(allowed) => actual.iter()
+      .filter(|(key, _)| find_entry(expected, key).is_none())
+      .filter(|(key, _)| !allowed.iter().any(|header| header.to_lowercase() == key.to_lowercase()))
+      .map(|(key, value)| {
+        (key.clone(), vec![Mismatch::HeaderMismatch { key: key.clone(),
+          expected: "".to_string(),
+          actual: format!("{:?}", value.join(", ")),
+          mismatch: format!("Unexpected header '{}' received", key) }])
+      }).collect()
+  }
+}
+
+/// Matches the actual headers to the expected ones. The header match config of the context controls
+/// if headers in the actual headers that were not expected cause a mismatch.
 pub fn match_headers(
   expected: Option<HashMap<String, Vec<String>>>,
   actual: Option<HashMap<String, Vec<String>>>,
   context: &(dyn MatchingContext + Send + Sync)
 ) -> HashMap<String, Vec<Mismatch>> {
+  let config = &context.header_match_config();
   match (actual, expected) {
-    (Some(aqm), Some(eqm)) => match_header_maps(eqm, aqm, context),
-    (Some(_), None) => hashmap!{},
+    (Some(aqm), Some(eqm)) => {
+      let mut result = unexpected_headers(&eqm, &aqm, config);
+      result.extend(match_header_maps(eqm, aqm, context));
+      result
+    },
+    (Some(aqm), None) => unexpected_headers(&hashmap!{}, &aqm, config),
     (None, Some(eqm)) => eqm.iter().map(|(key, value)| {
       (key.clone(), vec![Mismatch::HeaderMismatch { key: key.clone(),
         expected: format!("{:?}", value.join(", ")),
@@ -142,7 +171,7 @@ mod tests {
   use pact_models::matchingrules;
   use pact_models::matchingrules::MatchingRule;
 
-  use crate::{CoreMatchingContext, DiffConfig, Mismatch};
+  use crate::{CoreMatchingContext, DiffConfig, HeaderMatchConfig, Mismatch};
   use crate::headers::{match_header_value, match_headers};
 
   #[test]
@@ -191,6 +220,49 @@ mod tests {
     expect!(mismatches).to(be_ok());
   }
 
+  #[test]
+  fn set_cookie_header_values_are_not_split_on_commas() {
+    let context = CoreMatchingContext::new(
+      DiffConfig::AllowUnexpectedKeys,
+      &matchingrules! {
+        "header" => {
+          "Set-Cookie" => [ MatchingRule::Regex(s!("^id=\\w+; Expires=\\w{3}, \\d{2} \\w{3} \\d{4} .*")) ]
+        }
+      }.rules_for_category("header").unwrap_or_default(), &hashmap!{}
+    );
+    let mismatches = match_header_value("Set-Cookie", "id=a3fWa; Expires=Wed, 21 Oct 2015 07:28:00 GMT",
+      "id=b4gXb; Expires=Thu, 22 Oct 2015 07:28:00 GMT", &context);
+    expect!(mismatches).to(be_ok());
+
+    let mismatches = match_header_value("Set-Cookie", "id=a3fWa; Expires=Wed, 21 Oct 2015 07:28:00 GMT",
+      "id=a3fWa; Expires=Wed,21 Oct 2015 07:28:00 GMT", &CoreMatchingContext::default());
+    expect!(mismatches).to(be_err());
+  }
+
+  #[test]
+  fn single_value_headers_can_be_configured() {
+    let context = CoreMatchingContext {
+      single_value_headers: vec![s!("x-quoted")],
+      .. CoreMatchingContext::default()
+    };
+    let mismatches = match_header_value("X-Quoted", "\"a, b\", \"c\"", "\"a,b\",\"c\"", &context);
+    expect!(mismatches).to(be_err());
+    let mismatches = match_header_value("X-Quoted", "\"a, b\", \"c\"", "\"a,b\",\"c\"",
+      &CoreMatchingContext::default());
+    expect!(mismatches).to(be_ok());
+
+    let mismatches = match_header_value("Set-Cookie", "id=a3fWa; Expires=Wed, 21 Oct 2015 07:28:00 GMT",
+      "id=a3fWa; Expires=Wed,21 Oct 2015 07:28:00 GMT", &context);
+    expect!(mismatches).to(be_ok());
+  }
+
+  #[test]
+  fn multi_value_accept_header_values_are_split_on_commas() {
+    let mismatches = match_header_value("Accept", "text/html, application/xhtml+xml",
+      "text/html,application/xhtml+xml", &CoreMatchingContext::default());
+    expect!(mismatches).to(be_ok());
+  }
+
   #[test]
   fn content_type_header_matches_when_headers_are_equal() {
     let mismatches = match_header_value("CONTENT-TYPE", "application/json;charset=UTF-8",
@@ -345,4 +417,58 @@ mod tests {
       mismatch: s!(""),
     } ]));
   }
+
+  #[test]
+  fn unexpected_headers_are_ignored_by_default() {
+    let expected = Some(hashmap! { "content-type".to_string() => vec!["application/json".to_string()] });
+    let actual = Some(hashmap! {
+      "Content-Type".to_string() => vec!["application/json".to_string()],
+      "X-Extra".to_string() => vec!["extra".to_string()]
+    });
+    let result = match_headers(expected, actual, &CoreMatchingContext::default());
+    expect!(result.values().flatten()).to(be_empty());
+  }
+
+  #[test]
+  fn unexpected_headers_are_mismatches_in_strict_mode() {
+    let expected = Some(hashmap! { "content-type".to_string() => vec!["application/json".to_string()] });
+    let actual = Some(hashmap! {
+      "Content-Type".to_string() => vec!["application/json".to_string()],
+      "Date".to_string() => vec!["Wed, 21 Oct 2015 07:28:00 GMT".to_string()],
+      "Content-Length".to_string() => vec!["100".to_string()],
+      "X-Extra".to_string() => vec!["extra".to_string()]
+    });
+    let context = CoreMatchingContext {
+      header_match_config: HeaderMatchConfig::strict(),
+      .. CoreMatchingContext::default()
+    };
+    let result = match_headers(expected, actual, &context);
+    let mismatches: Vec<Mismatch> = result.values().flatten().cloned().collect();
+    expect!(mismatches).to(be_equal_to(vec![
+      Mismatch::HeaderMismatch {
+        key: s!("X-Extra"),
+        expected: s!(""),
+        actual: s!("\"extra\""),
+        mismatch: s!("Unexpected header 'X-Extra' received")
+      }
+    ]));
+  }
+
+  #[test]
+  fn strict_mode_uses_the_provided_allowlist() {
+    let actual = Some(hashmap! { "X-Extra".to_string() => vec!["extra".to_string()] });
+    let context = CoreMatchingContext {
+      header_match_config: HeaderMatchConfig::NoUnexpectedHeaders(vec![s!("x-extra")]),
+      .. CoreMatchingContext::default()
+    };
+    let result = match_headers(None, actual.clone(), &context);
+    expect!(result.values().flatten()).to(be_empty());
+
+    let context = CoreMatchingContext {
+      header_match_config: HeaderMatchConfig::NoUnexpectedHeaders(vec![]),
+      .. CoreMatchingContext::default()
+    };
+    let result = match_headers(None, actual, &context);
+    expect!(result.values().flatten().count()).to(be_equal_to(1));
+  }
 }
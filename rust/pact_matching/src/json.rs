@@ -4,7 +4,9 @@ use std::str::FromStr;
 
 use ansi_term::Colour::*;
 use anyhow::anyhow;
+use chrono::Utc;
 use difference::*;
+use itertools::Itertools;
 use log::*;
 use onig::Regex;
 use semver::Version;
@@ -12,12 +14,14 @@ use serde_json::{json, Value};
 
 use pact_models::http_parts::HttpPart;
 use pact_models::json_utils::json_to_string;
-use pact_models::matchingrules::MatchingRule;
-use pact_models::path_exp::DocPath;
+use pact_models::matchingrules::{MatchingRule, RuleLogic};
+use pact_models::matchingrules::expressions::MatchingRuleDefinition;
+use pact_models::path_exp::{DocPath, PathToken};
 use pact_models::time_utils::validate_datetime;
 
 use crate::{DiffConfig, MatchingContext, merge_result};
 use crate::binary_utils::{convert_data, match_content_type};
+use crate::json_schema::validate_json_schema;
 use crate::matchers::*;
 use crate::matchingrules::{compare_lists_with_matchingrule, compare_maps_with_matchingrule};
 
@@ -211,6 +215,79 @@ impl Matches<&Value> for Value {
         }
         _ => Err(anyhow!("Expected something that matches a semantic version, but got '{}'", actual))
       }
+      MatchingRule::Duration => match actual {
+        Value::String(s) => match_duration(s),
+        _ => Err(anyhow!("Expected something that matches an ISO-8601 duration, but got '{}'", actual))
+      }
+      MatchingRule::Email => match actual {
+        Value::String(s) => match_email(s),
+        _ => Err(anyhow!("Expected something that matches an email address, but got '{}'", actual))
+      }
+      MatchingRule::NamedRegex(name) => match_named_regex(name, &json_to_string(actual)),
+      // When cascaded to child values, they are compared by type
+      MatchingRule::AnyType(_) if cascaded => self.matches_with(actual, &MatchingRule::Type, cascaded),
+      MatchingRule::AnyType(types) => match_any_type(types, actual),
+      MatchingRule::Base64 => match actual {
+        Value::String(s) => match_base64(s),
+        _ => Err(anyhow!("Expected a base64 encoded string, but got '{}'", actual))
+      }
+      MatchingRule::OneOf(values) => if values.contains(actual) {
+        Ok(())
+      } else {
+        Err(anyhow!("Expected {} to be one of {}", actual, Value::Array(values.clone())))
+      }
+      MatchingRule::Latitude | MatchingRule::Longitude => match actual {
+        Value::Number(n) => match_coordinate(matcher, n.as_f64().unwrap_or_default()),
+        Value::String(s) => match s.parse::<f64>() {
+          Ok(value) => match_coordinate(matcher, value),
+          Err(_) => Err(anyhow!("Expected '{}' to be a valid {}", s, matcher.name()))
+        }
+        _ => Err(anyhow!("Expected a {}, but got '{}'", matcher.name(), actual))
+      }
+      MatchingRule::NumberRange { min, max, inclusive } => match actual {
+        Value::Number(n) => match_number_range(*min, *max, *inclusive, n.as_f64().unwrap_or_default()),
+        Value::String(s) => match s.parse::<f64>() {
+          Ok(value) => match_number_range(*min, *max, *inclusive, value),
+          Err(_) => Err(anyhow!("Expected '{}' to be a number", s))
+        }
+        _ => Err(anyhow!("Expected a number, but got '{}'", actual))
+      }
+      MatchingRule::ValidJson => match actual {
+        Value::String(s) => match_valid_json(s),
+        _ => Err(anyhow!("Expected a string containing valid JSON, but got '{}'", actual))
+      }
+      MatchingRule::Iso8601Timestamp => match actual {
+        Value::String(s) => match_iso8601_timestamp(s),
+        _ => Err(anyhow!("Expected a string containing an RFC-3339 timestamp, but got '{}'", actual))
+      }
+      MatchingRule::RecentTimestamp { within_seconds } => match actual {
+        Value::String(s) => match_recent_timestamp(*within_seconds, s, Utc::now()),
+        _ => Err(anyhow!("Expected a string containing an RFC-3339 timestamp, but got '{}'", actual))
+      }
+      // The value is compared to the referenced value once the whole document is available
+      MatchingRule::EqualsPath(_) => Ok(()),
+      MatchingRule::Hexadecimal { length } => match actual {
+        Value::String(s) => match_hexadecimal(*length, s),
+        _ => Err(anyhow!("Expected a string of hexadecimal digits, but got '{}'", actual))
+      }
+      MatchingRule::IpAddress(version) => match actual {
+        Value::String(s) => match_ip_address(*version, s),
+        _ => Err(anyhow!("Expected a string containing an IP address, but got '{}'", actual))
+      }
+      MatchingRule::Luhn => match actual {
+        Value::String(s) => match_luhn(s),
+        Value::Number(n) => match_luhn(&n.to_string()),
+        _ => Err(anyhow!("Expected a number or string that passes the Luhn check, but got '{}'", actual))
+      }
+      MatchingRule::JsonSchema(schema) => {
+        let violations = validate_json_schema(&DocPath::root(), schema, actual);
+        if violations.is_empty() {
+          Ok(())
+        } else {
+          Err(anyhow!("Expected '{}' to conform to the JSON Schema - {}", actual,
+            violations.iter().map(|v| format!("{}: {}", v.path, v.message)).join(", ")))
+        }
+      }
       _ => Ok(())
     };
     debug!("JSON -> JSON: Comparing '{}' to '{}' using {:?} -> {:?}", self, actual, matcher, result);
@@ -218,6 +295,29 @@ impl Matches<&Value> for Value {
   }
 }
 
+fn match_any_type(types: &[String], actual: &Value) -> anyhow::Result<()> {
+  let mut matched = false;
+  for type_name in types {
+    let is_type = match type_name.as_str() {
+      "string" => actual.is_string(),
+      "number" => actual.is_number(),
+      "integer" => actual.is_i64() || actual.is_u64(),
+      "decimal" => actual.is_f64(),
+      "boolean" => actual.is_boolean(),
+      "null" => actual.is_null(),
+      "array" => actual.is_array(),
+      "object" => actual.is_object(),
+      _ => return Err(anyhow!("'{}' is not a recognised type name", type_name))
+    };
+    matched = matched || is_type;
+  }
+  if matched {
+    Ok(())
+  } else {
+    Err(anyhow!("Expected '{}' to be one of the types [{}]", json_to_string(actual), types.join(", ")))
+  }
+}
+
 /// Matches the expected JSON to the actual, and populates the mismatches vector with any differences
 pub fn match_json(expected: &dyn HttpPart, actual: &dyn HttpPart, context: &dyn MatchingContext) -> Result<(), Vec<super::Mismatch>> {
   let expected_json = serde_json::from_slice(&*expected.body().value().unwrap_or_default());
@@ -231,6 +331,7 @@ pub fn match_json(expected: &dyn HttpPart, actual: &dyn HttpPart, context: &dyn
         expected: expected.body().value(),
         actual: actual.body().value(),
         mismatch: format!("Failed to parse the expected body: '{}'", e),
+        rule: None,
       });
     }
     if let Err(e) = actual_json {
@@ -239,11 +340,116 @@ pub fn match_json(expected: &dyn HttpPart, actual: &dyn HttpPart, context: &dyn
         expected: expected.body().value(),
         actual: actual.body().value(),
         mismatch: format!("Failed to parse the actual body: '{}'", e),
+        rule: None,
       });
     }
     Err(mismatches.clone())
   } else {
-    compare_json(&DocPath::root(), &expected_json.unwrap(), &actual_json.unwrap(), context)
+    let actual_json = actual_json.unwrap();
+    let result = compare_json(&DocPath::root(), &expected_json.unwrap(), &actual_json, context);
+    if equals_path_matcher_defined(context) {
+      let mismatches = compare_equal_paths(&DocPath::root(), &actual_json, &actual_json, context);
+      merge_result(result, if mismatches.is_empty() { Ok(()) } else { Err(mismatches) })
+    } else {
+      result
+    }
+  }
+}
+
+/// Matches the expected NDJSON (newline-delimited JSON) body to the actual one. Each line is parsed
+/// as a separate JSON document, and the documents are then compared as a JSON array (so array
+/// matching rules on `$` apply to the lines). Empty lines are ignored.
+pub fn match_ndjson(expected: &dyn HttpPart, actual: &dyn HttpPart, context: &dyn MatchingContext) -> Result<(), Vec<super::Mismatch>> {
+  let expected_json = parse_ndjson(&expected.body().value().unwrap_or_default());
+  let actual_json = parse_ndjson(&actual.body().value().unwrap_or_default());
+
+  match (expected_json, actual_json) {
+    (Ok(expected_json), Ok(actual_json)) => compare_json(&DocPath::root(), &Value::Array(expected_json),
+      &Value::Array(actual_json), context),
+    (expected_json, actual_json) => {
+      let mut mismatches = vec![];
+      if let Err(e) = expected_json {
+        mismatches.push(Mismatch::BodyMismatch {
+          path: "$".to_string(),
+          expected: expected.body().value(),
+          actual: actual.body().value(),
+          mismatch: format!("Failed to parse the expected body: '{}'", e),
+          rule: None,
+        });
+      }
+      if let Err(e) = actual_json {
+        mismatches.push(Mismatch::BodyMismatch {
+          path: "$".to_string(),
+          expected: expected.body().value(),
+          actual: actual.body().value(),
+          mismatch: format!("Failed to parse the actual body: '{}'", e),
+          rule: None,
+        });
+      }
+      Err(mismatches)
+    }
+  }
+}
+
+fn parse_ndjson(body: &[u8]) -> anyhow::Result<Vec<Value>> {
+  body.split(|b| *b == b'\n')
+    .enumerate()
+    .filter(|(_, line)| !line.iter().all(u8::is_ascii_whitespace))
+    .map(|(index, line)| serde_json::from_slice(line)
+      .map_err(|err| anyhow!("line {} is not valid JSON - {}", index + 1, err)))
+    .collect()
+}
+
+fn equals_path_matcher_defined(context: &dyn MatchingContext) -> bool {
+  context.matchers().rules.values()
+    .any(|rules| rules.rules.iter().any(|rule| matches!(rule, MatchingRule::EqualsPath(_))))
+}
+
+/// Walks the actual document, and compares any value with an `EqualsPath` matcher to the value at
+/// the referenced path in the document
+fn compare_equal_paths(path: &DocPath, value: &Value, root: &Value, context: &dyn MatchingContext) -> Vec<Mismatch> {
+  let mut mismatches = vec![];
+  if context.matcher_is_defined(path) {
+    let rules = context.select_best_matcher(path);
+    for rule in &rules.rules {
+      if let MatchingRule::EqualsPath(reference) = rule {
+        if let Err(message) = match_equals_path(reference, value, root) {
+          mismatches.push(Mismatch::BodyMismatch {
+            path: path.to_string(),
+            expected: None,
+            actual: Some(json_to_string(value).into()),
+            mismatch: message,
+            rule: Some(matching_rule_label(rule))
+          });
+        }
+      }
+    }
+  }
+
+  match value {
+    Value::Object(map) => for (key, item) in map {
+      mismatches.extend(compare_equal_paths(&path.join(key), item, root, context));
+    },
+    Value::Array(items) => for (index, item) in items.iter().enumerate() {
+      mismatches.extend(compare_equal_paths(&path.join(index.to_string()), item, root, context));
+    },
+    _ => {}
+  }
+  mismatches
+}
+
+fn match_equals_path(reference: &str, value: &Value, root: &Value) -> Result<(), String> {
+  let reference_path = DocPath::new(reference)
+    .map_err(|err| format!("Invalid path '{}' for the EqualsPath matcher - {}", reference, err))?;
+  match resolve_key_path(root, &reference_path) {
+    Some(referenced_value) => if referenced_value == value {
+      Ok(())
+    } else {
+      Err(format!("Expected '{}' to be equal to the value at '{}' ('{}')", json_to_string(value),
+        reference, json_to_string(referenced_value)))
+    },
+    None => Err(format!("Expected a value at '{}' to compare '{}' to, but the path does not exist in the document",
+      reference, json_to_string(value)))
   }
 }
 
@@ -306,6 +512,9 @@ pub fn display_diff(expected: &str, actual: &str, path: &str, indent: &str) -> S
 /// Compares the actual JSON to the expected one
 pub fn compare_json(path: &DocPath, expected: &Value, actual: &Value, context: &dyn MatchingContext) -> Result<(), Vec<Mismatch>> {
   debug!("compare: Comparing path {}", path);
+  if let Some(schema) = json_schema_for_path(path, context) {
+    return compare_with_json_schema(path, &schema, actual);
+  }
   match (expected, actual) {
     (&Value::Object(ref emap), &Value::Object(ref amap)) => compare_maps(path, emap, amap, context),
     (&Value::Object(_), _) => {
@@ -315,6 +524,7 @@ pub fn compare_json(path: &DocPath, expected: &Value, actual: &Value, context: &
         actual: Some(json_to_string(actual).into()),
         mismatch: format!("Type mismatch: Expected {} {} but received {} {}",
                           type_of(expected), expected, type_of(actual), actual),
+        rule: None,
       } ])
     }
     (&Value::Array(ref elist), &Value::Array(ref alist)) => compare_lists(path, elist, alist, context),
@@ -325,6 +535,7 @@ pub fn compare_json(path: &DocPath, expected: &Value, actual: &Value, context: &
         actual: Some(json_to_string(actual).into()),
         mismatch: format!("Type mismatch: Expected {} {} but received {} {}",
                           type_of(expected), json_to_string(expected), type_of(actual), json_to_string(actual)),
+        rule: None,
       } ])
     }
     (_, _) => compare_values(path, expected, actual, context)
@@ -346,6 +557,7 @@ fn compare_maps(
       expected: Some(json_to_string(&json!(expected)).into()),
       actual: Some(json_to_string(&json!(actual)).into()),
       mismatch: format!("Expected an empty Map but received {}", json_to_string(&json!(actual))),
+      rule: None,
     } ])
   } else {
     let mut result = Ok(());
@@ -356,6 +568,11 @@ fn compare_maps(
       debug!("compare_maps: Matcher is defined for path {}", path);
       let rule_list = context.select_best_matcher(path);
       for matcher in rule_list.rules {
+        let values_context = match &matcher {
+          MatchingRule::EachValue(definition) if !rule_list.cascaded => Some(each_value_context(path, definition, context)),
+          _ => None
+        };
+        let context = values_context.as_deref().unwrap_or(context);
         result = merge_result(result,compare_maps_with_matchingrule(&matcher, rule_list.cascaded, path, &expected, &actual, context, &mut |p, expected, actual| {
           compare_json(p, expected, actual, context)
         }));
@@ -375,6 +592,22 @@ fn compare_maps(
   }
 }
 
+/// Creates a matching context where the rules from an each value matcher apply to all the values
+/// of the map at the given path
+fn each_value_context(
+  path: &DocPath,
+  definition: &MatchingRuleDefinition,
+  context: &dyn MatchingContext
+) -> Box<dyn MatchingContext> {
+  let mut matchers = context.matchers().clone();
+  let mut values_path = path.clone();
+  values_path.push_star();
+  for rule in definition.rules.iter().filter_map(|rule| rule.as_ref().left()) {
+    matchers.add_rule(values_path.clone(), rule.clone(), RuleLogic::And);
+  }
+  context.clone_with(&matchers)
+}
+
 fn compare_lists(
   path: &DocPath,
   expected: &[Value],
@@ -387,9 +620,21 @@ fn compare_lists(
     let mut result = Ok(());
     let rule_list = context.select_best_matcher(path);
     for matcher in rule_list.rules {
-      let values_result = compare_lists_with_matchingrule(&matcher, path, expected, actual, context, rule_list.cascaded, &mut |p, expected, actual, context| {
-        compare_json(p, expected, actual, context)
-      });
+      let values_result = match matcher {
+        MatchingRule::UniqueItems(ref key) => if rule_list.cascaded {
+          Ok(())
+        } else {
+          compare_unique_items(path, key, actual)
+        },
+        MatchingRule::ArrayLength(length) => if rule_list.cascaded {
+          Ok(())
+        } else {
+          compare_array_length(path, length, actual)
+        },
+        _ => compare_lists_with_matchingrule(&matcher, path, expected, actual, context, rule_list.cascaded, &mut |p, expected, actual, context| {
+          compare_json(p, expected, actual, context)
+        })
+      };
       result = merge_result(result, values_result);
     }
     result
@@ -399,6 +644,7 @@ fn compare_lists(
       expected: Some(json_to_string(&json!(expected)).into()),
       actual: Some(json_to_string(&json!(actual)).into()),
       mismatch: format!("Expected an empty List but received {}", json_to_string(&json!(actual))),
+      rule: None,
     } ])
   } else {
     let result = compare_list_content(path, expected, actual, context);
@@ -409,6 +655,7 @@ fn compare_lists(
         actual: Some(json_to_string(&json!(actual)).into()),
         mismatch: format!("Expected a List with {} elements but received {} elements",
                           expected.len(), actual.len()),
+        rule: None,
       } ]))
     } else {
       result
@@ -434,7 +681,8 @@ fn compare_list_content(
         path: path.to_string(),
         expected: Some(json_to_string(&json!(expected)).into()),
         actual: Some(json_to_string(&json!(actual)).into()),
-        mismatch: format!("Expected {} but was missing", json_to_string(value)) } ]))
+        mismatch: format!("Expected {} but was missing", json_to_string(value)),
+        rule: None } ]))
     }
   }
   result
@@ -446,9 +694,20 @@ fn compare_values(
   actual: &Value,
   context: &dyn MatchingContext
 ) -> Result<(), Vec<Mismatch>> {
+  let mut rule = None;
   let matcher_result = if context.matcher_is_defined(path) {
     debug!("compare_values: Calling match_values for path {}", path);
-    match_values(path, &context.select_best_matcher(&path), expected, actual)
+    let rules = context.select_best_matcher(&path);
+    rule = rule_list_label(&rules);
+    match_values(path, &rules, expected, actual)
+  } else if context.empty_string_equals_null() && is_null_or_empty_string(expected) && is_null_or_empty_string(actual) {
+    Ok(())
+  } else if context.numeric_equality_ignores_representation() && expected.is_number() && actual.is_number() {
+    if expected.as_f64() == actual.as_f64() {
+      Ok(())
+    } else {
+      Err(vec![format!("Expected '{}' to be equal to '{}'", expected, actual)])
+    }
   } else {
     expected.matches_with(actual, &MatchingRule::Equality, false).map_err(|err| vec![err.to_string()])
   };
@@ -459,12 +718,130 @@ fn compare_values(
         path: path.to_string(),
         expected: Some(format!("{}", expected).into()),
         actual: Some(format!("{}", actual).into()),
-        mismatch: message.clone()
+        mismatch: message.clone(),
+        rule: rule.clone()
       }
     }).collect()
   })
 }
 
+fn is_null_or_empty_string(value: &Value) -> bool {
+  match value {
+    Value::Null => true,
+    Value::String(s) => s.is_empty(),
+    _ => false
+  }
+}
+
+/// Checks that the items in the actual list are unique. If a key path expression is given (i.e.
+/// `$.id`), only the values at that path in each item are compared.
+fn compare_unique_items(path: &DocPath, key: &Option<String>, actual: &[Value]) -> Result<(), Vec<Mismatch>> {
+  let rule = matching_rule_label(&MatchingRule::UniqueItems(key.clone()));
+  let key_path = match key {
+    Some(key) => match DocPath::new(key) {
+      Ok(key_path) => Some(key_path),
+      Err(err) => return Err(vec![ Mismatch::BodyMismatch {
+        path: path.to_string(),
+        expected: None,
+        actual: Some(json_to_string(&json!(actual)).into()),
+        mismatch: format!("Invalid key '{}' for the unique items matcher - {}", key, err),
+        rule: Some(rule)
+      } ])
+    },
+    None => None
+  };
+
+  let mut values: Vec<(&Value, Vec<usize>)> = vec![];
+  for (index, item) in actual.iter().enumerate() {
+    let value = match &key_path {
+      Some(key_path) => resolve_key_path(item, key_path),
+      None => Some(item)
+    };
+    if let Some(value) = value {
+      match values.iter_mut().find(|(v, _)| *v == value) {
+        Some((_, indices)) => indices.push(index),
+        None => values.push((value, vec![index]))
+      }
+    }
+  }
+
+  let mismatches = values.iter()
+    .filter(|(_, indices)| indices.len() > 1)
+    .map(|(value, indices)| Mismatch::BodyMismatch {
+      path: path.to_string(),
+      expected: None,
+      actual: Some(json_to_string(&json!(actual)).into()),
+      mismatch: match key {
+        Some(key) => format!("Expected the values at '{}' to be unique, but {} was found at indices {}",
+          key, value, indices.iter().join(", ")),
+        None => format!("Expected the items to be unique, but {} was found at indices {}",
+          value, indices.iter().join(", "))
+      },
+      rule: Some(rule.clone())
+    })
+    .collect_vec();
+  debug!("compare_unique_items: Checked {} items at path '{}' -> {:?}", actual.len(), path, mismatches);
+  if mismatches.is_empty() {
+    Ok(())
+  } else {
+    Err(mismatches)
+  }
+}
+
+fn compare_array_length(path: &DocPath, length: usize, actual: &[Value]) -> Result<(), Vec<Mismatch>> {
+  debug!("compare_array_length: Expecting {} items at path '{}', got {}", length, path, actual.len());
+  if actual.len() == length {
+    Ok(())
+  } else {
+    Err(vec![ Mismatch::BodyMismatch {
+      path: path.to_string(),
+      expected: None,
+      actual: Some(json_to_string(&json!(actual)).into()),
+      mismatch: format!("Expected a List with exactly {} item(s) but received {} item(s)", length, actual.len()),
+      rule: Some(matching_rule_label(&MatchingRule::ArrayLength(length)))
+    } ])
+  }
+}
+
+fn resolve_key_path<'a>(value: &'a Value, key_path: &DocPath) -> Option<&'a Value> {
+  key_path.tokens().iter().try_fold(value, |value, token| match token {
+    PathToken::Root => Some(value),
+    PathToken::Field(name) => value.get(name),
+    PathToken::Index(index) => value.get(index),
+    _ => None
+  })
+}
+
+fn json_schema_for_path(path: &DocPath, context: &dyn MatchingContext) -> Option<Value> {
+  if context.matcher_is_defined(path) {
+    context.select_best_matcher(path).rules.iter().find_map(|rule| match rule {
+      MatchingRule::JsonSchema(schema) => Some(schema.clone()),
+      _ => None
+    })
+  } else {
+    None
+  }
+}
+
+/// Validates the actual JSON against the schema, instead of comparing it to the expected JSON.
+/// Each schema violation results in a mismatch.
+fn compare_with_json_schema(path: &DocPath, schema: &Value, actual: &Value) -> Result<(), Vec<Mismatch>> {
+  let violations = validate_json_schema(path, schema, actual);
+  debug!("compare_with_json_schema: Validated '{}' at path '{}' -> {:?}", actual, path, violations);
+  if violations.is_empty() {
+    Ok(())
+  } else {
+    let rule = matching_rule_label(&MatchingRule::JsonSchema(schema.clone()));
+    Err(violations.iter().map(|violation| Mismatch::BodyMismatch {
+      path: violation.path.to_string(),
+      expected: Some(schema.to_string().into()),
+      actual: Some(actual.to_string().into()),
+      mismatch: violation.message.clone(),
+      rule: Some(rule.clone())
+    }).collect())
+  }
+}
+
 #[cfg(test)]
 mod tests {
   use std::collections::HashMap;
@@ -475,7 +852,8 @@ mod tests {
 
   use pact_models::{matchingrules, matchingrules_list};
   use pact_models::bodies::OptionalBody;
-  use pact_models::matchingrules::{MatchingRule, MatchingRuleCategory};
+  use pact_models::matchingrules::{MatchingRule, MatchingRuleCategory, RuleList, RuleLogic};
+  use pact_models::matchingrules::expressions::{MatchingRuleDefinition, ValueType};
   use pact_models::request::Request;
 
   use crate::{CoreMatchingContext, DiffConfig};
@@ -497,7 +875,8 @@ mod tests {
       path: s!("$"),
       expected: expected.body.value(),
       actual: actual.body.value(),
-      mismatch: s!("") }]));
+      mismatch: s!(""),
+      rule: None }]));
   }
 
   #[test]
@@ -511,7 +890,8 @@ mod tests {
           path: s!("$"),
           expected: expected.body.value(),
           actual: actual.body.value(),
-          mismatch: s!("Type mismatch: Expected List [{}] but received Map {}")
+          mismatch: s!("Type mismatch: Expected List [{}] but received Map {}"),
+          rule: None
         }
       ]
     ));
@@ -537,7 +917,8 @@ mod tests {
       path: s!("$"),
       expected: expected.body.value(),
       actual: actual.body.value(),
-      mismatch: s!("")
+      mismatch: s!(""),
+      rule: None
     }]));
   }
 
@@ -551,7 +932,8 @@ mod tests {
       path: s!("$"),
       expected: expected.body.value(),
       actual: actual.body.value(),
-      mismatch: s!("")
+      mismatch: s!(""),
+      rule: None
     }]));
   }
 
@@ -568,7 +950,8 @@ mod tests {
       path: s!("$"),
       expected: val1.body.value(),
       actual: val2.body.value(),
-      mismatch: s!("")
+      mismatch: s!(""),
+      rule: None
     } ]));
   }
 
@@ -585,7 +968,8 @@ mod tests {
       path: s!("$"),
       expected: val1.body.value(),
       actual: val2.body.value(),
-      mismatch: s!("")
+      mismatch: s!(""),
+      rule: None
     } ]));
   }
 
@@ -602,7 +986,8 @@ mod tests {
       path: s!("$"),
       expected: val1.body.value(),
       actual: val2.body.value(),
-      mismatch: s!("")
+      mismatch: s!(""),
+      rule: None
     } ]));
   }
 
@@ -619,7 +1004,8 @@ mod tests {
       path: s!("$"),
       expected: val1.body.value(),
       actual: val2.body.value(),
-      mismatch: s!("")
+      mismatch: s!(""),
+      rule: None
     } ]));
   }
 
@@ -636,10 +1022,39 @@ mod tests {
       path: s!("$"),
       expected: val1.clone().body.value(),
       actual: val2.clone().body.value(),
-      mismatch: s!("")
+      mismatch: s!(""),
+      rule: None
     } ]));
   }
 
+  fn mismatch_rule(mismatch: &Result<(), Vec<Mismatch>>) -> Option<String> {
+    match mismatch {
+      Err(mismatches) => match &mismatches.first() {
+        Some(Mismatch::BodyMismatch { rule, .. }) => rule.clone(),
+        _ => None
+      },
+      _ => None
+    }
+  }
+
+  #[test]
+  fn match_json_includes_the_matching_rule_in_the_mismatch() {
+    let expected = request!(r#"{"id": "100"}"#);
+    let actual = request!(r#"{"id": "abc"}"#);
+    let context = CoreMatchingContext::new(DiffConfig::AllowUnexpectedKeys, &matchingrules! {
+      "body" => { "$.id" => [ MatchingRule::Regex("^\\d+$".to_string()) ] }
+    }.rules_for_category("body").unwrap(), &hashmap!{});
+    let result = match_json(&expected, &actual, &context);
+    expect!(mismatch_rule(&result)).to(be_some().value("Regex"));
+    let json = result.unwrap_err().first().unwrap().to_json();
+    expect!(json["rule"].clone()).to(be_equal_to(json!("Regex")));
+
+    let result = match_json(&expected, &actual, &CoreMatchingContext::with_config(DiffConfig::AllowUnexpectedKeys));
+    expect!(mismatch_rule(&result)).to(be_none());
+    let json = result.unwrap_err().first().unwrap().to_json();
+    expect!(json.get("rule")).to(be_none());
+  }
+
   #[test]
   fn match_json_handles_comparing_lists() {
     let val1 = request!(r#"[]"#);
@@ -663,13 +1078,13 @@ mod tests {
     let result = match_json(&val2.clone(), &val3.clone(), &CoreMatchingContext::with_config(DiffConfig::AllowUnexpectedKeys));
     expect!(mismatch_message(&result)).to(be_equal_to("Expected '22' to be equal to '44'".to_string()));
     expect!(result).to(be_err().value(vec![ Mismatch::BodyMismatch { path: "$[1]".to_string(),
-        expected: Some("22".into()), actual: Some("44".into()), mismatch: "".to_string() } ]));
+        expected: Some("22".into()), actual: Some("44".into()), mismatch: "".to_string(), rule: None } ]));
 
     let result = match_json(&val3.clone(), &val4.clone(), &CoreMatchingContext::with_config(DiffConfig::AllowUnexpectedKeys));
     expect!(mismatch_message(&result)).to(be_equal_to("Expected a List with 3 elements but received 4 elements".to_string()));
     expect!(result).to(be_err().value(vec![ Mismatch::BodyMismatch { path: "$".to_string(),
         expected: Some("[11,44,33]".into()),
-        actual: Some("[11,44,33,66]".into()), mismatch: "".to_string() } ]));
+        actual: Some("[11,44,33,66]".into()), mismatch: "".to_string(), rule: None } ]));
 
     let result = match_json(&val2.clone(), &val4.clone(), &CoreMatchingContext::with_config(DiffConfig::AllowUnexpectedKeys));
     let mismatches = result.unwrap_err();
@@ -677,12 +1092,12 @@ mod tests {
     let mismatch = mismatches[0].clone();
     expect!(&mismatch).to(be_equal_to(&Mismatch::BodyMismatch { path: "$[1]".to_string(),
         expected: Some("22".into()),
-        actual: Some("44".into()), mismatch: "".to_string()}));
+        actual: Some("44".into()), mismatch: "".to_string(), rule: None}));
     expect!(mismatch.description()).to(be_equal_to("$[1] -> Expected '22' to be equal to '44'".to_string()));
     let mismatch = mismatches[1].clone();
     expect!(&mismatch).to(be_equal_to(&Mismatch::BodyMismatch { path: "$".to_string(),
         expected: Some("[11,22,33]".into()),
-        actual: Some("[11,44,33,66]".into()), mismatch: "".to_string()}));
+        actual: Some("[11,44,33,66]".into()), mismatch: "".to_string(), rule: None}));
     expect!(mismatch.description()).to(be_equal_to("$ -> Expected a List with 3 elements but received 4 elements".to_string()));
 
     let context = CoreMatchingContext::new(DiffConfig::AllowUnexpectedKeys, &matchingrules! {
@@ -718,7 +1133,7 @@ mod tests {
     let result = match_json(&val2.clone(), &val3.clone(), &CoreMatchingContext::with_config(DiffConfig::AllowUnexpectedKeys));
     expect!(mismatch_message(&result)).to(be_equal_to(s!("Expected '2' to be equal to '3'")));
     expect!(result).to(be_err().value(vec![ Mismatch::BodyMismatch { path: s!("$.b"),
-        expected: Some("2".into()), actual: Some("3".into()), mismatch: s!("") } ]));
+        expected: Some("2".into()), actual: Some("3".into()), mismatch: s!(""), rule: None } ]));
 
     let result = match_json(&val2.clone(), &val4.clone(), &CoreMatchingContext::with_config(DiffConfig::AllowUnexpectedKeys));
     expect!(result).to(be_ok());
@@ -727,14 +1142,14 @@ mod tests {
     expect!(mismatch_message(&result)).to(be_equal_to("Expected a Map with keys a, b but received one with keys a, b, c".to_string()));
     expect!(result).to(be_err().value(vec![ Mismatch::BodyMismatch { path: "$".to_string(),
         expected: Some("[\"a\",\"b\"]".into()),
-        actual: Some("[\"a\",\"b\",\"c\"]".into()), mismatch: "Expected a Map with keys a, b but received one with keys a, b, c".to_string()
+        actual: Some("[\"a\",\"b\",\"c\"]".into()), mismatch: "Expected a Map with keys a, b but received one with keys a, b, c".to_string(), rule: None
     } ]));
 
     let result = match_json(&val3.clone(), &val4.clone(), &CoreMatchingContext::with_config(DiffConfig::AllowUnexpectedKeys));
     expect!(mismatch_message(&result)).to(be_equal_to(s!("Expected '3' to be equal to '2'")));
     expect!(result).to(be_err().value(vec![ Mismatch::BodyMismatch { path: s!("$.b"),
         expected: Some("3".into()),
-        actual: Some("2".into()), mismatch: s!("") } ]));
+        actual: Some("2".into()), mismatch: s!(""), rule: None } ]));
 
     let result = match_json(&val3.clone(), &val4.clone(), &CoreMatchingContext::with_config(DiffConfig::NoUnexpectedKeys));
     let mismatches = result.unwrap_err();
@@ -742,12 +1157,12 @@ mod tests {
     let mismatch = mismatches[0].clone();
     expect!(&mismatch).to(be_equal_to(&Mismatch::BodyMismatch { path: "$".to_string(),
         expected: Some("[\"a\",\"b\"]".into()),
-        actual: Some("[\"a\",\"b\",\"c\"]".into()), mismatch: "".to_string()}));
+        actual: Some("[\"a\",\"b\",\"c\"]".into()), mismatch: "".to_string(), rule: None}));
     expect!(mismatch.description()).to(be_equal_to("$ -> Expected a Map with keys a, b but received one with keys a, b, c".to_string()));
     let mismatch = mismatches[1].clone();
     expect!(&mismatch).to(be_equal_to(&Mismatch::BodyMismatch { path: "$.b".to_string(),
         expected: Some("3".into()),
-        actual: Some("2".into()), mismatch: "".to_string()}));
+        actual: Some("2".into()), mismatch: "".to_string(), rule: None}));
     expect!(mismatch.description()).to(be_equal_to("$.b -> Expected '3' to be equal to '2'".to_string()));
 
     let result = match_json(&val4.clone(), &val2.clone(), &CoreMatchingContext::with_config(DiffConfig::AllowUnexpectedKeys));
@@ -756,7 +1171,7 @@ mod tests {
     let mismatch = mismatches[0].clone();
     expect!(&mismatch).to(be_equal_to(&Mismatch::BodyMismatch { path: "$".to_string(),
         expected: Some("[\"a\",\"b\",\"c\"]".into()),
-        actual: Some("[\"a\",\"b\"]".into()), mismatch: "".to_string()}));
+        actual: Some("[\"a\",\"b\"]".into()), mismatch: "".to_string(), rule: None}));
     expect!(mismatch.description()).to(be_equal_to("$ -> Actual map is missing the following keys: c".to_string()));
 
     let result = match_json(&val3, &val2, &CoreMatchingContext::new(DiffConfig::AllowUnexpectedKeys, &matchingrules!{
@@ -881,6 +1296,484 @@ mod tests {
     expect!(Value::String("100".into()).matches_with(&Value::Null, &matcher, false)).to(be_ok());
   }
 
+  #[test]
+  fn semver_matcher_test() {
+    let matcher = MatchingRule::Semver;
+    expect!(json!("1.0.0").matches_with(&json!("1.2.3"), &matcher, false)).to(be_ok());
+    expect!(json!("1.0.0").matches_with(&json!("1.2.3-beta.1"), &matcher, false)).to(be_ok());
+    expect!(json!("1.0.0").matches_with(&json!("1.2"), &matcher, false)).to(be_err());
+    expect!(json!("1.0.0").matches_with(&json!("not a version"), &matcher, false)).to(be_err());
+    expect!(json!("1.0.0").matches_with(&json!(123), &matcher, false)).to(be_err());
+    expect!(json!("1.0.0").matches_with(&Value::Null, &matcher, false)).to(be_err());
+  }
+
+  #[test]
+  fn email_matcher_test() {
+    let matcher = MatchingRule::Email;
+    expect!(json!("a@example.com").matches_with(&json!("a.b@example.com"), &matcher, false)).to(be_ok());
+    expect!(json!("a@example.com").matches_with(&json!("not-an-email"), &matcher, false)).to(be_err());
+    expect!(json!("a@example.com").matches_with(&json!("a@"), &matcher, false)).to(be_err());
+    expect!(json!("a@example.com").matches_with(&json!(100), &matcher, false)).to(be_err());
+    expect!(json!("a@example.com").matches_with(&Value::Null, &matcher, false)).to(be_err());
+  }
+
+  #[test]
+  fn any_type_matcher_test() {
+    let matcher = MatchingRule::AnyType(vec!["string".to_string(), "number".to_string()]);
+    expect!(json!("100").matches_with(&json!("200"), &matcher, false)).to(be_ok());
+    expect!(json!("100").matches_with(&json!(200), &matcher, false)).to(be_ok());
+    expect!(json!("100").matches_with(&json!(200.5), &matcher, false)).to(be_ok());
+    let result = json!("100").matches_with(&json!(true), &matcher, false);
+    expect!(result.unwrap_err().to_string()).to(
+      be_equal_to("Expected 'true' to be one of the types [string, number]"));
+    expect!(json!("100").matches_with(&Value::Null, &matcher, false)).to(be_err());
+    expect!(json!("100").matches_with(&json!([100]), &matcher, false)).to(be_err());
+
+    let matcher = MatchingRule::AnyType(vec!["integer".to_string(), "null".to_string()]);
+    expect!(json!(1).matches_with(&json!(200), &matcher, false)).to(be_ok());
+    expect!(json!(1).matches_with(&Value::Null, &matcher, false)).to(be_ok());
+    expect!(json!(1).matches_with(&json!(200.5), &matcher, false)).to(be_err());
+  }
+
+  #[test]
+  fn any_type_matcher_with_an_unrecognised_type_name_test() {
+    let matcher = MatchingRule::AnyType(vec!["string".to_string(), "date".to_string()]);
+    let result = json!("100").matches_with(&json!(100), &matcher, false);
+    expect!(result.unwrap_err().to_string()).to(be_equal_to("'date' is not a recognised type name"));
+  }
+
+  #[test]
+  fn coordinate_matcher_test() {
+    expect!(json!(0).matches_with(&json!(-33.8688), &MatchingRule::Latitude, false)).to(be_ok());
+    expect!(json!(0).matches_with(&json!(-90), &MatchingRule::Latitude, false)).to(be_ok());
+    expect!(json!(0).matches_with(&json!("51.5074"), &MatchingRule::Latitude, false)).to(be_ok());
+    expect!(json!(0).matches_with(&json!(-91), &MatchingRule::Latitude, false).unwrap_err().to_string()).to(
+      be_equal_to("Expected -91 to be a valid latitude (between -90 and 90)"));
+    expect!(json!(0).matches_with(&json!(151.2093), &MatchingRule::Longitude, false)).to(be_ok());
+    expect!(json!(0).matches_with(&json!(180), &MatchingRule::Longitude, false)).to(be_ok());
+    expect!(json!(0).matches_with(&json!("180.5"), &MatchingRule::Longitude, false).unwrap_err().to_string()).to(
+      be_equal_to("Expected 180.5 to be a valid longitude (between -180 and 180)"));
+    expect!(json!(0).matches_with(&json!("east"), &MatchingRule::Longitude, false).unwrap_err().to_string()).to(
+      be_equal_to("Expected 'east' to be a valid longitude"));
+    expect!(json!(0).matches_with(&json!(true), &MatchingRule::Latitude, false).unwrap_err().to_string()).to(
+      be_equal_to("Expected a latitude, but got 'true'"));
+    expect!(json!(0).matches_with(&Value::Null, &MatchingRule::Longitude, false)).to(be_err());
+  }
+
+  #[test]
+  fn number_range_matcher_test() {
+    let inclusive = MatchingRule::NumberRange { min: 0.0, max: 1000.0, inclusive: true };
+    let exclusive = MatchingRule::NumberRange { min: 0.0, max: 1000.0, inclusive: false };
+    expect!(json!(0).matches_with(&json!(99.99), &inclusive, false)).to(be_ok());
+    expect!(json!(0).matches_with(&json!("500"), &exclusive, false)).to(be_ok());
+    expect!(json!(0).matches_with(&json!(0), &inclusive, false)).to(be_ok());
+    expect!(json!(0).matches_with(&json!(1000), &inclusive, false)).to(be_ok());
+    expect!(json!(0).matches_with(&json!(0), &exclusive, false).unwrap_err().to_string()).to(
+      be_equal_to("Expected 0 to be greater than the minimum 0"));
+    expect!(json!(0).matches_with(&json!(1000), &exclusive, false).unwrap_err().to_string()).to(
+      be_equal_to("Expected 1000 to be less than the maximum 1000"));
+    expect!(json!(0).matches_with(&json!(-0.5), &inclusive, false).unwrap_err().to_string()).to(
+      be_equal_to("Expected -0.5 to be greater than or equal to the minimum 0"));
+    expect!(json!(0).matches_with(&json!("1000.01"), &inclusive, false).unwrap_err().to_string()).to(
+      be_equal_to("Expected 1000.01 to be less than or equal to the maximum 1000"));
+    expect!(json!(0).matches_with(&json!("cheap"), &inclusive, false).unwrap_err().to_string()).to(
+      be_equal_to("Expected 'cheap' to be a number"));
+    expect!(json!(0).matches_with(&json!(true), &inclusive, false).unwrap_err().to_string()).to(
+      be_equal_to("Expected a number, but got 'true'"));
+    expect!(json!(0).matches_with(&Value::Null, &inclusive, false)).to(be_err());
+  }
+
+  #[test]
+  fn iso8601_timestamp_matcher_test() {
+    let matcher = MatchingRule::Iso8601Timestamp;
+    expect!(json!("").matches_with(&json!("2023-01-01T12:00:00Z"), &matcher, false)).to(be_ok());
+    expect!(json!("").matches_with(&json!("2023-01-01T12:00:00.123+10:00"), &matcher, false)).to(be_ok());
+    expect!(json!("").matches_with(&json!("2023-01-01"), &matcher, false)).to(be_err());
+    expect!(json!("").matches_with(&json!(1672574400), &matcher, false).unwrap_err().to_string()).to(
+      be_equal_to("Expected a string containing an RFC-3339 timestamp, but got '1672574400'"));
+    expect!(json!("").matches_with(&Value::Null, &matcher, false)).to(be_err());
+  }
+
+  #[test]
+  fn recent_timestamp_matcher_test() {
+    let matcher = MatchingRule::RecentTimestamp { within_seconds: 60 };
+    expect!(json!("").matches_with(&json!(Utc::now().to_rfc3339()), &matcher, false)).to(be_ok());
+    expect!(json!("").matches_with(&json!("2023-01-01T12:00:00Z"), &matcher, false)).to(be_err());
+    expect!(json!("").matches_with(&json!("soon"), &matcher, false)).to(be_err());
+    expect!(json!("").matches_with(&json!(1672574400), &matcher, false).unwrap_err().to_string()).to(
+      be_equal_to("Expected a string containing an RFC-3339 timestamp, but got '1672574400'"));
+  }
+
+  #[test]
+  fn hexadecimal_matcher_test() {
+    let matcher = MatchingRule::Hexadecimal { length: Some(40) };
+    expect!(json!("").matches_with(&json!("9aed2db4e5f6c1b8a3d0e7f2c4b6a8d0e1f3a5c7"), &matcher, false)).to(be_ok());
+    expect!(json!("").matches_with(&json!("9aed2db"), &matcher, false)).to(be_err());
+    expect!(json!("").matches_with(&json!(1234), &MatchingRule::Hexadecimal { length: None }, false).unwrap_err().to_string()).to(
+      be_equal_to("Expected a string of hexadecimal digits, but got '1234'"));
+  }
+
+  #[test]
+  fn ip_address_matcher_test() {
+    expect!(json!("").matches_with(&json!("10.0.0.1"), &MatchingRule::IpAddress(None), false)).to(be_ok());
+    expect!(json!("").matches_with(&json!("fe80::1"), &MatchingRule::IpAddress(Some(6)), false)).to(be_ok());
+    expect!(json!("").matches_with(&json!("10.0.0.1"), &MatchingRule::IpAddress(Some(6)), false)).to(be_err());
+    expect!(json!("").matches_with(&json!(10001), &MatchingRule::IpAddress(None), false).unwrap_err().to_string()).to(
+      be_equal_to("Expected a string containing an IP address, but got '10001'"));
+  }
+
+  #[test]
+  fn luhn_matcher_test() {
+    let matcher = MatchingRule::Luhn;
+    expect!(json!("").matches_with(&json!("4111 1111 1111 1111"), &matcher, false)).to(be_ok());
+    expect!(json!("").matches_with(&json!(79927398713_u64), &matcher, false)).to(be_ok());
+    expect!(json!("").matches_with(&json!("4111 1111 1111 1112"), &matcher, false)).to(be_err());
+    expect!(json!("").matches_with(&json!("card"), &matcher, false)).to(be_err());
+    expect!(json!("").matches_with(&json!(true), &matcher, false).unwrap_err().to_string()).to(
+      be_equal_to("Expected a number or string that passes the Luhn check, but got 'true'"));
+  }
+
+  #[test]
+  fn valid_json_matcher_test() {
+    let matcher = MatchingRule::ValidJson;
+    expect!(json!("{}").matches_with(&json!("{\"id\":100,\"name\":\"Fred\"}"), &matcher, false)).to(be_ok());
+    expect!(json!("{}").matches_with(&json!("{\"id\":"), &matcher, false)).to(be_err());
+    expect!(json!("{}").matches_with(&json!({"id": 100}), &matcher, false).unwrap_err().to_string()).to(
+      be_equal_to("Expected a string containing valid JSON, but got '{\"id\":100}'"));
+    expect!(json!("{}").matches_with(&json!(100), &matcher, false)).to(be_err());
+  }
+
+  #[test]
+  fn one_of_matcher_test() {
+    let matcher = MatchingRule::OneOf(vec![json!("active"), json!("inactive"), json!("pending")]);
+    expect!(json!("active").matches_with(&json!("pending"), &matcher, false)).to(be_ok());
+    expect!(json!("active").matches_with(&json!("archived"), &matcher, false).unwrap_err().to_string()).to(
+      be_equal_to("Expected \"archived\" to be one of [\"active\",\"inactive\",\"pending\"]"));
+  }
+
+  #[test]
+  fn one_of_matcher_does_not_match_values_of_a_different_type() {
+    let matcher = MatchingRule::OneOf(vec![json!("1"), json!(2), json!(true)]);
+    expect!(json!("1").matches_with(&json!(2), &matcher, false)).to(be_ok());
+    expect!(json!("1").matches_with(&json!(true), &matcher, false)).to(be_ok());
+    expect!(json!("1").matches_with(&json!(1), &matcher, false).unwrap_err().to_string()).to(
+      be_equal_to("Expected 1 to be one of [\"1\",2,true]"));
+    expect!(json!("1").matches_with(&json!("2"), &matcher, false)).to(be_err());
+    expect!(json!("1").matches_with(&json!(["1"]), &matcher, false)).to(be_err());
+    expect!(json!("1").matches_with(&Value::Null, &matcher, false)).to(be_err());
+  }
+
+  #[test]
+  fn match_json_with_each_key_matcher_passes_when_all_keys_match() {
+    let expected = request!(r#"{"users": {"2f8a7c5e-1b2d-4e5f-8a9b-0c1d2e3f4a5b": "Fred"}}"#);
+    let actual = request!(r#"{"users": {"2f8a7c5e-1b2d-4e5f-8a9b-0c1d2e3f4a5b": "Fred",
+      "9d8c7b6a-5e4f-4a3b-2c1d-0e9f8a7b6c5d": "Mary"}}"#);
+    let context = CoreMatchingContext::new(DiffConfig::AllowUnexpectedKeys, &matchingrules! {
+      "body" => {
+        "$.users" => [ MatchingRule::EachKey(MatchingRuleDefinition::new("".to_string(), ValueType::Unknown,
+          MatchingRule::Regex("^[0-9a-f]{8}-[0-9a-f]{4}-[0-9a-f]{4}-[0-9a-f]{4}-[0-9a-f]{12}$".to_string()), None)) ]
+      }
+    }.rules_for_category("body").unwrap(), &hashmap!{});
+    expect!(match_json(&expected, &actual, &context)).to(be_ok());
+  }
+
+  #[test]
+  fn match_json_with_each_key_matcher_fails_for_an_invalid_key() {
+    let expected = request!(r#"{"users": {"2f8a7c5e-1b2d-4e5f-8a9b-0c1d2e3f4a5b": "Fred"}}"#);
+    let actual = request!(r#"{"users": {"2f8a7c5e-1b2d-4e5f-8a9b-0c1d2e3f4a5b": "Fred", "mary": "Mary"}}"#);
+    let context = CoreMatchingContext::new(DiffConfig::AllowUnexpectedKeys, &matchingrules! {
+      "body" => {
+        "$.users" => [ MatchingRule::EachKey(MatchingRuleDefinition::new("".to_string(), ValueType::Unknown,
+          MatchingRule::Regex("^[0-9a-f]{8}-[0-9a-f]{4}-[0-9a-f]{4}-[0-9a-f]{4}-[0-9a-f]{12}$".to_string()), None)) ]
+      }
+    }.rules_for_category("body").unwrap(), &hashmap!{});
+    let result = match_json(&expected, &actual, &context);
+    expect!(mismatch_messages(result)).to(be_equal_to(vec![
+      "$.users.mary -> Expected 'mary' to match '^[0-9a-f]{8}-[0-9a-f]{4}-[0-9a-f]{4}-[0-9a-f]{4}-[0-9a-f]{12}$'".to_string()
+    ]));
+  }
+
+  #[test]
+  fn match_json_with_each_key_and_each_value_matchers_checks_both_the_keys_and_values() {
+    let expected = request!(r#"{"users": {"2f8a7c5e-1b2d-4e5f-8a9b-0c1d2e3f4a5b": "Fred"}}"#);
+    let context = CoreMatchingContext::new(DiffConfig::AllowUnexpectedKeys, &matchingrules! {
+      "body" => {
+        "$.users" => [
+          MatchingRule::EachKey(MatchingRuleDefinition::new("".to_string(), ValueType::Unknown,
+            MatchingRule::Regex("^[0-9a-f]{8}-[0-9a-f]{4}-[0-9a-f]{4}-[0-9a-f]{4}-[0-9a-f]{12}$".to_string()), None)),
+          MatchingRule::EachValue(MatchingRuleDefinition::new("".to_string(), ValueType::Unknown,
+            MatchingRule::Type, None))
+        ]
+      }
+    }.rules_for_category("body").unwrap(), &hashmap!{});
+
+    let actual = request!(r#"{"users": {"9d8c7b6a-5e4f-4a3b-2c1d-0e9f8a7b6c5d": "Mary",
+      "1a2b3c4d-5e6f-4a7b-8c9d-0e1f2a3b4c5d": "Jane"}}"#);
+    expect!(match_json(&expected, &actual, &context)).to(be_ok());
+
+    let actual = request!(r#"{"users": {"9d8c7b6a-5e4f-4a3b-2c1d-0e9f8a7b6c5d": 100, "jane": "Jane"}}"#);
+    let mut messages = mismatch_messages(match_json(&expected, &actual, &context));
+    messages.sort();
+    expect!(messages).to(be_equal_to(vec![
+      "$.users.jane -> Expected 'jane' to match '^[0-9a-f]{8}-[0-9a-f]{4}-[0-9a-f]{4}-[0-9a-f]{4}-[0-9a-f]{12}$'".to_string(),
+      "$.users['9d8c7b6a-5e4f-4a3b-2c1d-0e9f8a7b6c5d'] -> Expected 'Fred' to be the same type as '100'".to_string()
+    ]));
+  }
+
+  #[test]
+  fn match_json_with_json_schema_matcher_passes_for_a_conforming_body() {
+    let expected = request!(r#"{"user": {"id": 1, "name": "Fred"}}"#);
+    let actual = request!(r#"{"user": {"id": 200, "name": "Mary", "email": "mary@example.com"}}"#);
+    let context = CoreMatchingContext::new(DiffConfig::AllowUnexpectedKeys, &matchingrules! {
+      "body" => {
+        "$.user" => [ MatchingRule::JsonSchema(json!({
+          "type": "object",
+          "required": ["id", "name"],
+          "properties": { "id": { "type": "integer" }, "name": { "type": "string" } }
+        })) ]
+      }
+    }.rules_for_category("body").unwrap(), &hashmap!{});
+    expect!(match_json(&expected, &actual, &context)).to(be_ok());
+  }
+
+  #[test]
+  fn match_json_with_json_schema_matcher_fails_with_each_schema_violation() {
+    let expected = request!(r#"{"user": {"id": 1, "name": "Fred"}}"#);
+    let actual = request!(r#"{"user": {"id": "200"}}"#);
+    let context = CoreMatchingContext::new(DiffConfig::AllowUnexpectedKeys, &matchingrules! {
+      "body" => {
+        "$.user" => [ MatchingRule::JsonSchema(json!({
+          "type": "object",
+          "required": ["id", "name"],
+          "properties": { "id": { "type": "integer" }, "name": { "type": "string" } }
+        })) ]
+      }
+    }.rules_for_category("body").unwrap(), &hashmap!{});
+    let result = match_json(&expected, &actual, &context);
+    expect!(mismatch_rule(&result)).to(be_some().value("JsonSchema"));
+    expect!(mismatch_messages(result)).to(be_equal_to(vec![
+      "$.user.id -> \"200\" is not of type \"integer\"".to_string(),
+      "$.user -> \"name\" is a required property".to_string()
+    ]));
+  }
+
+  fn mismatch_messages(result: Result<(), Vec<Mismatch>>) -> Vec<String> {
+    result.unwrap_err().iter().map(|mismatch| mismatch.description()).collect()
+  }
+
+  #[test]
+  fn match_json_with_unique_items_matcher_passes_for_a_unique_list() {
+    let context = CoreMatchingContext::new(DiffConfig::AllowUnexpectedKeys, &matchingrules! {
+      "body" => { "$.items" => [ MatchingRule::UniqueItems(None) ] }
+    }.rules_for_category("body").unwrap(), &hashmap!{});
+    let expected = request!(r#"{"items": [1, 2]}"#);
+    let actual = request!(r#"{"items": [3, 1, 2]}"#);
+    expect!(match_json(&expected, &actual, &context)).to(be_ok());
+
+    let context = CoreMatchingContext::new(DiffConfig::AllowUnexpectedKeys, &matchingrules! {
+      "body" => { "$.items" => [ MatchingRule::UniqueItems(Some("$.id".to_string())) ] }
+    }.rules_for_category("body").unwrap(), &hashmap!{});
+    let expected = request!(r#"{"items": [{"id": 1}]}"#);
+    let actual = request!(r#"{"items": [{"id": 1, "name": "a"}, {"id": 2, "name": "a"}]}"#);
+    expect!(match_json(&expected, &actual, &context)).to(be_ok());
+  }
+
+  #[test]
+  fn match_json_with_unique_items_matcher_fails_for_duplicate_items() {
+    let expected = request!(r#"{"items": [1, 2]}"#);
+    let actual = request!(r#"{"items": [1, 2, 1, 3, 2, 1]}"#);
+    let context = CoreMatchingContext::new(DiffConfig::AllowUnexpectedKeys, &matchingrules! {
+      "body" => { "$.items" => [ MatchingRule::UniqueItems(None) ] }
+    }.rules_for_category("body").unwrap(), &hashmap!{});
+    let result = match_json(&expected, &actual, &context);
+    expect!(mismatch_messages(result)).to(be_equal_to(vec![
+      "$.items -> Expected the items to be unique, but 1 was found at indices 0, 2, 5".to_string(),
+      "$.items -> Expected the items to be unique, but 2 was found at indices 1, 4".to_string()
+    ]));
+  }
+
+  #[test]
+  fn match_json_with_unique_items_matcher_fails_for_duplicate_keys() {
+    let expected = request!(r#"{"items": [{"id": 1}]}"#);
+    let actual = request!(r#"{"items": [{"id": 1, "name": "a"}, {"id": 2, "name": "b"}, {"id": 1, "name": "c"}]}"#);
+    let context = CoreMatchingContext::new(DiffConfig::AllowUnexpectedKeys, &matchingrules! {
+      "body" => { "$.items" => [ MatchingRule::UniqueItems(Some("$.id".to_string())) ] }
+    }.rules_for_category("body").unwrap(), &hashmap!{});
+    let result = match_json(&expected, &actual, &context);
+    expect!(mismatch_rule(&result)).to(be_some().value("UniqueItems"));
+    expect!(mismatch_messages(result)).to(be_equal_to(vec![
+      "$.items -> Expected the values at '$.id' to be unique, but 1 was found at indices 0, 2".to_string()
+    ]));
+  }
+
+  #[test]
+  fn match_ndjson_compares_each_line_as_a_json_document() {
+    let expected = request!("{\"id\": 1, \"name\": \"Mary\"}\n{\"id\": 2, \"name\": \"Fred\"}\n");
+    let context = CoreMatchingContext::with_config(DiffConfig::AllowUnexpectedKeys);
+
+    expect!(match_ndjson(&expected, &request!("{\"name\": \"Mary\", \"id\": 1}\r\n{\"id\": 2, \"name\": \"Fred\"}\n\n"), &context)).to(be_ok());
+    expect!(mismatch_messages(match_ndjson(&expected, &request!("{\"id\": 1, \"name\": \"Mary\"}\n{\"id\": 3, \"name\": \"Fred\"}"), &context))).to(be_equal_to(vec![
+      "$[1].id -> Expected '2' to be equal to '3'".to_string()
+    ]));
+    expect!(mismatch_messages(match_ndjson(&expected, &request!("{\"id\": 1, \"name\": \"Mary\"}\nnot json"), &context))).to(be_equal_to(vec![
+      "$ -> Failed to parse the actual body: 'line 2 is not valid JSON - expected ident at line 1 column 2'".to_string()
+    ]));
+  }
+
+  #[test]
+  fn match_ndjson_with_a_different_number_of_lines() {
+    let expected = request!("{\"id\": 1}\n{\"id\": 2}");
+    let actual = request!("{\"id\": 1}\n{\"id\": 2}\n{\"id\": 3}");
+    let context = CoreMatchingContext::with_config(DiffConfig::AllowUnexpectedKeys);
+    expect!(mismatch_messages(match_ndjson(&expected, &actual, &context))).to(be_equal_to(vec![
+      "$ -> Expected a List with 2 elements but received 3 elements".to_string()
+    ]));
+
+    let context = CoreMatchingContext::new(DiffConfig::AllowUnexpectedKeys, &matchingrules! {
+      "body" => { "$" => [ MatchingRule::MinType(1) ] }
+    }.rules_for_category("body").unwrap(), &hashmap!{});
+    expect!(match_ndjson(&expected, &actual, &context)).to(be_ok());
+  }
+
+  fn array_length_context(rules: Vec<MatchingRule>) -> CoreMatchingContext {
+    CoreMatchingContext::new(DiffConfig::AllowUnexpectedKeys, &MatchingRuleCategory {
+      name: "body".into(),
+      rules: hashmap!{ DocPath::new_unwrap("$.items") => RuleList { rules, rule_logic: RuleLogic::And, cascaded: false } }
+    }, &hashmap!{})
+  }
+
+  #[test]
+  fn match_json_with_array_length_matcher() {
+    let expected = request!(r#"{"items": [1, 2, 3]}"#);
+    let context = array_length_context(vec![ MatchingRule::ArrayLength(3) ]);
+
+    expect!(match_json(&expected, &request!(r#"{"items": [4, 5, 6]}"#), &context)).to(be_ok());
+    expect!(mismatch_messages(match_json(&expected, &request!(r#"{"items": [4, 5]}"#), &context))).to(be_equal_to(vec![
+      "$.items -> Expected a List with exactly 3 item(s) but received 2 item(s)".to_string()
+    ]));
+    expect!(mismatch_messages(match_json(&expected, &request!(r#"{"items": [4, 5, 6, 7]}"#), &context))).to(be_equal_to(vec![
+      "$.items -> Expected a List with exactly 3 item(s) but received 4 item(s)".to_string()
+    ]));
+  }
+
+  #[test]
+  fn match_json_with_array_length_and_type_matchers() {
+    let expected = request!(r#"{"items": [1]}"#);
+    let context = array_length_context(vec![ MatchingRule::ArrayLength(2), MatchingRule::Type ]);
+
+    expect!(match_json(&expected, &request!(r#"{"items": [4, 5]}"#), &context)).to(be_ok());
+    expect!(mismatch_messages(match_json(&expected, &request!(r#"{"items": [4, "5"]}"#), &context))).to(be_equal_to(vec![
+      "$.items[1] -> Expected '1' to be the same type as '5'".to_string()
+    ]));
+    expect!(mismatch_messages(match_json(&expected, &request!(r#"{"items": [4, 5, 6]}"#), &context))).to(be_equal_to(vec![
+      "$.items -> Expected a List with exactly 2 item(s) but received 3 item(s)".to_string()
+    ]));
+  }
+
+  fn equals_path_context(reference: &str) -> CoreMatchingContext {
+    CoreMatchingContext::new(DiffConfig::AllowUnexpectedKeys, &matchingrules! {
+      "body" => {
+        "$.confirmPassword" => [ MatchingRule::EqualsPath(reference.to_string()) ],
+        "$.password" => [ MatchingRule::Type ]
+      }
+    }.rules_for_category("body").unwrap(), &hashmap!{})
+  }
+
+  #[test]
+  fn match_json_with_equals_path_matcher_passes_if_the_values_are_equal() {
+    let expected = request!(r#"{"password": "secret", "confirmPassword": "secret"}"#);
+    let actual = request!(r#"{"password": "hunter2", "confirmPassword": "hunter2"}"#);
+    expect!(match_json(&expected, &actual, &equals_path_context("$.password"))).to(be_ok());
+  }
+
+  #[test]
+  fn match_json_with_equals_path_matcher_fails_if_the_values_are_not_equal() {
+    let expected = request!(r#"{"password": "secret", "confirmPassword": "secret"}"#);
+    let actual = request!(r#"{"password": "hunter2", "confirmPassword": "hunter3"}"#);
+    let result = match_json(&expected, &actual, &equals_path_context("$.password"));
+    expect!(mismatch_messages(result)).to(be_equal_to(vec![
+      "$.confirmPassword -> Expected 'hunter3' to be equal to the value at '$.password' ('hunter2')".to_string()
+    ]));
+  }
+
+  #[test]
+  fn match_json_with_equals_path_matcher_fails_if_the_referenced_path_does_not_exist() {
+    let expected = request!(r#"{"password": "secret", "confirmPassword": "secret"}"#);
+    let actual = request!(r#"{"password": "hunter2", "confirmPassword": "hunter2"}"#);
+    let result = match_json(&expected, &actual, &equals_path_context("$.user.password"));
+    expect!(mismatch_messages(result)).to(be_equal_to(vec![
+      "$.confirmPassword -> Expected a value at '$.user.password' to compare 'hunter2' to, but the path does not exist in the document".to_string()
+    ]));
+  }
+
+  #[test]
+  fn base64_matcher_test() {
+    let matcher = MatchingRule::Base64;
+    expect!(json!("aGVsbG8=").matches_with(&json!("d29ybGQ="), &matcher, false)).to(be_ok());
+    expect!(json!("aGVsbG8=").matches_with(&json!("d29y*GQ="), &matcher, false)).to(be_err());
+    expect!(json!("aGVsbG8=").matches_with(&json!("d29ybGQ"), &matcher, false)).to(be_err());
+    expect!(json!("aGVsbG8=").matches_with(&json!(100), &matcher, false)).to(be_err());
+    expect!(json!("aGVsbG8=").matches_with(&Value::Null, &matcher, false)).to(be_err());
+  }
+
+  #[test]
+  fn match_json_compares_numbers_by_value_if_configured() {
+    let context = CoreMatchingContext {
+      numeric_equality_ignores_representation: true,
+      .. CoreMatchingContext::with_config(DiffConfig::AllowUnexpectedKeys)
+    };
+    let expected = request!(r#"{"price": 100}"#);
+
+    let result = match_json(&expected, &request!(r#"{"price": 100.0}"#), &context);
+    expect!(result).to(be_ok());
+
+    let result = match_json(&expected, &request!(r#"{"price": 101}"#), &context);
+    expect!(mismatch_message(&result)).to(be_equal_to("Expected '100' to be equal to '101'"));
+  }
+
+  #[test]
+  fn match_json_compares_numbers_strictly_by_default() {
+    let context = CoreMatchingContext::with_config(DiffConfig::AllowUnexpectedKeys);
+    let expected = request!(r#"{"price": 100}"#);
+
+    expect!(match_json(&expected, &request!(r#"{"price": 100.0}"#), &context)).to(be_err());
+    expect!(match_json(&expected, &request!(r#"{"price": 101}"#), &context)).to(be_err());
+    expect!(match_json(&expected, &request!(r#"{"price": 100}"#), &context)).to(be_ok());
+  }
+
+  #[test]
+  fn match_json_treats_null_and_empty_strings_as_equal_if_configured() {
+    let context = CoreMatchingContext {
+      empty_string_equals_null: true,
+      .. CoreMatchingContext::with_config(DiffConfig::AllowUnexpectedKeys)
+    };
+    let expected = request!(r#"{"middleName": null}"#);
+
+    expect!(match_json(&expected, &request!(r#"{"middleName": ""}"#), &context)).to(be_ok());
+    expect!(match_json(&request!(r#"{"middleName": ""}"#), &request!(r#"{"middleName": null}"#), &context)).to(be_ok());
+    let result = match_json(&expected, &request!(r#"{"middleName": "Fred"}"#), &context);
+    expect!(mismatch_message(&result)).to(be_equal_to("Expected '' to be equal to 'Fred'"));
+  }
+
+  #[test]
+  fn match_json_does_not_treat_null_and_empty_strings_as_equal_by_default() {
+    let context = CoreMatchingContext::with_config(DiffConfig::AllowUnexpectedKeys);
+    let expected = request!(r#"{"middleName": null}"#);
+
+    expect!(match_json(&expected, &request!(r#"{"middleName": ""}"#), &context)).to(be_err());
+  }
+
+  #[test]
+  fn duration_matcher_test() {
+    let matcher = MatchingRule::Duration;
+    expect!(json!("PT1H").matches_with(&json!("PT1H30M"), &matcher, false)).to(be_ok());
+    expect!(json!("PT1H").matches_with(&json!("P1Y2M10DT2H30M"), &matcher, false)).to(be_ok());
+    expect!(json!("PT1H").matches_with(&json!("1 hour"), &matcher, false)).to(be_err());
+    expect!(json!("PT1H").matches_with(&json!("PT"), &matcher, false)).to(be_err());
+    expect!(json!("PT1H").matches_with(&json!(3600), &matcher, false)).to(be_err());
+    expect!(json!("PT1H").matches_with(&Value::Null, &matcher, false)).to(be_err());
+  }
+
   #[test_log::test]
   fn compare_maps_handles_wildcard_matchers() {
     let val1 = request!(r#"
@@ -983,14 +1876,16 @@ mod tests {
         "$" => [ MatchingRule::ArrayContains(vec![]) ]
       }
     }.rules_for_category("body").unwrap(), &hashmap!{}));
-    expect!(result).to(be_err().value(vec![
+    expect!(result.clone()).to(be_err().value(vec![
       BodyMismatch {
         path: "$".to_string(),
         expected: Some("3".into()),
         actual: Some("[\"10\",\"22\",\"6\",\"1\",\"5\",\"2\"]".into()),
-        mismatch: "Variant at index 2 (3) was not found in the actual list".to_string()
+        mismatch: "Variant at index 2 (3) was not found in the actual list".to_string(),
+        rule: None
       }
     ]));
+    expect!(mismatch_rule(&result)).to(be_some().value("ArrayContains"));
   }
 
   #[test]
@@ -1129,4 +2024,16 @@ mod tests {
     let result = compare_maps(&DocPath::root(), expected, actual, &context);
     expect!(result).to(be_err());
   }
+
+  #[test]
+  fn display_diff_ignores_the_order_of_object_keys() {
+    let expected = r#"{"id": 100, "name": "Fred", "address": {"street": "Main St", "city": "Sydney"}}"#;
+    let actual = r#"{"address": {"city": "Sydney", "street": "Main St"}, "name": "Fred", "id": 100}"#;
+    let diff = display_diff(expected, actual, "$", "");
+    expect!(diff.lines().any(|line| line.contains('+') || line.contains('-'))).to(be_false());
+
+    let diff = display_diff(expected, r#"{"name": "Mary", "id": 100}"#, "$", "");
+    expect!(diff.lines().any(|line| line.contains("-  \"name\": \"Fred\""))).to(be_true());
+    expect!(diff.lines().any(|line| line.contains("+  \"name\": \"Mary\""))).to(be_true());
+  }
 }
@@ -0,0 +1,135 @@
+//! Validation of JSON values against a JSON Schema.
+//!
+//! The validation is done with the `jsonschema` crate, which supports all the JSON Schema drafts
+//! (the draft is taken from the `$schema` keyword, defaulting to 2020-12). Format keywords are
+//! validated, and references are only resolved within the schema itself. A schema that is invalid
+//! or can not be resolved results in a violation.
+
+use jsonschema::paths::LocationSegment;
+use serde_json::Value;
+
+use pact_models::path_exp::DocPath;
+
+/// A violation of a JSON Schema
+#[derive(Debug, Clone, PartialEq)]
+pub struct SchemaViolation {
+  /// Path to the value that violates the schema
+  pub path: DocPath,
+  /// Description of the violation
+  pub message: String
+}
+
+/// Validates the value against the JSON Schema, returning all the violations found.
+pub fn validate_json_schema(path: &DocPath, schema: &Value, value: &Value) -> Vec<SchemaViolation> {
+  match jsonschema::options().should_validate_formats(true).build(schema) {
+    Ok(validator) => validator.iter_errors(value)
+      .map(|error| {
+        let mut violation_path = path.clone();
+        for segment in &error.instance_path {
+          match segment {
+            LocationSegment::Property(name) => violation_path.push_field(name),
+            LocationSegment::Index(index) => violation_path.push_index(index)
+          };
+        }
+        SchemaViolation { path: violation_path, message: error.to_string() }
+      })
+      .collect(),
+    Err(err) => vec![ SchemaViolation { path: path.clone(), message: format!("Invalid JSON Schema - {}", err) } ]
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use expectest::prelude::*;
+  use serde_json::json;
+
+  use super::*;
+
+  fn messages(schema: &Value, value: &Value) -> Vec<String> {
+    validate_json_schema(&DocPath::root(), schema, value).iter()
+      .map(|v| format!("{} -> {}", v.path, v.message))
+      .collect()
+  }
+
+  #[test]
+  fn validates_types() {
+    let schema = json!({ "type": "object" });
+    expect!(messages(&schema, &json!({})).is_empty()).to(be_true());
+    expect!(messages(&schema, &json!([]))).to(be_equal_to(vec![
+      "$ -> [] is not of type \"object\"".to_string()
+    ]));
+    expect!(messages(&json!({ "type": ["string", "null"] }), &Value::Null).is_empty()).to(be_true());
+    expect!(messages(&json!({ "type": "integer" }), &json!(1.0)).is_empty()).to(be_true());
+    expect!(messages(&json!({ "type": "integer" }), &json!(1.5)).is_empty()).to(be_false());
+  }
+
+  #[test]
+  fn validates_nested_properties() {
+    let schema = json!({
+      "type": "object",
+      "required": ["id", "name"],
+      "properties": {
+        "id": { "type": "integer", "minimum": 1 },
+        "name": { "type": "string", "minLength": 1 },
+        "tags": { "type": "array", "items": { "type": "string" } }
+      },
+      "additionalProperties": false
+    });
+    expect!(messages(&schema, &json!({ "id": 1, "name": "a", "tags": ["x"] })).is_empty()).to(be_true());
+    expect!(messages(&schema, &json!({ "id": 0, "tags": ["x", 2], "other": true }))).to(be_equal_to(vec![
+      "$.id -> 0 is less than the minimum of 1".to_string(),
+      "$.tags[1] -> 2 is not of type \"string\"".to_string(),
+      "$ -> Additional properties are not allowed ('other' was unexpected)".to_string(),
+      "$ -> \"name\" is a required property".to_string()
+    ]));
+  }
+
+  #[test]
+  fn validates_strings() {
+    expect!(messages(&json!({ "pattern": "^\\d+$" }), &json!("123")).is_empty()).to(be_true());
+    expect!(messages(&json!({ "pattern": "\\d" }), &json!("a1b")).is_empty()).to(be_true());
+    expect!(messages(&json!({ "pattern": "^\\d+$" }), &json!("12a")).is_empty()).to(be_false());
+    expect!(messages(&json!({ "maxLength": 2 }), &json!("abc")).is_empty()).to(be_false());
+  }
+
+  #[test]
+  fn validates_combinations() {
+    let schema = json!({ "anyOf": [{ "type": "string" }, { "type": "integer" }] });
+    expect!(messages(&schema, &json!(1)).is_empty()).to(be_true());
+    expect!(messages(&schema, &json!(true)).is_empty()).to(be_false());
+    let schema = json!({ "oneOf": [{ "type": "number" }, { "type": "integer" }] });
+    expect!(messages(&schema, &json!(1.5)).is_empty()).to(be_true());
+    expect!(messages(&schema, &json!(1)).is_empty()).to(be_false());
+    expect!(messages(&json!({ "enum": ["a", "b"] }), &json!("c")).is_empty()).to(be_false());
+    expect!(messages(&json!(false), &json!("c")).is_empty()).to(be_false());
+  }
+
+  #[test]
+  fn validates_formats() {
+    let schema = json!({ "type": "string", "format": "email" });
+    expect!(messages(&schema, &json!("fred@example.com")).is_empty()).to(be_true());
+    expect!(messages(&schema, &json!("fred"))).to(be_equal_to(vec![
+      "$ -> \"fred\" is not a \"email\"".to_string()
+    ]));
+  }
+
+  #[test]
+  fn resolves_references_within_the_schema() {
+    let schema = json!({
+      "$defs": { "id": { "type": "integer" } },
+      "type": "object",
+      "properties": { "id": { "$ref": "#/$defs/id" } }
+    });
+    expect!(messages(&schema, &json!({ "id": 1 })).is_empty()).to(be_true());
+    expect!(messages(&schema, &json!({ "id": "1" }))).to(be_equal_to(vec![
+      "$.id -> \"1\" is not of type \"integer\"".to_string()
+    ]));
+  }
+
+  #[test]
+  fn returns_a_violation_for_an_invalid_schema() {
+    let result = messages(&json!({ "type": 100 }), &json!(1));
+    expect!(result.len()).to(be_equal_to(1));
+    expect!(result[0].starts_with("$ -> Invalid JSON Schema - ")).to(be_true());
+  }
+}
@@ -168,6 +168,18 @@
 //! matcher
 //! 2. Otherwise compare the values using equality.
 //!
+//! #### Protobuf body matching rules
+//!
+//! Protobuf bodies (`application/protobuf` or `application/grpc`) are decoded into a tree of fields
+//! keyed by the field names, using the protobuf descriptors from the matching context
+//! (see `CoreMatchingContext.protobuf_descriptors`). When matching interactions, these are loaded
+//! from the plugin configuration in the same way as the protobuf plugin (the `descriptorKey` of the
+//! interaction is the key of the Base64 encoded `protoDescriptors` in the Pact). The message type
+//! is taken from the `message` parameter of the content type
+//! (i.e. `application/protobuf; message=.package.MessageType`).
+//! The decoded fields are then compared using the JSON body matching rules. If there are no
+//! descriptors available, the bodies don't match.
+//!
 //! ### Matching Paths
 //!
 //! Paths are matched by the following:
@@ -339,6 +351,24 @@
 //! | NotEmpty | V4 | `{ "match": "notEmpty" }` | Value must be present and not empty (not null or the empty string) |
 //! | Semver | V4 | `{ "match": "semver" }` | Value must be valid based on the semver specification |
 //! | Semver | V4 | `{ "match": "semver" }` | Value must be valid based on the semver specification |
+//! | Email | V4 | `{ "match": "email" }` | Value must be an email address |
+//! | Base64 | V4 | `{ "match": "base64" }` | Value must be a base64 encoded string |
+//! | JsonSchema | V4 | `{ "match": "jsonSchema", "schema": { "type": "object" } }` | Value must conform to the JSON Schema (references are only resolved within the schema) |
+//! | UniqueItems | V4 | `{ "match": "uniqueItems", "key": "$.id" }` | Array items must be unique. If a key path is given, only the values at that path in each item must be unique |
+//! | ArrayLength | V4 | `{ "match": "arrayLength", "length": 3 }` | Array must have exactly the given number of items. The items themselves are not compared |
+//! | EqualsPath | V4 | `{ "match": "equalsPath", "path": "$.password" }` | Value must be equal to the value at the given path in the same body (only supported for JSON bodies) |
+//! | AnyType | V4 | `{ "match": "anyType", "types": ["string", "number"] }` | Value must be any one of the listed types (`string`, `number`, `integer`, `decimal`, `boolean`, `null`, `array` or `object`) |
+//! | NamedRegex | V4 | `{ "match": "namedRegex", "name": "postcode" }` | Value must match the regular expression registered under the name with `register_named_regex` |
+//! | OneOf | V4 | `{ "match": "oneOf", "values": ["active", "inactive"] }` | Value must be equal to one of the values in the set |
+//! | ValidJson | V4 | `{ "match": "validJson" }` | Value must be a string containing valid JSON |
+//! | Iso8601Timestamp | V4 | `{ "match": "iso8601Timestamp" }` | Value must be a string containing an RFC-3339 timestamp (i.e. `2023-01-01T12:00:00Z`) |
+//! | Hexadecimal | V4 | `{ "match": "hexadecimal", "length": 40 }` | Value must be a string of hexadecimal digits. If a length is given, it must have exactly that many digits |
+//! | IpAddress | V4 | `{ "match": "ipAddress", "version": 4 }` | Value must be a string containing an IP address. If a version (4 or 6) is given, the address must be of that version |
+//! | Luhn | V4 | `{ "match": "luhn" }` | Value must be a number (or string) whose digits pass the Luhn checksum. Non-digit characters are ignored |
+//! | Latitude | V4 | `{ "match": "latitude" }` | Value must be a number (or numeric string) between -90 and 90 |
+//! | Longitude | V4 | `{ "match": "longitude" }` | Value must be a number (or numeric string) between -180 and 180 |
+//! | NumberRange | V4 | `{ "match": "numberRange", "min": 0, "max": 1000, "inclusive": true }` | Value must be a number (or numeric string) within the range. Inclusive defaults to true |
+//! | RecentTimestamp | V4 | `{ "match": "recentTimestamp", "withinSeconds": 60 }` | Value must be an RFC-3339 timestamp no more than the given number of seconds before the current time (a few seconds in the future is allowed for clock skew) |
 //! | EachKey | V4 | `{ "match": "eachKey", "rules": [{"match": "regex", "regex": "\\$(\\.\\w+)+"}], "value": "$.test.one" }` | Allows defining matching rules to apply to the keys in a map |
 //! | EachValue | V4 | `{ "match": "eachValue", "rules": [{"match": "regex", "regex": "\\$(\\.\\w+)+"}], "value": "$.test.one" }` | Allows defining matching rules to apply to the values in a collection. For maps, delgates to the Values matcher. |
 
@@ -362,6 +392,7 @@ use maplit::{hashmap, hashset};
 use pact_models::bodies::OptionalBody;
 use pact_models::content_types::ContentType;
 use pact_models::generators::{apply_generators, GenerateValue, GeneratorCategory, GeneratorTestMode, VariantMatcher};
+use pact_models::headers::SINGLE_VALUE_HEADERS;
 use pact_models::http_parts::HttpPart;
 use pact_models::interaction::Interaction;
 use pact_models::json_utils::json_to_string;
@@ -374,6 +405,7 @@ use pact_models::v4::message_parts::MessageContents;
 use pact_models::v4::sync_message::SynchronousMessage;
 use pact_plugin_driver::catalogue_manager::find_content_matcher;
 use pact_plugin_driver::plugin_models::PluginInteractionConfig;
+use prost_types::FileDescriptorSet;
 use serde_json::{json, Value};
 
 use crate::generators::{DefaultVariantMatcher, generators_process_body};
@@ -393,6 +425,7 @@ pub const PACT_RUST_VERSION: Option<&'static str> = option_env!("CARGO_PKG_VERSI
 
 pub mod matchers;
 pub mod json;
+mod json_schema;
 pub mod logging;
 pub mod matchingrules;
 pub mod metrics;
@@ -401,6 +434,7 @@ mod xml;
 mod binary_utils;
 mod headers;
 mod generators;
+mod protobuf;
 
 /// Context used to apply matching logic
 pub trait MatchingContext {
@@ -433,6 +467,41 @@ pub trait MatchingContext {
 
   /// Clones the current context with the provided matching rules
   fn clone_with(&self, matchers: &MatchingRuleCategory) -> Box<dyn MatchingContext>;
+
+  /// If JSON numbers should be compared by their numeric value when no matcher is defined
+  /// (i.e. `100` is equal to `100.0`). Defaults to strict equality.
+  fn numeric_equality_ignores_representation(&self) -> bool {
+    false
+  }
+
+  /// Protobuf descriptors to use to decode protobuf message bodies. Defaults to none.
+  fn protobuf_descriptors(&self) -> Option<&FileDescriptorSet> {
+    None
+  }
+
+  /// If null and empty string values (and null and empty bodies) should be treated as equal when
+  /// no matcher is defined. Defaults to strict equality.
+  fn empty_string_equals_null(&self) -> bool {
+    false
+  }
+
+  /// If the expected XML document should be matched as a subtree of the actual document, anchored
+  /// on the first actual element with the same name as the expected root element. Defaults to
+  /// matching the whole document.
+  fn xml_subtree_match(&self) -> bool {
+    false
+  }
+
+  /// If the values of the header should be compared as a whole, instead of being split on commas.
+  /// Defaults to the headers in `SINGLE_VALUE_HEADERS` (i.e. `Set-Cookie` and `Date`).
+  fn is_single_value_header(&self, name: &str) -> bool {
+    SINGLE_VALUE_HEADERS.contains(&name.to_lowercase().as_str())
+  }
+
+  /// How headers that were not expected are treated. Defaults to ignoring them.
+  fn header_match_config(&self) -> HeaderMatchConfig {
+    HeaderMatchConfig::AllowUnexpectedHeaders
+  }
 }
 
 #[derive(Debug, Clone)]
@@ -445,7 +514,19 @@ pub struct CoreMatchingContext {
   /// Specification version to apply when matching with the context
   pub matching_spec: PactSpecification,
   /// Any plugin configuration available for the interaction
-  pub plugin_configuration: HashMap<String, PluginInteractionConfig>
+  pub plugin_configuration: HashMap<String, PluginInteractionConfig>,
+  /// If JSON numbers should be compared by their numeric value instead of their representation
+  pub numeric_equality_ignores_representation: bool,
+  /// Protobuf descriptors to use to decode protobuf message bodies
+  pub protobuf_descriptors: Option<FileDescriptorSet>,
+  /// If null and empty string values (and null and empty bodies) should be treated as equal
+  pub empty_string_equals_null: bool,
+  /// If the expected XML document should be matched as a subtree anywhere in the actual document
+  pub xml_subtree_match: bool,
+  /// Headers (in lowercase) whose values are compared as a whole instead of being split on commas
+  pub single_value_headers: Vec<String>,
+  /// How headers that were not expected are treated
+  pub header_match_config: HeaderMatchConfig
 }
 
 impl CoreMatchingContext {
@@ -492,7 +573,13 @@ impl Default for CoreMatchingContext {
       matchers: Default::default(),
       config: DiffConfig::AllowUnexpectedKeys,
       matching_spec: PactSpecification::V3,
-      plugin_configuration: Default::default()
+      plugin_configuration: Default::default(),
+      numeric_equality_ignores_representation: false,
+      protobuf_descriptors: None,
+      empty_string_equals_null: false,
+      xml_subtree_match: false,
+      single_value_headers: SINGLE_VALUE_HEADERS.iter().map(|h| h.to_string()).collect(),
+      header_match_config: HeaderMatchConfig::AllowUnexpectedHeaders
     }
   }
 }
@@ -545,6 +632,7 @@ impl MatchingContext for CoreMatchingContext {
             expected: Some(expected.for_mismatch().into()),
             actual: Some(actual.for_mismatch().into()),
             mismatch: format!("Actual map is missing the following keys: {}", missing_keys.join(", ")),
+            rule: None,
           });
         }
         DiffConfig::NoUnexpectedKeys if expected_keys != actual_keys => {
@@ -554,6 +642,7 @@ impl MatchingContext for CoreMatchingContext {
             actual: Some(actual.for_mismatch().into()),
             mismatch: format!("Expected a Map with keys {} but received one with keys {}",
                               expected_keys.join(", "), actual_keys.join(", ")),
+            rule: None,
           });
         }
         _ => {}
@@ -576,6 +665,7 @@ impl MatchingContext for CoreMatchingContext {
                         expected: Some("".to_string().into()),
                         actual: Some(key.clone().into()),
                         mismatch: err.to_string(),
+                        rule: Some(matching_rule_label(&rule)),
                       });
                     }
                   }
@@ -587,6 +677,7 @@ impl MatchingContext for CoreMatchingContext {
                     actual: Some(actual.for_mismatch().into()),
                     mismatch: format!("Expected a matching rule, found an unresolved reference '{}'",
                       name.name),
+                    rule: None,
                   });
                 }
               }
@@ -621,20 +712,52 @@ impl MatchingContext for CoreMatchingContext {
       matchers: matchers.clone(),
       config: self.config.clone(),
       matching_spec: self.matching_spec,
-      plugin_configuration: self.plugin_configuration.clone()
+      plugin_configuration: self.plugin_configuration.clone(),
+      numeric_equality_ignores_representation: self.numeric_equality_ignores_representation,
+      protobuf_descriptors: self.protobuf_descriptors.clone(),
+      empty_string_equals_null: self.empty_string_equals_null,
+      xml_subtree_match: self.xml_subtree_match,
+      single_value_headers: self.single_value_headers.clone(),
+      header_match_config: self.header_match_config.clone()
     })
   }
+
+  fn numeric_equality_ignores_representation(&self) -> bool {
+    self.numeric_equality_ignores_representation
+  }
+
+  fn protobuf_descriptors(&self) -> Option<&FileDescriptorSet> {
+    self.protobuf_descriptors.as_ref()
+  }
+
+  fn empty_string_equals_null(&self) -> bool {
+    self.empty_string_equals_null
+  }
+
+  fn xml_subtree_match(&self) -> bool {
+    self.xml_subtree_match
+  }
+
+  fn is_single_value_header(&self, name: &str) -> bool {
+    self.single_value_headers.iter().any(|header| header.eq_ignore_ascii_case(name))
+  }
+
+  fn header_match_config(&self) -> HeaderMatchConfig {
+    self.header_match_config.clone()
+  }
 }
 
 lazy_static! {
   static ref BODY_MATCHERS: [
     (fn(content_type: &ContentType) -> bool,
-    fn(expected: &dyn HttpPart, actual: &dyn HttpPart, context: &dyn MatchingContext) -> Result<(), Vec<Mismatch>>); 4]
+    fn(expected: &dyn HttpPart, actual: &dyn HttpPart, context: &dyn MatchingContext) -> Result<(), Vec<Mismatch>>); 6]
      = [
       (|content_type| { content_type.is_json() }, json::match_json),
+      (|content_type| { content_type.base_type() == "application/x-ndjson" }, json::match_ndjson),
       (|content_type| { content_type.is_xml() }, xml::match_xml),
       (|content_type| { content_type.base_type() == "application/octet-stream" }, binary_utils::match_octet_stream),
-      (|content_type| { content_type.base_type() == "multipart/form-data" }, binary_utils::match_mime_multipart)
+      (|content_type| { content_type.base_type() == "multipart/form-data" }, binary_utils::match_mime_multipart),
+      (|content_type| { protobuf::is_protobuf_content_type(content_type) }, protobuf::match_protobuf)
   ];
 }
 
@@ -710,7 +833,10 @@ pub enum Mismatch {
       /// actual value
       actual: Option<Bytes>,
       /// description of the mismatch
-      mismatch: String
+      mismatch: String,
+      /// label of the matching rule that caused the mismatch (i.e. `Regex` or `Type`). Will be
+      /// `None` if the values were compared for equality.
+      rule: Option<String>
     },
     /// Message metadata mismatch
     MetadataMismatch {
@@ -722,6 +848,13 @@ pub enum Mismatch {
       actual: String,
       /// description of the mismatch
       mismatch: String
+    },
+    /// Interaction was missing or unexpected when comparing two pacts
+    InteractionMismatch {
+      /// description of the interaction
+      interaction: String,
+      /// description of the mismatch
+      mismatch: String
     }
 }
 
@@ -794,8 +927,8 @@ impl Mismatch {
           }
         })
       },
-      Mismatch::BodyMismatch { path, expected, actual, mismatch } => {
-        json!({
+      Mismatch::BodyMismatch { path, expected, actual, mismatch, rule } => {
+        let mut json = json!({
           "type" : "BodyMismatch",
           "path" : path,
           "expected" : match expected {
@@ -807,7 +940,11 @@ impl Mismatch {
             None => serde_json::Value::Null
           },
           "mismatch" : mismatch
-        })
+        });
+        if let (Some(rule), Some(map)) = (rule, json.as_object_mut()) {
+          map.insert("rule".to_string(), serde_json::Value::String(rule.clone()));
+        }
+        json
       }
       Mismatch::MetadataMismatch { key, expected, actual, mismatch } => {
         json!({
@@ -818,6 +955,13 @@ impl Mismatch {
           "mismatch" : mismatch
         })
       }
+      Mismatch::InteractionMismatch { interaction, mismatch } => {
+        json!({
+          "type" : "InteractionMismatch",
+          "interaction" : interaction,
+          "mismatch" : mismatch
+        })
+      }
     }
   }
 
@@ -831,7 +975,8 @@ impl Mismatch {
         Mismatch::HeaderMismatch { .. } => "HeaderMismatch",
         Mismatch::BodyTypeMismatch { .. } => "BodyTypeMismatch",
         Mismatch::BodyMismatch { .. } => "BodyMismatch",
-        Mismatch::MetadataMismatch { .. } => "MetadataMismatch"
+        Mismatch::MetadataMismatch { .. } => "MetadataMismatch",
+        Mismatch::InteractionMismatch { .. } => "InteractionMismatch"
       }
     }
 
@@ -845,7 +990,8 @@ impl Mismatch {
         Mismatch::HeaderMismatch { ref key, expected: ref e, .. } => format!("includes header '{}' with value '{}'", key, e),
         Mismatch::BodyTypeMismatch { .. } => s!("has a matching body"),
         Mismatch::BodyMismatch { .. } => s!("has a matching body"),
-        Mismatch::MetadataMismatch { .. } => s!("has matching metadata")
+        Mismatch::MetadataMismatch { .. } => s!("has matching metadata"),
+        Mismatch::InteractionMismatch { ref interaction, .. } => format!("has interaction '{}'", interaction)
       }
     }
 
@@ -859,7 +1005,8 @@ impl Mismatch {
         Mismatch::HeaderMismatch { mismatch, .. } => mismatch.clone(),
         Mismatch::BodyTypeMismatch {  expected: e, actual: a, .. } => format!("expected '{}' body but was '{}'", e, a),
         Mismatch::BodyMismatch { path, mismatch, .. } => format!("{} -> {}", path, mismatch),
-        Mismatch::MetadataMismatch { mismatch, .. } => mismatch.clone()
+        Mismatch::MetadataMismatch { mismatch, .. } => mismatch.clone(),
+        Mismatch::InteractionMismatch { mismatch, .. } => mismatch.clone()
       }
     }
 
@@ -876,7 +1023,8 @@ impl Mismatch {
         Mismatch::BodyTypeMismatch {  expected: e, actual: a, .. } => format!("expected '{}' body but was '{}'", Red.paint(e.clone()), Green.paint(a.clone())),
         Mismatch::BodyMismatch { path, mismatch, .. } => format!("{} -> {}", Style::new().bold().paint(path.clone()), mismatch),
         Mismatch::MetadataMismatch { expected: e, actual: a, key: k, .. } => format!("Expected message metadata '{}' to have value '{}' but was '{}'",
-          Style::new().bold().paint(k.clone()), Red.paint(e.to_string()), Green.paint(a.to_string()))
+          Style::new().bold().paint(k.clone()), Red.paint(e.to_string()), Green.paint(a.to_string())),
+        Mismatch::InteractionMismatch { mismatch, .. } => Red.paint(mismatch.clone()).to_string()
       }
     }
 }
@@ -916,6 +1064,10 @@ impl PartialEq for Mismatch {
         Mismatch::MetadataMismatch { key: p2, expected: e2, actual: a2, .. }) => {
         p1 == p2 && e1 == e2 && a1 == a2
       },
+      (Mismatch::InteractionMismatch { interaction: i1, mismatch: m1 },
+        Mismatch::InteractionMismatch { interaction: i2, mismatch: m2 }) => {
+        i1 == i2 && m1 == m2
+      },
       (_, _) => false
     }
   }
@@ -1097,6 +1249,54 @@ pub enum DiffConfig {
     NoUnexpectedKeys
 }
 
+/// Standard headers that are not reported as unexpected when unexpected headers cause a mismatch
+pub const DEFAULT_ALLOWED_UNEXPECTED_HEADERS: [&str; 8] = ["date", "content-length", "connection",
+  "keep-alive", "transfer-encoding", "server", "vary", "access-control-allow-origin"];
+
+/// Enum that defines how unexpected headers are treated when matching headers.
+#[derive(Debug, Clone, PartialEq)]
+pub enum HeaderMatchConfig {
+    /// Unexpected headers are ignored during matching (the default).
+    AllowUnexpectedHeaders,
+    /// Unexpected headers cause a mismatch, except for the headers in the allowlist
+    /// (compared case-insensitively).
+    NoUnexpectedHeaders(Vec<String>)
+}
+
+impl HeaderMatchConfig {
+  /// Strict header matching, allowing the standard headers in `DEFAULT_ALLOWED_UNEXPECTED_HEADERS`
+  pub fn strict() -> Self {
+    HeaderMatchConfig::NoUnexpectedHeaders(DEFAULT_ALLOWED_UNEXPECTED_HEADERS.iter()
+      .map(|h| h.to_string()).collect())
+  }
+}
+
+impl Default for HeaderMatchConfig {
+  fn default() -> Self {
+    HeaderMatchConfig::AllowUnexpectedHeaders
+  }
+}
+
+/// Configuration used when matching requests and responses with `match_request_with_config` and
+/// `match_response_with_config`
+#[derive(Debug, Clone, PartialEq)]
+pub struct MatchingConfig {
+  /// How headers that were not expected are treated
+  pub header_config: HeaderMatchConfig,
+  /// How unexpected keys in request bodies are treated. Defaults to `NoUnexpectedKeys`, as
+  /// required by the Pact specification.
+  pub request_diff_config: DiffConfig
+}
+
+impl Default for MatchingConfig {
+  fn default() -> Self {
+    MatchingConfig {
+      header_config: HeaderMatchConfig::AllowUnexpectedHeaders,
+      request_diff_config: DiffConfig::NoUnexpectedKeys
+    }
+  }
+}
+
 /// Matches the actual text body to the expected one.
 pub fn match_text(expected: &Option<Bytes>, actual: &Option<Bytes>, context: &dyn MatchingContext) -> Result<(), Vec<Mismatch>> {
   let path = DocPath::root();
@@ -1110,7 +1310,8 @@ pub fn match_text(expected: &Option<Bytes>, actual: &Option<Bytes>, context: &dy
           path: "$".to_string(),
           expected: expected.clone(),
           actual: actual.clone(),
-          mismatch: format!("Could not parse expected value as UTF-8 text: {}", err)
+          mismatch: format!("Could not parse expected value as UTF-8 text: {}", err),
+          rule: None
         });
         ""
       }
@@ -1122,18 +1323,21 @@ pub fn match_text(expected: &Option<Bytes>, actual: &Option<Bytes>, context: &dy
           path: "$".to_string(),
           expected: expected.clone(),
           actual: actual.clone(),
-          mismatch: format!("Could not parse actual value as UTF-8 text: {}", err)
+          mismatch: format!("Could not parse actual value as UTF-8 text: {}", err),
+          rule: None
         });
         ""
       }
     };
-    if let Err(messages) = match_values(&path, &context.select_best_matcher(&path), expected_str, actual_str) {
+    let rules = context.select_best_matcher(&path);
+    if let Err(messages) = match_values(&path, &rules, expected_str, actual_str) {
       for message in messages {
         mismatches.push(Mismatch::BodyMismatch {
           path: "$".to_string(),
           expected: expected.clone(),
           actual: actual.clone(),
-          mismatch: message.clone()
+          mismatch: message.clone(),
+          rule: rule_list_label(&rules)
         })
       }
     };
@@ -1145,7 +1349,7 @@ pub fn match_text(expected: &Option<Bytes>, actual: &Option<Bytes>, context: &dy
   } else if expected != actual {
     Err(vec![ Mismatch::BodyMismatch { path: "$".to_string(), expected: expected.clone(),
       actual: actual.clone(),
-      mismatch: format!("Expected text '{:?}' but received '{:?}'", expected, actual) } ])
+      mismatch: format!("Expected text '{:?}' but received '{:?}'", expected, actual), rule: None } ])
   } else {
     Ok(())
   }
@@ -1160,11 +1364,15 @@ pub fn match_method(expected: &str, actual: &str) -> Result<(), Mismatch> {
   }
 }
 
-/// Matches the actual request path to the expected one.
+/// Matches the actual request path to the expected one. A matcher defined for the whole path is
+/// applied to the path as a single string. Otherwise, if there are matchers defined for
+/// individual path segments (i.e. `$.path[1]`), the paths are compared segment by segment.
 pub fn match_path(expected: &str, actual: &str, context: &(dyn MatchingContext + Send + Sync)) -> Result<(), Vec<Mismatch>> {
   let path = DocPath::empty();
   let matcher_result = if context.matcher_is_defined(&path) {
     match_values(&path, &context.select_best_matcher(&path), expected.to_string(), actual.to_string())
+  } else if segment_matchers_defined(expected, context) {
+    match_path_segments(expected, actual, context)
   } else {
     expected.matches_with(actual, &MatchingRule::Equality, false).map_err(|err| vec![err])
       .map_err(|errors| errors.iter().map(|err| err.to_string()).collect())
@@ -1177,6 +1385,51 @@ pub fn match_path(expected: &str, actual: &str, context: &(dyn MatchingContext +
   }).collect())
 }
 
+fn path_segments(path: &str) -> Vec<&str> {
+  path.strip_prefix('/').unwrap_or(path).split('/').collect()
+}
+
+fn segment_path(index: usize) -> DocPath {
+  let mut path = DocPath::root();
+  path.push_index(index);
+  path
+}
+
+fn segment_matchers_defined(expected: &str, context: &(dyn MatchingContext + Send + Sync)) -> bool {
+  (0..path_segments(expected).len()).any(|index| context.matcher_is_defined(&segment_path(index)))
+}
+
+fn match_path_segments(
+  expected: &str,
+  actual: &str,
+  context: &(dyn MatchingContext + Send + Sync)
+) -> Result<(), Vec<String>> {
+  let expected_segments = path_segments(expected);
+  let actual_segments = path_segments(actual);
+  if expected_segments.len() != actual_segments.len() {
+    return Err(vec![format!("Expected path '{}' with {} segment(s) but received '{}' with {} segment(s)",
+      expected, expected_segments.len(), actual, actual_segments.len())]);
+  }
+
+  let mismatches: Vec<String> = expected_segments.iter().zip(actual_segments.iter()).enumerate()
+    .flat_map(|(index, (expected, actual))| {
+      let path = segment_path(index);
+      let result = if context.matcher_is_defined(&path) {
+        match_values(&path, &context.select_best_matcher(&path), expected.to_string(), actual.to_string())
+      } else {
+        expected.matches_with(*actual, &MatchingRule::Equality, false)
+          .map_err(|err| vec![format!("Path segment {}: {}", index, err)])
+      };
+      result.err().unwrap_or_default()
+    })
+    .collect();
+  if mismatches.is_empty() {
+    Ok(())
+  } else {
+    Err(mismatches)
+  }
+}
+
 fn compare_query_parameter_value(
   key: &str,
   expected: &str,
@@ -1373,7 +1626,8 @@ async fn compare_bodies(
                 path: mismatch.path.clone(),
                 expected: Some(Bytes::from(mismatch.expected)),
                 actual: Some(Bytes::from(mismatch.actual)),
-                mismatch: mismatch.mismatch.clone()
+                mismatch: mismatch.mismatch.clone(),
+                rule: None
               });
             }
           }
@@ -1409,7 +1663,12 @@ fn compare_bodies_core(
         mismatches.extend_from_slice(&*m);
       }
     },
-    None => {
+    None => if content_type_matcher_defined(context) {
+      debug!("No body matcher defined for content type '{}', but a content type matcher is defined, using binary matcher", content_type);
+      if let Err(m) = binary_utils::match_octet_stream(expected, actual, context) {
+        mismatches.extend_from_slice(&m);
+      }
+    } else {
       debug!("No body matcher defined for content type '{}', using plain text matcher", content_type);
       if let Err(m) = match_text(&expected.body().value(), &actual.body().value(), context) {
         mismatches.extend_from_slice(&*m);
@@ -1419,6 +1678,13 @@ fn compare_bodies_core(
   mismatches
 }
 
+/// If a content type matcher is defined for the root of the body
+fn content_type_matcher_defined(context: &dyn MatchingContext) -> bool {
+  let path = DocPath::root();
+  context.matcher_is_defined(&path) && context.select_best_matcher(&path).rules.iter()
+    .any(|rule| matches!(rule, MatchingRule::ContentType(_)))
+}
+
 async fn match_body_content(
   content_type: &ContentType,
   expected: &(dyn HttpPart + Send + Sync),
@@ -1429,15 +1695,16 @@ async fn match_body_content(
   let actual_body = actual.body();
   match (expected_body, actual_body) {
     (&OptionalBody::Missing, _) => BodyMatchResult::Ok,
+    (e, a) if context.empty_string_equals_null() && is_null_or_empty(e) && is_null_or_empty(a) => BodyMatchResult::Ok,
     (&OptionalBody::Null, &OptionalBody::Present(ref b, _, _)) => {
       BodyMatchResult::BodyMismatches(hashmap!{ "$".into() => vec![Mismatch::BodyMismatch { expected: None, actual: Some(b.clone()),
         mismatch: format!("Expected empty body but received {}", actual_body),
-        path: s!("/")}]})
+        path: s!("/"), rule: None}]})
     },
     (&OptionalBody::Empty, &OptionalBody::Present(ref b, _, _)) => {
       BodyMatchResult::BodyMismatches(hashmap!{ "$".into() => vec![Mismatch::BodyMismatch { expected: None, actual: Some(b.clone()),
         mismatch: format!("Expected empty body but received {}", actual_body),
-        path: s!("/")}]})
+        path: s!("/"), rule: None}]})
     },
     (&OptionalBody::Null, _) => BodyMatchResult::Ok,
     (&OptionalBody::Empty, _) => BodyMatchResult::Ok,
@@ -1446,19 +1713,29 @@ async fn match_body_content(
         expected: e.value(),
         actual: None,
         mismatch: format!("Expected body {} but was missing", e),
-        path: s!("/")}]})
+        path: s!("/"),
+        rule: None}]})
     },
     (e, &OptionalBody::Empty) => {
       BodyMatchResult::BodyMismatches(hashmap!{ "$".into() => vec![Mismatch::BodyMismatch {
         expected: e.value(),
         actual: None,
         mismatch: format!("Expected body {} but was empty", e),
-        path: s!("/")}]})
+        path: s!("/"),
+        rule: None}]})
     },
     (_, _) => compare_bodies(content_type, expected, actual, context).await
   }
 }
 
+fn is_null_or_empty(body: &OptionalBody) -> bool {
+  match body {
+    OptionalBody::Null | OptionalBody::Empty => true,
+    OptionalBody::Present(b, _, _) => b.is_empty(),
+    OptionalBody::Missing => false
+  }
+}
+
 /// Matches the actual body to the expected one. This takes into account the content type of each.
 pub async fn match_body(
   expected: &(dyn HttpPart + Send + Sync),
@@ -1498,6 +1775,17 @@ pub async fn match_request<'a>(
   actual: HttpRequest,
   pact: &Box<dyn Pact + Send + Sync + 'a>,
   interaction: &Box<dyn Interaction + Send + Sync>
+) -> RequestMatchResult {
+  match_request_with_config(expected, actual, pact, interaction, &MatchingConfig::default()).await
+}
+
+/// Matches the expected and actual requests, using the given config
+pub async fn match_request_with_config<'a>(
+  expected: HttpRequest,
+  actual: HttpRequest,
+  pact: &Box<dyn Pact + Send + Sync + 'a>,
+  interaction: &Box<dyn Interaction + Send + Sync>,
+  config: &MatchingConfig
 ) -> RequestMatchResult {
   info!("comparing to expected {}", expected);
   debug!("     body: '{}'", expected.body.str_value());
@@ -1505,18 +1793,25 @@ pub async fn match_request<'a>(
   debug!("     generators: {:?}", expected.generators);
 
   let plugin_data = setup_plugin_config(pact, interaction);
+  let protobuf_descriptors = protobuf::descriptors_from_plugin_config(&plugin_data);
   let path_context = CoreMatchingContext::new(DiffConfig::NoUnexpectedKeys,
     &expected.matching_rules.rules_for_category("path").unwrap_or_default(),
     &plugin_data);
-  let body_context = CoreMatchingContext::new(DiffConfig::NoUnexpectedKeys,
-    &expected.matching_rules.rules_for_category("body").unwrap_or_default(),
-    &plugin_data);
+  let body_context = CoreMatchingContext {
+    protobuf_descriptors,
+    .. CoreMatchingContext::new(config.request_diff_config,
+      &expected.matching_rules.rules_for_category("body").unwrap_or_default(),
+      &plugin_data)
+  };
   let query_context = CoreMatchingContext::new(DiffConfig::NoUnexpectedKeys,
     &expected.matching_rules.rules_for_category("query").unwrap_or_default(),
     &plugin_data);
-  let header_context = CoreMatchingContext::new(DiffConfig::NoUnexpectedKeys,
-    &expected.matching_rules.rules_for_category("header").unwrap_or_default(),
-    &plugin_data);
+  let header_context = CoreMatchingContext {
+    header_match_config: config.header_config.clone(),
+    .. CoreMatchingContext::new(config.request_diff_config,
+      &expected.matching_rules.rules_for_category("header").unwrap_or_default(),
+      &plugin_data)
+  };
   let result = RequestMatchResult {
     method: match_method(&expected.method, &actual.method).err(),
     path: match_path(&expected.path, &actual.path, &path_context).err(),
@@ -1529,7 +1824,9 @@ pub async fn match_request<'a>(
   result
 }
 
-/// Matches the actual response status to the expected one.
+/// Matches the actual response status to the expected one. If there is a status matching rule
+/// defined (i.e. `$.status` in V2 pacts), it is used to match the status (for example, any 2xx
+/// status with a `StatusCode` rule), otherwise the status codes must be equal.
 pub fn match_status(expected: u16, actual: u16, context: &dyn MatchingContext) -> Result<(), Vec<Mismatch>> {
   let path = DocPath::empty();
   if context.matcher_is_defined(&path) {
@@ -1558,29 +1855,46 @@ pub async fn match_response<'a>(
   actual: HttpResponse,
   pact: &Box<dyn Pact + Send + Sync + 'a>,
   interaction: &Box<dyn Interaction + Send + Sync>
+) -> Vec<Mismatch> {
+  match_response_with_config(expected, actual, pact, interaction, &MatchingConfig::default()).await
+}
+
+/// Matches the actual and expected responses, using the given config
+pub async fn match_response_with_config<'a>(
+  expected: HttpResponse,
+  actual: HttpResponse,
+  pact: &Box<dyn Pact + Send + Sync + 'a>,
+  interaction: &Box<dyn Interaction + Send + Sync>,
+  config: &MatchingConfig
 ) -> Vec<Mismatch> {
   let mut mismatches = vec![];
 
   info!("comparing to expected response: {}", expected);
   let plugin_data = setup_plugin_config(pact, interaction);
+  let protobuf_descriptors = protobuf::descriptors_from_plugin_config(&plugin_data);
 
   let status_context = CoreMatchingContext::new(DiffConfig::AllowUnexpectedKeys,
     &expected.matching_rules.rules_for_category("status").unwrap_or_default(),
     &plugin_data);
-  let body_context = CoreMatchingContext::new(DiffConfig::AllowUnexpectedKeys,
-    &expected.matching_rules.rules_for_category("body").unwrap_or_default(),
-    &plugin_data);
-  let header_context = CoreMatchingContext::new(DiffConfig::AllowUnexpectedKeys,
-    &expected.matching_rules.rules_for_category("header").unwrap_or_default(),
-    &plugin_data);
+  let body_context = CoreMatchingContext {
+    protobuf_descriptors,
+    .. CoreMatchingContext::new(DiffConfig::AllowUnexpectedKeys,
+      &expected.matching_rules.rules_for_category("body").unwrap_or_default(),
+      &plugin_data)
+  };
+  let header_context = CoreMatchingContext {
+    header_match_config: config.header_config.clone(),
+    .. CoreMatchingContext::new(DiffConfig::AllowUnexpectedKeys,
+      &expected.matching_rules.rules_for_category("header").unwrap_or_default(),
+      &plugin_data)
+  };
 
   mismatches.extend_from_slice(match_body(&expected, &actual, &body_context, &header_context).await
     .mismatches().as_slice());
   if let Err(m) = match_status(expected.status, actual.status, &status_context) {
     mismatches.extend_from_slice(&m);
   }
-  let result = match_headers(expected.headers, actual.headers,
-                             &header_context);
+  let result = match_headers(expected.headers, actual.headers, &header_context);
   for values in result.values() {
     mismatches.extend_from_slice(values.as_slice());
   }
@@ -1684,7 +1998,7 @@ fn match_metadata_value(
   context: &dyn MatchingContext
 ) -> Result<(), Vec<Mismatch>> {
   debug!("Comparing metadata values for key '{}'", key);
-  let path = DocPath::empty().join(key);
+  let path = DocPath::root().join(key);
   let matcher_result = if context.matcher_is_defined(&path) {
     matchers::match_values(&path, &context.select_best_matcher(&path), expected, actual)
   } else if key.to_ascii_lowercase() == "contenttype" || key.to_ascii_lowercase() == "content-type" {
@@ -1720,18 +2034,24 @@ pub async fn match_message<'a>(
 
     let matching_rules = &expected_message.matching_rules;
     let plugin_data = setup_plugin_config(pact, expected);
+    let protobuf_descriptors = protobuf::descriptors_from_plugin_config(&plugin_data);
 
     let body_context = if expected.is_v4() {
       CoreMatchingContext {
         matchers: matching_rules.rules_for_category("content").unwrap_or_default(),
         config: DiffConfig::AllowUnexpectedKeys,
         matching_spec: PactSpecification::V4,
-        plugin_configuration: plugin_data.clone()
+        plugin_configuration: plugin_data.clone(),
+        protobuf_descriptors,
+        .. CoreMatchingContext::default()
       }
     } else {
-      CoreMatchingContext::new(DiffConfig::AllowUnexpectedKeys,
-                           &matching_rules.rules_for_category("body").unwrap_or_default(),
-                           &plugin_data)
+      CoreMatchingContext {
+        protobuf_descriptors,
+        .. CoreMatchingContext::new(DiffConfig::AllowUnexpectedKeys,
+                             &matching_rules.rules_for_category("body").unwrap_or_default(),
+                             &plugin_data)
+      }
     };
 
     let metadata_context = CoreMatchingContext::new(DiffConfig::AllowUnexpectedKeys,
@@ -1779,7 +2099,9 @@ pub async fn match_sync_message_request<'a>(
     matchers: matching_rules.rules_for_category("content").unwrap_or_default(),
     config: DiffConfig::AllowUnexpectedKeys,
     matching_spec: PactSpecification::V4,
-    plugin_configuration: plugin_data.clone()
+    plugin_configuration: plugin_data.clone(),
+    protobuf_descriptors: protobuf::descriptors_from_plugin_config(&plugin_data),
+    .. CoreMatchingContext::default()
   };
 
   let metadata_context = CoreMatchingContext::new(DiffConfig::AllowUnexpectedKeys,
@@ -1827,13 +2149,16 @@ pub async fn match_sync_message_response<'a>(
     }
   } else {
     let plugin_data = setup_plugin_config(pact, &expected.boxed());
+    let protobuf_descriptors = protobuf::descriptors_from_plugin_config(&plugin_data);
     for (expected_response, actual_response) in expected_responses.iter().zip(actual_responses) {
       let matching_rules = &expected_response.matching_rules;
       let body_context = CoreMatchingContext {
         matchers: matching_rules.rules_for_category("content").unwrap_or_default(),
         config: DiffConfig::AllowUnexpectedKeys,
         matching_spec: PactSpecification::V4,
-        plugin_configuration: plugin_data.clone()
+        plugin_configuration: plugin_data.clone(),
+        protobuf_descriptors: protobuf_descriptors.clone(),
+        .. CoreMatchingContext::default()
       };
 
       let metadata_context = CoreMatchingContext::new(DiffConfig::AllowUnexpectedKeys,
@@ -2018,6 +2343,89 @@ pub async fn match_interaction(
   }
 }
 
+/// Compares two pacts and returns the differences between them, grouped by interaction. Interactions
+/// are paired up by their description and provider states (which also make up the interaction key
+/// returned with each group of mismatches). Paired interactions are matched against each other,
+/// and any interactions only found in one of the pacts are returned as interaction mismatches.
+/// Interactions with no differences are not returned.
+pub async fn compare_pacts(
+  expected: &(dyn Pact + Send + Sync),
+  actual: &(dyn Pact + Send + Sync)
+) -> Vec<(String, Vec<Mismatch>)> {
+  let mut mismatches = vec![];
+
+  for (key, expected_interaction, actual_interaction) in pair_interactions(expected, actual) {
+    match (expected_interaction, actual_interaction) {
+      (Some(expected_interaction), Some(actual_interaction)) => {
+        let result = match_interaction(expected_interaction, actual_interaction,
+          expected.boxed(), &expected.specification_version()).await;
+        match result {
+          Ok(result) => if !result.is_empty() {
+            mismatches.push((key, result));
+          },
+          Err(err) => mismatches.push((key.clone(), vec![Mismatch::InteractionMismatch {
+            interaction: key,
+            mismatch: err.to_string()
+          }]))
+        }
+      }
+      (Some(_), None) => mismatches.push((key.clone(), vec![Mismatch::InteractionMismatch {
+        interaction: key.clone(),
+        mismatch: format!("Interaction '{}' was removed", key)
+      }])),
+      (None, _) => mismatches.push((key.clone(), vec![Mismatch::InteractionMismatch {
+        interaction: key.clone(),
+        mismatch: format!("Interaction '{}' was added", key)
+      }]))
+    }
+  }
+
+  mismatches
+}
+
+/// Interaction key with the matching interactions from the expected and actual pacts
+pub type InteractionPair = (String, Option<Box<dyn Interaction + Send + Sync>>, Option<Box<dyn Interaction + Send + Sync>>);
+
+/// Pairs up the interactions from two pacts by their description and provider states. Returns the
+/// interaction key with the expected and actual interactions, where an interaction that is only
+/// in one of the pacts is paired with `None`. The interactions from the expected pact are returned
+/// first (in order), followed by any interactions that are only in the actual pact.
+pub fn pair_interactions(
+  expected: &(dyn Pact + Send + Sync),
+  actual: &(dyn Pact + Send + Sync)
+) -> Vec<InteractionPair> {
+  let expected_interactions = expected.interactions();
+  let actual_interactions = actual.interactions();
+  let same_interaction = |i1: &(dyn Interaction + Send + Sync), i2: &(dyn Interaction + Send + Sync)| {
+    i1.description() == i2.description() && i1.provider_states() == i2.provider_states()
+  };
+
+  let mut pairs = vec![];
+  for interaction in &expected_interactions {
+    let actual_interaction = actual_interactions.iter()
+      .find(|i| same_interaction(interaction.as_ref(), i.as_ref()));
+    pairs.push((interaction_key(interaction.as_ref()), Some(interaction.boxed()),
+      actual_interaction.map(|i| i.boxed())));
+  }
+  for interaction in &actual_interactions {
+    if !expected_interactions.iter().any(|i| same_interaction(i.as_ref(), interaction.as_ref())) {
+      pairs.push((interaction_key(interaction.as_ref()), None, Some(interaction.boxed())));
+    }
+  }
+  pairs
+}
+
+/// Key that identifies an interaction in a pact, made up of the description and provider states
+pub fn interaction_key(interaction: &(dyn Interaction + Send + Sync)) -> String {
+  let provider_states = interaction.provider_states();
+  if provider_states.is_empty() {
+    interaction.description()
+  } else {
+    format!("{} given {}", interaction.description(),
+      provider_states.iter().map(|state| state.name.as_str()).join(", "))
+  }
+}
+
 #[cfg(test)]
 mod tests;
 #[cfg(test)]
@@ -1,14 +1,20 @@
 //! Matching rule implementations
 
-use std::str::from_utf8;
+use std::collections::HashMap;
+use std::net::IpAddr;
+use std::str::{from_utf8, FromStr};
+use std::sync::RwLock;
 
 use anyhow::anyhow;
 use bytes::Bytes;
+use chrono::{DateTime, Utc};
+use itertools::Itertools;
 use lazy_static::lazy_static;
 use log::*;
 use maplit::hashmap;
 use onig::Regex;
 use pact_models::HttpStatus;
+use pact_models::json_utils::json_to_string;
 use pact_models::matchingrules::{MatchingRule, RuleList, RuleLogic};
 use pact_models::path_exp::DocPath;
 use pact_models::time_utils::validate_datetime;
@@ -19,6 +25,7 @@ use pact_plugin_driver::catalogue_manager::{
   register_core_entries
 };
 use semver::Version;
+use serde_json::Value;
 
 use crate::binary_utils::match_content_type;
 
@@ -99,7 +106,11 @@ lazy_static! {
       "v3-date", "v3-time", "v3-datetime", "v2-min-type", "v2-max-type", "v2-minmax-type",
       "v3-includes", "v3-null", "v4-equals-ignore-order", "v4-min-equals-ignore-order",
       "v4-max-equals-ignore-order", "v4-minmax-equals-ignore-order", "v3-content-type",
-      "v4-array-contains", "v1-equality", "v4-not-empty", "v4-semver"] {
+      "v4-array-contains", "v1-equality", "v4-not-empty", "v4-semver", "v4-duration", "v4-email",
+      "v4-base64", "v4-json-schema", "v4-unique-items",
+      "v4-one-of", "v4-valid-json", "v4-latitude", "v4-longitude", "v4-number-range",
+      "v4-iso8601-timestamp", "v4-luhn", "v4-ip-address", "v4-hexadecimal", "v4-named-regex", "v4-any-type",
+      "v4-recent-timestamp"] {
       entries.push(CatalogueEntry {
         entry_type: CatalogueEntryType::MATCHER,
         provider_type: CatalogueEntryProviderType::CORE,
@@ -110,6 +121,42 @@ lazy_static! {
     }
     entries
   };
+
+  /// Regex for an ISO-8601 duration (i.e. P1Y2M10DT2H30M). At least one component must be present,
+  /// and a time designator must be followed by at least one time component.
+  static ref DURATION_REGEX: Regex = Regex::new(
+    r"^-?P(?=\d|T\d)(\d+Y)?(\d+M)?(\d+W)?(\d+D)?(T(?=\d)(\d+H)?(\d+M)?(\d+([.,]\d+)?S)?)?$"
+  ).unwrap();
+
+  /// Regex for an email address. This is the dot-atom subset of RFC-5322 (no quoted local parts,
+  /// comments or IP address literals), and the domain must have at least two labels.
+  static ref EMAIL_REGEX: Regex = Regex::new(
+    r"^[A-Za-z0-9!#$%&'*+/=?^_`{|}~-]+(\.[A-Za-z0-9!#$%&'*+/=?^_`{|}~-]+)*@[A-Za-z0-9]([A-Za-z0-9-]{0,61}[A-Za-z0-9])?(\.[A-Za-z0-9]([A-Za-z0-9-]{0,61}[A-Za-z0-9])?)+$"
+  ).unwrap();
+
+  /// Regular expressions that can be referred to by name with the NamedRegex matching rule
+  static ref NAMED_REGEXES: RwLock<HashMap<String, Regex>> = RwLock::new(HashMap::new());
+}
+
+/// Registers a regular expression under the given name, so that it can be used with the
+/// `NamedRegex` matching rule. Registering a name again replaces the previous regular expression.
+/// Returns an error if the regular expression is not valid.
+pub fn register_named_regex(name: &str, regex: &str) -> anyhow::Result<()> {
+  let re = Regex::new(regex)
+    .map_err(|err| anyhow!("'{}' is not a valid regular expression - {}", regex, err))?;
+  NAMED_REGEXES.write().unwrap().insert(name.to_string(), re);
+  Ok(())
+}
+
+pub(crate) fn match_named_regex(name: &str, actual: &str) -> anyhow::Result<()> {
+  match NAMED_REGEXES.read().unwrap().get(name) {
+    Some(re) => if re.is_match(actual) {
+      Ok(())
+    } else {
+      Err(anyhow!("Expected '{}' to match the regular expression named '{}'", actual, name))
+    },
+    None => Err(anyhow!("No regular expression has been registered with the name '{}'", name))
+  }
 }
 
 /// Sets up all the core catalogue entries for matchers and generators
@@ -244,6 +291,29 @@ impl Matches<&str> for &str {
           Err(err) => Err(anyhow!("'{}' is not a valid semantic version - {}", actual, err))
         }
       }
+      MatchingRule::Duration => match_duration(actual),
+      MatchingRule::Email => match_email(actual),
+      MatchingRule::NamedRegex(name) => match_named_regex(name, actual),
+      MatchingRule::Base64 => match_base64(actual),
+      MatchingRule::OneOf(values) => if values.iter().any(|value| json_to_string(value) == actual) {
+        Ok(())
+      } else {
+        Err(anyhow!("Expected '{}' to be one of {}", actual, Value::Array(values.clone())))
+      }
+      MatchingRule::ValidJson => match_valid_json(actual),
+      MatchingRule::Iso8601Timestamp => match_iso8601_timestamp(actual),
+      MatchingRule::Luhn => match_luhn(actual),
+      MatchingRule::IpAddress(version) => match_ip_address(*version, actual),
+      MatchingRule::Hexadecimal { length } => match_hexadecimal(*length, actual),
+      MatchingRule::Latitude | MatchingRule::Longitude => match actual.parse::<f64>() {
+        Ok(value) => match_coordinate(matcher, value),
+        Err(_) => Err(anyhow!("Expected '{}' to be a valid {}", actual, matcher.name()))
+      }
+      MatchingRule::NumberRange { min, max, inclusive } => match actual.parse::<f64>() {
+        Ok(value) => match_number_range(*min, *max, *inclusive, value),
+        Err(_) => Err(anyhow!("Expected '{}' to be a number", actual))
+      }
+      MatchingRule::RecentTimestamp { within_seconds } => match_recent_timestamp(*within_seconds, actual, Utc::now()),
       _ => if !cascaded || can_cascade(matcher) { // TODO: replace this MatchingRule::can_cascade when models next released
         Err(anyhow!("Unable to match '{}' using {:?}", self, matcher))
       } else {
@@ -259,6 +329,9 @@ fn can_cascade(rule: &MatchingRule) -> bool {
     MatchingRule::Values => false,
     MatchingRule::EachValue(_) => false,
     MatchingRule::EachKey(_) => false,
+    MatchingRule::UniqueItems(_) => false,
+    MatchingRule::ArrayLength(_) => false,
+    MatchingRule::EqualsPath(_) => false,
     _ => true
   }
 }
@@ -712,6 +785,32 @@ impl Matches<&Bytes> for Bytes {
   }
 }
 
+/// Returns a label for the matching rule, which is the name of the rule in Pascal case (i.e.
+/// `Regex` or `MinType`)
+pub(crate) fn matching_rule_label(rule: &MatchingRule) -> String {
+  rule.name().split('-')
+    .map(|part| {
+      let mut chars = part.chars();
+      match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect::<String>(),
+        None => String::default()
+      }
+    })
+    .collect()
+}
+
+/// Returns a label for the rules in the rule list, or `None` if the list is empty
+pub(crate) fn rule_list_label(rules: &RuleList) -> Option<String> {
+  if rules.is_empty() {
+    None
+  } else {
+    Some(rules.rules.iter().map(matching_rule_label).join(", "))
+  }
+}
+
+/// Maximum length of a value or mismatch message to include in the debug logs
+const MAX_LOG_VALUE_LENGTH: usize = 200;
+
 /// Match the provided values using the path and matching rules
 pub fn match_values<E, A>(path: &DocPath, matching_rules: &RuleList, expected: E, actual: A) -> Result<(), Vec<String>>
   where E: Matches<A>, A: Clone {
@@ -722,27 +821,37 @@ pub fn match_values<E, A>(path: &DocPath, matching_rules: &RuleList, expected: E
     let results = matching_rules.rules.iter().map(|rule| {
       expected.matches_with(actual.clone(), rule, matching_rules.cascaded)
     }).collect::<Vec<anyhow::Result<()>>>();
-    match matching_rules.rule_logic {
-      RuleLogic::And => {
-        if results.iter().all(|result| result.is_ok()) {
-          Ok(())
-        } else {
-          Err(results.iter().filter(|result| result.is_err())
-            .map(|result| result.as_ref().unwrap_err().to_string()).collect())
-        }
-      },
-      RuleLogic::Or => {
-        if results.iter().any(|result| result.is_ok()) {
-          Ok(())
-        } else {
-          Err(results.iter().filter(|result| result.is_err())
-            .map(|result| result.as_ref().unwrap_err().to_string()).collect())
-        }
-      }
+    let matched = match matching_rules.rule_logic {
+      RuleLogic::And => results.iter().all(|result| result.is_ok()),
+      RuleLogic::Or => results.iter().any(|result| result.is_ok())
+    };
+
+    debug!("match_values: path '{}' with {:?} rules [{}] (cascaded = {}) -> {}", path,
+      matching_rules.rule_logic,
+      matching_rules.rules.iter().zip(results.iter()).map(|(rule, result)| match result {
+        Ok(_) => format!("{}: OK", rule.name()),
+        Err(err) => format!("{}: FAILED - {}", rule.name(), truncate_for_log(&err.to_string()))
+      }).join(", "),
+      matching_rules.cascaded,
+      if matched { "matched" } else { "did not match" });
+
+    if matched {
+      Ok(())
+    } else {
+      Err(results.iter().filter(|result| result.is_err())
+        .map(|result| result.as_ref().unwrap_err().to_string()).collect())
     }
   }
 }
 
+fn truncate_for_log(value: &str) -> String {
+  if value.chars().count() > MAX_LOG_VALUE_LENGTH {
+    format!("{}...", value.chars().take(MAX_LOG_VALUE_LENGTH).collect::<String>())
+  } else {
+    value.to_string()
+  }
+}
+
 fn match_status_code(status_code: u16, status: &HttpStatus) -> anyhow::Result<()> {
   let matches = match status {
     HttpStatus::Information => (100..=199).contains(&status_code),
@@ -761,6 +870,141 @@ fn match_status_code(status_code: u16, status: &HttpStatus) -> anyhow::Result<()
   }
 }
 
+pub(crate) fn match_duration(actual: &str) -> anyhow::Result<()> {
+  if DURATION_REGEX.is_match(actual) {
+    Ok(())
+  } else {
+    Err(anyhow!("'{}' is not a valid ISO-8601 duration", actual))
+  }
+}
+
+pub(crate) fn match_email(actual: &str) -> anyhow::Result<()> {
+  if EMAIL_REGEX.is_match(actual) {
+    Ok(())
+  } else {
+    Err(anyhow!("'{}' is not a valid email address", actual))
+  }
+}
+
+pub(crate) fn match_base64(actual: &str) -> anyhow::Result<()> {
+  // The decoder accepts missing padding, so check the length to reject it
+  if actual.len() % 4 != 0 {
+    return Err(anyhow!("'{}' is not valid base64 - the length must be a multiple of 4", actual));
+  }
+  base64::decode(actual)
+    .map(|_| ())
+    .map_err(|err| anyhow!("'{}' is not valid base64 - {}", actual, err))
+}
+
+/// Checks that the value is a latitude (between -90 and 90) or longitude (between -180 and 180)
+pub(crate) fn match_coordinate(matcher: &MatchingRule, actual: f64) -> anyhow::Result<()> {
+  let (name, limit) = match matcher {
+    MatchingRule::Latitude => ("latitude", 90.0),
+    _ => ("longitude", 180.0)
+  };
+  if (-limit..=limit).contains(&actual) {
+    Ok(())
+  } else {
+    Err(anyhow!("Expected {} to be a valid {} (between -{} and {})", actual, name, limit, limit))
+  }
+}
+
+/// Checks that the value is within the range, reporting the bound that was violated
+pub(crate) fn match_number_range(min: f64, max: f64, inclusive: bool, actual: f64) -> anyhow::Result<()> {
+  if !actual.is_finite() {
+    Err(anyhow!("Expected {} to be a finite number", actual))
+  } else if inclusive && actual < min {
+    Err(anyhow!("Expected {} to be greater than or equal to the minimum {}", actual, min))
+  } else if !inclusive && actual <= min {
+    Err(anyhow!("Expected {} to be greater than the minimum {}", actual, min))
+  } else if inclusive && actual > max {
+    Err(anyhow!("Expected {} to be less than or equal to the maximum {}", actual, max))
+  } else if !inclusive && actual >= max {
+    Err(anyhow!("Expected {} to be less than the maximum {}", actual, max))
+  } else {
+    Ok(())
+  }
+}
+
+/// Checks that the value is an RFC-3339 timestamp (a date and time with a UTC offset)
+pub(crate) fn match_iso8601_timestamp(actual: &str) -> anyhow::Result<()> {
+  DateTime::parse_from_rfc3339(actual)
+    .map(|_| ())
+    .map_err(|err| anyhow!("'{}' is not a valid RFC-3339 timestamp - {}", actual, err))
+}
+
+/// Number of seconds a recent timestamp can be after the current time, to allow for clock skew
+/// between the provider and the machine running the verification
+const RECENT_TIMESTAMP_CLOCK_SKEW_SECONDS: i64 = 5;
+
+/// Checks that the value is an RFC-3339 timestamp that is no more than `within_seconds` before `now`
+pub(crate) fn match_recent_timestamp(within_seconds: i64, actual: &str, now: DateTime<Utc>) -> anyhow::Result<()> {
+  let timestamp = DateTime::parse_from_rfc3339(actual)
+    .map_err(|err| anyhow!("'{}' is not a valid RFC-3339 timestamp - {}", actual, err))?;
+  let delta = now.signed_duration_since(timestamp).num_seconds();
+  if delta > within_seconds {
+    Err(anyhow!("Expected '{}' to be within {} seconds of the current time, but it was {} seconds before it",
+      actual, within_seconds, delta))
+  } else if delta < -RECENT_TIMESTAMP_CLOCK_SKEW_SECONDS {
+    Err(anyhow!("Expected '{}' to be within {} seconds of the current time, but it was {} seconds after it",
+      actual, within_seconds, -delta))
+  } else {
+    Ok(())
+  }
+}
+
+/// Checks that the value only contains hexadecimal digits, and has the given number of digits if
+/// a length is given
+pub(crate) fn match_hexadecimal(length: Option<usize>, actual: &str) -> anyhow::Result<()> {
+  if actual.is_empty() || !actual.chars().all(|ch| ch.is_ascii_hexdigit()) {
+    Err(anyhow!("Expected '{}' to be a hexadecimal string", actual))
+  } else {
+    match length {
+      Some(length) if actual.len() != length =>
+        Err(anyhow!("Expected '{}' to have {} hexadecimal digits, but it has {}", actual, length, actual.len())),
+      _ => Ok(())
+    }
+  }
+}
+
+/// Checks that the value is an IP address, and of the given version (4 or 6) if there is one
+pub(crate) fn match_ip_address(version: Option<u8>, actual: &str) -> anyhow::Result<()> {
+  match (IpAddr::from_str(actual), version) {
+    (Ok(IpAddr::V4(_)), Some(6)) | (Ok(IpAddr::V6(_)), Some(4)) =>
+      Err(anyhow!("Expected '{}' to be an IPv{} address", actual, version.unwrap_or_default())),
+    (Ok(_), _) => Ok(()),
+    (Err(_), Some(version)) => Err(anyhow!("Expected '{}' to be an IPv{} address", actual, version)),
+    (Err(_), None) => Err(anyhow!("Expected '{}' to be an IP address", actual))
+  }
+}
+
+/// Checks that the digits in the value pass the Luhn checksum. Non-digit characters are ignored.
+pub(crate) fn match_luhn(actual: &str) -> anyhow::Result<()> {
+  let digits: Vec<u32> = actual.chars().filter_map(|ch| ch.to_digit(10)).collect();
+  if digits.is_empty() {
+    return Err(anyhow!("Expected '{}' to contain digits that pass the Luhn check", actual));
+  }
+
+  let sum: u32 = digits.iter().rev().enumerate()
+    .map(|(index, digit)| if index % 2 == 1 {
+      let doubled = digit * 2;
+      if doubled > 9 { doubled - 9 } else { doubled }
+    } else {
+      *digit
+    })
+    .sum();
+  match sum % 10 {
+    0 => Ok(()),
+    _ => Err(anyhow!("Expected '{}' to pass the Luhn check", actual))
+  }
+}
+
+pub(crate) fn match_valid_json(actual: &str) -> anyhow::Result<()> {
+  serde_json::from_str::<Value>(actual)
+    .map(|_| ())
+    .map_err(|err| anyhow!("'{}' is not valid JSON - {}", actual, err))
+}
+
 #[cfg(test)]
 mod tests {
   use expectest::expect;
@@ -848,6 +1092,20 @@ mod tests {
       be_equal_to(RuleList::new(MatchingRule::Regex("2".to_string()))));
   }
 
+  #[test]
+  fn select_best_matcher_breaks_ties_between_equal_weight_wildcards_deterministically() {
+    for _ in 0..100 {
+      let matchers = matchingrules_list! {
+        "body";
+        "$.items[0].*" => [ MatchingRule::Regex("1".to_string()) ],
+        "$.items[*].id" => [ MatchingRule::Regex("2".to_string()) ]
+      };
+
+      expect!(matchers.select_best_matcher(&vec!["$", "items", "0", "id"])).to(
+        be_equal_to(RuleList::new(MatchingRule::Regex("2".to_string()))));
+    }
+  }
+
     #[test]
     fn select_best_matcher_selects_handles_missing_type_attribute() {
       let matchers = matchingrules_list! {
@@ -1124,7 +1382,186 @@ mod tests {
     expect!("1.0.0".to_string().matches_with("1.0.0", &matcher, false)).to(be_ok());
     expect!("1.0.0".to_string().matches_with("1", &matcher, false)).to(be_err());
     expect!("1.0.0".to_string().matches_with("1.0.0-beta.1", &matcher, false)).to(be_ok());
+    expect!("1.0.0".to_string().matches_with("1.2.3", &matcher, false)).to(be_ok());
+    expect!("1.0.0".to_string().matches_with("1.2.3-beta.1", &matcher, false)).to(be_ok());
+    expect!("1.0.0".to_string().matches_with("1.2", &matcher, false)).to(be_err());
+    expect!("1.0.0".to_string().matches_with("not a version", &matcher, false)).to(be_err());
     expect!(json!("1.0.0").matches_with(&json!("1.0.0"), &matcher, false)).to(be_ok());
     expect!(json!("1.0.0").matches_with(&json!("1"), &matcher, false)).to(be_err());
   }
+
+  #[test]
+  fn duration_matcher_test() {
+    let matcher = MatchingRule::Duration;
+    expect!("PT1H".to_string().matches_with("PT1H30M", &matcher, false)).to(be_ok());
+    expect!("PT1H".to_string().matches_with("P1Y2M10DT2H30M", &matcher, false)).to(be_ok());
+    expect!("PT1H".to_string().matches_with("P3W", &matcher, false)).to(be_ok());
+    expect!("PT1H".to_string().matches_with("PT0.5S", &matcher, false)).to(be_ok());
+    expect!("PT1H".to_string().matches_with("-P1D", &matcher, false)).to(be_ok());
+    expect!("PT1H".to_string().matches_with("P", &matcher, false)).to(be_err());
+    expect!("PT1H".to_string().matches_with("PT", &matcher, false)).to(be_err());
+    expect!("PT1H".to_string().matches_with("P1DT", &matcher, false)).to(be_err());
+    expect!("PT1H".to_string().matches_with("1H30M", &matcher, false)).to(be_err());
+    expect!("PT1H".to_string().matches_with("PT1H30", &matcher, false)).to(be_err());
+    expect!("PT1H".to_string().matches_with("100", &matcher, false)).to(be_err());
+    expect!("PT1H".to_string().matches_with(100, &matcher, false)).to(be_err());
+  }
+
+  #[test]
+  fn email_matcher_test() {
+    let matcher = MatchingRule::Email;
+    expect!("a@example.com".to_string().matches_with("a.b@example.com", &matcher, false)).to(be_ok());
+    expect!("a@example.com".to_string().matches_with("first.last+tag@sub.example.co.uk", &matcher, false)).to(be_ok());
+    expect!("a@example.com".to_string().matches_with("not-an-email", &matcher, false)).to(be_err());
+    expect!("a@example.com".to_string().matches_with("a@", &matcher, false)).to(be_err());
+    expect!("a@example.com".to_string().matches_with("@example.com", &matcher, false)).to(be_err());
+    expect!("a@example.com".to_string().matches_with("a..b@example.com", &matcher, false)).to(be_err());
+    expect!("a@example.com".to_string().matches_with("a@example", &matcher, false)).to(be_err());
+    expect!("a@example.com".to_string().matches_with("a b@example.com", &matcher, false)).to(be_err());
+    expect!("a@example.com".to_string().matches_with(100, &matcher, false)).to(be_err());
+  }
+
+  #[test]
+  fn named_regex_matcher_test() {
+    expect!(register_named_regex("uk-postcode-test", r"^[A-Z]{1,2}\d[A-Z\d]? ?\d[A-Z]{2}$")).to(be_ok());
+    let matcher = MatchingRule::NamedRegex("uk-postcode-test".to_string());
+    expect!("SW1A 1AA".to_string().matches_with("EC1A 1BB", &matcher, false)).to(be_ok());
+    expect!("SW1A 1AA".to_string().matches_with("12345", &matcher, false)).to(be_err());
+    expect!(json!("SW1A 1AA").matches_with(&json!("M1 1AE"), &matcher, false)).to(be_ok());
+    expect!(json!("SW1A 1AA").matches_with(&json!(12345), &matcher, false)).to(be_err());
+  }
+
+  #[test]
+  fn named_regex_matcher_with_an_unregistered_name_test() {
+    let matcher = MatchingRule::NamedRegex("not-registered".to_string());
+    let result = "SW1A 1AA".to_string().matches_with("SW1A 1AA", &matcher, false);
+    expect!(result.unwrap_err().to_string()).to(
+      be_equal_to("No regular expression has been registered with the name 'not-registered'"));
+  }
+
+  #[test]
+  fn register_named_regex_rejects_invalid_regular_expressions() {
+    expect!(register_named_regex("invalid-regex-test", "[a-z")).to(be_err());
+  }
+
+  #[test]
+  fn coordinate_matcher_test() {
+    expect!("0".to_string().matches_with("-33.8688", &MatchingRule::Latitude, false)).to(be_ok());
+    expect!("0".to_string().matches_with("90", &MatchingRule::Latitude, false)).to(be_ok());
+    expect!("0".to_string().matches_with("90.5", &MatchingRule::Latitude, false).unwrap_err().to_string()).to(
+      be_equal_to("Expected 90.5 to be a valid latitude (between -90 and 90)"));
+    expect!("0".to_string().matches_with("151.2093", &MatchingRule::Longitude, false)).to(be_ok());
+    expect!("0".to_string().matches_with("-180.1", &MatchingRule::Longitude, false)).to(be_err());
+    expect!("0".to_string().matches_with("north", &MatchingRule::Longitude, false).unwrap_err().to_string()).to(
+      be_equal_to("Expected 'north' to be a valid longitude"));
+  }
+
+  #[test]
+  fn number_range_matcher_test() {
+    let inclusive = MatchingRule::NumberRange { min: 0.0, max: 1000.0, inclusive: true };
+    let exclusive = MatchingRule::NumberRange { min: 0.0, max: 1000.0, inclusive: false };
+    expect!("0".to_string().matches_with("250.5", &inclusive, false)).to(be_ok());
+    expect!("0".to_string().matches_with("1000", &inclusive, false)).to(be_ok());
+    expect!("0".to_string().matches_with("1000", &exclusive, false).unwrap_err().to_string()).to(
+      be_equal_to("Expected 1000 to be less than the maximum 1000"));
+    expect!("0".to_string().matches_with("-1", &exclusive, false).unwrap_err().to_string()).to(
+      be_equal_to("Expected -1 to be greater than the minimum 0"));
+    expect!("0".to_string().matches_with("free", &inclusive, false).unwrap_err().to_string()).to(
+      be_equal_to("Expected 'free' to be a number"));
+    expect!("0".to_string().matches_with("NaN", &inclusive, false).unwrap_err().to_string()).to(
+      be_equal_to("Expected NaN to be a finite number"));
+    expect!("0".to_string().matches_with("inf", &inclusive, false)).to(be_err());
+  }
+
+  #[test]
+  fn iso8601_timestamp_matcher_test() {
+    let matcher = MatchingRule::Iso8601Timestamp;
+    expect!("".to_string().matches_with("2023-01-01T12:00:00Z", &matcher, false)).to(be_ok());
+    expect!("".to_string().matches_with("2023-01-01T12:00:00-05:00", &matcher, false)).to(be_ok());
+    expect!("".to_string().matches_with("2023-01-01", &matcher, false).unwrap_err().to_string()).to(
+      be_equal_to("'2023-01-01' is not a valid RFC-3339 timestamp - premature end of input"));
+    expect!("".to_string().matches_with("yesterday", &matcher, false)).to(be_err());
+  }
+
+  #[test]
+  fn recent_timestamp_matcher_test() {
+    let now = DateTime::parse_from_rfc3339("2023-01-01T12:00:00Z").unwrap().with_timezone(&Utc);
+    expect!(match_recent_timestamp(60, "2023-01-01T11:59:30Z", now)).to(be_ok());
+    expect!(match_recent_timestamp(60, "2023-01-01T07:00:00-05:00", now)).to(be_ok());
+    expect!(match_recent_timestamp(60, "2023-01-01T12:00:03Z", now)).to(be_ok());
+    expect!(match_recent_timestamp(60, "2023-01-01T11:58:00Z", now).unwrap_err().to_string()).to(
+      be_equal_to("Expected '2023-01-01T11:58:00Z' to be within 60 seconds of the current time, but it was 120 seconds before it"));
+    expect!(match_recent_timestamp(60, "2023-01-01T12:01:00Z", now).unwrap_err().to_string()).to(
+      be_equal_to("Expected '2023-01-01T12:01:00Z' to be within 60 seconds of the current time, but it was 60 seconds after it"));
+    expect!(match_recent_timestamp(60, "just now", now).unwrap_err().to_string()).to(
+      be_equal_to("'just now' is not a valid RFC-3339 timestamp - input contains invalid characters"));
+
+    let matcher = MatchingRule::RecentTimestamp { within_seconds: 60 };
+    expect!("".to_string().matches_with(Utc::now().to_rfc3339().as_str(), &matcher, false)).to(be_ok());
+    expect!("".to_string().matches_with("2023-01-01T12:00:00Z", &matcher, false)).to(be_err());
+  }
+
+  #[test]
+  fn hexadecimal_matcher_test() {
+    let sha_matcher = MatchingRule::Hexadecimal { length: Some(40) };
+    expect!("".to_string().matches_with("c5cb9c5e2a1f0d3b4c6e8a7f9d1b3c5e7a9f0b2d", &sha_matcher, false)).to(be_ok());
+    expect!("".to_string().matches_with("DEADBEEF", &MatchingRule::Hexadecimal { length: None }, false)).to(be_ok());
+    expect!("".to_string().matches_with("not-hex", &sha_matcher, false).unwrap_err().to_string()).to(
+      be_equal_to("Expected 'not-hex' to be a hexadecimal string"));
+    expect!("".to_string().matches_with("c5cb9c5", &sha_matcher, false).unwrap_err().to_string()).to(
+      be_equal_to("Expected 'c5cb9c5' to have 40 hexadecimal digits, but it has 7"));
+  }
+
+  #[test]
+  fn ip_address_matcher_test() {
+    expect!("".to_string().matches_with("192.168.1.20", &MatchingRule::IpAddress(None), false)).to(be_ok());
+    expect!("".to_string().matches_with("2001:db8::ff00:42:8329", &MatchingRule::IpAddress(None), false)).to(be_ok());
+    expect!("".to_string().matches_with("192.168.1.256", &MatchingRule::IpAddress(None), false).unwrap_err().to_string()).to(
+      be_equal_to("Expected '192.168.1.256' to be an IP address"));
+    expect!("".to_string().matches_with("192.168.1.20", &MatchingRule::IpAddress(Some(4)), false)).to(be_ok());
+    expect!("".to_string().matches_with("::1", &MatchingRule::IpAddress(Some(4)), false).unwrap_err().to_string()).to(
+      be_equal_to("Expected '::1' to be an IPv4 address"));
+  }
+
+  #[test]
+  fn luhn_matcher_test() {
+    let matcher = MatchingRule::Luhn;
+    expect!("".to_string().matches_with("4539-1488-0343-6467", &matcher, false)).to(be_ok());
+    expect!("".to_string().matches_with("79927398713", &matcher, false)).to(be_ok());
+    expect!("".to_string().matches_with("79927398710", &matcher, false).unwrap_err().to_string()).to(
+      be_equal_to("Expected '79927398710' to pass the Luhn check"));
+    expect!("".to_string().matches_with("not a card", &matcher, false).unwrap_err().to_string()).to(
+      be_equal_to("Expected 'not a card' to contain digits that pass the Luhn check"));
+  }
+
+  #[test]
+  fn valid_json_matcher_test() {
+    let matcher = MatchingRule::ValidJson;
+    expect!("{}".to_string().matches_with(r#"{"id": 100, "tags": ["a"]}"#, &matcher, false)).to(be_ok());
+    expect!("{}".to_string().matches_with("[1, 2]", &matcher, false)).to(be_ok());
+    expect!("{}".to_string().matches_with("{\"id\": 100", &matcher, false).unwrap_err().to_string()).to(
+      be_equal_to("'{\"id\": 100' is not valid JSON - EOF while parsing an object at line 1 column 10"));
+  }
+
+  #[test]
+  fn one_of_matcher_test() {
+    let matcher = MatchingRule::OneOf(vec![json!("active"), json!("inactive"), json!(1)]);
+    expect!("active".to_string().matches_with("inactive", &matcher, false)).to(be_ok());
+    expect!("active".to_string().matches_with("1", &matcher, false)).to(be_ok());
+    expect!("active".to_string().matches_with("pending", &matcher, false).unwrap_err().to_string()).to(
+      be_equal_to("Expected 'pending' to be one of [\"active\",\"inactive\",1]"));
+  }
+
+  #[test]
+  fn base64_matcher_test() {
+    let matcher = MatchingRule::Base64;
+    expect!("aGVsbG8=".to_string().matches_with("d29ybGQ=", &matcher, false)).to(be_ok());
+    expect!("aGVsbG8=".to_string().matches_with("", &matcher, false)).to(be_ok());
+    expect!("aGVsbG8=".to_string().matches_with("YWJj", &matcher, false)).to(be_ok());
+    expect!("aGVsbG8=".to_string().matches_with("d29ybGQ", &matcher, false)).to(be_err());
+    expect!("aGVsbG8=".to_string().matches_with("d29y=GQ=", &matcher, false)).to(be_err());
+    expect!("aGVsbG8=".to_string().matches_with("d29y*GQ=", &matcher, false)).to(be_err());
+    expect!("aGVsbG8=".to_string().matches_with("not base64!", &matcher, false)).to(be_err());
+    expect!("aGVsbG8=".to_string().matches_with(100, &matcher, false)).to(be_err());
+  }
 }
@@ -13,7 +13,7 @@ use serde_json::{self, json, Value};
 
 use crate::{Either, MatchingContext, merge_result, Mismatch};
 use crate::binary_utils::match_content_type;
-use crate::matchers::{match_values, Matches};
+use crate::matchers::{match_values, matching_rule_label, Matches};
 
 impl <T: Debug + Display + PartialEq + Clone> Matches<&Vec<T>> for &Vec<T> {
   fn matches_with(&self, actual: &Vec<T>, matcher: &MatchingRule, cascaded: bool) -> anyhow::Result<()> {
@@ -77,6 +77,7 @@ impl <T: Debug + Display + PartialEq + Clone> Matches<&[T]> for &[T] {
         }
       }
       MatchingRule::ArrayContains(_) => Ok(()),
+      MatchingRule::EqualsPath(_) => Ok(()),
       MatchingRule::EachKey(_) => Ok(()),
       MatchingRule::EachValue(_) => Ok(()),
       MatchingRule::Values => Ok(()),
@@ -236,8 +237,10 @@ pub fn compare_maps_with_matchingrule<T: Display + Debug>(
     let expected_keys = expected.keys().cloned().collect();
     let actual_keys = actual.keys().cloned().collect();
     result = merge_result(result, context.match_keys(path, &expected_keys, &actual_keys));
+    // If a values matcher is also defined for the path, it will compare all the values
+    let values_matcher_defined = context.values_matcher_defined(path);
     for (key, value) in expected.iter() {
-      if actual.contains_key(key) {
+      if !values_matcher_defined && actual.contains_key(key) {
         let p = path.join(key);
         result = merge_result(result, callback(&p, value, &actual[key]));
       }
@@ -259,6 +262,7 @@ pub fn compare_lists_with_matchingrule<T: Display + Debug + PartialEq + Clone +
   let mut result = vec![];
 
   if !expected.is_empty() {
+    let label = matching_rule_label(rule);
     match rule {
       // TODO: need to implement the ignore order matchers (See Pact-JVM core/matchers/src/main/kotlin/au/com/dius/pact/core/matchers/Matchers.kt:133)
       // is EqualsIgnoreOrderMatcher,
@@ -286,7 +290,8 @@ pub fn compare_lists_with_matchingrule<T: Display + Debug + PartialEq + Clone +
                   path: path.to_string(),
                   expected: Some(expected_value.to_string().into()),
                   actual: Some(actual.for_mismatch().into()),
-                  mismatch: format!("Variant at index {} ({}) was not found in the actual list", index, expected_value)
+                  mismatch: format!("Variant at index {} ({}) was not found in the actual list", index, expected_value),
+                  rule: Some(label.clone())
                 });
               };
             },
@@ -296,7 +301,8 @@ pub fn compare_lists_with_matchingrule<T: Display + Debug + PartialEq + Clone +
                 expected: Some(expected.for_mismatch().into()),
                 actual: Some(actual.for_mismatch().into()),
                 mismatch: format!("ArrayContains: variant {} is missing from the expected list, which has {} items",
-                                  index, expected.len())
+                                  index, expected.len()),
+                rule: Some(label.clone())
               });
             }
           }
@@ -312,7 +318,8 @@ pub fn compare_lists_with_matchingrule<T: Display + Debug + PartialEq + Clone +
                 path: path.to_string(),
                 expected: Some(expected.for_mismatch().into()),
                 actual: Some(actual.for_mismatch().into()),
-                mismatch: format!("Found an un-resolved reference {}", reference.name)
+                mismatch: format!("Found an un-resolved reference {}", reference.name),
+                rule: Some(label.clone())
               });
               None
             }
@@ -328,7 +335,8 @@ pub fn compare_lists_with_matchingrule<T: Display + Debug + PartialEq + Clone +
               path: path.to_string(),
               expected: Some(expected.for_mismatch().into()),
               actual: Some(actual.for_mismatch().into()),
-              mismatch: mismatch.to_string()
+              mismatch: mismatch.to_string(),
+              rule: Some(label.clone())
             });
           }
         }
@@ -339,7 +347,8 @@ pub fn compare_lists_with_matchingrule<T: Display + Debug + PartialEq + Clone +
             path: path.to_string(),
             expected: Some(expected.for_mismatch().into()),
             actual: Some(actual.for_mismatch().into()),
-            mismatch: mismatch.to_string()
+            mismatch: mismatch.to_string(),
+            rule: Some(label.clone())
           });
         }
 
@@ -384,7 +393,8 @@ fn match_list_contents<T: Display + Debug + PartialEq + Clone + Sized>(
         path: path.to_string(),
         expected: Some(expected.for_mismatch().into()),
         actual: Some(actual.for_mismatch().into()),
-        mismatch: format!("Expected {} ({}) but was missing", value, index)
+        mismatch: format!("Expected {} ({}) but was missing", value, index),
+        rule: None
       });
     }
   }
@@ -427,7 +437,7 @@ mod tests {
     }
 
     fn values_matcher_defined(&self, _path: &DocPath) -> bool {
-      todo!()
+      false
     }
 
     fn direct_matcher_defined(&self, _path: &DocPath, _matchers: &HashSet<&str>) -> bool {
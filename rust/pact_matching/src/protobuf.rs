@@ -0,0 +1,488 @@
+//! Matching of protobuf message bodies (i.e. `application/protobuf` or `application/grpc`).
+//!
+//! Both bodies are decoded into a JSON field tree using the protobuf descriptors from the
+//! matching context, and the resulting trees are then compared using the JSON matching logic.
+//! The message type is taken from the `message` content type parameter
+//! (i.e. `application/protobuf; message=.package.MessageType`).
+//!
+//! The descriptors are loaded from the plugin configuration of the Pact and interaction, using the
+//! same layout as the protobuf plugin.
+
+use std::collections::HashMap;
+
+use anyhow::anyhow;
+use bytes::{Buf, Bytes};
+use log::*;
+use pact_plugin_driver::plugin_models::PluginInteractionConfig;
+use prost::Message;
+use prost::encoding::{decode_key, decode_varint, WireType};
+use prost_types::{DescriptorProto, EnumDescriptorProto, FieldDescriptorProto, FileDescriptorSet};
+use prost_types::field_descriptor_proto::{Label, Type};
+use serde_json::{json, Map, Value};
+
+use pact_models::content_types::ContentType;
+use pact_models::http_parts::HttpPart;
+use pact_models::path_exp::DocPath;
+
+use crate::{MatchingContext, Mismatch};
+use crate::json::compare_json;
+
+/// Name of the protobuf plugin in the plugin configuration
+const PROTOBUF_PLUGIN: &str = "protobuf";
+
+/// Loads the protobuf descriptors for the interaction from the plugin configuration. The
+/// interaction configuration has the key of the descriptors (`descriptorKey`), and the Pact
+/// configuration for that key has the Base64 encoded `FileDescriptorSet` (`protoDescriptors`).
+pub(crate) fn descriptors_from_plugin_config(
+  plugin_config: &HashMap<String, PluginInteractionConfig>
+) -> Option<FileDescriptorSet> {
+  let config = plugin_config.get(PROTOBUF_PLUGIN)?;
+  let key = config.interaction_configuration.get("descriptorKey")?.as_str()?;
+  let encoded = config.pact_configuration.get(key)?.get("protoDescriptors")?.as_str()?;
+  let descriptors = base64::decode(encoded)
+    .map_err(|err| anyhow!(err))
+    .and_then(|data| FileDescriptorSet::decode(data.as_slice()).map_err(|err| anyhow!(err)));
+  match descriptors {
+    Ok(descriptors) => Some(descriptors),
+    Err(err) => {
+      warn!("Failed to load the protobuf descriptors '{}' from the plugin configuration - {}", key, err);
+      None
+    }
+  }
+}
+
+/// If the content type is one of the protobuf content types
+pub(crate) fn is_protobuf_content_type(content_type: &ContentType) -> bool {
+  matches!(content_type.base_type().to_string().as_str(), "application/protobuf" | "application/x-protobuf" |
+    "application/grpc" | "application/grpc+proto")
+}
+
+/// Matches the expected protobuf body to the actual one, using the protobuf descriptors from the
+/// matching context to decode them
+pub(crate) fn match_protobuf(
+  expected: &dyn HttpPart,
+  actual: &dyn HttpPart,
+  context: &dyn MatchingContext
+) -> Result<(), Vec<Mismatch>> {
+  let content_type = expected.content_type().unwrap_or_default();
+  let decoded = decode_bodies(&content_type, expected, actual, context);
+  debug!("match_protobuf: decoded bodies -> {:?}", decoded);
+  match decoded {
+    Ok((expected_value, actual_value)) => compare_json(&DocPath::root(), &expected_value, &actual_value, context),
+    Err(err) => Err(vec![ Mismatch::BodyMismatch {
+      path: "$".to_string(),
+      expected: expected.body().value(),
+      actual: actual.body().value(),
+      mismatch: err.to_string(),
+      rule: None
+    } ])
+  }
+}
+
+fn decode_bodies(
+  content_type: &ContentType,
+  expected: &dyn HttpPart,
+  actual: &dyn HttpPart,
+  context: &dyn MatchingContext
+) -> anyhow::Result<(Value, Value)> {
+  let descriptors = context.protobuf_descriptors()
+    .ok_or_else(|| anyhow!("No protobuf descriptors are available to decode the '{}' body", content_type))?;
+  let message_name = content_type.attributes.get("message")
+    .ok_or_else(|| anyhow!("The content type '{}' does not specify the protobuf message type (the 'message' parameter)",
+      content_type))?;
+  let descriptor = find_message(descriptors, message_name)
+    .ok_or_else(|| anyhow!("Protobuf message type '{}' was not found in the descriptors", message_name))?;
+  let expected_value = decode_message(descriptors, descriptor, expected.body().value().unwrap_or_default())
+    .map_err(|err| anyhow!("Failed to decode the expected body as a '{}' message - {}", message_name, err))?;
+  let actual_value = decode_message(descriptors, descriptor, actual.body().value().unwrap_or_default())
+    .map_err(|err| anyhow!("Failed to decode the actual body as a '{}' message - {}", message_name, err))?;
+  Ok((expected_value, actual_value))
+}
+
+/// Decodes the protobuf message into a JSON field tree, keyed by the field names
+fn decode_message(descriptors: &FileDescriptorSet, descriptor: &DescriptorProto, mut buffer: Bytes) -> anyhow::Result<Value> {
+  let mut fields = Map::new();
+  while buffer.has_remaining() {
+    let (number, wire_type) = decode_key(&mut buffer)?;
+    let field = descriptor.field.iter().find(|f| f.number == Some(number as i32));
+    let value = decode_field(descriptors, field, wire_type, &mut buffer)?;
+    let name = field.map(|f| f.name.clone().unwrap_or_default())
+      .unwrap_or_else(|| number.to_string());
+    match field {
+      Some(field) if field.label() == Label::Repeated => {
+        let entry = fields.entry(name).or_insert_with(|| json!([]));
+        match (entry, value) {
+          (Value::Array(items), Value::Array(values)) if is_packable(field) => items.extend(values),
+          (Value::Array(items), value) => items.push(value),
+          _ => {}
+        }
+      }
+      _ => {
+        fields.insert(name, value);
+      }
+    }
+  }
+
+  for field in descriptor.field.iter().filter(|f| f.label() == Label::Repeated && f.r#type() == Type::Message) {
+    let is_map = find_message(descriptors, field.type_name()).map(is_map_entry).unwrap_or(false);
+    let name = field.name.clone().unwrap_or_default();
+    if is_map {
+      if let Some(Value::Array(entries)) = fields.remove(&name) {
+        let map = entries.iter()
+          .map(|entry| (entry.get("key").map(map_key).unwrap_or_default(), entry.get("value").cloned().unwrap_or_default()))
+          .collect();
+        fields.insert(name, Value::Object(map));
+      }
+    }
+  }
+
+  Ok(Value::Object(fields))
+}
+
+fn decode_field(
+  descriptors: &FileDescriptorSet,
+  field: Option<&FieldDescriptorProto>,
+  wire_type: WireType,
+  buffer: &mut Bytes
+) -> anyhow::Result<Value> {
+  match wire_type {
+    WireType::Varint => {
+      let value = decode_varint(buffer)?;
+      Ok(field.map(|field| varint_value(descriptors, field, value)).unwrap_or_else(|| json!(value)))
+    }
+    WireType::SixtyFourBit => {
+      check_remaining(buffer, 8)?;
+      let value = buffer.get_u64_le();
+      Ok(match field.map(|f| f.r#type()) {
+        Some(Type::Double) => json!(f64::from_bits(value)),
+        Some(Type::Sfixed64) => json!(value as i64),
+        _ => json!(value)
+      })
+    }
+    WireType::ThirtyTwoBit => {
+      check_remaining(buffer, 4)?;
+      let value = buffer.get_u32_le();
+      Ok(match field.map(|f| f.r#type()) {
+        Some(Type::Float) => json!(f32::from_bits(value)),
+        Some(Type::Sfixed32) => json!(value as i32),
+        _ => json!(value)
+      })
+    }
+    WireType::LengthDelimited => {
+      let length = decode_varint(buffer)? as usize;
+      check_remaining(buffer, length)?;
+      let data = buffer.split_to(length);
+      match field {
+        Some(field) => match field.r#type() {
+          Type::String => Ok(Value::String(String::from_utf8(data.to_vec())?)),
+          Type::Message => match find_message(descriptors, field.type_name()) {
+            Some(descriptor) => decode_message(descriptors, descriptor, data),
+            None => Err(anyhow!("Protobuf message type '{}' was not found in the descriptors", field.type_name()))
+          },
+          _ if is_packable(field) => decode_packed(descriptors, field, data),
+          _ => Ok(Value::String(base64::encode(&data)))
+        },
+        None => Ok(Value::String(base64::encode(&data)))
+      }
+    }
+    _ => Err(anyhow!("Protobuf groups are not supported"))
+  }
+}
+
+fn decode_packed(descriptors: &FileDescriptorSet, field: &FieldDescriptorProto, mut data: Bytes) -> anyhow::Result<Value> {
+  let wire_type = match field.r#type() {
+    Type::Double | Type::Fixed64 | Type::Sfixed64 => WireType::SixtyFourBit,
+    Type::Float | Type::Fixed32 | Type::Sfixed32 => WireType::ThirtyTwoBit,
+    _ => WireType::Varint
+  };
+  let mut values = vec![];
+  while data.has_remaining() {
+    values.push(decode_field(descriptors, Some(field), wire_type, &mut data)?);
+  }
+  Ok(Value::Array(values))
+}
+
+fn varint_value(descriptors: &FileDescriptorSet, field: &FieldDescriptorProto, value: u64) -> Value {
+  match field.r#type() {
+    Type::Bool => json!(value != 0),
+    Type::Int32 => json!(value as i32),
+    Type::Int64 => json!(value as i64),
+    Type::Uint32 => json!(value as u32),
+    Type::Sint32 => json!(((value >> 1) as i32) ^ -((value & 1) as i32)),
+    Type::Sint64 => json!(((value >> 1) as i64) ^ -((value & 1) as i64)),
+    Type::Enum => find_enum(descriptors, field.type_name())
+      .and_then(|e| e.value.iter().find(|v| v.number == Some(value as i32)))
+      .and_then(|v| v.name.clone())
+      .map(Value::String)
+      .unwrap_or_else(|| json!(value as i32)),
+    _ => json!(value)
+  }
+}
+
+fn is_packable(field: &FieldDescriptorProto) -> bool {
+  field.label() == Label::Repeated &&
+    !matches!(field.r#type(), Type::String | Type::Bytes | Type::Message | Type::Group)
+}
+
+fn is_map_entry(descriptor: &DescriptorProto) -> bool {
+  descriptor.options.as_ref().and_then(|options| options.map_entry).unwrap_or(false)
+}
+
+fn map_key(key: &Value) -> String {
+  match key {
+    Value::String(s) => s.clone(),
+    _ => key.to_string()
+  }
+}
+
+fn check_remaining(buffer: &Bytes, length: usize) -> anyhow::Result<()> {
+  if buffer.remaining() < length {
+    Err(anyhow!("Expected {} more bytes, but only {} are remaining", length, buffer.remaining()))
+  } else {
+    Ok(())
+  }
+}
+
+fn qualified_name(name: &str) -> String {
+  if name.starts_with('.') {
+    name.to_string()
+  } else {
+    format!(".{}", name)
+  }
+}
+
+fn package_prefix(package: &Option<String>) -> String {
+  match package {
+    Some(package) if !package.is_empty() => format!(".{}", package),
+    _ => String::default()
+  }
+}
+
+/// Finds the message descriptor with the fully qualified name (i.e. `.package.Message`)
+fn find_message<'a>(descriptors: &'a FileDescriptorSet, name: &str) -> Option<&'a DescriptorProto> {
+  let name = qualified_name(name);
+  descriptors.file.iter().find_map(|file| {
+    find_nested_message(&package_prefix(&file.package), &file.message_type, &name)
+  })
+}
+
+fn find_nested_message<'a>(prefix: &str, messages: &'a [DescriptorProto], name: &str) -> Option<&'a DescriptorProto> {
+  messages.iter().find_map(|message| {
+    let message_name = format!("{}.{}", prefix, message.name.clone().unwrap_or_default());
+    if message_name == name {
+      Some(message)
+    } else {
+      find_nested_message(&message_name, &message.nested_type, name)
+    }
+  })
+}
+
+/// Finds the enum descriptor with the fully qualified name (i.e. `.package.Enum`)
+fn find_enum<'a>(descriptors: &'a FileDescriptorSet, name: &str) -> Option<&'a EnumDescriptorProto> {
+  let name = qualified_name(name);
+  descriptors.file.iter().find_map(|file| {
+    let prefix = package_prefix(&file.package);
+    find_enum_in(&prefix, &file.enum_type, &name)
+      .or_else(|| find_nested_enum(&prefix, &file.message_type, &name))
+  })
+}
+
+fn find_enum_in<'a>(prefix: &str, enums: &'a [EnumDescriptorProto], name: &str) -> Option<&'a EnumDescriptorProto> {
+  enums.iter().find(|e| format!("{}.{}", prefix, e.name.clone().unwrap_or_default()) == name)
+}
+
+fn find_nested_enum<'a>(prefix: &str, messages: &'a [DescriptorProto], name: &str) -> Option<&'a EnumDescriptorProto> {
+  messages.iter().find_map(|message| {
+    let message_name = format!("{}.{}", prefix, message.name.clone().unwrap_or_default());
+    find_enum_in(&message_name, &message.enum_type, name)
+      .or_else(|| find_nested_enum(&message_name, &message.nested_type, name))
+  })
+}
+
+#[cfg(test)]
+mod tests {
+  use bytes::{Bytes, BytesMut};
+  use expectest::prelude::*;
+  use itertools::Itertools;
+  use maplit::hashmap;
+  use prost::Message;
+  use prost::encoding::{encode_key, encode_varint, WireType};
+  use prost_types::{DescriptorProto, FieldDescriptorProto, FileDescriptorProto, FileDescriptorSet};
+  use prost_types::field_descriptor_proto::{Label, Type};
+  use serde_json::json;
+
+  use pact_models::bodies::OptionalBody;
+  use pact_models::interaction::Interaction;
+  use pact_models::matchingrules;
+  use pact_models::matchingrules::MatchingRule;
+  use pact_models::pact::Pact;
+  use pact_models::plugins::PluginData;
+  use pact_models::request::Request;
+  use pact_models::v4::http_parts::HttpResponse;
+  use pact_models::v4::interaction::V4Interaction;
+  use pact_models::v4::pact::V4Pact;
+  use pact_models::v4::synch_http::SynchronousHttp;
+
+  use crate::{CoreMatchingContext, DiffConfig, match_body, match_response, Mismatch};
+
+  fn field(name: &str, number: i32, field_type: Type, label: Label, type_name: Option<&str>) -> FieldDescriptorProto {
+    FieldDescriptorProto {
+      name: Some(name.to_string()),
+      number: Some(number),
+      label: Some(label as i32),
+      r#type: Some(field_type as i32),
+      type_name: type_name.map(|name| name.to_string()),
+      .. FieldDescriptorProto::default()
+    }
+  }
+
+  // message Person { string name = 1; int32 id = 2; repeated string emails = 3; Address address = 4; }
+  // message Address { string street = 1; }
+  fn descriptors() -> FileDescriptorSet {
+    FileDescriptorSet {
+      file: vec![
+        FileDescriptorProto {
+          name: Some("person.proto".to_string()),
+          package: Some("test".to_string()),
+          message_type: vec![
+            DescriptorProto {
+              name: Some("Person".to_string()),
+              field: vec![
+                field("name", 1, Type::String, Label::Optional, None),
+                field("id", 2, Type::Int32, Label::Optional, None),
+                field("emails", 3, Type::String, Label::Repeated, None),
+                field("address", 4, Type::Message, Label::Optional, Some(".test.Address"))
+              ],
+              .. DescriptorProto::default()
+            },
+            DescriptorProto {
+              name: Some("Address".to_string()),
+              field: vec![ field("street", 1, Type::String, Label::Optional, None) ],
+              .. DescriptorProto::default()
+            }
+          ],
+          .. FileDescriptorProto::default()
+        }
+      ]
+    }
+  }
+
+  fn encode_string(tag: u32, value: &str, buffer: &mut BytesMut) {
+    encode_key(tag, WireType::LengthDelimited, buffer);
+    encode_varint(value.len() as u64, buffer);
+    buffer.extend_from_slice(value.as_bytes());
+  }
+
+  fn person(name: &str, id: u64, emails: &[&str], street: &str) -> Bytes {
+    let mut buffer = BytesMut::new();
+    encode_string(1, name, &mut buffer);
+    encode_key(2, WireType::Varint, &mut buffer);
+    encode_varint(id, &mut buffer);
+    for email in emails {
+      encode_string(3, email, &mut buffer);
+    }
+    let mut address = BytesMut::new();
+    encode_string(1, street, &mut address);
+    encode_key(4, WireType::LengthDelimited, &mut buffer);
+    encode_varint(address.len() as u64, &mut buffer);
+    buffer.extend_from_slice(&address);
+    buffer.freeze()
+  }
+
+  fn request(body: Bytes) -> Request {
+    Request {
+      headers: Some(hashmap!{
+        "Content-Type".to_string() => vec!["application/protobuf; message=.test.Person".to_string()]
+      }),
+      body: OptionalBody::Present(body, None, None),
+      .. Request::default()
+    }
+  }
+
+  fn context(descriptors: Option<FileDescriptorSet>) -> CoreMatchingContext {
+    CoreMatchingContext {
+      protobuf_descriptors: descriptors,
+      .. CoreMatchingContext::new(DiffConfig::AllowUnexpectedKeys, &matchingrules! {
+        "body" => { "$.id" => [ MatchingRule::Integer ] }
+      }.rules_for_category("body").unwrap(), &hashmap!{})
+    }
+  }
+
+  fn mismatches(result: Vec<Mismatch>) -> Vec<String> {
+    result.iter().map(|mismatch| mismatch.description()).sorted().collect()
+  }
+
+  #[tokio::test]
+  async fn matches_the_protobuf_message_fields() {
+    let expected = request(person("Fred", 100, &["fred@example.com"], "Main St"));
+    let actual = request(person("Fred", 2000, &["fred@example.com"], "Main St"));
+    let result = match_body(&expected, &actual, &context(Some(descriptors())),
+      &CoreMatchingContext::default()).await;
+    expect!(result.mismatches().is_empty()).to(be_true());
+  }
+
+  #[tokio::test]
+  async fn reports_a_mismatch_for_a_different_field() {
+    let expected = request(person("Fred", 100, &["fred@example.com"], "Main St"));
+    let actual = request(person("Mary", 100, &["fred@example.com"], "High St"));
+    let result = match_body(&expected, &actual, &context(Some(descriptors())),
+      &CoreMatchingContext::default()).await;
+    expect!(mismatches(result.mismatches())).to(be_equal_to(vec![
+      "$.address.street -> Expected 'Main St' to be equal to 'High St'".to_string(),
+      "$.name -> Expected 'Fred' to be equal to 'Mary'".to_string()
+    ]));
+  }
+
+  #[tokio::test]
+  async fn reports_an_error_if_there_are_no_descriptors() {
+    let expected = request(person("Fred", 100, &[], "Main St"));
+    let actual = request(person("Fred", 100, &[], "Main St"));
+    let result = match_body(&expected, &actual, &context(None), &CoreMatchingContext::default()).await;
+    expect!(mismatches(result.mismatches())).to(be_equal_to(vec![
+      "$ -> No protobuf descriptors are available to decode the 'application/protobuf;message=.test.Person' body".to_string()
+    ]));
+  }
+
+  fn response(body: Bytes) -> HttpResponse {
+    HttpResponse {
+      headers: Some(hashmap!{
+        "Content-Type".to_string() => vec!["application/protobuf; message=.test.Person".to_string()]
+      }),
+      body: OptionalBody::Present(body, None, None),
+      .. HttpResponse::default()
+    }
+  }
+
+  #[tokio::test]
+  async fn match_response_decodes_the_bodies_with_the_descriptors_from_the_pact() {
+    let interaction = SynchronousHttp {
+      plugin_config: hashmap!{
+        "protobuf".to_string() => hashmap!{ "descriptorKey".to_string() => json!("person") }
+      },
+      .. SynchronousHttp::default()
+    };
+    let pact = V4Pact {
+      interactions: vec![ interaction.boxed_v4() ],
+      plugin_data: vec![ PluginData {
+        name: "protobuf".to_string(),
+        version: "0.1.0".to_string(),
+        configuration: hashmap!{
+          "person".to_string() => json!({ "protoDescriptors": base64::encode(descriptors().encode_to_vec()) })
+        }
+      } ],
+      .. V4Pact::default()
+    }.boxed();
+    let interaction = interaction.boxed();
+    let expected = response(person("Fred", 100, &["fred@example.com"], "Main St"));
+
+    let result = match_response(expected.clone(), response(person("Fred", 100, &["fred@example.com"], "Main St")),
+      &pact, &interaction).await;
+    expect!(result.iter()).to(be_empty());
+
+    let result = match_response(expected, response(person("Mary", 100, &["fred@example.com"], "Main St")),
+      &pact, &interaction).await;
+    expect!(mismatches(result)).to(be_equal_to(vec![
+      "$.name -> Expected 'Fred' to be equal to 'Mary'".to_string()
+    ]));
+  }
+}
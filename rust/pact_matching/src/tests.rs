@@ -4,9 +4,15 @@ use expectest::prelude::*;
 
 use pact_models::{matchingrules, matchingrules_list};
 use pact_models::bodies::OptionalBody;
-use pact_models::content_types::TEXT;
+use pact_models::content_types::{JSON, TEXT};
 use pact_models::HttpStatus;
+use pact_models::provider_states::ProviderState;
 use pact_models::request::Request;
+use pact_models::sync_interaction::RequestResponseInteraction;
+use pact_models::sync_pact::RequestResponsePact;
+use pact_models::v4::interaction::V4Interaction;
+use pact_models::v4::pact::V4Pact;
+use pact_models::v4::synch_http::SynchronousHttp;
 
 use super::*;
 
@@ -58,6 +64,23 @@ fn match_status_using_matchers() {
     be_equal_to("Expected status code 500 to be a Successful response (200–299)"));
 }
 
+#[test]
+fn match_status_using_a_status_rule_loaded_from_the_status_path() {
+  let rules = pact_models::matchingrules::matchers_from_json(&json!({
+    "matchingRules": {
+      "$.status": { "match": "statusCode", "status": "success" }
+    }
+  }), &None).unwrap();
+  let context = CoreMatchingContext::new(
+    DiffConfig::AllowUnexpectedKeys,
+    &rules.rules_for_category("status").unwrap_or_default(), &hashmap!{}
+  );
+  expect!(match_status(200, 200, &context)).to(be_ok());
+  expect!(match_status(200, 204, &context)).to(be_ok());
+  expect!(match_status(200, 404, &context)).to(be_err());
+  expect!(match_status(200, 204, &CoreMatchingContext::default())).to(be_err());
+}
+
 #[test]
 fn match_query_returns_nothing_if_there_are_no_query_strings() {
   let expected = None;
@@ -253,6 +276,52 @@ async fn body_does_not_match_if_different_content_types() {
   }));
 }
 
+#[tokio::test]
+async fn ndjson_bodies_are_compared_line_by_line() {
+  let request = |body: &str| Request {
+    method: s!("GET"),
+    path: s!("/"),
+    query: None,
+    headers: Some(hashmap! { s!("Content-Type") => vec![s!("application/x-ndjson")] }),
+    body: OptionalBody::Present(Bytes::from(body.to_string()), None, None),
+    ..Request::default()
+  };
+
+  let result = match_body(&request("{\"a\":1}\n{\"a\":2}\n"), &request("{\"a\":1}\n{\"a\":2}"),
+    &CoreMatchingContext::default(), &CoreMatchingContext::default()).await;
+  expect!(result.mismatches().iter()).to(be_empty());
+
+  let result = match_body(&request("{\"a\":1}\n{\"a\":2}\n"), &request("{\"a\":1}\n{\"a\":3}"),
+    &CoreMatchingContext::default(), &CoreMatchingContext::default()).await;
+  expect!(result.mismatches().iter().map(|m| m.description()).collect::<Vec<_>>()).to(be_equal_to(vec![
+    "$[1].a -> Expected '2' to be equal to '3'".to_string()
+  ]));
+}
+
+#[tokio::test]
+async fn body_content_type_matching_ignores_the_charset_if_not_expected() {
+  let request = |content_type: &str| Request {
+    method: s!("GET"),
+    path: s!("/"),
+    query: None,
+    headers: Some(hashmap! { s!("Content-Type") => vec![content_type.to_string()] }),
+    body: OptionalBody::Present(Bytes::from("{\"a\":100}"), None, None),
+    ..Request::default()
+  };
+
+  let result = match_body(&request("application/json;version=1"), &request("application/json;version=1;charset=utf-8"),
+    &CoreMatchingContext::default(), &CoreMatchingContext::default()).await;
+  expect!(result.mismatches().iter()).to(be_empty());
+
+  let result = match_body(&request("application/json;charset=UTF-8"), &request("application/json;charset=utf-8"),
+    &CoreMatchingContext::default(), &CoreMatchingContext::default()).await;
+  expect!(result.mismatches().iter()).to(be_empty());
+
+  let result = match_body(&request("application/json;charset=utf-8"), &request("application/json;charset=utf-16"),
+    &CoreMatchingContext::default(), &CoreMatchingContext::default()).await;
+  expect!(result.mismatches().iter()).to_not(be_empty());
+}
+
 #[tokio::test]
 async fn body_matching_uses_any_matcher_for_content_type_header() {
   let expected = Request {
@@ -282,6 +351,77 @@ async fn body_matching_uses_any_matcher_for_content_type_header() {
   expect!(mismatches.iter()).to(be_empty());
 }
 
+const PNG_BYTES: [u8; 33] = [
+  0x89, 0x50, 0x4e, 0x47, 0x0d, 0x0a, 0x1a, 0x0a, 0x00, 0x00, 0x00, 0x0d, 0x49, 0x48, 0x44, 0x52,
+  0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0x01, 0x08, 0x06, 0x00, 0x00, 0x00, 0x1f, 0x15, 0xc4,
+  0x89
+];
+
+const JPEG_BYTES: [u8; 24] = [
+  0xff, 0xd8, 0xff, 0xe0, 0x00, 0x10, 0x4a, 0x46, 0x49, 0x46, 0x00, 0x01, 0x01, 0x00, 0x00, 0x01,
+  0x00, 0x01, 0x00, 0x00, 0xff, 0xdb, 0x00, 0x43
+];
+
+fn image_request(body: &[u8]) -> Request {
+  Request {
+    headers: Some(hashmap! { s!("Content-Type") => vec![s!("image/png")] }),
+    body: OptionalBody::Present(Bytes::copy_from_slice(body), None, None),
+    ..Request::default()
+  }
+}
+
+fn png_content_type_context() -> CoreMatchingContext {
+  CoreMatchingContext::new(DiffConfig::AllowUnexpectedKeys, &matchingrules! {
+    "body" => { "$" => [ MatchingRule::ContentType("image/png".into()) ] }
+  }.rules_for_category("body").unwrap_or_default(), &hashmap!{})
+}
+
+#[tokio::test]
+#[cfg(not(target_os = "windows"))] // Requires shared mime-info db, not available on Windows
+async fn body_matching_uses_the_content_type_matcher_for_binary_bodies() {
+  let mut actual = PNG_BYTES.to_vec();
+  actual[19] = 0x02;
+  let result = match_body(&image_request(&PNG_BYTES), &image_request(&actual),
+    &png_content_type_context(), &CoreMatchingContext::default()).await;
+  expect!(result.mismatches().iter()).to(be_empty());
+}
+
+#[tokio::test]
+#[cfg(not(target_os = "windows"))] // Requires shared mime-info db, not available on Windows
+async fn body_matching_with_the_content_type_matcher_fails_for_a_different_binary_type() {
+  let result = match_body(&image_request(&PNG_BYTES), &image_request(&JPEG_BYTES),
+    &png_content_type_context(), &CoreMatchingContext::default()).await;
+  let mismatches = result.mismatches();
+  expect!(mismatches.iter()).to(have_count(1));
+  expect!(mismatches[0].description()).to(be_equal_to(
+    "$ -> Expected binary contents to have content type 'image/png' but detected contents was 'image/jpeg'"));
+}
+
+#[tokio::test]
+async fn body_matching_treats_null_and_empty_bodies_as_equal_if_configured() {
+  let request = |body: OptionalBody| Request {
+    headers: Some(hashmap! { s!("Content-Type") => vec![s!("application/json")] }),
+    body,
+    ..Request::default()
+  };
+  let context = CoreMatchingContext {
+    empty_string_equals_null: true,
+    .. CoreMatchingContext::default()
+  };
+
+  let result = match_body(&request(OptionalBody::Present(Bytes::new(), None, None)), &request(OptionalBody::Null),
+    &context, &CoreMatchingContext::default()).await;
+  expect!(result.mismatches().iter()).to(be_empty());
+
+  let result = match_body(&request(OptionalBody::Null), &request(OptionalBody::Present(Bytes::new(), None, None)),
+    &context, &CoreMatchingContext::default()).await;
+  expect!(result.mismatches().iter()).to(be_empty());
+
+  let result = match_body(&request(OptionalBody::Null), &request(OptionalBody::Present(Bytes::new(), None, None)),
+    &CoreMatchingContext::default(), &CoreMatchingContext::default()).await;
+  expect!(result.mismatches().iter()).to_not(be_empty());
+}
+
 #[tokio::test]
 async fn body_matches_if_expected_is_missing() {
   let expected = Request {
@@ -341,7 +481,7 @@ fn partial_equal_for_method_mismatch() {
   expect!(&mismatch).to_not(be_equal_to(&Mismatch::QueryMismatch { parameter: s!(""), expected: s!(""), actual: s!(""), mismatch: "".into() }));
   expect!(&mismatch).to_not(be_equal_to(&Mismatch::HeaderMismatch { key: s!(""), expected: s!(""), actual: s!(""), mismatch: "".into() }));
   expect!(&mismatch).to_not(be_equal_to(&Mismatch::BodyTypeMismatch { expected: s!(""), actual: s!(""), mismatch: "".into(), expected_body: None, actual_body: None }));
-  expect!(&mismatch).to_not(be_equal_to(&Mismatch::BodyMismatch { expected: Some("get".into()), actual: Some("post".into()), mismatch: "".into(), path: s!("/") }));
+  expect!(&mismatch).to_not(be_equal_to(&Mismatch::BodyMismatch { expected: Some("get".into()), actual: Some("post".into()), mismatch: "".into(), path: s!("/"), rule: None }));
 }
 
 #[test]
@@ -359,7 +499,7 @@ fn partial_equal_for_path_mismatch() {
   expect!(&mismatch).to_not(be_equal_to(&Mismatch::QueryMismatch { parameter: s!(""), expected: s!(""), actual: s!(""), mismatch: "".into() }));
   expect!(&mismatch).to_not(be_equal_to(&Mismatch::HeaderMismatch { key: s!(""), expected: s!(""), actual: s!(""), mismatch: "".into() }));
   expect!(&mismatch).to_not(be_equal_to(&Mismatch::BodyTypeMismatch { expected: s!(""), actual: s!(""), mismatch: "".into(), expected_body: None, actual_body: None }));
-  expect!(&mismatch).to_not(be_equal_to(&Mismatch::BodyMismatch { expected: Some("get".into()), actual: Some("post".into()), mismatch: "".into(), path: s!("/") }));
+  expect!(&mismatch).to_not(be_equal_to(&Mismatch::BodyMismatch { expected: Some("get".into()), actual: Some("post".into()), mismatch: "".into(), path: s!("/"), rule: None }));
 }
 
 #[test]
@@ -377,7 +517,7 @@ fn partial_equal_for_status_mismatch() {
   expect!(&mismatch).to_not(be_equal_to(&Mismatch::QueryMismatch { parameter: s!(""), expected: s!(""), actual: s!(""), mismatch: s!("") }));
   expect!(&mismatch).to_not(be_equal_to(&Mismatch::HeaderMismatch { key: s!(""), expected: s!(""), actual: s!(""), mismatch: s!("") }));
   expect!(&mismatch).to_not(be_equal_to(&Mismatch::BodyTypeMismatch { expected: s!(""), actual: s!(""), mismatch: s!(""), expected_body: None, actual_body: None }));
-  expect!(&mismatch).to_not(be_equal_to(&Mismatch::BodyMismatch { expected: Some("get".into()), actual: Some("post".into()), mismatch: s!(""), path: s!("/") }));
+  expect!(&mismatch).to_not(be_equal_to(&Mismatch::BodyMismatch { expected: Some("get".into()), actual: Some("post".into()), mismatch: s!(""), path: s!("/"), rule: None }));
 }
 
 #[test]
@@ -395,7 +535,7 @@ fn partial_equal_for_body_type_mismatch() {
   expect!(&mismatch).to_not(be_equal_to(&Mismatch::QueryMismatch { parameter: s!(""), expected: s!(""), actual: s!(""), mismatch: s!("") }));
   expect!(&mismatch).to_not(be_equal_to(&Mismatch::HeaderMismatch { key: s!(""), expected: s!(""), actual: s!(""), mismatch: s!("") }));
   expect!(&mismatch).to_not(be_equal_to(&Mismatch::PathMismatch { expected: s!(""), actual: s!(""), mismatch: s!("") }));
-  expect!(&mismatch).to_not(be_equal_to(&Mismatch::BodyMismatch { expected: Some("get".into()), actual: Some("post".into()), mismatch: s!(""), path: s!("/") }));
+  expect!(&mismatch).to_not(be_equal_to(&Mismatch::BodyMismatch { expected: Some("get".into()), actual: Some("post".into()), mismatch: s!(""), path: s!("/"), rule: None }));
 }
 
 #[test]
@@ -417,7 +557,7 @@ fn partial_equal_for_query_mismatch() {
   expect!(&mismatch).to_not(be_equal_to(&Mismatch::PathMismatch { expected: s!(""), actual: s!(""), mismatch: s!("") }));
   expect!(&mismatch).to_not(be_equal_to(&Mismatch::HeaderMismatch { key: s!(""), expected: s!(""), actual: s!(""), mismatch: s!("") }));
   expect!(&mismatch).to_not(be_equal_to(&Mismatch::BodyTypeMismatch { expected: s!(""), actual: s!(""), mismatch: s!(""), expected_body: None, actual_body: None }));
-  expect!(&mismatch).to_not(be_equal_to(&Mismatch::BodyMismatch { expected: Some("get".into()), actual: Some("post".into()), mismatch: s!(""), path: s!("/") }));
+  expect!(&mismatch).to_not(be_equal_to(&Mismatch::BodyMismatch { expected: Some("get".into()), actual: Some("post".into()), mismatch: s!(""), path: s!("/"), rule: None }));
 }
 
 #[test]
@@ -439,17 +579,17 @@ fn partial_equal_for_header_mismatch() {
   expect!(&mismatch).to_not(be_equal_to(&Mismatch::PathMismatch { expected: s!(""), actual: s!(""), mismatch: s!("") }));
   expect!(&mismatch).to_not(be_equal_to(&Mismatch::QueryMismatch { parameter: s!(""), expected: s!(""), actual: s!(""), mismatch: s!("") }));
   expect!(&mismatch).to_not(be_equal_to(&Mismatch::BodyTypeMismatch { expected: s!(""), actual: s!(""), mismatch: s!(""), expected_body: None, actual_body: None }));
-  expect!(&mismatch).to_not(be_equal_to(&Mismatch::BodyMismatch { expected: Some("get".into()), actual: Some("post".into()), mismatch: s!(""), path: s!("/") }));
+  expect!(&mismatch).to_not(be_equal_to(&Mismatch::BodyMismatch { expected: Some("get".into()), actual: Some("post".into()), mismatch: s!(""), path: s!("/"), rule: None }));
 }
 
 #[test]
 fn partial_equal_for_body_mismatch() {
-  let mismatch = Mismatch::BodyMismatch { path: s!("key"), expected: Some("v1".into()), actual: Some("v2".into()), mismatch: s!("") };
-  let mismatch2 = Mismatch::BodyMismatch { path: s!("key"), expected: Some("v1".into()), actual: Some("v2".into()), mismatch: s!("") };
-  let mismatch3 = Mismatch::BodyMismatch { path: s!("key2"), expected: Some("v1".into()), actual: Some("v2".into()), mismatch: s!("") };
-  let mismatch4 = Mismatch::BodyMismatch { path: s!("key"), expected: None, actual: Some("v2".into()), mismatch: s!("") };
-  let mismatch5 = Mismatch::BodyMismatch { path: s!("key"), expected: Some("v1".into()), actual: None, mismatch: s!("") };
-  let mismatch6 = Mismatch::BodyMismatch { path: s!("key"), expected: Some("v1".into()), actual: Some("v2".into()), mismatch: s!("did not match") };
+  let mismatch = Mismatch::BodyMismatch { path: s!("key"), expected: Some("v1".into()), actual: Some("v2".into()), mismatch: s!(""), rule: None };
+  let mismatch2 = Mismatch::BodyMismatch { path: s!("key"), expected: Some("v1".into()), actual: Some("v2".into()), mismatch: s!(""), rule: None };
+  let mismatch3 = Mismatch::BodyMismatch { path: s!("key2"), expected: Some("v1".into()), actual: Some("v2".into()), mismatch: s!(""), rule: None };
+  let mismatch4 = Mismatch::BodyMismatch { path: s!("key"), expected: None, actual: Some("v2".into()), mismatch: s!(""), rule: None };
+  let mismatch5 = Mismatch::BodyMismatch { path: s!("key"), expected: Some("v1".into()), actual: None, mismatch: s!(""), rule: None };
+  let mismatch6 = Mismatch::BodyMismatch { path: s!("key"), expected: Some("v1".into()), actual: Some("v2".into()), mismatch: s!("did not match"), rule: None };
   expect!(&mismatch).to(be_equal_to(&mismatch));
   expect!(&mismatch).to(be_equal_to(&mismatch2));
   expect!(&mismatch).to(be_equal_to(&mismatch6));
@@ -510,6 +650,35 @@ fn match_path_returns_a_mismatch_if_the_path_does_not_match_with_a_matcher() {
   }]));
 }
 
+#[test]
+fn match_path_applies_segment_matchers_to_the_path_segments() {
+  let context = CoreMatchingContext::new(
+    DiffConfig::AllowUnexpectedKeys,
+    &matchingrules! {
+        "path" => { "$[1]" => [ MatchingRule::Type ], "$[3]" => [ MatchingRule::Regex(s!("\\d+")) ] }
+    }.rules_for_category("path").unwrap_or_default(), &hashmap!{}
+  );
+  let result = match_path(&"/users/100/orders/1".to_string(), &"/users/abc/orders/2002".to_string(), &context);
+  expect!(result).to(be_ok());
+}
+
+#[test]
+fn match_path_returns_a_mismatch_if_a_literal_segment_does_not_match() {
+  let context = CoreMatchingContext::new(
+    DiffConfig::AllowUnexpectedKeys,
+    &matchingrules! {
+        "path" => { "$[1]" => [ MatchingRule::Type ] }
+    }.rules_for_category("path").unwrap_or_default(), &hashmap!{}
+  );
+  let result = match_path(&"/users/100/orders".to_string(), &"/users/200/invoices".to_string(), &context);
+  expect!(result.unwrap_err().iter().map(|mismatch| mismatch.description()).collect::<Vec<_>>()).to(be_equal_to(vec![
+    s!("Path segment 2: Expected 'orders' to be equal to 'invoices'")
+  ]));
+
+  let result = match_path(&"/users/100/orders".to_string(), &"/users/200".to_string(), &context);
+  expect!(result).to(be_err());
+}
+
 #[test]
 fn match_query_returns_no_mismatch_if_the_values_are_not_the_same_but_match_by_a_matcher() {
   let context = CoreMatchingContext::new(
@@ -527,7 +696,7 @@ fn match_query_returns_no_mismatch_if_the_values_are_not_the_same_but_match_by_a
   query_map.insert(s!("a"), vec![s!("c")]);
   let actual = Some(query_map);
   let result = match_query(expected, actual, &context);
-  expect!(result.get("a".into()).unwrap().iter()).to(be_empty());
+  expect!(result.get("a").unwrap().iter()).to(be_empty());
 }
 
 #[test]
@@ -548,7 +717,7 @@ fn match_query_returns_a_mismatch_if_the_values_do_not_match_by_a_matcher() {
   let actual = Some(query_map);
   let result = match_query(expected, actual, &context);
   expect!(result.iter()).to_not(be_empty());
-  assert_eq!(result.get("a".into()).unwrap()[0], Mismatch::QueryMismatch {
+  assert_eq!(result.get("a").unwrap()[0], Mismatch::QueryMismatch {
     parameter: s!("a"),
     expected: s!("b"),
     actual: s!("b"),
@@ -581,6 +750,7 @@ async fn matching_text_body_be_false_when_bodies_are_not_equal() {
     expected: expected.body.value(),
     actual: actual.body.value(),
     mismatch: s!(""),
+    rule: None,
   });
 }
 
@@ -639,3 +809,235 @@ fn values_matcher_defined() {
   expect!(context.values_matcher_defined(&path_x.join("0").join("z"))).to(be_false());
   expect!(context.values_matcher_defined(&path_y.join("0").join("y"))).to(be_false());
 }
+
+#[test]
+fn mismatch_summary_and_description() {
+  let mismatch = Mismatch::MethodMismatch { expected: "GET".into(), actual: "POST".into() };
+  expect!(mismatch.summary()).to(be_equal_to("is a GET request"));
+  expect!(mismatch.description()).to(be_equal_to("expected GET but was POST"));
+
+  let mismatch = Mismatch::PathMismatch { expected: "/a".into(), actual: "/b".into(),
+    mismatch: "Expected '/a' but got '/b'".into() };
+  expect!(mismatch.summary()).to(be_equal_to("to path '/a'"));
+  expect!(mismatch.description()).to(be_equal_to("Expected '/a' but got '/b'"));
+
+  let mismatch = Mismatch::StatusMismatch { expected: 200, actual: 404,
+    mismatch: "expected 200 but was 404".into() };
+  expect!(mismatch.summary()).to(be_equal_to("has status code 200"));
+  expect!(mismatch.description()).to(be_equal_to("expected 200 but was 404"));
+
+  let mismatch = Mismatch::QueryMismatch { parameter: "a".into(), expected: "b".into(),
+    actual: "c".into(), mismatch: "Expected 'b' but received 'c' for query parameter 'a'".into() };
+  expect!(mismatch.summary()).to(be_equal_to("includes parameter 'a' with value 'b'"));
+  expect!(mismatch.description()).to(be_equal_to("Expected 'b' but received 'c' for query parameter 'a'"));
+
+  let mismatch = Mismatch::HeaderMismatch { key: "A".into(), expected: "b".into(),
+    actual: "c".into(), mismatch: "Mismatch with header 'A': Expected 'b' to be equal to 'c'".into() };
+  expect!(mismatch.summary()).to(be_equal_to("includes header 'A' with value 'b'"));
+  expect!(mismatch.description()).to(be_equal_to("Mismatch with header 'A': Expected 'b' to be equal to 'c'"));
+
+  let mismatch = Mismatch::BodyTypeMismatch { expected: "application/json".into(), actual: "text/plain".into(),
+    mismatch: "".into(), expected_body: None, actual_body: None };
+  expect!(mismatch.summary()).to(be_equal_to("has a matching body"));
+  expect!(mismatch.description()).to(be_equal_to("expected 'application/json' body but was 'text/plain'"));
+
+  let mismatch = Mismatch::BodyMismatch { path: "$.1".into(), expected: None, actual: None,
+    mismatch: "Expected '1' to be equal to '2'".into(), rule: None };
+  expect!(mismatch.summary()).to(be_equal_to("has a matching body"));
+  expect!(mismatch.description()).to(be_equal_to("$.1 -> Expected '1' to be equal to '2'"));
+
+  let mismatch = Mismatch::MetadataMismatch { key: "a".into(), expected: "b".into(), actual: "c".into(),
+    mismatch: "Expected message metadata 'a' to have value 'b' but was 'c'".into() };
+  expect!(mismatch.summary()).to(be_equal_to("has matching metadata"));
+  expect!(mismatch.description()).to(be_equal_to("Expected message metadata 'a' to have value 'b' but was 'c'"));
+
+  let mismatch = Mismatch::InteractionMismatch { interaction: "a request".into(),
+    mismatch: "Interaction 'a request' was added".into() };
+  expect!(mismatch.summary()).to(be_equal_to("has interaction 'a request'"));
+  expect!(mismatch.description()).to(be_equal_to("Interaction 'a request' was added"));
+}
+
+fn interaction_with_body(description: &str, body: &str) -> RequestResponseInteraction {
+  RequestResponseInteraction {
+    description: description.to_string(),
+    provider_states: vec![ ProviderState::default("a user exists") ],
+    request: Request {
+      method: "POST".to_string(),
+      body: OptionalBody::Present(body.as_bytes().to_vec().into(), Some(JSON.clone()), None),
+      .. Request::default()
+    },
+    .. RequestResponseInteraction::default()
+  }
+}
+
+#[tokio::test]
+async fn compare_pacts_returns_no_mismatches_for_the_same_pact() {
+  let pact = RequestResponsePact {
+    interactions: vec![ interaction_with_body("create a user", r#"{"id": 1}"#) ],
+    .. RequestResponsePact::default()
+  };
+
+  expect!(compare_pacts(&pact, &pact).await.iter()).to(be_empty());
+}
+
+#[tokio::test]
+async fn compare_pacts_returns_a_mismatch_for_an_added_interaction() {
+  let expected = RequestResponsePact {
+    interactions: vec![ interaction_with_body("create a user", r#"{"id": 1}"#) ],
+    .. RequestResponsePact::default()
+  };
+  let actual = RequestResponsePact {
+    interactions: vec![
+      interaction_with_body("create a user", r#"{"id": 1}"#),
+      interaction_with_body("update a user", r#"{"id": 1}"#)
+    ],
+    .. RequestResponsePact::default()
+  };
+
+  expect!(compare_pacts(&expected, &actual).await).to(be_equal_to(vec![
+    ("update a user given a user exists".to_string(), vec![
+      Mismatch::InteractionMismatch {
+        interaction: "update a user given a user exists".to_string(),
+        mismatch: "Interaction 'update a user given a user exists' was added".to_string()
+      }
+    ])
+  ]));
+}
+
+#[tokio::test]
+async fn compare_pacts_returns_a_mismatch_for_a_removed_interaction() {
+  let expected = RequestResponsePact {
+    interactions: vec![
+      interaction_with_body("create a user", r#"{"id": 1}"#),
+      interaction_with_body("update a user", r#"{"id": 1}"#)
+    ],
+    .. RequestResponsePact::default()
+  };
+  let actual = RequestResponsePact {
+    interactions: vec![ interaction_with_body("update a user", r#"{"id": 1}"#) ],
+    .. RequestResponsePact::default()
+  };
+
+  expect!(compare_pacts(&expected, &actual).await).to(be_equal_to(vec![
+    ("create a user given a user exists".to_string(), vec![
+      Mismatch::InteractionMismatch {
+        interaction: "create a user given a user exists".to_string(),
+        mismatch: "Interaction 'create a user given a user exists' was removed".to_string()
+      }
+    ])
+  ]));
+}
+
+#[tokio::test]
+async fn compare_pacts_returns_the_mismatches_for_a_changed_request_body() {
+  let expected = RequestResponsePact {
+    interactions: vec![
+      interaction_with_body("create a user", r#"{"id": 1}"#),
+      interaction_with_body("update a user", r#"{"id": 1}"#)
+    ],
+    .. RequestResponsePact::default()
+  };
+  let actual = RequestResponsePact {
+    interactions: vec![
+      interaction_with_body("create a user", r#"{"id": 2}"#),
+      interaction_with_body("update a user", r#"{"id": 3}"#)
+    ],
+    .. RequestResponsePact::default()
+  };
+
+  let result = compare_pacts(&expected, &actual).await;
+  expect!(result.iter()).to(have_count(2));
+  let (interaction, mismatches) = &result[0];
+  expect!(interaction.as_str()).to(be_equal_to("create a user given a user exists"));
+  expect!(mismatches.iter()).to(have_count(1));
+  expect!(mismatches[0].mismatch_type()).to(be_equal_to("BodyMismatch"));
+  expect!(mismatches[0].description()).to(be_equal_to("$.id -> Expected '1' to be equal to '2'"));
+  let (interaction, mismatches) = &result[1];
+  expect!(interaction.as_str()).to(be_equal_to("update a user given a user exists"));
+  expect!(mismatches.iter().map(|m| m.description()).collect::<Vec<String>>()).to(be_equal_to(vec![
+    "$.id -> Expected '1' to be equal to '3'".to_string()
+  ]));
+}
+
+fn message_with_metadata(metadata: HashMap<String, Value>) -> MessageContents {
+  MessageContents {
+    metadata,
+    .. MessageContents::default()
+  }
+}
+
+#[test]
+fn match_message_metadata_with_matching_metadata() {
+  let expected = message_with_metadata(hashmap!{
+    "topic".to_string() => json!("users"),
+    "contentType".to_string() => json!("application/json")
+  });
+  let actual = message_with_metadata(hashmap!{
+    "topic".to_string() => json!("users"),
+    "contentType".to_string() => json!("application/json;charset=UTF-8"),
+    "partition".to_string() => json!(2)
+  });
+  let context = CoreMatchingContext::default();
+
+  let result = match_message_metadata(&expected, &actual, &context);
+  expect!(result.values().flatten()).to(be_empty());
+}
+
+#[test]
+fn match_message_metadata_with_a_missing_key() {
+  let expected = message_with_metadata(hashmap!{ "topic".to_string() => json!("users") });
+  let actual = message_with_metadata(hashmap!{ "partition".to_string() => json!(2) });
+  let context = CoreMatchingContext::default();
+
+  let result = match_message_metadata(&expected, &actual, &context);
+  expect!(result.get("topic").cloned()).to(be_some().value(vec![
+    Mismatch::MetadataMismatch {
+      key: "topic".to_string(),
+      expected: "users".to_string(),
+      actual: "".to_string(),
+      mismatch: "Expected message metadata 'topic' but was missing".to_string()
+    }
+  ]));
+}
+
+#[test]
+fn match_message_metadata_with_a_value_that_fails_a_regex_matcher() {
+  let expected = message_with_metadata(hashmap!{ "topic".to_string() => json!("users.created") });
+  let actual = message_with_metadata(hashmap!{ "topic".to_string() => json!("orders.created") });
+  let context = CoreMatchingContext::new(DiffConfig::AllowUnexpectedKeys, &matchingrules!{
+    "metadata" => { "topic" => [ MatchingRule::Regex(r"^users\.\w+$".to_string()) ] }
+  }.rules_for_category("metadata").unwrap(), &hashmap!{});
+
+  let result = match_message_metadata(&expected, &actual, &context);
+  let mismatches = result.get("topic").cloned().unwrap_or_default();
+  expect!(mismatches.iter()).to(have_count(1));
+  expect!(mismatches[0].description()).to(be_equal_to(
+    r#"Expected metadata key 'topic' to have value '"users.created"' but was '"orders.created"' - Expected 'orders.created' to match '^users\.\w+$'"#));
+}
+
+#[tokio::test]
+async fn match_response_with_config_reports_unexpected_headers_in_strict_mode() {
+  let expected = HttpResponse {
+    headers: Some(hashmap!{ "Content-Type".to_string() => vec!["application/json".to_string()] }),
+    .. HttpResponse::default()
+  };
+  let actual = HttpResponse {
+    headers: Some(hashmap!{
+      "Content-Type".to_string() => vec!["application/json".to_string()],
+      "Date".to_string() => vec!["Wed, 21 Oct 2015 07:28:00 GMT".to_string()],
+      "X-Powered-By".to_string() => vec!["Express".to_string()]
+    }),
+    .. HttpResponse::default()
+  };
+  let interaction = SynchronousHttp { response: expected.clone(), .. SynchronousHttp::default() };
+  let pact = V4Pact { interactions: vec![interaction.boxed_v4()], .. V4Pact::default() }.boxed();
+
+  let mismatches = match_response(expected.clone(), actual.clone(), &pact, &interaction.boxed()).await;
+  expect!(mismatches.iter()).to(be_empty());
+
+  let config = MatchingConfig { header_config: HeaderMatchConfig::strict(), .. MatchingConfig::default() };
+  let mismatches = match_response_with_config(expected, actual, &pact, &interaction.boxed(), &config).await;
+  expect!(mismatches.iter().map(|m| m.description()).collect::<Vec<String>>()).to(be_equal_to(vec![
+    "Unexpected header 'X-Powered-By' received".to_string()
+  ]));
+}
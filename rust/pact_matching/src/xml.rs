@@ -37,14 +37,16 @@ pub fn match_xml(expected: &dyn HttpPart, actual: &dyn HttpPart, context: &dyn M
             path: "$".to_string(),
             expected: expected.body().value(),
             actual: actual.body().value(),
-            mismatch: format!("Failed to parse the expected body: '{:?}'", e)});
+            mismatch: format!("Failed to parse the expected body: '{:?}'", e),
+            rule: None});
         }
         if let Err(e) = actual_result {
           mismatches.push(Mismatch::BodyMismatch {
             path: "$".to_string(),
             expected: expected.body().value(),
             actual: actual.body().value(),
-            mismatch: format!("Failed to parse the actual body: '{:?}'", e)});
+            mismatch: format!("Failed to parse the actual body: '{:?}'", e),
+            rule: None});
         }
       } else {
         let expected_package = expected_result.unwrap();
@@ -54,9 +56,14 @@ pub fn match_xml(expected: &dyn HttpPart, actual: &dyn HttpPart, context: &dyn M
         let actual_root = actual_package.as_document().root();
         let actual_root_node = actual_root.children().iter().cloned().find(|n| n.element().is_some());
         let element = expected_root_node.unwrap().element().unwrap();
+        let actual_element = actual_root_node.unwrap().element().unwrap();
         let name = name(element.name());
         let path = DocPath::root().join(name);
-        compare_element(&path, &element, &actual_root_node.unwrap().element().unwrap(), &mut mismatches, context);
+        if context.xml_subtree_match() {
+          compare_subtree(&path, &element, &actual_element, &mut mismatches, context);
+        } else {
+          compare_element(&path, &element, &actual_element, &mut mismatches, context);
+        }
       }
     },
     _ => {
@@ -64,7 +71,8 @@ pub fn match_xml(expected: &dyn HttpPart, actual: &dyn HttpPart, context: &dyn M
         path: "$".into(),
         expected: expected.body().value(),
         actual: None,
-        mismatch: format!("Expected an XML body {} but was missing", expected.body())
+        mismatch: format!("Expected an XML body {} but was missing", expected.body()),
+        rule: None
       });
     }
   }
@@ -76,6 +84,51 @@ pub fn match_xml(expected: &dyn HttpPart, actual: &dyn HttpPart, context: &dyn M
   }
 }
 
+/// Compares the expected element to the elements in the actual document with the same name. The
+/// first one that matches is used, otherwise the mismatches for the first candidate are returned.
+fn compare_subtree(
+  path: &DocPath,
+  expected: &Element,
+  actual_root: &Element,
+  mismatches: &mut Vec<super::Mismatch>,
+  context: &dyn MatchingContext
+) {
+  let mut candidates = vec![];
+  find_elements(actual_root, &expected.name(), &mut candidates);
+  debug!("Found {} candidate element(s) for the expected subtree '{}'", candidates.len(), path);
+
+  let mut first_mismatches = None;
+  for candidate in &candidates {
+    let mut candidate_mismatches = vec![];
+    compare_element(path, expected, candidate, &mut candidate_mismatches, context);
+    if candidate_mismatches.is_empty() {
+      return;
+    } else if first_mismatches.is_none() {
+      first_mismatches = Some(candidate_mismatches);
+    }
+  }
+
+  match first_mismatches {
+    Some(candidate_mismatches) => mismatches.extend(candidate_mismatches),
+    None => mismatches.push(Mismatch::BodyMismatch {
+      path: path.to_string(),
+      expected: Some(name(expected.name()).into()),
+      actual: Some(name(actual_root.name()).into()),
+      mismatch: format!("Expected an element '{}' in the document but it was not found", name(expected.name())),
+      rule: None
+    })
+  }
+}
+
+fn find_elements<'a>(element: &Element<'a>, element_name: &QName, found: &mut Vec<Element<'a>>) {
+  if element.name() == *element_name {
+    found.push(*element);
+  }
+  for child in children(element) {
+    find_elements(&child, element_name, found);
+  }
+}
+
 fn name(name: QName) -> String {
   if let Some(namespace) = name.namespace_uri() {
     format!("{}:{}", namespace, name.local_part())
@@ -148,9 +201,12 @@ fn compare_element(
   mismatches: &mut Vec<super::Mismatch>,
   context: &dyn MatchingContext
 ) {
+  let mut rule = None;
   let matcher_result = if context.matcher_is_defined(path) {
     debug!("calling match_values {:?} on {:?}", path, actual);
-    match_values(path, &context.select_best_matcher(&path), expected, actual)
+    let rules = context.select_best_matcher(&path);
+    rule = rule_list_label(&rules);
+    match_values(path, &rules, expected, actual)
   } else {
     expected.matches_with(actual, &MatchingRule::Equality, false).map_err(|err| vec![err.to_string()])
   };
@@ -162,7 +218,8 @@ fn compare_element(
           path: path.to_string(),
           expected: Some(name(expected.name()).into()),
           actual: Some(name(actual.name()).into()),
-          mismatch: message.clone()
+          mismatch: message.clone(),
+          rule: rule.clone()
         })
       }
     },
@@ -190,7 +247,8 @@ fn compare_attributes(
         path: path.to_string(),
         expected: Some(format!("{:?}", expected_attributes).into()),
         actual: Some(format!("{:?}", actual_attributes).into()),
-        mismatch: format!("Did not expect any attributes but received {:?}", actual_attributes)
+        mismatch: format!("Did not expect any attributes but received {:?}", actual_attributes),
+        rule: None
       });
     } else {
         match context.config() {
@@ -199,14 +257,14 @@ fn compare_attributes(
                     expected: Some(format!("{:?}", expected_attributes).into()),
                     actual: Some(format!("{:?}", actual_attributes).into()),
                     mismatch: format!("Expected at least {} attribute(s) but received {} attribute(s)",
-                    expected_attributes.len(), actual_attributes.len())});
+                    expected_attributes.len(), actual_attributes.len()), rule: None});
             },
             DiffConfig::NoUnexpectedKeys if expected_attributes.len() != actual_attributes.len() => {
                 mismatches.push(Mismatch::BodyMismatch { path: path.to_string(),
                     expected: Some(format!("{:?}", expected_attributes).into()),
                     actual: Some(format!("{:?}", actual_attributes).into()),
                     mismatch: format!("Expected {} attribute(s) but received {} attribute(s)",
-                    expected_attributes.len(), actual_attributes.len())});
+                    expected_attributes.len(), actual_attributes.len()), rule: None});
             },
             _ => ()
         }
@@ -222,7 +280,8 @@ fn compare_attributes(
               path: p.to_string(),
               expected: Some(Bytes::from(key.clone())),
               actual: None,
-              mismatch: format!("Expected attribute '{}'='{}' but was missing", key, value)
+              mismatch: format!("Expected attribute '{}'='{}' but was missing", key, value),
+              rule: None
             });
           }
         }
@@ -255,7 +314,8 @@ fn compare_children(
       path: path.to_string(),
       expected: Some(desc_children(&expected_children).into()),
       actual: Some(desc_children(&actual_children).into()),
-      mismatch: format!("Expected no children but received [{}]", desc_children(&actual_children))
+      mismatch: format!("Expected no children but received [{}]", desc_children(&actual_children)),
+      rule: None
     });
   } else {
     let mut expected_children_by_name: BTreeMap<String, Vec<Element>> = btreemap!{};
@@ -292,7 +352,8 @@ fn compare_children(
                   path: p.to_string(),
                   expected: Some(desc_children(&expected_children).into()),
                   actual: Some(desc_children(&actual_children).into()),
-                  mismatch: format!("Unexpected child <{}/>", name(actual.name()))
+                  mismatch: format!("Unexpected child <{}/>", name(actual.name())),
+                  rule: None
                 });
               },
               EitherOrBoth::Left(expected) => {
@@ -300,7 +361,8 @@ fn compare_children(
                   path: p.to_string(),
                   expected: Some(desc_children(&expected_children.clone()).into()),
                   actual: Some(desc_children(&actual_children.clone()).into()),
-                  mismatch: format!("Expected child <{}/> but was missing", name(expected.name()))
+                  mismatch: format!("Expected child <{}/> but was missing", name(expected.name())),
+                  rule: None
                 });
               },
               EitherOrBoth::Both(expected, actual) => {
@@ -314,7 +376,8 @@ fn compare_children(
           path: path.to_string(),
           expected: Some(desc_children(&expected_children.clone()).into()),
           actual: Some(desc_children(&actual_children.clone()).into()),
-          mismatch: format!("Unexpected child <{}/>", key)
+          mismatch: format!("Unexpected child <{}/>", key),
+          rule: None
         });
       }
     }
@@ -325,7 +388,8 @@ fn compare_children(
           path: path.to_string(),
           expected: Some(desc_children(&expected_children.clone()).into()),
           actual: Some(desc_children(&actual_children.clone()).into()),
-          mismatch: format!("Expected child <{}/> but was missing", key)
+          mismatch: format!("Expected child <{}/> but was missing", key),
+          rule: None
         });
       }
     }
@@ -348,8 +412,11 @@ fn compare_text(
         .map(|child| child.text().unwrap().text().trim())
         .collect::<String>();
     let p = path.join("#text");
+    let mut rule = None;
     let matcher_result = if context.matcher_is_defined(&p) {
-      match_values(&p, &context.select_best_matcher(&p), expected_text.trim(), actual_text.trim())
+      let rules = context.select_best_matcher(&p);
+      rule = rule_list_label(&rules);
+      match_values(&p, &rules, expected_text.trim(), actual_text.trim())
     } else {
       expected_text.matches_with(actual_text.trim(), &MatchingRule::Equality, false)
         .map_err(|err| vec![err.to_string()])
@@ -362,7 +429,8 @@ fn compare_text(
           path: p.to_string(),
           expected: Some(expected_text.clone().into()),
           actual: Some(actual_text.clone().into()),
-          mismatch: message.clone()
+          mismatch: message.clone(),
+          rule: rule.clone()
         })
       }
     }
@@ -374,8 +442,11 @@ fn compare_value(
   actual: &str,
   context: &dyn MatchingContext
 ) -> Result<(), Vec<Mismatch>> {
+  let mut rule = None;
   let matcher_result = if context.matcher_is_defined(path) {
-    match_values(path, &context.select_best_matcher(&path), expected, actual)
+    let rules = context.select_best_matcher(&path);
+    rule = rule_list_label(&rules);
+    match_values(path, &rules, expected, actual)
   } else {
     expected.matches_with(actual, &MatchingRule::Equality, false).map_err(|err| vec![err.to_string()])
   };
@@ -386,7 +457,8 @@ fn compare_value(
         path: path.to_string(),
         expected: Some(expected.to_string().into()),
         actual: Some(actual.to_string().into()),
-        mismatch: message.clone()
+        mismatch: message.clone(),
+        rule: rule.clone()
       }
     }).collect()
   })
@@ -462,7 +534,8 @@ mod tests {
       path: s!("$"),
       expected: Some(Bytes::new()),
       actual: Some(Bytes::new()),
-      mismatch: s!("")
+      mismatch: s!(""),
+      rule: None
     }));
   }
 
@@ -475,7 +548,8 @@ mod tests {
       path: s!("$"),
       expected: expected.body.value(),
       actual: actual.body.value(),
-      mismatch: s!("")
+      mismatch: s!(""),
+      rule: None
     } ]));
   }
 
@@ -488,7 +562,8 @@ mod tests {
       path: s!("$"),
       expected: expected.body.value(),
       actual: actual.body.value(),
-      mismatch: s!("")
+      mismatch: s!(""),
+      rule: None
     } ]));
   }
 
@@ -551,7 +626,7 @@ mod tests {
     let result = match_xml(&expected, &actual, &CoreMatchingContext::with_config(DiffConfig::AllowUnexpectedKeys));
     expect!(mismatch_message(&result)).to(be_equal_to(s!("Expected 'foo' to be equal to 'bar'")));
     expect!(result).to(be_err().value(vec![ Mismatch::BodyMismatch { path: s!("$.foo"), expected: Some("foo".into()),
-        actual: Some("bar".into()), mismatch: s!("") } ]));
+        actual: Some("bar".into()), mismatch: s!(""), rule: None } ]));
   }
 
   #[test]
@@ -580,15 +655,15 @@ mod tests {
     let mismatch = mismatches[0].clone();
     expect!(&mismatch).to(be_equal_to(&Mismatch::BodyMismatch { path: "$.blah".to_string(),
         expected: Some("{\"a\": \"c\", \"c\": \"b\"}".into()),
-        actual: Some("{\"a\": \"b\"}".into()), mismatch: "".to_string()}));
+        actual: Some("{\"a\": \"b\"}".into()), mismatch: "".to_string(), rule: None}));
     expect!(mismatch.description()).to(be_equal_to("$.blah -> Expected at least 2 attribute(s) but received 1 attribute(s)".to_string()));
     let mismatch = mismatches[1].clone();
     expect!(&mismatch).to(be_equal_to(&Mismatch::BodyMismatch { path: "$.blah['@a']".to_string(), expected: Some("c".into()),
-        actual: Some("b".into()), mismatch: "".to_string()}));
+        actual: Some("b".into()), mismatch: "".to_string(), rule: None}));
     expect!(mismatch.description()).to(be_equal_to("$.blah['@a'] -> Expected 'c' to be equal to 'b'".to_string()));
     let mismatch = mismatches[2].clone();
     expect!(&mismatch).to(be_equal_to(&Mismatch::BodyMismatch { path: "$.blah['@c']".to_string(), expected: Some("c".into()),
-        actual: None, mismatch: "".to_string() }));
+        actual: None, mismatch: "".to_string(), rule: None }));
     expect!(mismatch.description()).to(be_equal_to("$.blah['@c'] -> Expected attribute \'c\'=\'b\' but was missing".to_string()));
   }
 
@@ -603,7 +678,7 @@ mod tests {
     let result = match_xml(&expected, &actual, &CoreMatchingContext::with_config(DiffConfig::NoUnexpectedKeys));
     expect!(mismatch_message(&result)).to(be_equal_to(s!("Did not expect any attributes but received {\"a\": \"b\", \"c\": \"d\"}")));
     expect!(result).to(be_err().value(vec![ Mismatch::BodyMismatch { path: s!("$.blah"), expected: Some("{}".into()),
-      actual: Some("{\"a\": \"b\", \"c\": \"d\"}".into()), mismatch: s!("") } ]));
+      actual: Some("{\"a\": \"b\", \"c\": \"d\"}".into()), mismatch: s!(""), rule: None } ]));
   }
 
   #[test]
@@ -629,7 +704,7 @@ mod tests {
     let result = match_xml(&expected, &actual, &CoreMatchingContext::with_config(DiffConfig::NoUnexpectedKeys));
     expect!(mismatch_message(&result)).to(be_equal_to(s!("Expected 1 attribute(s) but received 2 attribute(s)")));
     expect!(result).to(be_err().value(vec![ Mismatch::BodyMismatch { path: s!("$.foo"), expected: Some("{\"something\": \"100\"}".into()),
-        actual: Some("{\"something\": \"100\", \"somethingElse\": \"101\"}".into()), mismatch: s!("") } ]));
+        actual: Some("{\"something\": \"100\", \"somethingElse\": \"101\"}".into()), mismatch: s!(""), rule: None } ]));
   }
 
   #[test]
@@ -643,7 +718,7 @@ mod tests {
     let result = match_xml(&expected, &actual, &CoreMatchingContext::with_config(DiffConfig::NoUnexpectedKeys));
     expect!(mismatch_message(&result)).to(be_equal_to("Expected attribute \'somethingElse\'=\'100\' but was missing".to_string()));
     expect!(result).to(be_err().value(vec![ Mismatch::BodyMismatch { path: "$.foo['@somethingElse']".to_string(), expected: Some("somethingElse".into()),
-        actual: None, mismatch: "".to_string() } ]));
+        actual: None, mismatch: "".to_string(), rule: None } ]));
   }
 
   #[test]
@@ -657,7 +732,7 @@ mod tests {
     let result = match_xml(&expected.clone(), &actual.clone(), &CoreMatchingContext::with_config(DiffConfig::NoUnexpectedKeys));
     expect!(mismatch_message(&result)).to(be_equal_to("Expected \'100\' to be equal to \'101\'".to_string()));
     expect!(result).to(be_err().value(vec![ Mismatch::BodyMismatch { path: "$.foo['@somethingElse']".to_string(), expected: Some("100".into()),
-        actual: Some("101".into()), mismatch: "".to_string() } ]));
+        actual: Some("101".into()), mismatch: "".to_string(), rule: None } ]));
 
     let result = match_xml(&expected, &actual, &CoreMatchingContext::new(DiffConfig::NoUnexpectedKeys, &matchingrules!{
       "body" => {
@@ -681,7 +756,8 @@ mod tests {
       path: s!("$.foo"),
       expected: Some(Bytes::new()),
       actual: Some("bar".into()),
-      mismatch: s!("")
+      mismatch: s!(""),
+      rule: None
     } ]));
   }
 
@@ -723,7 +799,8 @@ mod tests {
       path: s!("$.foo"),
       expected: Some("bar".into()),
       actual: Some(Bytes::new()),
-      mismatch: s!("")
+      mismatch: s!(""),
+      rule: None
     } ]));
   }
 
@@ -739,7 +816,7 @@ mod tests {
     expect!(mismatch_message(&result)).to(be_equal_to(s!("Expected child <four/> but was missing")));
     expect!(result).to(be_err().value(vec![ Mismatch::BodyMismatch { path: s!("$.foo"),
         expected: Some("one, two, three, four".into()),
-        actual: Some("one, two, three".into()), mismatch: s!("") } ]));
+        actual: Some("one, two, three".into()), mismatch: s!(""), rule: None } ]));
   }
 
   #[test]
@@ -756,12 +833,12 @@ mod tests {
     let mismatch = mismatches[0].clone();
     expect!(&mismatch).to(be_equal_to(&Mismatch::BodyMismatch { path: s!("$.foo"),
       expected: Some("one, two, three, three".into()),
-      actual: Some("one, two, three, four".into()), mismatch: s!("")}));
+      actual: Some("one, two, three, four".into()), mismatch: s!(""), rule: None}));
     expect!(mismatch.description()).to(be_equal_to(s!("$.foo -> Unexpected child <four/>")));
     let mismatch = mismatches[1].clone();
     expect!(&mismatch).to(be_equal_to(&Mismatch::BodyMismatch { path: s!("$.foo.three"),
       expected: Some("three, three".into()),
-      actual: Some("one, two, three, four".into()), mismatch: s!("")}));
+      actual: Some("one, two, three, four".into()), mismatch: s!(""), rule: None}));
     expect!(mismatch.description()).to(be_equal_to(s!("$.foo.three -> Expected child <three/> but was missing")));
   }
 
@@ -813,7 +890,7 @@ mod tests {
     expect!(mismatch_message(&result)).to(be_equal_to(s!("Expected 'hello world' to be equal to 'hello mars'")));
     expect!(result).to(be_err().value(vec![ Mismatch::BodyMismatch { path: "$.foo['#text']".to_string(),
         expected: Some("hello world".into()),
-        actual: Some("hello mars".into()), mismatch: "".to_string() } ]));
+        actual: Some("hello mars".into()), mismatch: "".to_string(), rule: None } ]));
 
     let result = match_xml(&expected, &actual, &CoreMatchingContext::new(DiffConfig::AllowUnexpectedKeys, &matchingrules!{
         "body" => {
@@ -835,7 +912,7 @@ mod tests {
     expect!(mismatch_message(&result)).to(be_equal_to("Expected 'helloworld' to be equal to 'hellomars'".to_string()));
     expect!(result).to(be_err().value(vec![ Mismatch::BodyMismatch { path: "$.foo['#text']".to_string(),
         expected: Some("helloworld".into()),
-        actual: Some("hellomars".into()), mismatch: "".to_string() } ]));
+        actual: Some("hellomars".into()), mismatch: "".to_string(), rule: None } ]));
 
     let result = match_xml(&expected, &actual, &CoreMatchingContext::new(DiffConfig::AllowUnexpectedKeys, &matchingrules!{
       "body" => {
@@ -968,7 +1045,8 @@ mod tests {
       path: "$['urn:other:blah']".to_string(),
       expected: Some("urn:other:blah".into()),
       actual: Some("urn:ns:blah".into()),
-      mismatch: "Expected 'urn:other:blah' to be equal to 'urn:ns:blah'".to_string()
+      mismatch: "Expected 'urn:other:blah' to be equal to 'urn:ns:blah'".to_string(),
+      rule: None
     } ]));
   }
 
@@ -981,7 +1059,8 @@ mod tests {
       path: "$['urn:other:blah']".to_string(),
       expected: Some("urn:other:blah".into()),
       actual: Some("blah".into()),
-      mismatch: "Expected 'urn:other:blah' to be equal to 'blah'".to_string()
+      mismatch: "Expected 'urn:other:blah' to be equal to 'blah'".to_string(),
+      rule: None
     } ]));
   }
 
@@ -994,7 +1073,8 @@ mod tests {
       path: "$.blah".to_string(),
       expected: Some("blah".into()),
       actual: Some("urn:ns:blah".into()),
-      mismatch: "Expected 'blah' to be equal to 'urn:ns:blah'".to_string()
+      mismatch: "Expected 'blah' to be equal to 'urn:ns:blah'".to_string(),
+      rule: None
     } ]));
   }
 
@@ -1015,7 +1095,8 @@ mod tests {
       path: "$.foo['@urn:b:something']".to_string(),
       expected: Some("urn:b:something".into()),
       actual: None,
-      mismatch: "Expected attribute 'urn:b:something'='100' but was missing".to_string()
+      mismatch: "Expected attribute 'urn:b:something'='100' but was missing".to_string(),
+      rule: None
     } ]));
   }
 
@@ -1091,17 +1172,17 @@ mod tests {
     let mismatch = mismatches[0].clone();
     expect!(&mismatch).to(be_equal_to(&Mismatch::BodyMismatch { path: s!("$.animals.cat"),
       expected: Some("cat".into()),
-      actual: Some("dog, dog, cat, cat, cat, wolf".into()), mismatch: s!("")}));
+      actual: Some("dog, dog, cat, cat, cat, wolf".into()), mismatch: s!(""), rule: None}));
     expect!(mismatch.description()).to(be_equal_to(s!("$.animals.cat -> Unexpected child <cat/>")));
     let mismatch = mismatches[1].clone();
     expect!(&mismatch).to(be_equal_to(&Mismatch::BodyMismatch { path: s!("$.animals.cat"),
       expected: Some("cat".into()),
-      actual: Some("dog, dog, cat, cat, cat, wolf".into()), mismatch: s!("")}));
+      actual: Some("dog, dog, cat, cat, cat, wolf".into()), mismatch: s!(""), rule: None}));
     expect!(mismatch.description()).to(be_equal_to(s!("$.animals.cat -> Unexpected child <cat/>")));
     let mismatch = mismatches[2].clone();
     expect!(&mismatch).to(be_equal_to(&Mismatch::BodyMismatch { path: s!("$.animals.dog"),
       expected: Some("dog".into()),
-      actual: Some("dog, dog, cat, cat, cat, wolf".into()), mismatch: "Unexpected child <dog/>".into()}));
+      actual: Some("dog, dog, cat, cat, cat, wolf".into()), mismatch: "Unexpected child <dog/>".into(), rule: None}));
     expect!(mismatch.description()).to(be_equal_to(s!("$.animals.dog -> Unexpected child <dog/>")));
   }
 
@@ -1157,8 +1238,129 @@ mod tests {
         path: "$.foo['@somethingElse']".into(),
         expected: Some("true".into()),
         actual: Some("101".into()),
-        mismatch: Default::default()
+        mismatch: Default::default(),
+        rule: None
+      }
+    ]));
+  }
+
+  fn match_xml_with_regex(path: &'static str, regex: &str, expected: &'static str, actual: &'static str) -> Result<(), Vec<Mismatch>> {
+    let matching_rules = matchingrules! {
+      "body" => { path => [ MatchingRule::Regex(regex.to_string()) ] }
+    }.rules_for_category("body").unwrap();
+    match_xml(&request!(expected), &request!(actual),
+      &CoreMatchingContext::new(DiffConfig::NoUnexpectedKeys, &matching_rules, &hashmap!{}))
+  }
+
+  fn mismatch_descriptions(result: &Result<(), Vec<Mismatch>>) -> Vec<String> {
+    match result {
+      Err(mismatches) => mismatches.iter().map(|m| m.description()).collect(),
+      Ok(_) => vec![]
+    }
+  }
+
+  #[test]
+  fn match_xml_applies_an_attribute_matcher_only_to_the_attribute() {
+    expect!(match_xml_with_regex("$.root['@id']", "^[0-9]+$", r#"<root id="1"><id>abc</id></root>"#,
+      r#"<root id="100"><id>abc</id></root>"#)).to(be_ok());
+
+    let result = match_xml_with_regex("$.root['@id']", "^[0-9]+$", r#"<root id="1"><id>1</id></root>"#,
+      r#"<root id="100"><id>100</id></root>"#);
+    expect!(mismatch_descriptions(&result)).to(be_equal_to(vec![
+      "$.root.id['#text'] -> Expected '1' to be equal to '100'".to_string()
+    ]));
+  }
+
+  #[test]
+  fn match_xml_applies_an_element_matcher_only_to_the_child_element() {
+    expect!(match_xml_with_regex("$.root.id", "^[a-z]+$", r#"<root id="1"><id>abc</id></root>"#,
+      r#"<root id="1"><id>xyz</id></root>"#)).to(be_ok());
+
+    let result = match_xml_with_regex("$.root.id", "^[a-z]+$", r#"<root id="1"><id>abc</id></root>"#,
+      r#"<root id="100"><id>xyz</id></root>"#);
+    expect!(mismatch_descriptions(&result)).to(be_equal_to(vec![
+      "$.root['@id'] -> Expected '1' to be equal to '100'".to_string()
+    ]));
+  }
+
+  #[test]
+  fn match_xml_element_regex_matches_the_element_name() {
+    let result = match_xml_with_regex("$.root.id", "^[0-9]+$", r#"<root id="1"><id>1</id></root>"#,
+      r#"<root id="1"><id>1</id></root>"#);
+    expect!(mismatch_descriptions(&result)).to(be_equal_to(vec![
+      "$.root.id -> Expected 'id' to match '^[0-9]+$'".to_string()
+    ]));
+  }
+
+  #[test]
+  fn match_xml_selects_attribute_and_child_element_matchers_independently() {
+    let matching_rules = matchingrules! {
+      "body" => {
+        "$.root['@id']" => [ MatchingRule::Regex("^[0-9]+$".to_string()) ],
+        "$.root.id" => [ MatchingRule::Regex("^[a-z]+$".to_string()) ]
       }
+    }.rules_for_category("body").unwrap();
+    let context = CoreMatchingContext::new(DiffConfig::NoUnexpectedKeys, &matching_rules, &hashmap!{});
+    let expected = request!(r#"<root id="1"><id>abc</id></root>"#);
+
+    expect!(match_xml(&expected, &request!(r#"<root id="100"><id>xyz</id></root>"#), &context)).to(be_ok());
+
+    let result = match_xml(&expected, &request!(r#"<root id="abc"><id>xyz</id></root>"#), &context);
+    expect!(mismatch_descriptions(&result)).to(be_equal_to(vec![
+      "$.root['@id'] -> Expected 'abc' to match '^[0-9]+$'".to_string()
+    ]));
+
+    let result = match_xml(&expected, &request!(r#"<root id="100"><id>100</id></root>"#), &context);
+    expect!(mismatch_descriptions(&result)).to(be_equal_to(vec![
+      "$.root.id['#text'] -> Expected '100' to match '^[a-z]+$'".to_string()
     ]));
   }
+
+  fn match_xml_subtree(expected: &'static str, actual: &'static str) -> Result<(), Vec<Mismatch>> {
+    let context = CoreMatchingContext {
+      xml_subtree_match: true,
+      .. CoreMatchingContext::with_config(DiffConfig::AllowUnexpectedKeys)
+    };
+    match_xml(&request!(expected), &request!(actual), &context)
+  }
+
+  #[test]
+  fn match_xml_with_subtree_matching_finds_the_expected_element_nested_in_the_actual_document() {
+    let expected = r#"<address><city>London</city></address>"#;
+    let actual = r#"<customer id="1"><name>Mary</name><contact>
+      <address><street>1 Main St</street><city>Paris</city></address>
+      <address><street>2 High St</street><city>London</city></address>
+    </contact></customer>"#;
+    expect!(match_xml_subtree(expected, actual)).to(be_ok());
+
+    let result = match_xml(&request!(expected), &request!(actual),
+      &CoreMatchingContext::with_config(DiffConfig::AllowUnexpectedKeys));
+    expect!(mismatch_message(&result)).to(
+      be_equal_to("Expected 'address' to be equal to 'customer'".to_string()));
+  }
+
+  #[test]
+  fn match_xml_with_subtree_matching_fails_if_the_expected_element_is_not_in_the_actual_document() {
+    let result = match_xml_subtree(r#"<address><city>London</city></address>"#,
+      r#"<customer id="1"><name>Mary</name></customer>"#);
+    expect!(mismatch_message(&result)).to(
+      be_equal_to("Expected an element 'address' in the document but it was not found".to_string()));
+    expect!(result).to(be_err().value(vec![
+      Mismatch::BodyMismatch {
+        path: "$.address".into(),
+        expected: Some("address".into()),
+        actual: Some("customer".into()),
+        mismatch: Default::default(),
+        rule: None
+      }
+    ]));
+  }
+
+  #[test]
+  fn match_xml_with_subtree_matching_reports_the_mismatches_for_the_first_candidate() {
+    let result = match_xml_subtree(r#"<address><city>London</city></address>"#,
+      r#"<customer><address><city>Paris</city></address><address><city>Rome</city></address></customer>"#);
+    expect!(mismatch_message(&result)).to(
+      be_equal_to("Expected 'London' to be equal to 'Paris'".to_string()));
+  }
 }
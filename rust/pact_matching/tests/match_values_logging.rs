@@ -0,0 +1,54 @@
+//! These tests install their own logger to capture the log output, so need to run in their own
+//! test binary.
+
+use std::sync::Mutex;
+
+use expectest::prelude::*;
+use log::{Level, LevelFilter, Log, Metadata, Record};
+
+use pact_matching::matchers::match_values;
+use pact_models::matchingrules::{MatchingRule, RuleList, RuleLogic};
+use pact_models::path_exp::DocPath;
+
+struct CapturingLogger {
+  lines: Mutex<Vec<String>>
+}
+
+impl Log for CapturingLogger {
+  fn enabled(&self, metadata: &Metadata) -> bool {
+    metadata.level() <= Level::Debug
+  }
+
+  fn log(&self, record: &Record) {
+    if self.enabled(record.metadata()) {
+      self.lines.lock().unwrap().push(format!("{} {}", record.level(), record.args()));
+    }
+  }
+
+  fn flush(&self) {}
+}
+
+static LOGGER: CapturingLogger = CapturingLogger { lines: Mutex::new(vec![]) };
+
+#[test]
+fn match_values_logs_each_rule_applied_and_the_outcome() {
+  log::set_logger(&LOGGER).unwrap();
+  log::set_max_level(LevelFilter::Debug);
+
+  let rules = RuleList {
+    rules: vec![ MatchingRule::Regex("\\d+".to_string()), MatchingRule::Include("abc".to_string()) ],
+    rule_logic: RuleLogic::Or,
+    cascaded: false
+  };
+  let path = DocPath::new_unwrap("$.id");
+  let result = match_values(&path, &rules, "100".to_string(), "x".repeat(500));
+  expect!(result).to(be_err());
+
+  let lines = LOGGER.lines.lock().unwrap();
+  let line = lines.iter().find(|line| line.starts_with("DEBUG match_values: path '$.id'")).unwrap();
+  expect!(line.contains("with Or rules")).to(be_true());
+  expect!(line.contains("[regex: FAILED - ")).to(be_true());
+  expect!(line.contains(", include: FAILED - ")).to(be_true());
+  expect!(line.ends_with("-> did not match")).to(be_true());
+  expect!(line.contains(&"x".repeat(500))).to(be_false());
+}
@@ -9,14 +9,14 @@ use futures::prelude::*;
 use futures::StreamExt;
 use futures::task::{Context, Poll};
 use hyper::{Body, Error, Response, Server};
-use hyper::http::header::{HeaderName, HeaderValue};
+use hyper::http::header::{HeaderMap, HeaderName, HeaderValue};
 use hyper::http::response::Builder as ResponseBuilder;
 use hyper::service::make_service_fn;
 use hyper::service::service_fn;
 use log::*;
 use maplit::*;
 use rustls::ServerConfig;
-use serde_json::json;
+use serde_json::{json, Value};
 use tokio::net::{TcpListener, TcpStream};
 use tokio_rustls::server::TlsStream;
 use tokio_rustls::TlsAcceptor;
@@ -29,9 +29,11 @@ use pact_models::pact::Pact;
 use pact_models::query_strings::parse_query_string;
 use pact_models::v4::http_parts::HttpRequest;
 
-use crate::matching::{match_request, MatchResult};
+use crate::matching::{match_request_in_order, match_request_with_config, MatchResult};
 use crate::mock_server::MockServer;
 
+const CORS_DEFAULT_ALLOWED_METHODS: &str = "GET, HEAD, POST, PUT, DELETE, CONNECT, OPTIONS, TRACE, PATCH";
+
 #[derive(Debug, Clone)]
 enum InteractionError {
     RequestHeaderEncodingError,
@@ -121,58 +123,107 @@ async fn hyper_request_to_pact_request(req: hyper::Request<Body>) -> Result<Http
 }
 
 fn set_hyper_headers(builder: &mut ResponseBuilder, headers: &Option<HashMap<String, Vec<String>>>) -> Result<(), InteractionError> {
-    let hyper_headers = builder.headers_mut().unwrap();
     match headers {
-        Some(header_map) => {
-            for (k, v) in header_map {
-                for val in v {
-                    // FIXME?: Headers are not sent in "raw" mode.
-                    // Names are converted to lower case and values are parsed.
-                    hyper_headers.append(
-                        HeaderName::from_bytes(k.as_bytes())
-                            .map_err(|err| {
-                                error!("Invalid header name '{}' ({})", k, err);
-                                InteractionError::ResponseHeaderEncodingError
-                            })?,
-                        val.parse::<HeaderValue>()
-                            .map_err(|err| {
-                                error!("Invalid header value '{}': '{}' ({})", k, val, err);
-                                InteractionError::ResponseHeaderEncodingError
-                            })?
-                    );
-                }
-            }
-        },
-        _ => {}
+        Some(header_map) => append_hyper_headers(builder.headers_mut().unwrap(), header_map),
+        _ => Ok(())
+    }
+}
+
+fn append_hyper_headers(hyper_headers: &mut HeaderMap, header_map: &HashMap<String, Vec<String>>) -> Result<(), InteractionError> {
+    for (k, v) in header_map {
+        for val in v {
+            // FIXME?: Headers are not sent in "raw" mode.
+            // Names are converted to lower case and values are parsed.
+            hyper_headers.append(
+                HeaderName::from_bytes(k.as_bytes())
+                    .map_err(|err| {
+                        error!("Invalid header name '{}' ({})", k, err);
+                        InteractionError::ResponseHeaderEncodingError
+                    })?,
+                val.parse::<HeaderValue>()
+                    .map_err(|err| {
+                        error!("Invalid header value '{}': '{}' ({})", k, val, err);
+                        InteractionError::ResponseHeaderEncodingError
+                    })?
+            );
+        }
     }
     Ok(())
 }
 
+/// Returns a streaming body that sends the trailers after the body data. Note that hyper only
+/// sends trailers on HTTP/2 connections.
+fn body_with_trailers(body: &OptionalBody, trailers: &HashMap<String, Vec<String>>) -> Result<Body, InteractionError> {
+  let mut trailer_map = HeaderMap::new();
+  append_hyper_headers(&mut trailer_map, trailers)?;
+  let data = match body {
+    OptionalBody::Present(data, _, _) => Some(data.clone()),
+    _ => None
+  };
+  let (mut sender, body) = Body::channel();
+  tokio::spawn(async move {
+    if let Some(data) = data {
+      if sender.send_data(data).await.is_err() {
+        return;
+      }
+    }
+    if let Err(err) = sender.send_trailers(trailer_map).await {
+      warn!("Failed to send the response trailers - {}", err);
+    }
+  });
+  Ok(body)
+}
+
 fn error_body(request: &HttpRequest, error: &String) -> String {
     let body = json!({ "error" : format!("{} : {:?}", error, request) });
     body.to_string()
 }
 
+/// Details of the received request that response generators can look values up from. Header
+/// and query parameter values are joined with commas, and JSON bodies are parsed.
+fn request_context(request: &HttpRequest) -> Value {
+  let join_values = |values: &Option<HashMap<String, Vec<String>>>| Value::Object(values.iter()
+    .flatten()
+    .map(|(k, v)| (k.clone(), Value::String(v.join(", "))))
+    .collect());
+  let body = match &request.body {
+    OptionalBody::Present(bytes, _, _) => if request.content_type().unwrap_or_default().is_json() {
+      serde_json::from_slice(bytes).unwrap_or_else(|_| Value::String(request.body.str_value().to_string()))
+    } else {
+      Value::String(request.body.str_value().to_string())
+    },
+    _ => Value::Null
+  };
+  json!({
+    "method": request.method,
+    "path": request.path,
+    "query": join_values(&request.query),
+    "headers": join_values(&request.headers),
+    "body": body
+  })
+}
+
 async fn match_result_to_hyper_response(
   request: &HttpRequest,
   match_result: MatchResult,
   mock_server: Arc<Mutex<MockServer>>
 ) -> Result<Response<Body>, InteractionError> {
-  let (context, cors_preflight) = {
+  let (context, config) = {
     let ms = mock_server.lock().unwrap();
     (
       hashmap!{
         "mockServer" => json!({
           "href": ms.url(),
           "port": ms.port
-        })
+        }),
+        "request" => request_context(request)
       },
-      ms.config.cors_preflight
+      ms.config.clone()
     )
   };
 
   match match_result {
-    MatchResult::RequestMatch(_, ref response) => {
+    MatchResult::RequestMatch(_, ref response, _) => {
       debug!("Test context = {:?}", context);
       let response = pact_matching::generate_response(response, &GeneratorTestMode::Consumer, &context).await;
       info!("Request matched, sending response {}", response);
@@ -184,20 +235,24 @@ async fn match_result_to_hyper_response(
         .status(response.status)
         .header(hyper::header::ACCESS_CONTROL_ALLOW_ORIGIN, "*")
         .header(hyper::header::ACCESS_CONTROL_ALLOW_HEADERS, "*")
-        .header(hyper::header::ACCESS_CONTROL_ALLOW_METHODS, "GET, HEAD, POST, PUT, DELETE, CONNECT, OPTIONS, TRACE, PATCH")
+        .header(hyper::header::ACCESS_CONTROL_ALLOW_METHODS, CORS_DEFAULT_ALLOWED_METHODS)
         .header(hyper::header::ACCESS_CONTROL_EXPOSE_HEADERS, "Location, Link");
 
       set_hyper_headers(&mut builder, &response.headers)?;
 
-      builder.body(match response.body {
-        OptionalBody::Present(ref s, _, _) => Body::from(s.clone()),
-        _ => Body::empty()
-      })
+      let body = match response.trailers {
+        Some(ref trailers) if !trailers.is_empty() => body_with_trailers(&response.body, trailers)?,
+        _ => match response.body {
+          OptionalBody::Present(ref s, _, _) => Body::from(s.clone()),
+          _ => Body::empty()
+        }
+      };
+      builder.body(body)
         .map_err(|_| InteractionError::ResponseBodyError)
     },
     _ => {
       debug!("Request did not match: {}", match_result);
-      if cors_preflight && request.method.to_uppercase() == "OPTIONS" {
+      if config.cors_preflight && request.method.to_uppercase() == "OPTIONS" {
         info!("Responding to CORS pre-flight request");
         let origin = match request.headers.clone() {
           Some(ref h) => h.iter()
@@ -205,21 +260,38 @@ async fn match_result_to_hyper_response(
             .map(|kv| kv.1.clone().join(", ")).unwrap_or("*".to_string()),
           None => "*".to_string()
         };
-        let cors_headers = match request.headers.clone() {
-          Some(ref h) => h.iter()
-            .find(|kv| kv.0.to_lowercase() == "access-control-request-headers")
-            .map(|kv| kv.1.clone().join(", ") + ", *").unwrap_or("*".to_string()),
-          None => "*".to_string()
+        let cors_headers = if config.cors_allowed_headers.is_empty() {
+          match request.headers.clone() {
+            Some(ref h) => h.iter()
+              .find(|kv| kv.0.to_lowercase() == "access-control-request-headers")
+              .map(|kv| kv.1.clone().join(", ") + ", *").unwrap_or("*".to_string()),
+            None => "*".to_string()
+          }
+        } else {
+          config.cors_allowed_headers.join(", ")
+        };
+        let cors_methods = if config.cors_allowed_methods.is_empty() {
+          CORS_DEFAULT_ALLOWED_METHODS.to_string()
+        } else {
+          config.cors_allowed_methods.join(", ")
         };
 
         Response::builder()
           .status(204)
           .header(hyper::header::ACCESS_CONTROL_ALLOW_ORIGIN, origin)
-          .header(hyper::header::ACCESS_CONTROL_ALLOW_METHODS, "GET, HEAD, POST, PUT, DELETE, CONNECT, OPTIONS, TRACE, PATCH")
+          .header(hyper::header::ACCESS_CONTROL_ALLOW_METHODS, cors_methods)
           .header(hyper::header::ACCESS_CONTROL_ALLOW_HEADERS, cors_headers)
           .header(hyper::header::ACCESS_CONTROL_EXPOSE_HEADERS, "Location, Link")
           .body(Body::empty())
           .map_err(|_| InteractionError::ResponseBodyError)
+      } else if let (MatchResult::RequestNotFound(_), Some((status, body))) = (&match_result, &config.not_found_response) {
+        info!("Request did not match any interaction, sending the configured not found response");
+        Response::builder()
+          .status(*status)
+          .header(hyper::header::ACCESS_CONTROL_ALLOW_ORIGIN, "*")
+          .header("X-Pact", match_result.match_key())
+          .body(Body::from(body.clone()))
+          .map_err(|_| InteractionError::ResponseBodyError)
       } else {
         Response::builder()
           .status(500)
@@ -257,9 +329,23 @@ async fn handle_request(
     let inner = pact.lock().unwrap();
     inner.as_v4_pact().unwrap()
   };
-  let match_result = match_request(&pact_request, &pact).await;
+  let (enforce_request_order, matching_config) = {
+    let guard = mock_server.lock().unwrap();
+    (guard.config.enforce_request_order, guard.config.matching_config())
+  };
+  let match_result = if enforce_request_order {
+    let matched_count = matches.lock().unwrap().iter().filter(|m| m.matched()).count();
+    match_request_in_order(&pact_request, &pact, matched_count, &matching_config).await
+  } else {
+    match_request_with_config(&pact_request, &pact, &matching_config).await
+  };
 
+  mock_server.lock().unwrap().record_received_request(&pact_request);
   matches.lock().unwrap().push(match_result.clone());
+  let match_callback = mock_server.lock().unwrap().match_callback();
+  if let Some(callback) = match_callback {
+    callback(&match_result);
+  }
 
   match_result_to_hyper_response(&pact_request, match_result, mock_server).await
 }
@@ -363,9 +449,12 @@ pub(crate) async fn create_and_bind_tls(
   addr: SocketAddr,
   shutdown: impl std::future::Future<Output = ()>,
   matches: Arc<Mutex<Vec<MatchResult>>>,
-  tls_cfg: ServerConfig,
+  mut tls_cfg: ServerConfig,
   mock_server: Arc<Mutex<MockServer>>
 ) -> Result<(impl std::future::Future<Output = ()>, SocketAddr), io::Error> {
+  if mock_server.lock().unwrap().config.enable_http2 {
+    tls_cfg.alpn_protocols = vec![b"h2".to_vec(), b"http/1.1".to_vec()];
+  }
   let tcp = TcpListener::bind(&addr).await?;
   let socket_addr = tcp.local_addr()?;
   let tls_acceptor = Arc::new(TlsAcceptor::from(Arc::new(tls_cfg)));
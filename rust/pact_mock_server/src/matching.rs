@@ -9,7 +9,7 @@ use futures::prelude::*;
 use itertools::Itertools;
 use serde_json::json;
 
-use pact_matching::{Mismatch, RequestMatchResult};
+use pact_matching::{MatchingConfig, Mismatch, RequestMatchResult};
 use pact_models::interaction::Interaction;
 use pact_models::PactSpecification;
 use pact_models::prelude::Pact;
@@ -18,11 +18,14 @@ use pact_models::v4::http_parts::{HttpRequest, HttpResponse};
 use pact_models::v4::V4InteractionType;
 use pact_models::v4::pact::V4Pact;
 
+use crate::mock_server::MockServerConfig;
+
 /// Enum to define a match result
 #[derive(Debug, Clone, PartialEq)]
 pub enum MatchResult {
-  /// Match result where the request was successfully matched
-  RequestMatch(HttpRequest, HttpResponse),
+  /// Match result where the request was successfully matched. The last value is the index of
+  /// the interaction in the Pact that was matched
+  RequestMatch(HttpRequest, HttpResponse, usize),
   /// Match result where there were a number of mismatches
   RequestMismatch(HttpRequest, Vec<Mismatch>),
   /// Match result where the request was not expected
@@ -35,7 +38,7 @@ impl MatchResult {
     /// Returns the match key for this mismatch
     pub fn match_key(&self) -> String {
         match self {
-            &MatchResult::RequestMatch(_, _) => "Request-Matched",
+            &MatchResult::RequestMatch(_, _, _) => "Request-Matched",
             &MatchResult::RequestMismatch(_, _) => "Request-Mismatch",
             &MatchResult::RequestNotFound(_) => "Unexpected-Request",
             &MatchResult::MissingRequest(_) => "Missing-Request"
@@ -45,11 +48,20 @@ impl MatchResult {
     /// Returns true if this match result is a `RequestMatch`
     pub fn matched(&self) -> bool {
         match self {
-            &MatchResult::RequestMatch(_, _) => true,
+            &MatchResult::RequestMatch(_, _, _) => true,
             _ => false
         }
     }
 
+    /// Returns the index of the interaction in the Pact that was matched, if this match result
+    /// is a `RequestMatch`
+    pub fn matched_interaction(&self) -> Option<usize> {
+      match self {
+        MatchResult::RequestMatch(_, _, index) => Some(*index),
+        _ => None
+      }
+    }
+
     /// Returns true if this is an unexpected OPTIONS request
     pub fn cors_preflight(&self) -> bool {
       match self {
@@ -61,7 +73,7 @@ impl MatchResult {
     /// Converts this match result to a `Value` struct
     pub fn to_json(&self) -> serde_json::Value {
         match self {
-            &MatchResult::RequestMatch(_, _) => json!({ "type" : "request-match"}),
+            &MatchResult::RequestMatch(_, _, _) => json!({ "type" : "request-match"}),
             &MatchResult::RequestMismatch(ref request, ref mismatches) => mismatches_to_json(request, mismatches),
             &MatchResult::RequestNotFound(ref req) => json!({
                 "type": "request-not-found",
@@ -82,7 +94,7 @@ impl MatchResult {
 impl Display for MatchResult {
   fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
     match self {
-      MatchResult::RequestMatch(request, _) => {
+      MatchResult::RequestMatch(request, _, _) => {
         write!(f, "Request matched OK - {}", request)
       },
       MatchResult::RequestMismatch(request, mismatches) => {
@@ -112,28 +124,43 @@ fn mismatches_to_json(request: &HttpRequest, mismatches: &Vec<Mismatch>) -> serd
 }
 
 ///
-/// Matches a request against a list of interactions
+/// Matches a request against a list of interactions, using the default mock server configuration.
+/// Requests are matched strictly, so any unexpected keys in a JSON body (or unexpected query
+/// parameters) result in a mismatch.
 ///
 pub async fn match_request(
   req: &HttpRequest,
   pact: &V4Pact,
 ) -> MatchResult {
-  let interactions = pact.filter_interactions(V4InteractionType::Synchronous_HTTP);
+  match_request_with_config(req, pact, &MockServerConfig::default().matching_config()).await
+}
+
+///
+/// Matches a request against a list of interactions, using the given matching configuration
+///
+pub async fn match_request_with_config(
+  req: &HttpRequest,
+  pact: &V4Pact,
+  config: &MatchingConfig
+) -> MatchResult {
+  let interactions = pact.interactions.iter()
+    .enumerate()
+    .filter(|(_, i)| i.v4_type() == V4InteractionType::Synchronous_HTTP && i.is_request_response())
+    .map(|(index, i)| (index, i.boxed()));
   let match_results = futures::stream::iter(interactions)
-    .filter(|i| future::ready(i.is_request_response()))
-    .then(|i| async move {
+    .then(|(index, i)| async move {
       let interaction = i.as_v4_http().unwrap();
-      (interaction.clone(), pact_matching::match_request(interaction.request.clone(),
-        req.clone(), &pact.boxed(), &i).await)
-    }).collect::<Vec<(SynchronousHttp, RequestMatchResult)>>().await;
-  let mut sorted = match_results.iter().sorted_by(|(_, i1), (_, i2)| {
+      (index, interaction.clone(), pact_matching::match_request_with_config(interaction.request.clone(),
+        req.clone(), &pact.boxed(), &i, config).await)
+    }).collect::<Vec<(usize, SynchronousHttp, RequestMatchResult)>>().await;
+  let mut sorted = match_results.iter().sorted_by(|(_, _, i1), (_, _, i2)| {
     Ord::cmp(&i2.score(), &i1.score())
   });
   match sorted.next() {
-    Some((interaction, result)) => {
+    Some((index, interaction, result)) => {
       let request_response_interaction = interaction.as_v4_http().unwrap();
       if result.all_matched() {
-        MatchResult::RequestMatch(request_response_interaction.request, request_response_interaction.response)
+        MatchResult::RequestMatch(request_response_interaction.request, request_response_interaction.response, *index)
       } else if result.method_or_path_mismatch() {
         MatchResult::RequestNotFound(req.clone())
       } else {
@@ -143,3 +170,46 @@ pub async fn match_request(
     None => MatchResult::RequestNotFound(req.clone())
   }
 }
+
+///
+/// Matches a request against a list of interactions, where the requests must be received in the
+/// order the interactions are defined in the Pact. `matched_count` is the number of requests that
+/// have already been matched. A request that matches an interaction other than the next one is
+/// returned as a mismatch.
+///
+pub async fn match_request_in_order(
+  req: &HttpRequest,
+  pact: &V4Pact,
+  matched_count: usize,
+  config: &MatchingConfig
+) -> MatchResult {
+  let expected = pact.interactions.iter()
+    .enumerate()
+    .filter(|(_, i)| i.v4_type() == V4InteractionType::Synchronous_HTTP && i.is_request_response())
+    .nth(matched_count);
+  match expected {
+    Some((expected_index, expected)) => {
+      let interaction = expected.as_v4_http().unwrap();
+      let result = pact_matching::match_request_with_config(interaction.request.clone(), req.clone(),
+        &pact.boxed(), &expected.boxed(), config).await;
+      if result.all_matched() {
+        MatchResult::RequestMatch(interaction.request, interaction.response, expected_index)
+      } else {
+        match match_request_with_config(req, pact, config).await {
+          MatchResult::RequestMatch(request, _, index) => {
+            let description = pact.interactions.get(index)
+              .map(|i| i.description())
+              .unwrap_or_default();
+            MatchResult::RequestMismatch(request, vec![ Mismatch::InteractionMismatch {
+              interaction: description.clone(),
+              mismatch: format!("Request for interaction '{}' was received out of order, expected a request for interaction '{}'",
+                description, expected.description())
+            } ])
+          },
+          result => result
+        }
+      }
+    },
+    None => match_request_with_config(req, pact, config).await
+  }
+}
@@ -4,18 +4,25 @@
 //!
 
 use std::cell::RefCell;
+use std::collections::HashSet;
 use std::ffi::CString;
-use std::ops::DerefMut;
+use std::fmt::{self, Debug, Formatter};
+use std::ops::{Deref, DerefMut};
 use std::path::PathBuf;
 use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 
 use log::*;
 use pact_plugin_driver::plugin_manager::drop_plugin_access;
 use pact_plugin_driver::plugin_models::{PluginDependency, PluginDependencyType};
+use ring::digest::{digest, SHA256};
 use rustls::ServerConfig;
 use serde::{Deserialize, Serialize};
 use serde_json::json;
 
+use pact_matching::{DiffConfig, MatchingConfig};
+use pact_models::bodies::OptionalBody;
+use pact_models::interaction::Interaction;
 use pact_models::pact::{Pact, write_pact};
 use pact_models::PactSpecification;
 use pact_models::sync_pact::RequestResponsePact;
@@ -29,8 +36,109 @@ use crate::matching::MatchResult;
 pub struct MockServerConfig {
   /// If CORS Pre-Flight requests should be responded to
   pub cors_preflight: bool,
+  /// Methods to return in the `Access-Control-Allow-Methods` header of CORS Pre-Flight responses.
+  /// If empty, all the standard HTTP methods are allowed.
+  pub cors_allowed_methods: Vec<String>,
+  /// Headers to return in the `Access-Control-Allow-Headers` header of CORS Pre-Flight responses.
+  /// If empty, the headers from the request (and any header) are allowed.
+  pub cors_allowed_headers: Vec<String>,
+  /// Status code and body to return for requests that do not match any interaction. If not set,
+  /// an error response is returned. The request is still recorded as a mismatch.
+  pub not_found_response: Option<(u16, String)>,
   /// Pact specification to use
-  pub pact_specification: PactSpecification
+  pub pact_specification: PactSpecification,
+  /// If HTTP/2 should be offered with ALPN (`h2`) for TLS connections. Plain HTTP connections
+  /// always accept HTTP/2 with prior knowledge (h2c). By default, TLS connections only use HTTP/1.1.
+  pub enable_http2: bool,
+  /// If requests must be received in the order the interactions are defined in the Pact. A request
+  /// received out of order is recorded as a mismatch. By default, requests can be received in any order.
+  pub enforce_request_order: bool,
+  /// If set, the mock server retains the requests it receives, with the bodies retained according
+  /// to the mode. By default, the received requests are not retained.
+  pub capture_bodies: Option<BodyCaptureMode>,
+  /// If the interactions should be sorted by description and provider states when the pact file is
+  /// written, so that the file does not change between test runs. By default, the interactions are
+  /// written in the order they were added to the pact.
+  pub sort_interactions: bool,
+  /// If unexpected keys in a JSON request body should be allowed. By default, request bodies are
+  /// matched strictly, so any unexpected keys result in a mismatch.
+  pub allow_unexpected_body_keys: bool
+}
+
+impl MockServerConfig {
+  /// Configuration used to match the received requests against the interactions
+  pub fn matching_config(&self) -> MatchingConfig {
+    MatchingConfig {
+      request_diff_config: if self.allow_unexpected_body_keys {
+        DiffConfig::AllowUnexpectedKeys
+      } else {
+        DiffConfig::NoUnexpectedKeys
+      },
+      .. MatchingConfig::default()
+    }
+  }
+}
+
+/// How the mock server retains the bodies of the requests it receives
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BodyCaptureMode {
+  /// Keep the full body of each request
+  Full,
+  /// Only keep the length and SHA-256 hash of each body. Useful for tests that send large bodies.
+  Hashed,
+  /// Do not keep the bodies
+  None
+}
+
+impl BodyCaptureMode {
+  /// Returns the request as it should be retained with this mode
+  pub fn capture(&self, request: &HttpRequest) -> ReceivedRequest {
+    match self {
+      BodyCaptureMode::Full => ReceivedRequest {
+        request: request.clone(),
+        body_summary: None
+      },
+      BodyCaptureMode::Hashed => ReceivedRequest {
+        request: HttpRequest { body: OptionalBody::Missing, .. request.clone() },
+        body_summary: Some(BodySummary::from_body(&request.body))
+      },
+      BodyCaptureMode::None => ReceivedRequest {
+        request: HttpRequest { body: OptionalBody::Missing, .. request.clone() },
+        body_summary: None
+      }
+    }
+  }
+}
+
+/// Length and SHA-256 hash of a request body
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BodySummary {
+  /// Length of the body in bytes
+  pub length: usize,
+  /// Hex encoded SHA-256 hash of the body
+  pub sha256: String
+}
+
+impl BodySummary {
+  /// Creates the summary of the given body. A missing or null body is treated as empty.
+  pub fn from_body(body: &OptionalBody) -> BodySummary {
+    let bytes = body.value().unwrap_or_default();
+    let digest = digest(&SHA256, &bytes);
+    BodySummary {
+      length: bytes.len(),
+      sha256: digest.as_ref().iter().map(|b| format!("{:02x}", b)).collect()
+    }
+  }
+}
+
+/// Request received by the mock server, with the body retained according to the configured
+/// `BodyCaptureMode`
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReceivedRequest {
+  /// The received request. The body is only present if the full body was captured.
+  pub request: HttpRequest,
+  /// Summary of the body, if only the hash of the body was captured
+  pub body_summary: Option<BodySummary>
 }
 
 /// Mock server scheme
@@ -64,8 +172,47 @@ pub struct MockServerMetrics {
   pub requests: usize
 }
 
+/// Handle to wait for all the interactions of a mock server to be matched. This only holds the
+/// pact and matches that are shared with the running mock server, so it can be used while
+/// requests are being made to the mock server.
+#[derive(Debug, Clone)]
+pub struct MatchWaiter {
+  pact: Arc<Mutex<dyn Pact + Send + Sync>>,
+  matches: Arc<Mutex<Vec<MatchResult>>>
+}
+
+impl MatchWaiter {
+  /// Waits until every interaction in the pact has been matched by a request, polling the
+  /// collected matches until the timeout elapses. Returns true if all the interactions were
+  /// matched before the timeout.
+  pub async fn wait_for_all_matched(&self, timeout: Duration) -> bool {
+    let start = Instant::now();
+    loop {
+      if self.all_interactions_matched() {
+        return true;
+      } else if start.elapsed() >= timeout {
+        return false;
+      }
+      tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+  }
+
+  fn all_interactions_matched(&self) -> bool {
+    let matched: HashSet<usize> = self.matches.lock().unwrap().iter()
+      .filter_map(|m| m.matched_interaction())
+      .collect();
+    let pact = self.pact.lock().unwrap();
+    pact.interactions().iter()
+      .enumerate()
+      .filter(|(_, i)| i.as_v4_http().is_some())
+      .all(|(index, _)| matched.contains(&index))
+  }
+}
+
+/// Callback invoked with the result of matching each request received by the mock server
+pub type MatchCallback = Arc<dyn Fn(&MatchResult) + Send + Sync>;
+
 /// Struct to represent the "foreground" part of mock server
-#[derive(Debug)]
 pub struct MockServer {
   /// Mock server unique ID
   pub id: String,
@@ -81,6 +228,8 @@ pub struct MockServer {
   pub pact: Arc<Mutex<dyn Pact + Send + Sync>>,
   /// Receiver of match results
   matches: Arc<Mutex<Vec<MatchResult>>>,
+  /// Requests received by the mock server
+  received_requests: Arc<Mutex<Vec<ReceivedRequest>>>,
   /// Shutdown signal
   shutdown_tx: RefCell<Option<futures::channel::oneshot::Sender<()>>>,
   /// Mock server config
@@ -88,7 +237,29 @@ pub struct MockServer {
   /// Metrics collected by the mock server
   pub metrics: MockServerMetrics,
   /// Pact spec version to use
-  pub spec_version: PactSpecification
+  pub spec_version: PactSpecification,
+  /// Callback to invoke as each request is matched (or not matched)
+  match_callback: Option<MatchCallback>
+}
+
+impl Debug for MockServer {
+  fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+    f.debug_struct("MockServer")
+      .field("id", &self.id)
+      .field("scheme", &self.scheme)
+      .field("port", &self.port)
+      .field("address", &self.address)
+      .field("resources", &self.resources)
+      .field("pact", &self.pact)
+      .field("matches", &self.matches)
+      .field("received_requests", &self.received_requests)
+      .field("shutdown_tx", &self.shutdown_tx)
+      .field("config", &self.config)
+      .field("metrics", &self.metrics)
+      .field("spec_version", &self.spec_version)
+      .field("match_callback", &self.match_callback.as_ref().map(|_| "<callback>"))
+      .finish()
+  }
 }
 
 impl MockServer {
@@ -110,10 +281,12 @@ impl MockServer {
       resources: vec![],
       pact: pact.thread_safe(),
       matches: matches.clone(),
+      received_requests: Arc::new(Mutex::new(vec![])),
       shutdown_tx: RefCell::new(Some(shutdown_tx)),
       config: config.clone(),
       metrics: MockServerMetrics::default(),
-      spec_version: pact_specification(config.pact_specification, pact.specification_version())
+      spec_version: pact_specification(config.pact_specification, pact.specification_version()),
+      match_callback: None
     }));
 
     let (future, socket_addr) = hyper_server::create_and_bind(
@@ -158,10 +331,12 @@ impl MockServer {
       resources: vec![],
       pact: pact.thread_safe(),
       matches: matches.clone(),
+      received_requests: Arc::new(Mutex::new(vec![])),
       shutdown_tx: RefCell::new(Some(shutdown_tx)),
       config: config.clone(),
       metrics: MockServerMetrics::default(),
-      spec_version: pact_specification(config.pact_specification, pact.specification_version())
+      spec_version: pact_specification(config.pact_specification, pact.specification_version()),
+      match_callback: None
     }));
 
     let (future, socket_addr) = hyper_server::create_and_bind_tls(
@@ -233,6 +408,42 @@ impl MockServer {
         self.matches.lock().unwrap().clone()
     }
 
+    /// Sets a callback that will be invoked with the result of matching each request received by
+    /// the mock server, in the order the requests are received. This should be set before any
+    /// requests are made to the mock server.
+    pub fn set_match_callback(&mut self, callback: MatchCallback) {
+      self.match_callback = Some(callback);
+    }
+
+    /// Returns the callback to invoke with the result of matching each request, if one is set
+    pub(crate) fn match_callback(&self) -> Option<MatchCallback> {
+      self.match_callback.clone()
+    }
+
+    /// Returns all the requests received by the mock server, with the bodies retained according
+    /// to the `capture_bodies` config. This will be empty if `capture_bodies` is not set.
+    pub fn received_requests(&self) -> Vec<ReceivedRequest> {
+      self.received_requests.lock().unwrap().clone()
+    }
+
+    /// Records the received request according to the `capture_bodies` config. The request is not
+    /// recorded if `capture_bodies` is not set.
+    pub(crate) fn record_received_request(&self, request: &HttpRequest) {
+      if let Some(mode) = self.config.capture_bodies {
+        self.received_requests.lock().unwrap().push(mode.capture(request));
+      }
+    }
+
+    /// Clears all the collected matches and received requests and resets the request metrics, so
+    /// that the requests made after this call can be checked independently of the earlier ones.
+    /// The matches are shared with the running server, so any request still being processed will
+    /// be recorded after the reset.
+    pub fn reset_matches(&mut self) {
+      self.matches.lock().unwrap().clear();
+      self.received_requests.lock().unwrap().clear();
+      self.metrics.requests = 0;
+    }
+
     /// Returns all the mismatches that have occurred with this mock server
     pub fn mismatches(&self) -> Vec<MatchResult> {
       let matches = self.matches();
@@ -241,7 +452,7 @@ impl MockServer {
         .map(|m| m.clone());
       let requests: Vec<HttpRequest> = matches.iter().map(|m| {
         match m {
-          MatchResult::RequestMatch(request, _) => Some(request),
+          MatchResult::RequestMatch(request, _, _) => Some(request),
           MatchResult::RequestMismatch(request, _) => Some(request),
           MatchResult::RequestNotFound(_) => None,
           MatchResult::MissingRequest(_) => None
@@ -257,6 +468,53 @@ impl MockServer {
       mismatches.chain(missing).collect()
     }
 
+    /// Returns all the requests received by the mock server that did not match any interaction in
+    /// the pact (CORS pre-flight requests are ignored)
+    pub fn unexpected_requests(&self) -> Vec<HttpRequest> {
+      self.matches().iter()
+        .filter(|m| !m.cors_preflight())
+        .filter_map(|m| match m {
+          MatchResult::RequestNotFound(request) => Some(request.clone()),
+          _ => None
+        })
+        .collect()
+    }
+
+    /// If the mock server has not received any requests that did not match an interaction in the
+    /// pact. This does not check for mismatched or missing requests, use `mismatches` for that.
+    pub fn all_matched(&self) -> bool {
+      self.unexpected_requests().is_empty()
+    }
+
+    /// If a request has been received that matched the interaction with the given description
+    pub fn interaction_matched(&self, description: &str) -> bool {
+      let pact = self.pact.lock().unwrap();
+      let interactions = pact.interactions();
+      self.matches().iter()
+        .filter_map(|m| m.matched_interaction())
+        .any(|index| interactions.get(index).map(|i| i.description() == description).unwrap_or(false))
+    }
+
+    /// Waits until every interaction in the pact has been matched by a request, polling the
+    /// collected matches until the timeout elapses. Returns true if all the interactions were
+    /// matched before the timeout.
+    ///
+    /// The mock server needs to lock itself to record each request, so this must not be called
+    /// while holding the lock of a shared mock server (`Arc<Mutex<MockServer>>`), as it would never
+    /// return true. Use `match_waiter` to get a handle that can be used without the lock.
+    pub async fn wait_for_all_matched(&self, timeout: Duration) -> bool {
+      self.match_waiter().wait_for_all_matched(timeout).await
+    }
+
+    /// Returns a handle that can be used to wait for all the interactions to be matched, without
+    /// needing to hold the lock on the mock server
+    pub fn match_waiter(&self) -> MatchWaiter {
+      MatchWaiter {
+        pact: self.pact.clone(),
+        matches: self.matches.clone()
+      }
+    }
+
   /// Mock server writes its pact out to the provided directory
   pub fn write_pact(&self, output_path: &Option<String>, overwrite: bool) -> anyhow::Result<()> {
     trace!("write_pact: output_path = {:?}, overwrite = {}", output_path, overwrite);
@@ -277,7 +535,12 @@ impl MockServer {
       PactSpecification::Unknown => PactSpecification::V3,
       _ => self.spec_version
     };
-    match write_pact(pact.boxed(), filename.as_path(), specification, overwrite) {
+    let pact_to_write = if self.config.sort_interactions {
+      sort_interactions(pact.deref())?
+    } else {
+      pact.boxed()
+    };
+    match write_pact(pact_to_write, filename.as_path(), specification, overwrite) {
       Ok(_) => Ok(()),
       Err(err) => {
         warn!("Failed to write pact to file - {}", err);
@@ -297,6 +560,24 @@ impl MockServer {
     }
 }
 
+/// Key to sort the interactions on: the description, and then the provider state names
+fn interaction_sort_key(interaction: &dyn Interaction) -> (String, Vec<String>) {
+  (interaction.description(), interaction.provider_states().iter().map(|state| state.name.clone()).collect())
+}
+
+/// Returns a copy of the pact with the interactions sorted by description and provider states
+fn sort_interactions(pact: &(dyn Pact + Send + Sync)) -> anyhow::Result<Box<dyn Pact + Send + Sync>> {
+  if pact.is_v4() {
+    let mut pact = pact.as_v4_pact()?;
+    pact.interactions.sort_by_cached_key(|interaction| interaction_sort_key(interaction.as_ref()));
+    Ok(pact.boxed())
+  } else {
+    let mut pact = pact.as_request_response_pact()?;
+    pact.interactions.sort_by_cached_key(|interaction| interaction_sort_key(interaction));
+    Ok(pact.boxed())
+  }
+}
+
 fn pact_specification(spec1: PactSpecification, spec2: PactSpecification) -> PactSpecification {
   match spec1 {
     PactSpecification::Unknown => spec2,
@@ -316,10 +597,12 @@ impl Clone for MockServer {
       resources: vec![],
       pact: self.pact.clone(),
       matches: self.matches.clone(),
+      received_requests: self.received_requests.clone(),
       shutdown_tx: RefCell::new(None),
       config: self.config.clone(),
       metrics: self.metrics.clone(),
-      spec_version: self.spec_version
+      spec_version: self.spec_version,
+      match_callback: self.match_callback.clone()
     }
   }
 }
@@ -334,10 +617,12 @@ impl Default for MockServer {
       resources: vec![],
       pact: Arc::new(Mutex::new(RequestResponsePact::default())),
       matches: Arc::new(Mutex::new(vec![])),
+      received_requests: Arc::new(Mutex::new(vec![])),
       shutdown_tx: RefCell::new(None),
       config: Default::default(),
       metrics: Default::default(),
-      spec_version: Default::default()
+      spec_version: Default::default(),
+      match_callback: None
     }
   }
 }
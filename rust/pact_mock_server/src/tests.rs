@@ -1,3 +1,5 @@
+use std::sync::{Arc, Mutex};
+
 use expectest::expect;
 use expectest::prelude::*;
 use maplit::*;
@@ -5,12 +7,19 @@ use reqwest::header::ACCEPT;
 
 use pact_matching::Mismatch;
 use pact_models::bodies::OptionalBody;
+use pact_models::generators;
+use pact_models::generators::Generator;
 use pact_models::matchingrules;
 use pact_models::matchingrules::MatchingRule;
 use pact_models::prelude::v4::{SynchronousHttp, V4Pact};
+use pact_models::provider_states::ProviderState;
+use pact_models::sync_interaction::RequestResponseInteraction;
+use pact_models::sync_pact::RequestResponsePact;
+use pact_models::{Consumer, Provider};
 use pact_models::v4::http_parts::{HttpRequest, HttpResponse};
 
-use crate::matching::{match_request, MatchResult};
+use crate::matching::{match_request, match_request_with_config, MatchResult};
+use crate::mock_server::{BodyCaptureMode, BodySummary, MockServer, ReceivedRequest};
 
 use super::*;
 use pact_models::v4::interaction::V4Interaction;
@@ -23,7 +32,7 @@ async fn match_request_returns_a_match_for_identical_requests() {
     let pact = V4Pact { interactions, .. V4Pact::default() };
     let result = match_request(&request, &pact).await;
     expect!(result).to(be_equal_to(MatchResult::RequestMatch(interaction.request.clone(),
-      interaction.response.clone())));
+      interaction.response.clone(), 0)));
 }
 
 #[tokio::test]
@@ -50,7 +59,7 @@ async fn match_request_returns_a_match_for_multiple_identical_requests() {
     ];
     let pact = V4Pact { interactions, .. V4Pact::default() };
     let result = match_request(&request, &pact).await;
-    expect!(result).to(be_equal_to(MatchResult::RequestMatch(interaction.request, interaction.response)));
+    expect!(result).to(be_equal_to(MatchResult::RequestMatch(interaction.request, interaction.response, 0)));
 }
 
 #[tokio::test]
@@ -69,7 +78,7 @@ async fn match_request_returns_a_match_for_multiple_requests() {
     ];
     let pact = V4Pact { interactions, .. V4Pact::default() };
     let result = match_request(&request, &pact).await;
-    expect!(result).to(be_equal_to(MatchResult::RequestMatch(interaction.request, interaction.response)));
+    expect!(result).to(be_equal_to(MatchResult::RequestMatch(interaction.request, interaction.response, 0)));
 }
 
 #[tokio::test]
@@ -124,7 +133,7 @@ async fn match_request_returns_the_most_appropriate_mismatch_for_multiple_reques
     let result = match_request(&request3, &pact).await;
     expect!(result).to(be_equal_to(MatchResult::RequestMismatch(interaction2.request,
         vec![Mismatch::BodyMismatch { path: "/".to_string(), expected: Some("This is a body".into()), actual: None,
-        mismatch: "Expected body \'This is a body\' but was missing".to_string() }])));
+        mismatch: "Expected body \'This is a body\' but was missing".to_string(), rule: None }])));
 }
 
 #[tokio::test]
@@ -158,7 +167,7 @@ async fn match_request_supports_v2_matchers() {
     let interactions = vec![interaction.boxed_v4()];
     let pact = V4Pact { interactions, .. V4Pact::default() };
     let result = match_request(&request, &pact).await;
-    expect!(result).to(be_equal_to(MatchResult::RequestMatch(interaction.request, interaction.response)));
+    expect!(result).to(be_equal_to(MatchResult::RequestMatch(interaction.request, interaction.response, 0)));
 }
 
 #[tokio::test]
@@ -186,7 +195,7 @@ async fn match_request_supports_v2_matchers_with_xml() {
     let interactions = vec![interaction.boxed_v4()];
     let pact = V4Pact { interactions, .. V4Pact::default() };
     let result = match_request(&request, &pact).await;
-    expect!(result).to(be_equal_to(MatchResult::RequestMatch(interaction.request, interaction.response)));
+    expect!(result).to(be_equal_to(MatchResult::RequestMatch(interaction.request, interaction.response, 0)));
 }
 
 #[test]
@@ -244,9 +253,581 @@ async fn match_request_with_more_specific_request() {
   let interactions = vec![interaction1.boxed_v4(), interaction2.boxed_v4()];
   let pact = V4Pact { interactions, .. V4Pact::default() };
   let result1 = match_request(&request1.clone(), &pact).await;
-  expect!(result1).to(be_equal_to(MatchResult::RequestMatch(expected.request, expected.response)));
+  expect!(result1).to(be_equal_to(MatchResult::RequestMatch(expected.request, expected.response, 0)));
 
   let expected = interaction2.clone();
   let result2 = match_request(&request2.clone(), &pact).await;
-  expect!(result2).to(be_equal_to(MatchResult::RequestMatch(expected.request, expected.response)));
+  expect!(result2).to(be_equal_to(MatchResult::RequestMatch(expected.request, expected.response, 1)));
+}
+
+#[test]
+fn cors_preflight_with_configured_allowed_methods_and_headers() {
+  let pact = V4Pact::default();
+  let mut manager = ServerManager::new();
+  let id = "cors_preflight_with_configured_allowed_methods_and_headers".to_string();
+  let config = MockServerConfig {
+    cors_preflight: true,
+    cors_allowed_methods: vec!["GET".to_string(), "POST".to_string()],
+    cors_allowed_headers: vec!["Content-Type".to_string(), "X-Request-Id".to_string()],
+    .. MockServerConfig::default()
+  };
+  let port = manager.start_mock_server(id.clone(), pact.boxed(), 0, config).unwrap();
+
+  let client = reqwest::blocking::Client::new();
+  let response = client.request(reqwest::Method::OPTIONS, format!("http://127.0.0.1:{}/", port).as_str())
+    .header("Access-Control-Request-Method", "POST")
+    .send()
+    .unwrap();
+
+  let mismatches = manager.find_mock_server_by_id(&id, &|ms| ms.mismatches());
+  manager.shutdown_mock_server_by_port(port);
+
+  expect!(response.status()).to(be_equal_to(204));
+  let headers = response.headers();
+  expect!(headers.get("access-control-allow-methods").unwrap().to_str().unwrap())
+    .to(be_equal_to("GET, POST"));
+  expect!(headers.get("access-control-allow-headers").unwrap().to_str().unwrap())
+    .to(be_equal_to("Content-Type, X-Request-Id"));
+  expect!(mismatches).to(be_some().value(vec![]));
+}
+
+#[test]
+fn cors_preflight_defaults_to_permissive_headers() {
+  let pact = V4Pact::default();
+  let mut manager = ServerManager::new();
+  let id = "cors_preflight_defaults_to_permissive_headers".to_string();
+  let config = MockServerConfig {
+    cors_preflight: true,
+    .. MockServerConfig::default()
+  };
+  let port = manager.start_mock_server(id.clone(), pact.boxed(), 0, config).unwrap();
+
+  let client = reqwest::blocking::Client::new();
+  let response = client.request(reqwest::Method::OPTIONS, format!("http://127.0.0.1:{}/", port).as_str())
+    .header("Access-Control-Request-Headers", "X-Custom")
+    .send()
+    .unwrap();
+  manager.shutdown_mock_server_by_port(port);
+
+  expect!(response.status()).to(be_equal_to(204));
+  let headers = response.headers();
+  expect!(headers.get("access-control-allow-methods").unwrap().to_str().unwrap())
+    .to(be_equal_to("GET, HEAD, POST, PUT, DELETE, CONNECT, OPTIONS, TRACE, PATCH"));
+  expect!(headers.get("access-control-allow-headers").unwrap().to_str().unwrap())
+    .to(be_equal_to("X-Custom, *"));
+}
+
+#[test]
+fn unmatched_requests_receive_the_configured_not_found_response() {
+  let pact = V4Pact::default();
+  let mut manager = ServerManager::new();
+  let id = "unmatched_requests_receive_the_configured_not_found_response".to_string();
+  let config = MockServerConfig {
+    not_found_response: Some((404, "{\"error\": \"not found\"}".to_string())),
+    .. MockServerConfig::default()
+  };
+  let port = manager.start_mock_server(id.clone(), pact.boxed(), 0, config).unwrap();
+
+  let client = reqwest::blocking::Client::new();
+  let response = client.get(format!("http://127.0.0.1:{}/unknown", port).as_str())
+    .send()
+    .unwrap();
+  let mismatches = manager.find_mock_server_by_port_mut(port, &|ms| ms.mismatches()).unwrap();
+  manager.shutdown_mock_server_by_port(port);
+
+  expect!(response.status()).to(be_equal_to(404));
+  expect!(response.text().unwrap()).to(be_equal_to("{\"error\": \"not found\"}"));
+  expect!(mismatches.len()).to(be_equal_to(1));
+  expect!(mismatches[0].match_key()).to(be_equal_to("Unexpected-Request"));
+}
+
+async fn start_mock_server_for_path(path: &str, config: MockServerConfig) -> (MockServer, u16) {
+  let interaction = SynchronousHttp {
+    request: HttpRequest { path: path.to_string(), .. HttpRequest::default() },
+    .. SynchronousHttp::default()
+  };
+  let pact = V4Pact { interactions: vec![interaction.boxed_v4()], .. V4Pact::default() };
+  let (mock_server, future) = MockServer::new(path.to_string(), pact.boxed(),
+    "127.0.0.1:0".parse().unwrap(), config).await.unwrap();
+  tokio::spawn(future);
+  let mock_server = mock_server.lock().unwrap().clone();
+  let port = mock_server.port.unwrap();
+  (mock_server, port)
+}
+
+#[tokio::test]
+async fn mock_server_records_the_index_of_the_matched_interactions() {
+  let interactions = (1..=2).map(|page| SynchronousHttp {
+    description: format!("a request for page {}", page),
+    request: HttpRequest {
+      path: "/items".to_string(),
+      query: Some(hashmap!{ "page".to_string() => vec![page.to_string()] }),
+      .. HttpRequest::default()
+    },
+    .. SynchronousHttp::default()
+  }.boxed_v4()).collect();
+  let pact = V4Pact { interactions, .. V4Pact::default() };
+  let (mock_server, future) = MockServer::new("matched_index".to_string(), pact.boxed(),
+    "127.0.0.1:0".parse().unwrap(), MockServerConfig::default()).await.unwrap();
+  tokio::spawn(future);
+  let mock_server = mock_server.lock().unwrap().clone();
+  let port = mock_server.port.unwrap();
+
+  let client = reqwest::Client::new();
+  for page in ["2", "1", "2"] {
+    let response = client.get(format!("http://127.0.0.1:{}/items?page={}", port, page)).send().await.unwrap();
+    expect!(response.status()).to(be_equal_to(200));
+  }
+
+  let indices: Vec<usize> = mock_server.matches().iter()
+    .filter_map(|m| m.matched_interaction())
+    .collect();
+  expect!(indices).to(be_equal_to(vec![1, 0, 1]));
+}
+
+#[tokio::test]
+async fn reset_matches_clears_the_matches_recorded_before_it() {
+  let interactions = ["/one", "/two"].iter().map(|path| SynchronousHttp {
+    description: format!("a request to {}", path),
+    request: HttpRequest { path: path.to_string(), .. HttpRequest::default() },
+    .. SynchronousHttp::default()
+  }.boxed_v4()).collect();
+  let pact = V4Pact { interactions, .. V4Pact::default() };
+  let (mock_server, future) = MockServer::new("reset_matches".to_string(), pact.boxed(),
+    "127.0.0.1:0".parse().unwrap(), MockServerConfig::default()).await.unwrap();
+  tokio::spawn(future);
+  let port = mock_server.lock().unwrap().port.unwrap();
+
+  let client = reqwest::Client::new();
+  let response = client.get(format!("http://127.0.0.1:{}/one", port)).send().await.unwrap();
+  expect!(response.status()).to(be_equal_to(200));
+  mock_server.lock().unwrap().reset_matches();
+  let response = client.get(format!("http://127.0.0.1:{}/two", port)).send().await.unwrap();
+  expect!(response.status()).to(be_equal_to(200));
+
+  let mock_server = mock_server.lock().unwrap();
+  let paths: Vec<String> = mock_server.matches().iter()
+    .filter_map(|m| match m {
+      MatchResult::RequestMatch(request, _, _) => Some(request.path.clone()),
+      _ => None
+    })
+    .collect();
+  expect!(paths).to(be_equal_to(vec!["/two".to_string()]));
+  expect!(mock_server.metrics.requests).to(be_equal_to(1));
+}
+
+async fn start_ordered_mock_server(name: &str) -> (MockServer, u16) {
+  let interactions = ["/login", "/items"].iter().map(|path| SynchronousHttp {
+    description: format!("a request to {}", path),
+    request: HttpRequest { path: path.to_string(), .. HttpRequest::default() },
+    .. SynchronousHttp::default()
+  }.boxed_v4()).collect();
+  let pact = V4Pact { interactions, .. V4Pact::default() };
+  let config = MockServerConfig { enforce_request_order: true, .. MockServerConfig::default() };
+  let (mock_server, future) = MockServer::new(name.to_string(), pact.boxed(),
+    "127.0.0.1:0".parse().unwrap(), config).await.unwrap();
+  tokio::spawn(future);
+  let mock_server = mock_server.lock().unwrap().clone();
+  let port = mock_server.port.unwrap();
+  (mock_server, port)
+}
+
+#[tokio::test]
+async fn mock_server_accepts_requests_in_order_when_the_order_is_enforced() {
+  let (mock_server, port) = start_ordered_mock_server("in_order").await;
+
+  let client = reqwest::Client::new();
+  for path in ["/login", "/items"] {
+    let response = client.get(format!("http://127.0.0.1:{}{}", port, path)).send().await.unwrap();
+    expect!(response.status()).to(be_equal_to(200));
+  }
+
+  expect!(mock_server.mismatches()).to(be_equal_to(vec![]));
+}
+
+#[tokio::test]
+async fn mock_server_rejects_requests_out_of_order_when_the_order_is_enforced() {
+  let (mock_server, port) = start_ordered_mock_server("out_of_order").await;
+
+  let client = reqwest::Client::new();
+  let response = client.get(format!("http://127.0.0.1:{}/items", port)).send().await.unwrap();
+  expect!(response.status()).to(be_equal_to(500));
+  let response = client.get(format!("http://127.0.0.1:{}/login", port)).send().await.unwrap();
+  expect!(response.status()).to(be_equal_to(200));
+
+  let mismatches = mock_server.mismatches();
+  expect!(mismatches.len()).to(be_equal_to(1));
+  expect!(mismatches[0].clone()).to(be_equal_to(MatchResult::RequestMismatch(
+    HttpRequest { path: "/items".to_string(), .. HttpRequest::default() },
+    vec![ Mismatch::InteractionMismatch {
+      interaction: "a request to /items".to_string(),
+      mismatch: "Request for interaction 'a request to /items' was received out of order, expected a request for interaction 'a request to /login'".to_string()
+    } ]
+  )));
+}
+
+#[tokio::test]
+async fn wait_for_all_matched_returns_true_once_all_the_requests_are_received() {
+  let (mock_server, port) = start_mock_server_for_path("/delayed", MockServerConfig::default()).await;
+
+  tokio::spawn(async move {
+    tokio::time::sleep(std::time::Duration::from_millis(200)).await;
+    reqwest::Client::new().get(format!("http://127.0.0.1:{}/delayed", port)).send().await
+  });
+
+  expect!(mock_server.wait_for_all_matched(std::time::Duration::from_secs(5)).await).to(be_true());
+}
+
+#[tokio::test]
+async fn wait_for_all_matched_returns_false_if_the_requests_are_never_received() {
+  let (mock_server, _) = start_mock_server_for_path("/never", MockServerConfig::default()).await;
+
+  expect!(mock_server.wait_for_all_matched(std::time::Duration::from_millis(200)).await).to(be_false());
+}
+
+#[tokio::test]
+async fn match_waiter_waits_for_the_matched_interactions_without_locking_the_mock_server() {
+  let interaction = SynchronousHttp {
+    request: HttpRequest {
+      path: "/items/1".to_string(),
+      matching_rules: matchingrules! { "path" => { "" => [ MatchingRule::Regex("^/items/\\d+$".to_string()) ] } },
+      .. HttpRequest::default()
+    },
+    .. SynchronousHttp::default()
+  };
+  let pact = V4Pact { interactions: vec![interaction.boxed_v4()], .. V4Pact::default() };
+  let (mock_server, future) = MockServer::new("match_waiter".to_string(), pact.boxed(),
+    "127.0.0.1:0".parse().unwrap(), MockServerConfig::default()).await.unwrap();
+  tokio::spawn(future);
+  let port = mock_server.lock().unwrap().port.unwrap();
+  let waiter = mock_server.lock().unwrap().match_waiter();
+
+  tokio::spawn(async move {
+    tokio::time::sleep(std::time::Duration::from_millis(200)).await;
+    reqwest::Client::new().get(format!("http://127.0.0.1:{}/items/100", port)).send().await
+  });
+
+  expect!(waiter.wait_for_all_matched(std::time::Duration::from_secs(5)).await).to(be_true());
+  expect!(mock_server.lock().unwrap().mismatches()).to(be_equal_to(vec![]));
+}
+
+#[tokio::test]
+async fn mock_server_accepts_http2_requests_with_prior_knowledge() {
+  let (mock_server, port) = start_mock_server_for_path("/http2", MockServerConfig::default()).await;
+
+  let client = reqwest::Client::builder().http2_prior_knowledge().build().unwrap();
+  let response = client.get(format!("http://127.0.0.1:{}/http2", port)).send().await.unwrap();
+
+  expect!(response.version()).to(be_equal_to(reqwest::Version::HTTP_2));
+  expect!(response.status()).to(be_equal_to(200));
+  expect!(mock_server.mismatches()).to(be_equal_to(vec![]));
+  expect!(mock_server.wait_for_all_matched(std::time::Duration::from_secs(1)).await).to(be_true());
+}
+
+/// Posts a body to the mock server for a matching and an unknown path, returning the received
+/// requests and the bodies of the requests that were not found
+async fn post_body_with_capture_mode(capture_bodies: Option<BodyCaptureMode>) -> (Vec<ReceivedRequest>, Vec<OptionalBody>) {
+  let interaction = SynchronousHttp {
+    description: "an upload".to_string(),
+    request: HttpRequest { method: "POST".to_string(), path: "/upload".to_string(), .. HttpRequest::default() },
+    .. SynchronousHttp::default()
+  };
+  let pact = V4Pact { interactions: vec![ interaction.boxed_v4() ], .. V4Pact::default() };
+  let config = MockServerConfig { capture_bodies, .. MockServerConfig::default() };
+  let (mock_server, future) = MockServer::new("capture_bodies".to_string(), pact.boxed(),
+    "127.0.0.1:0".parse().unwrap(), config).await.unwrap();
+  tokio::spawn(future);
+  let port = mock_server.lock().unwrap().port.unwrap();
+
+  let client = reqwest::Client::new();
+  let response = client.post(format!("http://127.0.0.1:{}/upload", port))
+    .header("content-type", "text/plain")
+    .body("hello")
+    .send().await.unwrap();
+  expect!(response.status()).to(be_equal_to(200));
+  client.post(format!("http://127.0.0.1:{}/unknown", port))
+    .header("content-type", "text/plain")
+    .body("hello")
+    .send().await.unwrap();
+
+  let mock_server = mock_server.lock().unwrap();
+  let not_found_bodies: Vec<OptionalBody> = mock_server.matches().iter()
+    .filter_map(|m| match m {
+      MatchResult::RequestNotFound(request) => Some(request.body.clone()),
+      _ => None
+    })
+    .collect();
+  (mock_server.received_requests(), not_found_bodies)
+}
+
+#[tokio::test]
+async fn mock_server_does_not_keep_the_received_requests_by_default() {
+  let (received, not_found_bodies) = post_body_with_capture_mode(None).await;
+  expect!(received.iter()).to(be_empty());
+  expect!(not_found_bodies).to(be_equal_to(vec![OptionalBody::Present("hello".into(), Some("text/plain".into()), None)]));
+}
+
+#[tokio::test]
+async fn mock_server_keeps_the_full_request_bodies_in_full_mode() {
+  let (received, not_found_bodies) = post_body_with_capture_mode(Some(BodyCaptureMode::Full)).await;
+  expect!(received.len()).to(be_equal_to(2));
+  expect!(not_found_bodies).to(be_equal_to(vec![OptionalBody::Present("hello".into(), Some("text/plain".into()), None)]));
+  expect!(received[0].request.path.as_str()).to(be_equal_to("/upload"));
+  expect!(received[0].request.body.str_value()).to(be_equal_to("hello"));
+  expect!(received[0].body_summary.clone()).to(be_none());
+}
+
+#[tokio::test]
+async fn mock_server_only_keeps_a_summary_of_the_request_bodies_in_hashed_mode() {
+  let (received, not_found_bodies) = post_body_with_capture_mode(Some(BodyCaptureMode::Hashed)).await;
+  expect!(received.len()).to(be_equal_to(2));
+  expect!(not_found_bodies).to(be_equal_to(vec![OptionalBody::Present("hello".into(), Some("text/plain".into()), None)]));
+  expect!(received[0].request.path.as_str()).to(be_equal_to("/upload"));
+  expect!(received[0].request.body.clone()).to(be_equal_to(OptionalBody::Missing));
+  expect!(received[0].body_summary.clone()).to(be_some().value(BodySummary {
+    length: 5,
+    sha256: "2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824".to_string()
+  }));
+}
+
+#[tokio::test]
+async fn mock_server_does_not_keep_the_request_bodies_if_capture_is_disabled() {
+  let (received, not_found_bodies) = post_body_with_capture_mode(Some(BodyCaptureMode::None)).await;
+  expect!(received.len()).to(be_equal_to(2));
+  expect!(not_found_bodies).to(be_equal_to(vec![OptionalBody::Present("hello".into(), Some("text/plain".into()), None)]));
+  expect!(received[0].request.path.as_str()).to(be_equal_to("/upload"));
+  expect!(received[0].request.body.clone()).to(be_equal_to(OptionalBody::Missing));
+  expect!(received[0].body_summary.clone()).to(be_none());
+}
+
+#[tokio::test]
+async fn mock_server_can_generate_response_values_from_the_request() {
+  let interaction = SynchronousHttp {
+    description: "a request with a correlation ID".to_string(),
+    request: HttpRequest { path: "/orders".to_string(), .. HttpRequest::default() },
+    response: HttpResponse {
+      headers: Some(hashmap!{ "Content-Type".to_string() => vec!["application/json".to_string()] }),
+      body: OptionalBody::from(&json!({ "correlationId": "placeholder", "status": "ok" })),
+      generators: generators! {
+        "BODY" => {
+          "$.correlationId" => Generator::RequestValue("headers.X-Correlation-Id".to_string(), None)
+        }
+      },
+      .. HttpResponse::default()
+    },
+    .. SynchronousHttp::default()
+  };
+  let pact = V4Pact { interactions: vec![ interaction.boxed_v4() ], .. V4Pact::default() };
+  let (mock_server, future) = MockServer::new("request_values".to_string(), pact.boxed(),
+    "127.0.0.1:0".parse().unwrap(), MockServerConfig::default()).await.unwrap();
+  tokio::spawn(future);
+  let port = mock_server.lock().unwrap().port.unwrap();
+
+  let response = reqwest::Client::new().get(format!("http://127.0.0.1:{}/orders", port))
+    .header("X-Correlation-Id", "4a6b2c1d")
+    .send().await.unwrap();
+  expect!(response.status()).to(be_equal_to(200));
+  let body: serde_json::Value = response.json().await.unwrap();
+  expect!(body).to(be_equal_to(json!({ "correlationId": "4a6b2c1d", "status": "ok" })));
+}
+
+#[tokio::test]
+async fn mock_server_invokes_the_match_callback_for_each_request() {
+  let interaction = SynchronousHttp {
+    description: "a request for an item".to_string(),
+    request: HttpRequest { path: "/item".to_string(), .. HttpRequest::default() },
+    .. SynchronousHttp::default()
+  };
+  let pact = V4Pact { interactions: vec![ interaction.boxed_v4() ], .. V4Pact::default() };
+  let (mock_server, future) = MockServer::new("match_callback".to_string(), pact.boxed(),
+    "127.0.0.1:0".parse().unwrap(), MockServerConfig::default()).await.unwrap();
+  let results = Arc::new(Mutex::new(vec![]));
+  let callback_results = results.clone();
+  mock_server.lock().unwrap().set_match_callback(Arc::new(move |result: &MatchResult| {
+    callback_results.lock().unwrap().push(result.clone());
+  }));
+  tokio::spawn(future);
+  let port = mock_server.lock().unwrap().port.unwrap();
+
+  let client = reqwest::Client::new();
+  for path in ["/item", "/other", "/item"] {
+    client.get(format!("http://127.0.0.1:{}{}", port, path)).send().await.unwrap();
+  }
+
+  let results = results.lock().unwrap();
+  let match_keys: Vec<String> = results.iter().map(|result| result.match_key()).collect();
+  expect!(match_keys).to(be_equal_to(vec!["Request-Matched".to_string(), "Unexpected-Request".to_string(),
+    "Request-Matched".to_string()]));
+  expect!(results.clone()).to(be_equal_to(mock_server.lock().unwrap().matches()));
+}
+
+#[tokio::test]
+async fn mock_server_returns_the_requests_that_did_not_match_any_interaction() {
+  let interaction = SynchronousHttp {
+    description: "a request for an item".to_string(),
+    request: HttpRequest { path: "/item".to_string(), .. HttpRequest::default() },
+    .. SynchronousHttp::default()
+  };
+  let pact = V4Pact { interactions: vec![ interaction.boxed_v4() ], .. V4Pact::default() };
+  let (mock_server, future) = MockServer::new("unexpected_requests".to_string(), pact.boxed(),
+    "127.0.0.1:0".parse().unwrap(), MockServerConfig::default()).await.unwrap();
+  tokio::spawn(future);
+  let port = mock_server.lock().unwrap().port.unwrap();
+
+  let client = reqwest::Client::new();
+  client.get(format!("http://127.0.0.1:{}/item", port)).send().await.unwrap();
+  expect!(mock_server.lock().unwrap().all_matched()).to(be_true());
+  expect!(mock_server.lock().unwrap().unexpected_requests().iter()).to(be_empty());
+
+  client.get(format!("http://127.0.0.1:{}/undeclared", port)).send().await.unwrap();
+  let mock_server = mock_server.lock().unwrap();
+  expect!(mock_server.all_matched()).to(be_false());
+  let paths: Vec<String> = mock_server.unexpected_requests().iter().map(|req| req.path.clone()).collect();
+  expect!(paths).to(be_equal_to(vec!["/undeclared".to_string()]));
+}
+
+fn write_pact_with_interactions(descriptions: &[(&str, &str)], sort_interactions: bool, dir: &std::path::Path) -> String {
+  let interactions = descriptions.iter().map(|(description, state)| RequestResponseInteraction {
+    description: description.to_string(),
+    provider_states: vec![ ProviderState::default(state) ],
+    .. RequestResponseInteraction::default()
+  }).collect();
+  let mut mock_server = MockServer::default();
+  mock_server.pact = Arc::new(Mutex::new(RequestResponsePact {
+    consumer: Consumer { name: "sort_consumer".to_string() },
+    provider: Provider { name: "sort_provider".to_string() },
+    interactions,
+    .. RequestResponsePact::default()
+  }));
+  mock_server.config.sort_interactions = sort_interactions;
+  mock_server.write_pact(&Some(dir.to_string_lossy().to_string()), true).unwrap();
+
+  let pact_json: serde_json::Value = serde_json::from_str(
+    &std::fs::read_to_string(dir.join("sort_consumer-sort_provider.json")).unwrap()).unwrap();
+  pact_json["interactions"].as_array().unwrap().iter()
+    .map(|interaction| format!("{} / {}", interaction["description"].as_str().unwrap(),
+      interaction["providerStates"][0]["name"].as_str().unwrap()))
+    .collect::<Vec<_>>()
+    .join(", ")
+}
+
+#[test]
+fn write_pact_sorts_the_interactions_if_configured() {
+  let dir = std::env::temp_dir().join(format!("pact_mock_server_sort_{}", std::process::id()));
+  let first_order = [("b request", "state 1"), ("a request", "state 2"), ("a request", "state 1")];
+  let second_order = [("a request", "state 1"), ("b request", "state 1"), ("a request", "state 2")];
+
+  expect!(write_pact_with_interactions(&first_order, false, &dir)).to(
+    be_equal_to("b request / state 1, a request / state 2, a request / state 1"));
+
+  let expected = "a request / state 1, a request / state 2, b request / state 1";
+  expect!(write_pact_with_interactions(&first_order, true, &dir)).to(be_equal_to(expected));
+  expect!(write_pact_with_interactions(&second_order, true, &dir)).to(be_equal_to(expected));
+
+  std::fs::remove_dir_all(&dir).unwrap_or(());
+}
+
+#[tokio::test]
+async fn mock_server_returns_if_an_interaction_was_matched() {
+  let pact = V4Pact {
+    interactions: vec![
+      SynchronousHttp {
+        description: "a request for an item".to_string(),
+        request: HttpRequest { path: "/item".to_string(), .. HttpRequest::default() },
+        .. SynchronousHttp::default()
+      }.boxed_v4(),
+      SynchronousHttp {
+        description: "a request for another item".to_string(),
+        request: HttpRequest { path: "/other".to_string(), .. HttpRequest::default() },
+        .. SynchronousHttp::default()
+      }.boxed_v4()
+    ],
+    .. V4Pact::default()
+  };
+  let (mock_server, future) = MockServer::new("interaction_matched".to_string(), pact.boxed(),
+    "127.0.0.1:0".parse().unwrap(), MockServerConfig::default()).await.unwrap();
+  tokio::spawn(future);
+  let port = mock_server.lock().unwrap().port.unwrap();
+
+  expect!(mock_server.lock().unwrap().interaction_matched("a request for an item")).to(be_false());
+  reqwest::Client::new().get(format!("http://127.0.0.1:{}/item", port)).send().await.unwrap();
+
+  let mock_server = mock_server.lock().unwrap();
+  expect!(mock_server.interaction_matched("a request for an item")).to(be_true());
+  expect!(mock_server.interaction_matched("a request for another item")).to(be_false());
+  expect!(mock_server.interaction_matched("an unknown interaction")).to(be_false());
+}
+
+#[tokio::test]
+async fn mock_server_sends_the_response_trailers_after_the_body() {
+  let interaction = SynchronousHttp {
+    request: HttpRequest { path: "/stream".to_string(), .. HttpRequest::default() },
+    response: HttpResponse {
+      body: OptionalBody::Present("streamed".into(), None, None),
+      trailers: Some(hashmap!{ "grpc-status".to_string() => vec!["0".to_string()] }),
+      .. HttpResponse::default()
+    },
+    .. SynchronousHttp::default()
+  };
+  let pact = V4Pact { interactions: vec![interaction.boxed_v4()], .. V4Pact::default() };
+  let (mock_server, future) = MockServer::new("trailers".to_string(), pact.boxed(),
+    "127.0.0.1:0".parse().unwrap(), MockServerConfig::default()).await.unwrap();
+  tokio::spawn(future);
+  let port = mock_server.lock().unwrap().port.unwrap();
+
+  let client = hyper::Client::builder().http2_only(true).build_http::<hyper::Body>();
+  let uri = format!("http://127.0.0.1:{}/stream", port).parse().unwrap();
+  let mut body = client.get(uri).await.unwrap().into_body();
+  let data = hyper::body::HttpBody::data(&mut body).await.unwrap().unwrap();
+  let trailers = hyper::body::HttpBody::trailers(&mut body).await.unwrap().unwrap();
+
+  expect!(data.as_ref()).to(be_equal_to("streamed".as_bytes()));
+  expect!(trailers.get("grpc-status").unwrap().to_str().unwrap()).to(be_equal_to("0"));
+}
+
+#[tokio::test]
+async fn match_request_returns_a_mismatch_for_a_body_with_an_unexpected_field() {
+  let expected = HttpRequest {
+    method: "POST".to_string(),
+    headers: Some(hashmap!{ "Content-Type".to_string() => vec!["application/json".to_string()] }),
+    body: OptionalBody::Present(r#"{"name":"Mary"}"#.into(), Some("application/json".into()), None),
+    .. HttpRequest::default()
+  };
+  let actual = HttpRequest {
+    body: OptionalBody::Present(r#"{"name":"Mary","age":21}"#.into(), Some("application/json".into()), None),
+    .. expected.clone()
+  };
+  let interaction = SynchronousHttp { request: expected.clone(), .. SynchronousHttp::default() };
+  let pact = V4Pact { interactions: vec![interaction.boxed_v4()], .. V4Pact::default() };
+
+  let result = match_request(&actual, &pact).await;
+  expect!(result).to(be_equal_to(MatchResult::RequestMismatch(expected.clone(), vec![
+    Mismatch::BodyMismatch {
+      path: "$".to_string(),
+      expected: Some(r#"["name"]"#.into()),
+      actual: Some(r#"["age","name"]"#.into()),
+      mismatch: String::default(),
+      rule: None
+    }
+  ])));
+}
+
+#[tokio::test]
+async fn match_request_allows_a_body_with_an_unexpected_field_if_configured() {
+  let expected = HttpRequest {
+    method: "POST".to_string(),
+    headers: Some(hashmap!{ "Content-Type".to_string() => vec!["application/json".to_string()] }),
+    body: OptionalBody::Present(r#"{"name":"Mary"}"#.into(), Some("application/json".into()), None),
+    .. HttpRequest::default()
+  };
+  let actual = HttpRequest {
+    body: OptionalBody::Present(r#"{"name":"Mary","age":21}"#.into(), Some("application/json".into()), None),
+    .. expected.clone()
+  };
+  let interaction = SynchronousHttp { request: expected.clone(), .. SynchronousHttp::default() };
+  let pact = V4Pact { interactions: vec![interaction.boxed_v4()], .. V4Pact::default() };
+
+  let config = MockServerConfig { allow_unexpected_body_keys: true, .. MockServerConfig::default() };
+  let result = match_request_with_config(&actual, &pact, &config.matching_config()).await;
+  expect!(result).to(be_equal_to(MatchResult::RequestMatch(expected, HttpResponse::default(), 0)));
 }
@@ -67,7 +67,8 @@ fn start_provider(context: &mut WebmachineContext, options: ServerOpts) -> Resul
           let mock_server_id = Uuid::new_v4().to_string();
           let config = MockServerConfig {
             cors_preflight: query_param_set(context, "cors"),
-            pact_specification: PactSpecification::default()
+            pact_specification: PactSpecification::default(),
+            .. MockServerConfig::default()
           };
           debug!("Mock server config = {:?}", config);
 
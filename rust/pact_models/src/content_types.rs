@@ -136,17 +136,29 @@ impl ContentType {
     self.main_type == "*" || self.sub_type == "*"
   }
 
-  /// Equals, ignoring attributes if not present on self
+  /// Equals, ignoring attributes if not present on self. A charset attribute that is missing
+  /// from self will match any charset, otherwise charsets are compared ignoring case.
   pub fn is_equivalent_to(&self, other: &ContentType) -> bool {
     if self.is_strict_xml() && other.is_strict_xml() {
-      self.attributes.is_empty() || self.attributes == other.attributes
+      self.attributes.is_empty() || self.attributes_equivalent_to(other)
     }
     else if self.attributes.is_empty() {
       self.main_type == other.main_type && self.sub_type == other.sub_type
     } else {
-      self == other
+      self.main_type == other.main_type && self.sub_type == other.sub_type &&
+        self.suffix == other.suffix && self.attributes_equivalent_to(other)
     }
   }
+
+  fn attributes_equivalent_to(&self, other: &ContentType) -> bool {
+    let charset_matches = match (self.attributes.get("charset"), other.attributes.get("charset")) {
+      (Some(expected), Some(actual)) => expected.eq_ignore_ascii_case(actual),
+      (Some(_), None) => false,
+      (None, _) => true
+    };
+    charset_matches && self.attributes.iter().filter(|(k, _)| k.as_str() != "charset")
+      .eq(other.attributes.iter().filter(|(k, _)| k.as_str() != "charset"))
+  }
 }
 
 impl Default for ContentType {
@@ -499,4 +511,21 @@ mod tests {
     expect!(content_type2.is_equivalent_to(&content_type3)).to(be_true());
     expect!(content_type2.is_equivalent_to(&content_type4)).to(be_false());
   }
+
+  #[test]
+  fn charset_equivalent_test() {
+    let json = ContentType::parse("application/json").unwrap();
+    let json_version = ContentType::parse("application/json;version=1").unwrap();
+    let json_version_utf8 = ContentType::parse("application/json;version=1;charset=utf-8").unwrap();
+    let json_version_upper_utf8 = ContentType::parse("application/json;version=1;charset=UTF-8").unwrap();
+    let json_version_utf16 = ContentType::parse("application/json;version=1;charset=utf-16").unwrap();
+    let json_version2_utf8 = ContentType::parse("application/json;version=2;charset=utf-8").unwrap();
+
+    expect!(json.is_equivalent_to(&json_version_utf8)).to(be_true());
+    expect!(json_version.is_equivalent_to(&json_version_utf8)).to(be_true());
+    expect!(json_version_utf8.is_equivalent_to(&json_version_upper_utf8)).to(be_true());
+    expect!(json_version_utf8.is_equivalent_to(&json_version_utf16)).to(be_false());
+    expect!(json_version_utf8.is_equivalent_to(&json_version)).to(be_false());
+    expect!(json_version.is_equivalent_to(&json_version2_utf8)).to(be_false());
+  }
 }
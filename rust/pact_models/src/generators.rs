@@ -25,7 +25,7 @@ use serde_json::{json, Value};
 use uuid::Uuid;
 
 use crate::bodies::OptionalBody;
-use crate::expression_parser::{contains_expressions, DataType, DataValue, MapValueResolver, parse_expression};
+use crate::expression_parser::{contains_expressions, DataType, DataValue, MapValueResolver, parse_expression, ValueResolver};
 use crate::json_utils::{get_field_as_string, json_to_string, JsonToNum};
 use crate::matchingrules::{Category, MatchingRuleCategory};
 use crate::PactSpecification;
@@ -139,6 +139,9 @@ pub enum Generator {
   ProviderStateGenerator(String, Option<DataType>),
   /// Generates a URL with the mock server as the base URL
   MockServerURL(String, String),
+  /// Generates a value that is looked up from the request received by the mock server (i.e.
+  /// `headers.x-correlation-id` or `body.user.id`)
+  RequestValue(String, Option<DataType>),
   /// List of variants which can have embedded generators
   ArrayContains(Vec<(usize, MatchingRuleCategory, HashMap<DocPath, Generator>)>)
 }
@@ -178,6 +181,13 @@ impl Generator {
         }
       }
       Generator::MockServerURL(example, regex) => Some(json!({ "type": "MockServerURL", "example": example, "regex": regex })),
+      Generator::RequestValue(ref expression, ref data_type) => {
+        if let Some(data_type) = data_type {
+          Some(json!({"type": "RequestValue", "expression": expression, "dataType": data_type}))
+        } else {
+          Some(json!({"type": "RequestValue", "expression": expression}))
+        }
+      }
       _ => None
     }
   }
@@ -208,6 +218,9 @@ impl Generator {
           .map(|dt| DataType::from(dt.clone())))),
       "MockServerURL" => Some(Generator::MockServerURL(get_field_as_string("example", map).unwrap_or_default(),
                                                        get_field_as_string("regex", map).unwrap_or_default())),
+      "RequestValue" => map.get("expression").map(|f|
+        Generator::RequestValue(json_to_string(f), map.get("dataType")
+          .map(|dt| DataType::from(dt.clone())))),
       _ => {
         log::warn!("'{}' is not a valid generator type", gen_type);
         None
@@ -220,6 +233,7 @@ impl Generator {
     match self {
       Generator::ProviderStateGenerator(_, _) => mode == &GeneratorTestMode::Provider,
       Generator::MockServerURL(_, _) => mode == &GeneratorTestMode::Consumer,
+      Generator::RequestValue(_, _) => mode == &GeneratorTestMode::Consumer,
       _ => true
     }
   }
@@ -239,6 +253,7 @@ impl Generator {
       Generator::RandomBoolean => "RandomBoolean",
       Generator::ProviderStateGenerator(_, _) => "ProviderStateGenerator",
       Generator::MockServerURL(_, _) => "MockServerURL",
+      Generator::RequestValue(_, _) => "RequestValue",
       Generator::ArrayContains(_) => "ArrayContains",
     }.to_string()
   }
@@ -279,6 +294,11 @@ impl Generator {
         hashmap!{ "expression" => Value::String(exp.clone()) }
       }
       Generator::MockServerURL(example, regex) => hashmap!{ "example" => json!(example), "regex" => json!(regex) },
+      Generator::RequestValue(exp, data_type) => if let Some(data_type) = data_type {
+        hashmap!{ "expression" => Value::String(exp.clone()), "data_type" => data_type.into() }
+      } else {
+        hashmap!{ "expression" => Value::String(exp.clone()) }
+      }
       Generator::ArrayContains(variants) => hashmap!{ "variants" => variants.iter().map(|(variant, rules, gens)| {
           Value::Array(vec![json!(variant), rules.to_v3_json(), Value::Object(gens.iter().map(|(key, gen)| {
             (key.to_string(), gen.to_json().unwrap())
@@ -321,6 +341,10 @@ impl Hash for Generator {
         str1.hash(state);
         str2.hash(state);
       },
+      Generator::RequestValue(str, datatype) => {
+        str.hash(state);
+        datatype.hash(state);
+      },
       Generator::ArrayContains(variants) => {
         for (index, rules, generators) in variants {
           index.hash(state);
@@ -350,6 +374,7 @@ impl PartialEq for Generator {
       (Generator::Date(format1), Generator::Date(format2)) => format1 == format2,
       (Generator::ProviderStateGenerator(str1, data1), Generator::ProviderStateGenerator(str2, data2)) => str1 == str2 && data1 == data2,
       (Generator::MockServerURL(ex1, re1), Generator::MockServerURL(ex2, re2)) => ex1 == ex2 && re1 == re2,
+      (Generator::RequestValue(str1, data1), Generator::RequestValue(str2, data2)) => str1 == str2 && data1 == data2,
       (Generator::ArrayContains(variants1), Generator::ArrayContains(variants2)) => variants1 == variants2,
       (Generator::Uuid(format), Generator::Uuid(format2)) => format == format2,
       _ => mem::discriminant(self) == mem::discriminant(other)
@@ -934,6 +959,37 @@ pub fn generate_value_from_context(expression: &str, context: &HashMap<&str, Val
   data_type.clone().unwrap_or(DataType::RAW).wrap(result)
 }
 
+/// Value resolver that looks up a dot separated path (i.e. `headers.x-correlation-id`) in the
+/// request details in the test context. Keys are matched ignoring case if there is no exact match.
+struct RequestValueResolver<'a> {
+  request: &'a Value
+}
+
+impl ValueResolver<Value> for RequestValueResolver<'_> {
+  fn resolve_value(&self, name: &str) -> Option<Value> {
+    name.split('.').try_fold(self.request, |value, key| match value {
+      Value::Object(map) => map.get(key)
+        .or_else(|| map.iter().find(|(k, _)| k.eq_ignore_ascii_case(key)).map(|(_, v)| v)),
+      Value::Array(items) => key.parse::<usize>().ok().and_then(|index| items.get(index)),
+      _ => None
+    }).cloned()
+  }
+}
+
+/// Generates a value from the request details (under the `request` key) in the test context
+pub fn generate_value_from_request(expression: &str, context: &HashMap<&str, Value>, data_type: &Option<DataType>) -> anyhow::Result<DataValue> {
+  let request = context.get("request")
+    .ok_or_else(|| anyhow!("RequestValue: can not generate a value as there is no request in the test context"))?;
+  let resolver = RequestValueResolver { request };
+  let result = if contains_expressions(expression) {
+    parse_expression(expression, &resolver)
+  } else {
+    resolver.resolve_value(expression)
+      .ok_or_else(|| anyhow!("Value '{}' was not found in the request", expression))
+  };
+  data_type.clone().unwrap_or(DataType::RAW).wrap(result)
+}
+
 const DIGIT_CHARSET: &str = "0123456789";
 pub fn generate_decimal(digits: usize) -> String {
   let mut rnd = rand::thread_rng();
@@ -985,6 +1041,11 @@ impl GenerateValue<u16> for Generator {
           Ok(val) => u16::try_from(val),
           Err(err) => Err(err)
         },
+      Generator::RequestValue(exp, dt) =>
+        match generate_value_from_request(exp, context, dt) {
+          Ok(val) => u16::try_from(val),
+          Err(err) => Err(err)
+        },
       _ => Err(anyhow!("Could not generate a u16 value from {} using {:?}", value, self))
     }
   }
@@ -1085,6 +1146,8 @@ impl GenerateValue<String> for Generator {
       Generator::RandomBoolean => Ok(format!("{}", rnd.gen::<bool>())),
       Generator::ProviderStateGenerator(ref exp, ref dt) =>
         generate_value_from_context(exp, context, dt).map(|val| val.to_string()),
+      Generator::RequestValue(ref exp, ref dt) =>
+        generate_value_from_request(exp, context, dt).map(|val| val.to_string()),
       Generator::MockServerURL(example, regex) => if let Some(mock_server_details) = context.get("mockServer") {
         debug!("Generating URL from Mock Server details");
         match mock_server_details.as_object() {
@@ -1211,6 +1274,11 @@ impl GenerateValue<Value> for Generator {
           Ok(val) => val.as_json(),
           Err(err) => Err(err)
         },
+      Generator::RequestValue(ref exp, ref dt) =>
+        match generate_value_from_request(exp, context, dt) {
+          Ok(val) => val.as_json(),
+          Err(err) => Err(err)
+        },
       Generator::MockServerURL(example, regex) => {
         debug!("context = {:?}", context);
         if let Some(mock_server_details) = context.get("mockServer") {
@@ -1470,6 +1538,8 @@ mod tests {
     expect!(Generator::from_map("Uuid", &json!({ "format": "simple"}).as_object().unwrap())).to(be_some().value(Generator::Uuid(Some(UuidFormat::Simple))));
     expect!(Generator::from_map("Uuid", &json!({ "format": "other"}).as_object().unwrap())).to(be_some().value(Generator::Uuid(None)));
     expect!(Generator::from_map("RandomBoolean", &serde_json::Map::new())).to(be_some().value(Generator::RandomBoolean));
+    expect!(Generator::from_map("RequestValue", json!({ "expression": "headers.x-id" }).as_object().unwrap())).to(
+      be_some().value(Generator::RequestValue("headers.x-id".into(), None)));
   }
 
   #[test]
@@ -1921,7 +1991,7 @@ mod tests2 {
   use serde_json::{json, Value};
 
   use crate::expression_parser::DataType;
-  use crate::generators::generate_value_from_context;
+  use crate::generators::{generate_value_from_context, generate_value_from_request};
 
   #[rstest]
   //     expression, value,          data_type,               expected
@@ -1952,4 +2022,33 @@ mod tests2 {
     let result_value = result.unwrap();
     expect!(result_value.as_json().unwrap()).to(be_equal_to(expected));
   }
+
+  #[rstest]
+  //     expression,                        data_type,               expected
+  #[case("headers.x-correlation-id",        None,                    json!("abc-123"))]
+  #[case("headers.X-Correlation-Id",        None,                    json!("abc-123"))]
+  #[case("/items/${body.items.1.id}",       None,                    json!("/items/200"))]
+  #[case("body.items.0.id",                 Some(DataType::STRING),  json!("100"))]
+  #[case("query.page",                      Some(DataType::INTEGER), json!(2))]
+  fn generate_value_from_request_test(#[case] expression: &str, #[case] data_type: Option<DataType>, #[case] expected: Value) {
+    let context = hashmap!{
+      "request" => json!({
+        "method": "POST",
+        "path": "/items",
+        "query": { "page": "2" },
+        "headers": { "x-correlation-id": "abc-123" },
+        "body": { "items": [ { "id": 100 }, { "id": 200 } ] }
+      })
+    };
+    let result = generate_value_from_request(expression, &context, &data_type);
+    expect!(result.as_ref()).to(be_ok());
+    expect!(result.unwrap().as_json().unwrap()).to(be_equal_to(expected));
+  }
+
+  #[test]
+  fn generate_value_from_request_fails_if_the_value_is_not_in_the_request() {
+    let context = hashmap!{ "request" => json!({ "headers": {} }) };
+    expect!(generate_value_from_request("headers.x-correlation-id", &context, &None)).to(be_err());
+    expect!(generate_value_from_request("headers.x-correlation-id", &hashmap!{}, &None)).to(be_err());
+  }
 }
@@ -1,2 +1,4 @@
 pub static PARAMETERISED_HEADERS: [&str; 2] = ["accept", "content-type"];
-pub static SINGLE_VALUE_HEADERS: [&str; 1] = ["date"];
+/// Headers whose values can contain commas, so are compared as a whole instead of being split
+/// on commas. This is the default set used when matching headers.
+pub static SINGLE_VALUE_HEADERS: [&str; 2] = ["date", "set-cookie"];
@@ -1,8 +1,10 @@
 //! Module for fetching documents via HTTP
 
+use std::env;
 use std::fmt::{Display, Formatter};
 
 use anyhow::anyhow;
+use log::warn;
 use reqwest::blocking::Client;
 use reqwest::Error;
 use serde_json::Value;
@@ -14,11 +16,42 @@ pub enum HttpAuth {
   User(String, Option<String>),
   /// Bearer token
   Token(String),
+  /// Bearer token read from the named environment variable when the request is made
+  TokenFromEnv(String),
+  /// NTLM authentication with a username, password and optional domain
+  Ntlm {
+    /// Username
+    user: String,
+    /// Password
+    password: String,
+    /// Windows domain of the user
+    domain: Option<String>
+  },
   /// No authentication
   None
 }
 
 impl HttpAuth {
+  /// Creates the authentication from the standard Pact Broker environment variables. If
+  /// `PACT_BROKER_TOKEN` is set, bearer token authentication is used. Otherwise, if
+  /// `PACT_BROKER_USERNAME` is set, basic authentication is used with the optional
+  /// `PACT_BROKER_PASSWORD`. Returns `None` if neither is set (or they are empty).
+  pub fn from_env() -> Option<HttpAuth> {
+    HttpAuth::from_env_lookup(|name| env::var(name).ok())
+  }
+
+  /// Creates the authentication from the standard Pact Broker environment variables, the same as
+  /// `from_env`, but reads the variables with the given lookup function.
+  pub fn from_env_lookup<F: Fn(&str) -> Option<String>>(lookup: F) -> Option<HttpAuth> {
+    let var = |name: &str| lookup(name).filter(|value| !value.is_empty());
+    if let Some(token) = var("PACT_BROKER_TOKEN") {
+      Some(HttpAuth::Token(token))
+    } else {
+      var("PACT_BROKER_USERNAME")
+        .map(|username| HttpAuth::User(username, var("PACT_BROKER_PASSWORD")))
+    }
+  }
+
   /// If no authentication is set
   pub fn is_none(&self) -> bool {
     match self {
@@ -26,6 +59,22 @@ impl HttpAuth {
       _ => false
     }
   }
+
+  /// Returns the bearer token for token authentication. For `TokenFromEnv`, this is read from
+  /// the environment variable when this is called, and will be `None` if the variable is not set.
+  pub fn token(&self) -> Option<String> {
+    match self {
+      HttpAuth::Token(token) => Some(token.clone()),
+      HttpAuth::TokenFromEnv(name) => match env::var(name) {
+        Ok(token) => Some(token),
+        Err(err) => {
+          warn!("Could not read the bearer token from environment variable '{}' - {}", name, err);
+          None
+        }
+      },
+      _ => None
+    }
+  }
 }
 
 /// Fetches the JSON from a URL
@@ -35,7 +84,10 @@ pub fn fetch_json_from_url(url: &String, auth: &Option<HttpAuth>) -> anyhow::Res
     &Some(ref auth) => {
       match auth {
         &HttpAuth::User(ref username, ref password) => client.get(url).basic_auth(username.clone(), password.clone()),
-        &HttpAuth::Token(ref token) => client.get(url).bearer_auth(token.clone()),
+        &HttpAuth::Token(_) | &HttpAuth::TokenFromEnv(_) => match auth.token() {
+          Some(token) => client.get(url).bearer_auth(token),
+          None => client.get(url)
+        },
         _ => client.get(url)
       }
     },
@@ -61,6 +113,7 @@ impl Display for HttpAuth {
     match *self {
       HttpAuth::Token(ref t) =>
         write!(f, "Token({:*<width$})", t.get(0..4).unwrap_or(""), width = t.len()),
+      HttpAuth::TokenFromEnv(ref name) => write!(f, "TokenFromEnv({})", name),
       HttpAuth::User(ref u, ref p) => {
         let username = if u.is_empty() { "none" } else { u.as_str() };
         if let Some(pass) = p {
@@ -69,6 +122,13 @@ impl Display for HttpAuth {
           write!(f, "User({}, [no password])", username)
         }
       }
+      HttpAuth::Ntlm { ref user, ref password, ref domain } => {
+        let username = match domain {
+          Some(domain) => format!("{}\\{}", domain, user),
+          None => user.clone()
+        };
+        write!(f, "Ntlm({}, {:*<width$})", username, password.get(0..4).unwrap_or(""), width = password.len())
+      }
       _ => write!(f, "None")
     }
   }
@@ -79,3 +139,30 @@ impl Default for HttpAuth {
     HttpAuth::User("".to_string(), None)
   }
 }
+
+#[cfg(test)]
+mod tests {
+  use expectest::prelude::*;
+
+  use super::HttpAuth;
+
+  fn from_env(token: Option<&str>, username: Option<&str>, password: Option<&str>) -> Option<HttpAuth> {
+    HttpAuth::from_env_lookup(|name| match name {
+      "PACT_BROKER_TOKEN" => token,
+      "PACT_BROKER_USERNAME" => username,
+      "PACT_BROKER_PASSWORD" => password,
+      _ => None
+    }.map(|value| value.to_string()))
+  }
+
+  #[test]
+  fn http_auth_from_env_test() {
+    expect!(from_env(None, None, None)).to(be_none());
+    expect!(matches!(from_env(Some("abc123"), None, None), Some(HttpAuth::Token(token)) if token == "abc123")).to(be_true());
+    expect!(matches!(from_env(Some("abc123"), Some("user"), Some("pass")), Some(HttpAuth::Token(token)) if token == "abc123")).to(be_true());
+    expect!(matches!(from_env(None, Some("user"), Some("pass")), Some(HttpAuth::User(user, Some(password)))
+      if user == "user" && password == "pass")).to(be_true());
+    expect!(matches!(from_env(None, Some("user"), None), Some(HttpAuth::User(user, None)) if user == "user")).to(be_true());
+    expect!(from_env(Some(""), None, Some("pass"))).to(be_none());
+  }
+}
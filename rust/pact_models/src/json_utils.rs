@@ -104,7 +104,12 @@ pub fn get_field_as_string(field: &str, map: &Map<String, Value>) -> Option<Stri
 
 /// Returns the headers from a JSON struct as Map String -> Vec<String>
 pub fn headers_from_json(request: &Value) -> Option<HashMap<String, Vec<String>>> {
-  match request.get("headers") {
+  headers_from_json_field(request, "headers")
+}
+
+/// Converts the headers stored in the given field of the JSON (i.e. `trailers`) into a map
+pub fn headers_from_json_field(json: &Value, field: &str) -> Option<HashMap<String, Vec<String>>> {
+  match json.get(field) {
     Some(Value::Object(m)) => {
       Some(m.iter().map(|(key, val)| {
         match val {
@@ -23,6 +23,21 @@ use crate::path_exp::DocPath;
 
 pub mod expressions;
 
+/// Type names that can be used with the `AnyType` matching rule
+pub const ANY_TYPE_NAMES: [&str; 8] = ["string", "number", "integer", "decimal", "boolean", "null",
+  "array", "object"];
+
+fn any_type_names_from_json(types: &Value) -> anyhow::Result<Vec<String>> {
+  match types {
+    Value::Array(types) => types.iter().map(|name| match name {
+      Value::String(name) if ANY_TYPE_NAMES.contains(&name.as_str()) => Ok(name.clone()),
+      _ => Err(anyhow!("AnyType matcher type '{}' is not recognised, it must be one of {}", json_to_string(name),
+        ANY_TYPE_NAMES.join(", ")))
+    }).collect(),
+    _ => Err(anyhow!("AnyType matcher 'types' field is not an array - {}", types))
+  }
+}
+
 fn generator_from_json(json: &Map<String, Value>) -> Option<Generator> {
   if let Some(generator_json) = json.get("generator") {
     match generator_json {
@@ -60,7 +75,7 @@ fn rules_from_json(attributes: &Map<String, Value>) -> anyhow::Result<Vec<Either
 }
 
 /// Set of all matching rules
-#[derive(Debug, Clone, Eq)]
+#[derive(Debug, Clone)]
 pub enum MatchingRule {
   /// Matcher using equals
   Equality,
@@ -104,6 +119,63 @@ pub enum MatchingRule {
   NotEmpty,
   /// Value must a semantic version
   Semver,
+  /// Value must be an ISO-8601 duration
+  Duration,
+  /// Value must be an email address
+  Email,
+  /// Value must be a base64 encoded string
+  Base64,
+  /// Value must conform to the JSON Schema
+  JsonSchema(Value),
+  /// Array items must be unique, optionally only comparing the values at the key path expression
+  /// (i.e. `$.id`) for each item
+  UniqueItems(Option<String>),
+  /// Array must have exactly this number of items. The items themselves are not compared.
+  ArrayLength(usize),
+  /// Value must be equal to the value at the given path expression (i.e. `$.password`) in the same
+  /// document
+  EqualsPath(String),
+  /// Value must match the regular expression registered under the given name
+  NamedRegex(String),
+  /// Value must be any one of the types (i.e. `string` or `number`) in the list. See
+  /// `ANY_TYPE_NAMES` for the type names that can be used.
+  AnyType(Vec<String>),
+  /// Value must be one of the values in the set
+  OneOf(Vec<Value>),
+  /// Value must be a string containing valid JSON
+  ValidJson,
+  /// Value must be a string containing an RFC-3339 (ISO-8601) timestamp, i.e. `2023-01-01T12:00:00Z`
+  Iso8601Timestamp,
+  /// Value must be a number (or string) whose digits pass the Luhn checksum, i.e. a credit card
+  /// number. Any non-digit characters (like spaces or dashes) are ignored.
+  Luhn,
+  /// Value must be an IP address. If a version is given (4 or 6), only addresses of that version
+  /// will match.
+  IpAddress(Option<u8>),
+  /// Value must be a string of hexadecimal digits, i.e. a hash. If a length is given, the value
+  /// must have exactly that many digits.
+  Hexadecimal {
+    /// Number of hexadecimal digits the value must have
+    length: Option<usize>
+  },
+  /// Value must be a latitude (a number between -90 and 90)
+  Latitude,
+  /// Value must be a longitude (a number between -180 and 180)
+  Longitude,
+  /// Value must be a number within the range. If inclusive, the value can be equal to the bounds.
+  NumberRange {
+    /// Lower bound of the range
+    min: f64,
+    /// Upper bound of the range
+    max: f64,
+    /// If the bounds are included in the range
+    inclusive: bool
+  },
+  /// Value must be an RFC-3339 timestamp within the given number of seconds of the current time
+  RecentTimestamp {
+    /// Number of seconds the timestamp can be before the current time
+    within_seconds: i64
+  },
   /// Matcher for keys in a map
   EachKey(MatchingRuleDefinition),
   /// Matcher for values in a collection. This delegates to the Values matcher for maps.
@@ -195,6 +267,42 @@ impl MatchingRule {
       MatchingRule::StatusCode(status) => json!({ "match": "statusCode", "status": status.to_json() }),
       MatchingRule::NotEmpty => json!({ "match": "notEmpty" }),
       MatchingRule::Semver => json!({ "match": "semver" }),
+      MatchingRule::Duration => json!({ "match": "duration" }),
+      MatchingRule::Email => json!({ "match": "email" }),
+      MatchingRule::Base64 => json!({ "match": "base64" }),
+      MatchingRule::JsonSchema(schema) => json!({ "match": "jsonSchema", "schema": schema }),
+      MatchingRule::UniqueItems(key) => match key {
+        Some(key) => json!({ "match": "uniqueItems", "key": key }),
+        None => json!({ "match": "uniqueItems" })
+      },
+      MatchingRule::ArrayLength(length) => json!({ "match": "arrayLength", "length": length }),
+      MatchingRule::EqualsPath(path) => json!({ "match": "equalsPath", "path": path }),
+      MatchingRule::NamedRegex(name) => json!({ "match": "namedRegex", "name": name }),
+      MatchingRule::AnyType(types) => json!({ "match": "anyType", "types": types }),
+      MatchingRule::OneOf(values) => json!({ "match": "oneOf", "values": values }),
+      MatchingRule::ValidJson => json!({ "match": "validJson" }),
+      MatchingRule::Iso8601Timestamp => json!({ "match": "iso8601Timestamp" }),
+      MatchingRule::Luhn => json!({ "match": "luhn" }),
+      MatchingRule::IpAddress(version) => match version {
+        Some(version) => json!({ "match": "ipAddress", "version": version }),
+        None => json!({ "match": "ipAddress" })
+      },
+      MatchingRule::Hexadecimal { length } => match length {
+        Some(length) => json!({ "match": "hexadecimal", "length": length }),
+        None => json!({ "match": "hexadecimal" })
+      },
+      MatchingRule::Latitude => json!({ "match": "latitude" }),
+      MatchingRule::Longitude => json!({ "match": "longitude" }),
+      MatchingRule::NumberRange { min, max, inclusive } => json!({
+        "match": "numberRange",
+        "min": min,
+        "max": max,
+        "inclusive": inclusive
+      }),
+      MatchingRule::RecentTimestamp { within_seconds } => json!({
+        "match": "recentTimestamp",
+        "withinSeconds": within_seconds
+      }),
       MatchingRule::EachKey(definition) => {
         let mut json = json!({
           "match": "eachKey",
@@ -277,6 +385,25 @@ impl MatchingRule {
       MatchingRule::StatusCode(_) => "status-code",
       MatchingRule::NotEmpty => "not-empty",
       MatchingRule::Semver => "semver",
+      MatchingRule::Duration => "duration",
+      MatchingRule::Email => "email",
+      MatchingRule::Base64 => "base64",
+      MatchingRule::JsonSchema(_) => "json-schema",
+      MatchingRule::UniqueItems(_) => "unique-items",
+      MatchingRule::ArrayLength(_) => "array-length",
+      MatchingRule::EqualsPath(_) => "equals-path",
+      MatchingRule::NamedRegex(_) => "named-regex",
+      MatchingRule::AnyType(_) => "any-type",
+      MatchingRule::OneOf(_) => "one-of",
+      MatchingRule::ValidJson => "valid-json",
+      MatchingRule::Iso8601Timestamp => "iso8601-timestamp",
+      MatchingRule::Luhn => "luhn",
+      MatchingRule::IpAddress(_) => "ip-address",
+      MatchingRule::Hexadecimal { .. } => "hexadecimal",
+      MatchingRule::Latitude => "latitude",
+      MatchingRule::Longitude => "longitude",
+      MatchingRule::NumberRange { .. } => "number-range",
+      MatchingRule::RecentTimestamp { .. } => "recent-timestamp",
       MatchingRule::EachKey(_) => "each-key",
       MatchingRule::EachValue(_) => "each-value"
     }.to_string()
@@ -313,6 +440,38 @@ impl MatchingRule {
       MatchingRule::StatusCode(sc) => hashmap!{ "status" => sc.to_json() },
       MatchingRule::NotEmpty => empty,
       MatchingRule::Semver => empty,
+      MatchingRule::Duration => empty,
+      MatchingRule::Email => empty,
+      MatchingRule::Base64 => empty,
+      MatchingRule::JsonSchema(schema) => hashmap!{ "schema" => schema.clone() },
+      MatchingRule::UniqueItems(key) => match key {
+        Some(key) => hashmap!{ "key" => Value::String(key.clone()) },
+        None => empty
+      },
+      MatchingRule::ArrayLength(length) => hashmap!{ "length" => json!(length) },
+      MatchingRule::EqualsPath(path) => hashmap!{ "path" => Value::String(path.clone()) },
+      MatchingRule::NamedRegex(name) => hashmap!{ "name" => Value::String(name.clone()) },
+      MatchingRule::AnyType(types) => hashmap!{ "types" => json!(types) },
+      MatchingRule::OneOf(values) => hashmap!{ "values" => Value::Array(values.clone()) },
+      MatchingRule::ValidJson => empty,
+      MatchingRule::Iso8601Timestamp => empty,
+      MatchingRule::Luhn => empty,
+      MatchingRule::IpAddress(version) => match version {
+        Some(version) => hashmap!{ "version" => json!(version) },
+        None => empty
+      },
+      MatchingRule::Hexadecimal { length } => match length {
+        Some(length) => hashmap!{ "length" => json!(length) },
+        None => empty
+      },
+      MatchingRule::Latitude => empty,
+      MatchingRule::Longitude => empty,
+      MatchingRule::NumberRange { min, max, inclusive } => hashmap!{
+        "min" => json!(min),
+        "max" => json!(max),
+        "inclusive" => Value::Bool(*inclusive)
+      },
+      MatchingRule::RecentTimestamp { within_seconds } => hashmap!{ "withinSeconds" => json!(within_seconds) },
       MatchingRule::EachKey(definition) | MatchingRule::EachValue(definition) => {
         let mut map = hashmap! {
           "rules" => Value::Array(definition.rules.iter()
@@ -439,6 +598,83 @@ impl MatchingRule {
       },
       "notEmpty" | "not-empty" => Ok(MatchingRule::NotEmpty),
       "semver" => Ok(MatchingRule::Semver),
+      "duration" => Ok(MatchingRule::Duration),
+      "email" => Ok(MatchingRule::Email),
+      "base64" => Ok(MatchingRule::Base64),
+      "jsonSchema" | "json-schema" => match attributes.get("schema") {
+        Some(schema @ Value::Object(_)) | Some(schema @ Value::Bool(_)) => Ok(MatchingRule::JsonSchema(schema.clone())),
+        Some(_) => Err(anyhow!("JsonSchema matcher 'schema' field is not a JSON Schema")),
+        None => Err(anyhow!("JsonSchema matcher missing 'schema' field")),
+      },
+      "uniqueItems" | "unique-items" => match attributes.get("key") {
+        Some(Value::String(key)) => Ok(MatchingRule::UniqueItems(Some(key.clone()))),
+        Some(key) => Err(anyhow!("UniqueItems matcher 'key' field is not a string - {}", key)),
+        None => Ok(MatchingRule::UniqueItems(None))
+      },
+      "arrayLength" | "array-length" => match attributes.get("length") {
+        Some(length) => match length.as_u64() {
+          Some(length) => Ok(MatchingRule::ArrayLength(length as usize)),
+          None => Err(anyhow!("ArrayLength matcher 'length' field is not a positive integer - {}", length))
+        },
+        None => Err(anyhow!("ArrayLength matcher missing 'length' field"))
+      },
+      "equalsPath" | "equals-path" => match attributes.get("path") {
+        Some(Value::String(path)) => Ok(MatchingRule::EqualsPath(path.clone())),
+        Some(path) => Err(anyhow!("EqualsPath matcher 'path' field is not a string - {}", path)),
+        None => Err(anyhow!("EqualsPath matcher missing 'path' field"))
+      },
+      "namedRegex" | "named-regex" => match attributes.get("name") {
+        Some(Value::String(name)) => Ok(MatchingRule::NamedRegex(name.clone())),
+        Some(name) => Err(anyhow!("NamedRegex matcher 'name' field is not a string - {}", name)),
+        None => Err(anyhow!("NamedRegex matcher missing 'name' field"))
+      },
+      "anyType" | "any-type" => match attributes.get("types") {
+        Some(types) => Ok(MatchingRule::AnyType(any_type_names_from_json(types)?)),
+        None => Err(anyhow!("AnyType matcher missing 'types' field"))
+      },
+      "oneOf" | "one-of" => match attributes.get("values") {
+        Some(Value::Array(values)) => Ok(MatchingRule::OneOf(values.clone())),
+        Some(values) => Err(anyhow!("OneOf matcher 'values' field is not an array - {}", values)),
+        None => Err(anyhow!("OneOf matcher missing 'values' field"))
+      },
+      "validJson" | "valid-json" => Ok(MatchingRule::ValidJson),
+      "iso8601Timestamp" | "iso8601-timestamp" => Ok(MatchingRule::Iso8601Timestamp),
+      "luhn" => Ok(MatchingRule::Luhn),
+      "ipAddress" | "ip-address" => match attributes.get("version") {
+        Some(version) => match version.as_u64() {
+          Some(4) => Ok(MatchingRule::IpAddress(Some(4))),
+          Some(6) => Ok(MatchingRule::IpAddress(Some(6))),
+          _ => Err(anyhow!("IpAddress matcher 'version' field must be either 4 or 6 - {}", version))
+        },
+        None => Ok(MatchingRule::IpAddress(None))
+      },
+      "hexadecimal" => match attributes.get("length") {
+        Some(length) => match length.as_u64() {
+          Some(length) => Ok(MatchingRule::Hexadecimal { length: Some(length as usize) }),
+          None => Err(anyhow!("Hexadecimal matcher 'length' field is not a positive integer - {}", length))
+        },
+        None => Ok(MatchingRule::Hexadecimal { length: None })
+      },
+      "latitude" => Ok(MatchingRule::Latitude),
+      "longitude" => Ok(MatchingRule::Longitude),
+      "numberRange" | "number-range" => {
+        let min = attributes.get("min").and_then(|min| min.as_f64())
+          .ok_or_else(|| anyhow!("NumberRange matcher 'min' field is missing or not a number"))?;
+        let max = attributes.get("max").and_then(|max| max.as_f64())
+          .ok_or_else(|| anyhow!("NumberRange matcher 'max' field is missing or not a number"))?;
+        if min > max {
+          return Err(anyhow!("NumberRange matcher 'min' ({}) is greater than 'max' ({})", min, max));
+        }
+        let inclusive = attributes.get("inclusive").and_then(|inclusive| inclusive.as_bool()).unwrap_or(true);
+        Ok(MatchingRule::NumberRange { min, max, inclusive })
+      },
+      "recentTimestamp" | "recent-timestamp" => match attributes.get("withinSeconds") {
+        Some(within_seconds) => match within_seconds.as_i64() {
+          Some(within_seconds) if within_seconds >= 0 => Ok(MatchingRule::RecentTimestamp { within_seconds }),
+          _ => Err(anyhow!("RecentTimestamp matcher 'withinSeconds' field is not a positive integer - {}", within_seconds))
+        },
+        None => Err(anyhow!("RecentTimestamp matcher missing 'withinSeconds' field"))
+      },
       "eachKey" | "each-key" => {
         let generator = generator_from_json(&attributes);
         let value = attributes.get("value").cloned().unwrap_or_default();
@@ -482,6 +718,9 @@ impl MatchingRule {
       MatchingRule::Values => false,
       MatchingRule::EachValue(_) => false,
       MatchingRule::EachKey(_) => false,
+      MatchingRule::UniqueItems(_) => false,
+      MatchingRule::ArrayLength(_) => false,
+      MatchingRule::EqualsPath(_) => false,
       _ => true
     }
   }
@@ -503,6 +742,23 @@ impl Hash for MatchingRule {
       MatchingRule::Date(format) => format.hash(state),
       MatchingRule::Include(str) => str.hash(state),
       MatchingRule::ContentType(str) => str.hash(state),
+      MatchingRule::JsonSchema(schema) => schema.to_string().hash(state),
+      MatchingRule::UniqueItems(key) => key.hash(state),
+      MatchingRule::ArrayLength(length) => length.hash(state),
+      MatchingRule::EqualsPath(path) => path.hash(state),
+      MatchingRule::NamedRegex(name) => name.hash(state),
+      MatchingRule::AnyType(types) => types.hash(state),
+      MatchingRule::IpAddress(version) => version.hash(state),
+      MatchingRule::Hexadecimal { length } => length.hash(state),
+      MatchingRule::OneOf(values) => for value in values {
+        value.to_string().hash(state);
+      },
+      MatchingRule::NumberRange { min, max, inclusive } => {
+        min.to_bits().hash(state);
+        max.to_bits().hash(state);
+        inclusive.hash(state);
+      }
+      MatchingRule::RecentTimestamp { within_seconds } => within_seconds.hash(state),
       MatchingRule::ArrayContains(variants) => {
         for (index, rules, generators) in variants {
           index.hash(state);
@@ -530,12 +786,28 @@ impl PartialEq for MatchingRule {
       (MatchingRule::Date(format1), MatchingRule::Date(format2)) => format1 == format2,
       (MatchingRule::Include(str1), MatchingRule::Include(str2)) => str1 == str2,
       (MatchingRule::ContentType(str1), MatchingRule::ContentType(str2)) => str1 == str2,
+      (MatchingRule::JsonSchema(schema1), MatchingRule::JsonSchema(schema2)) => schema1 == schema2,
+      (MatchingRule::UniqueItems(key1), MatchingRule::UniqueItems(key2)) => key1 == key2,
+      (MatchingRule::ArrayLength(length1), MatchingRule::ArrayLength(length2)) => length1 == length2,
+      (MatchingRule::EqualsPath(path1), MatchingRule::EqualsPath(path2)) => path1 == path2,
+      (MatchingRule::NamedRegex(name1), MatchingRule::NamedRegex(name2)) => name1 == name2,
+      (MatchingRule::AnyType(types1), MatchingRule::AnyType(types2)) => types1 == types2,
+      (MatchingRule::OneOf(values1), MatchingRule::OneOf(values2)) => values1 == values2,
+      (MatchingRule::IpAddress(version1), MatchingRule::IpAddress(version2)) => version1 == version2,
+      (MatchingRule::Hexadecimal { length: length1 }, MatchingRule::Hexadecimal { length: length2 }) => length1 == length2,
+      (MatchingRule::NumberRange { min: min1, max: max1, inclusive: inclusive1 },
+        MatchingRule::NumberRange { min: min2, max: max2, inclusive: inclusive2 }) =>
+        min1.to_bits() == min2.to_bits() && max1.to_bits() == max2.to_bits() && inclusive1 == inclusive2,
+      (MatchingRule::RecentTimestamp { within_seconds: within1 }, MatchingRule::RecentTimestamp { within_seconds: within2 }) =>
+        within1 == within2,
       (MatchingRule::ArrayContains(variants1), MatchingRule::ArrayContains(variants2)) => variants1 == variants2,
       _ => mem::discriminant(self) == mem::discriminant(other)
     }
   }
 }
 
+impl Eq for MatchingRule {}
+
 #[cfg(test)]
 fn h(rule: &MatchingRule) -> u64 {
   let mut hasher = DefaultHasher::new();
@@ -749,6 +1021,16 @@ fn hash_and_partial_eq_for_matching_rule() {
   expect!(&ac7).to_not(be_equal_to(&ac5));
   expect!(&ac7).to_not(be_equal_to(&ac6));
   expect!(&ac7).to_not(be_equal_to(&ac1));
+
+  let range = MatchingRule::NumberRange { min: 0.0, max: 10.0, inclusive: true };
+  let negative_zero_range = MatchingRule::NumberRange { min: -0.0, max: 10.0, inclusive: true };
+  let nan_range = MatchingRule::NumberRange { min: f64::NAN, max: 10.0, inclusive: true };
+  expect!(h(&range)).to(be_equal_to(h(&range.clone())));
+  expect!(&range).to(be_equal_to(&range.clone()));
+  expect!(&range).to_not(be_equal_to(&negative_zero_range));
+  expect!(h(&range)).to_not(be_equal_to(h(&negative_zero_range)));
+  expect!(&nan_range).to(be_equal_to(&nan_range.clone()));
+  expect!(h(&nan_range)).to(be_equal_to(h(&nan_range.clone())));
 }
 
 /// Enumeration to define how to combine rules
@@ -1054,7 +1336,11 @@ impl MatchingRuleCategory {
   fn max_by_path(&self, path: &[&str]) -> RuleList {
     self.rules.iter().map(|(k, v)| (k, v, k.path_weight(path)))
       .filter(|&(_, _, (w, _))| w > 0)
-      .max_by_key(|&(_, _, (w, t))| w * t)
+      .max_by(|&(k1, _, (w1, t1)), &(k2, _, (w2, t2))| {
+        (w1 * t1).cmp(&(w2 * t2))
+          .then(t1.cmp(&t2))
+          .then_with(|| String::from(k2).cmp(&String::from(k1)))
+      })
       .map(|(_, v, (_, t))| v.as_cascaded(t != path.len()))
       .unwrap_or_default()
   }
@@ -1072,8 +1358,12 @@ impl MatchingRuleCategory {
     let mut map = hashmap!{};
 
     match &self.name {
-      Category::PATH => for (_, v) in self.rules.clone() {
-        map.insert("$.path".to_string(), v.to_v2_json());
+      Category::PATH => for (k, v) in self.rules.clone() {
+        if k.len() == 0 {
+          map.insert("$.path".to_string(), v.to_v2_json());
+        } else {
+          map.insert(String::from(k).replace("$", "$.path"), v.to_v2_json());
+        }
       }
       Category::BODY => for (k, v) in self.rules.clone() {
         map.insert(String::from(k).replace("$", "$.body"), v.to_v2_json());
@@ -1111,11 +1401,16 @@ impl MatchingRuleCategory {
       Category::CONTENTS | Category::METADATA => self.filter(|(val, _)| {
         val.matches_path(path)
       }),
+      Category::PATH => self.filter(|(val, _)| {
+        val.to_vec().iter().map(|token| token.as_str()).eq(path.iter().copied())
+      }),
       _ => self.clone()
     }
   }
 
-  /// Selects the best matcher for the given path by calculating a weighting for each one
+  /// Selects the best matcher for the given path by calculating a weighting for each one.
+  /// If more than one matcher has the same weighting, the one with the most path segments
+  /// is selected, and then the one whose path expression is first in lexical order.
   pub fn select_best_matcher(&self, path: &[&str]) -> RuleList {
     match self.name {
       Category::BODY | Category::METADATA => self.max_by_path(path),
@@ -1354,6 +1649,8 @@ impl MatchingRules {
         } else {
           self.add_v2_rule("body", DocPath::new(format!("${}", &key[6..]))?, v)?;
         }
+      } else if key.starts_with("$.path[") {
+        self.add_v2_rule("path", DocPath::new(format!("${}", &key[6..]))?, v)?;
       } else if key.starts_with("$.headers") {
         self.add_v2_rule("header", DocPath::new(path[2])?, v)?;
       } else {
@@ -1394,8 +1691,10 @@ impl MatchingRules {
   fn to_v3_json(&self) -> Value {
     Value::Object(self.rules.iter().fold(serde_json::Map::new(), |mut map, (name, sub_category)| {
       match name {
-        Category::PATH => if let Some(rules) = sub_category.rules.get(&DocPath::empty()) {
-          map.insert(name.to_string(), rules.to_v3_json());
+        Category::PATH if sub_category.rules.keys().all(|path| path.len() == 0) => {
+          if let Some(rules) = sub_category.rules.get(&DocPath::empty()) {
+            map.insert(name.to_string(), rules.to_v3_json());
+          }
         }
         _ => {
           map.insert(name.to_string(), sub_category.to_v3_json());
@@ -1620,6 +1919,26 @@ mod tests {
     expect!(matching_rules.rules.iter()).to(be_empty());
   }
 
+  #[test]
+  fn loads_v2_path_segment_matching_rules() {
+    let matching_rules_json = Value::from_str(r#"{"matchingRules": {
+      "$.path[1]": { "match": "type" }
+    }}"#).unwrap();
+
+    let matching_rules = matchers_from_json(&matching_rules_json, &None).unwrap();
+
+    expect!(matching_rules.rules_for_category("path")).to(be_some().value(MatchingRuleCategory {
+      name: "path".into(),
+      rules: hashmap! { DocPath::new_unwrap("$[1]") => RuleList { rules: vec![ MatchingRule::Type ], rule_logic: RuleLogic::And, cascaded: false } }
+    }));
+    expect!(matching_rules.to_v2_json()).to(be_equal_to(json!({
+      "$.path[1]": { "match": "type" }
+    })));
+    expect!(matching_rules.to_v3_json()).to(be_equal_to(json!({
+      "path": { "$[1]": { "combine": "AND", "matchers": [ { "match": "type" } ] } }
+    })));
+  }
+
   #[test]
   fn loads_v2_matching_rules() {
     let matching_rules_json = Value::from_str(r#"{"matchingRules": {
@@ -1832,6 +2151,67 @@ mod tests {
       be_ok().value(MatchingRule::Include("A".to_string())));
     expect!(MatchingRule::from_json(&Value::from_str("{\"match\": \"include\"}").unwrap())).to(be_err());
 
+    expect!(MatchingRule::from_json(&json!({ "match": "jsonSchema", "schema": { "type": "object" } }))).to(
+      be_ok().value(MatchingRule::JsonSchema(json!({ "type": "object" }))));
+    expect!(MatchingRule::from_json(&json!({ "match": "jsonSchema", "schema": "object" }))).to(be_err());
+    expect!(MatchingRule::from_json(&json!({ "match": "jsonSchema" }))).to(be_err());
+
+    expect!(MatchingRule::from_json(&json!({ "match": "uniqueItems" }))).to(
+      be_ok().value(MatchingRule::UniqueItems(None)));
+    expect!(MatchingRule::from_json(&json!({ "match": "uniqueItems", "key": "$.id" }))).to(
+      be_ok().value(MatchingRule::UniqueItems(Some("$.id".to_string()))));
+    expect!(MatchingRule::from_json(&json!({ "match": "uniqueItems", "key": 1 }))).to(be_err());
+    expect!(MatchingRule::from_json(&json!({ "match": "arrayLength", "length": 3 }))).to(
+      be_ok().value(MatchingRule::ArrayLength(3)));
+    expect!(MatchingRule::from_json(&json!({ "match": "arrayLength" }))).to(be_err());
+    expect!(MatchingRule::from_json(&json!({ "match": "arrayLength", "length": -1 }))).to(be_err());
+    expect!(MatchingRule::from_json(&json!({ "match": "equalsPath", "path": "$.password" }))).to(
+      be_ok().value(MatchingRule::EqualsPath("$.password".to_string())));
+    expect!(MatchingRule::from_json(&json!({ "match": "equalsPath" }))).to(be_err());
+    expect!(MatchingRule::from_json(&json!({ "match": "namedRegex", "name": "postcode" }))).to(
+      be_ok().value(MatchingRule::NamedRegex("postcode".to_string())));
+    expect!(MatchingRule::from_json(&json!({ "match": "namedRegex" }))).to(be_err());
+    expect!(MatchingRule::from_json(&json!({ "match": "anyType", "types": ["string", "number"] }))).to(
+      be_ok().value(MatchingRule::AnyType(vec!["string".to_string(), "number".to_string()])));
+    expect!(MatchingRule::from_json(&json!({ "match": "anyType", "types": ["string", "date"] }))).to(be_err());
+    expect!(MatchingRule::from_json(&json!({ "match": "anyType", "types": "string" }))).to(be_err());
+    expect!(MatchingRule::from_json(&json!({ "match": "anyType" }))).to(be_err());
+
+    expect!(MatchingRule::from_json(&json!({ "match": "oneOf", "values": ["a", 1] }))).to(
+      be_ok().value(MatchingRule::OneOf(vec![json!("a"), json!(1)])));
+    expect!(MatchingRule::from_json(&json!({ "match": "oneOf", "values": "a" }))).to(be_err());
+    expect!(MatchingRule::from_json(&json!({ "match": "oneOf" }))).to(be_err());
+    expect!(MatchingRule::from_json(&json!({ "match": "validJson" }))).to(
+      be_ok().value(MatchingRule::ValidJson));
+    expect!(MatchingRule::from_json(&json!({ "match": "iso8601Timestamp" }))).to(
+      be_ok().value(MatchingRule::Iso8601Timestamp));
+    expect!(MatchingRule::from_json(&json!({ "match": "luhn" }))).to(
+      be_ok().value(MatchingRule::Luhn));
+    expect!(MatchingRule::from_json(&json!({ "match": "ipAddress" }))).to(
+      be_ok().value(MatchingRule::IpAddress(None)));
+    expect!(MatchingRule::from_json(&json!({ "match": "ipAddress", "version": 6 }))).to(
+      be_ok().value(MatchingRule::IpAddress(Some(6))));
+    expect!(MatchingRule::from_json(&json!({ "match": "ipAddress", "version": 5 }))).to(be_err());
+    expect!(MatchingRule::from_json(&json!({ "match": "hexadecimal" }))).to(
+      be_ok().value(MatchingRule::Hexadecimal { length: None }));
+    expect!(MatchingRule::from_json(&json!({ "match": "hexadecimal", "length": 40 }))).to(
+      be_ok().value(MatchingRule::Hexadecimal { length: Some(40) }));
+    expect!(MatchingRule::from_json(&json!({ "match": "hexadecimal", "length": "40" }))).to(be_err());
+    expect!(MatchingRule::from_json(&json!({ "match": "latitude" }))).to(
+      be_ok().value(MatchingRule::Latitude));
+    expect!(MatchingRule::from_json(&json!({ "match": "longitude" }))).to(
+      be_ok().value(MatchingRule::Longitude));
+    expect!(MatchingRule::from_json(&json!({ "match": "numberRange", "min": 0, "max": 1000.5, "inclusive": false }))).to(
+      be_ok().value(MatchingRule::NumberRange { min: 0.0, max: 1000.5, inclusive: false }));
+    expect!(MatchingRule::from_json(&json!({ "match": "numberRange", "min": 0, "max": 10 }))).to(
+      be_ok().value(MatchingRule::NumberRange { min: 0.0, max: 10.0, inclusive: true }));
+    expect!(MatchingRule::from_json(&json!({ "match": "numberRange", "min": 0 }))).to(be_err());
+    expect!(MatchingRule::from_json(&json!({ "match": "numberRange", "min": 10, "max": 0 }))).to(be_err());
+    expect!(MatchingRule::from_json(&json!({ "match": "recentTimestamp", "withinSeconds": 60 }))).to(
+      be_ok().value(MatchingRule::RecentTimestamp { within_seconds: 60 }));
+    expect!(MatchingRule::from_json(&json!({ "match": "recentTimestamp", "withinSeconds": -1 }))).to(be_err());
+    expect!(MatchingRule::from_json(&json!({ "match": "recentTimestamp" }))).to(be_err());
+
     expect!(MatchingRule::from_json(&Value::from_str("{\"match\": \"type\", \"min\": 1}").unwrap())).to(
       be_ok().value(MatchingRule::MinType(1)));
     expect!(MatchingRule::from_json(&Value::from_str("{\"match\": \"type\", \"max\": \"1\"}").unwrap())).to(
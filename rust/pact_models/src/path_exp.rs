@@ -592,6 +592,16 @@ mod tests {
     expect!(DocPath::new_unwrap("$.name.other").path_weight(&vec!["$", "name"]).0 > 0).to(be_false());
   }
 
+  #[test]
+  fn matches_path_distinguishes_attributes_from_fields_with_the_same_name() {
+    expect!(DocPath::new_unwrap("$.root['@id']").path_weight(&vec!["$", "root", "@id"]).0 > 0).to(be_true());
+    expect!(DocPath::new_unwrap("$.root.@id").path_weight(&vec!["$", "root", "@id"]).0 > 0).to(be_true());
+    expect!(DocPath::new_unwrap("$.root['@id']").path_weight(&vec!["$", "root", "id"]).0 > 0).to(be_false());
+    expect!(DocPath::new_unwrap("$.root.id").path_weight(&vec!["$", "root", "@id"]).0 > 0).to(be_false());
+    expect!(DocPath::new_unwrap("$.root.id").path_weight(&vec!["$", "root", "id", "#text"]).0 > 0).to(be_true());
+    expect!(DocPath::new_unwrap("$.root['@id']").path_weight(&vec!["$", "root", "id", "#text"]).0 > 0).to(be_false());
+  }
+
   #[test]
   fn matches_path_matches_array_indices() {
     expect!(DocPath::new_unwrap("$[0]").path_weight(&vec!["$", "0"]).0 > 0).to(be_true());
@@ -15,7 +15,7 @@ use crate::bodies::OptionalBody;
 use crate::content_types::{ContentType, ContentTypeHint, detect_content_type_from_bytes};
 use crate::generators::{Generators, generators_from_json, generators_to_json};
 use crate::http_parts::HttpPart;
-use crate::json_utils::{headers_from_json, json_to_string};
+use crate::json_utils::{headers_from_json, headers_from_json_field, json_to_string};
 use crate::matchingrules::{matchers_from_json, matchers_to_json, MatchingRules};
 use crate::PactSpecification;
 use crate::query_strings::{query_to_json, v3_query_from_json};
@@ -347,7 +347,9 @@ pub struct HttpResponse {
   /// Response matching rules
   pub matching_rules: MatchingRules,
   /// Response generators
-  pub generators: Generators
+  pub generators: Generators,
+  /// Trailing headers that are sent after the response body
+  pub trailers: Option<HashMap<String, Vec<String>>>
 }
 
 impl Display for HttpResponse {
@@ -364,7 +366,8 @@ impl Default for HttpResponse {
       headers: None,
       body: OptionalBody::Missing,
       matching_rules: MatchingRules::default(),
-      generators: Generators::default()
+      generators: Generators::default(),
+      trailers: None
     }
   }
 }
@@ -372,7 +375,8 @@ impl Default for HttpResponse {
 impl PartialEq for HttpResponse {
   fn eq(&self, other: &Self) -> bool {
     self.status == other.status && self.headers == other.headers && self.body == other.body &&
-      self.matching_rules == other.matching_rules && self.generators == other.generators
+      self.matching_rules == other.matching_rules && self.generators == other.generators &&
+      self.trailers == other.trailers
   }
 }
 
@@ -390,6 +394,13 @@ impl Hash for HttpResponse {
     self.body.hash(state);
     self.matching_rules.hash(state);
     self.generators.hash(state);
+
+    if let Some(ref trailers) = self.trailers {
+      for (k, v) in trailers {
+        k.hash(state);
+        v.hash(state);
+      }
+    }
   }
 }
 
@@ -407,6 +418,7 @@ impl HttpResponse {
       body: body_from_json(response, "body", &headers),
       matching_rules: matchers_from_json(response, &None)?,
       generators: generators_from_json(response)?,
+      trailers: headers_from_json_field(response, "trailers")
     })
   }
 
@@ -437,11 +449,18 @@ impl HttpResponse {
         map.insert("generators".to_string(), generators_to_json(
           &self.generators.clone(), &PactSpecification::V4));
       }
+
+      if let Some(ref trailers) = self.trailers {
+        map.insert("trailers".to_string(), Value::Object(
+          trailers.iter().map(|(k, v)| (k.clone(), json!(v))).collect()
+        ));
+      }
     }
     json
   }
 
-  /// Converts this response to a v3 response struct
+  /// Converts this response to a v3 response struct. Any trailers are not supported by V3, and
+  /// will be dropped.
   pub fn as_v3_response(&self) -> Response {
     Response {
       status: self.status,
@@ -0,0 +1,60 @@
+//! Support for loading pact files from zip archives
+
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+
+use anyhow::{anyhow, Context};
+use log::*;
+use serde_json::Value;
+use zip::ZipArchive;
+use zip::read::ZipFile;
+
+use pact_models::pact::{load_pact_from_json, Pact};
+
+/// Maximum uncompressed size of a pact file in a zip archive
+const MAX_ZIP_ENTRY_SIZE: u64 = 50 * 1024 * 1024;
+
+/// Result of loading a pact from an entry in a zip archive, along with the name of the entry
+type ZipPactResult = (String, anyhow::Result<Box<dyn Pact + Send + Sync>>);
+
+/// Loads all the pact files (entries ending in `.json`) from the zip archive. Returns the name of
+/// each entry with the result of loading it, so that one invalid entry does not prevent the
+/// others from being loaded. An error is returned if the archive itself could not be read.
+pub(crate) fn read_pacts_from_zip(path: &Path) -> anyhow::Result<Vec<ZipPactResult>> {
+  read_pacts_from_zip_with_limit(path, MAX_ZIP_ENTRY_SIZE)
+}
+
+/// Loads all the pact files from the zip archive, failing any entry that is larger than
+/// `max_entry_size` bytes once uncompressed.
+pub(crate) fn read_pacts_from_zip_with_limit(path: &Path, max_entry_size: u64) -> anyhow::Result<Vec<ZipPactResult>> {
+  let mut archive = ZipArchive::new(File::open(path)?)
+    .map_err(|err| anyhow!("File is not a zip archive - {}", err))?;
+  let mut results = vec![];
+  for index in 0..archive.len() {
+    let entry = archive.by_index(index)?;
+    if entry.is_file() && entry.name().ends_with(".json") {
+      let name = entry.name().to_string();
+      debug!("Loading pact from zip entry '{}'", name);
+      let result = read_entry(entry, max_entry_size)
+        .and_then(|contents| serde_json::from_slice::<Value>(&contents).context("Failed to parse Pact JSON"))
+        .and_then(|json| load_pact_from_json(&format!("{}!{}", path.to_string_lossy(), name), &json));
+      results.push((name, result));
+    }
+  }
+  Ok(results)
+}
+
+/// Returns the uncompressed contents of the entry. The size in the entry header can not be
+/// trusted, so the decompressed data is also limited to `max_entry_size` bytes.
+fn read_entry(entry: ZipFile, max_entry_size: u64) -> anyhow::Result<Vec<u8>> {
+  if entry.size() > max_entry_size {
+    return Err(anyhow!("Entry is larger than the maximum size of {} bytes", max_entry_size));
+  }
+  let mut contents = vec![];
+  entry.take(max_entry_size + 1).read_to_end(&mut contents)?;
+  if contents.len() as u64 > max_entry_size {
+    return Err(anyhow!("Entry is larger than the maximum size of {} bytes", max_entry_size));
+  }
+  Ok(contents)
+}
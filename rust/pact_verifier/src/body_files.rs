@@ -0,0 +1,93 @@
+//! Support for interaction bodies that are stored in external files. If the `resolve_body_files`
+//! verification option is set, a request or response body (or message contents) given as a
+//! `file://` reference (i.e. `"body": "file://bodies/user.json"`) is loaded from the file. The
+//! file is resolved relative to the directory containing the pact file, and must be within it.
+
+use std::fs;
+use std::path::{Component, Path};
+
+use anyhow::anyhow;
+use bytes::Bytes;
+use log::*;
+use serde_json::Value;
+
+use pact_models::bodies::OptionalBody;
+use pact_models::pact::{Pact, read_pact};
+
+const FILE_REFERENCE_PREFIX: &str = "file://";
+
+/// Reads the pact file, and if `resolve_body_files` is set, loads any bodies that reference
+/// external files
+pub(crate) fn read_pact_file(path: &Path, resolve_body_files: bool) -> anyhow::Result<Box<dyn Pact + Send + Sync>> {
+  let pact = read_pact(path)?;
+  if resolve_body_files {
+    resolve_bodies(pact, path.parent().unwrap_or_else(|| Path::new(".")))
+  } else {
+    Ok(pact)
+  }
+}
+
+fn resolve_bodies(pact: Box<dyn Pact + Send + Sync>, base_dir: &Path) -> anyhow::Result<Box<dyn Pact + Send + Sync>> {
+  if pact.is_v4() {
+    let mut pact = pact.as_v4_pact()?;
+    for interaction in pact.interactions.iter_mut() {
+      if let Some(http) = interaction.as_v4_http_mut() {
+        resolve_body(&mut http.request.body, base_dir)?;
+        resolve_body(&mut http.response.body, base_dir)?;
+      } else if let Some(message) = interaction.as_v4_async_message_mut() {
+        resolve_body(&mut message.contents.contents, base_dir)?;
+      } else if let Some(message) = interaction.as_v4_sync_message_mut() {
+        resolve_body(&mut message.request.contents, base_dir)?;
+        for response in message.response.iter_mut() {
+          resolve_body(&mut response.contents, base_dir)?;
+        }
+      }
+    }
+    Ok(pact.boxed())
+  } else if let Ok(mut pact) = pact.as_request_response_pact() {
+    for interaction in pact.interactions.iter_mut() {
+      resolve_body(&mut interaction.request.body, base_dir)?;
+      resolve_body(&mut interaction.response.body, base_dir)?;
+    }
+    Ok(pact.boxed())
+  } else if let Ok(mut pact) = pact.as_message_pact() {
+    for message in pact.messages.iter_mut() {
+      resolve_body(&mut message.contents, base_dir)?;
+    }
+    Ok(pact.boxed())
+  } else {
+    Ok(pact)
+  }
+}
+
+fn resolve_body(body: &mut OptionalBody, base_dir: &Path) -> anyhow::Result<()> {
+  if let OptionalBody::Present(contents, content_type, content_type_hint) = body {
+    if let Some(file) = file_reference(contents) {
+      *body = OptionalBody::Present(load_body_file(base_dir, &file)?, content_type.clone(), *content_type_hint);
+    }
+  }
+  Ok(())
+}
+
+/// Returns the file the body refers to. JSON bodies will contain the reference as a JSON string.
+fn file_reference(contents: &[u8]) -> Option<String> {
+  let text = match serde_json::from_slice(contents) {
+    Ok(Value::String(text)) => text,
+    _ => String::from_utf8(contents.to_vec()).ok()?
+  };
+  text.strip_prefix(FILE_REFERENCE_PREFIX).map(|file| file.to_string())
+}
+
+fn load_body_file(base_dir: &Path, file: &str) -> anyhow::Result<Bytes> {
+  let within_base_dir = Path::new(file).components()
+    .all(|component| matches!(component, Component::Normal(_) | Component::CurDir));
+  if !within_base_dir {
+    return Err(anyhow!("Body file '{}' referenced in the pact is not within the directory of the pact file", file));
+  }
+
+  let path = base_dir.join(file);
+  debug!("Loading body from file '{}'", path.display());
+  fs::read(&path)
+    .map(Bytes::from)
+    .map_err(|err| anyhow!("Failed to load the body file '{}' referenced in the pact - {}", path.display(), err))
+}
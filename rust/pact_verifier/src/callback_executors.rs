@@ -6,7 +6,7 @@ use std::sync::Arc;
 use ansi_term::Colour::Yellow;
 use async_trait::async_trait;
 use maplit::*;
-use serde_json::{json, Value};
+use serde_json::{json, Map, Value};
 
 use pact_models::bodies::OptionalBody;
 use pact_models::content_types::JSON;
@@ -38,13 +38,25 @@ impl RequestFilterExecutor for NullRequestFilterExecutor {
   }
 }
 
+/// Kind of error that occurred executing a provider state
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ProviderStateErrorKind {
+  /// The provider state change failed
+  #[default]
+  Failed,
+  /// The provider state is not known to the provider
+  NotFound
+}
+
 /// Struct for returning errors from executing a provider state
 #[derive(Debug, Clone)]
 pub struct ProviderStateError {
   /// Description of the error
   pub description: String,
   /// Interaction ID of the interaction that the error occurred
-  pub interaction_id: Option<String>
+  pub interaction_id: Option<String>,
+  /// Kind of error that occurred
+  pub kind: ProviderStateErrorKind
 }
 
 impl Display for ProviderStateError {
@@ -80,7 +92,12 @@ pub struct HttpRequestProviderStateExecutor {
   /// If teardown state change requests should be made (default is false)
   pub state_change_teardown: bool,
   /// If state change request data should be sent in the body (true) or as query parameters (false)
-  pub state_change_body: bool
+  pub state_change_body: bool,
+  /// HTTP method to use for state change requests (default is POST)
+  pub state_change_method: String,
+  /// JSON object to use as the template for the state change request body. The state name, params
+  /// and action are added to the template before the request is made.
+  pub state_change_body_template: Option<Value>
 }
 
 impl Default for HttpRequestProviderStateExecutor {
@@ -89,7 +106,9 @@ impl Default for HttpRequestProviderStateExecutor {
     HttpRequestProviderStateExecutor {
       state_change_url: None,
       state_change_teardown: false,
-      state_change_body: true
+      state_change_body: true,
+      state_change_method: "POST".to_string(),
+      state_change_body_template: None
     }
   }
 }
@@ -105,17 +124,19 @@ impl ProviderStateExecutor for HttpRequestProviderStateExecutor {
   ) -> anyhow::Result<HashMap<String, Value>> {
     match &self.state_change_url {
       Some(state_change_url) => {
-        let mut state_change_request = HttpRequest { method: "POST".to_string(), .. HttpRequest::default() };
+        let mut state_change_request = HttpRequest {
+          method: self.state_change_method.to_uppercase(),
+          .. HttpRequest::default()
+        };
         if self.state_change_body {
-          let json_body = json!({
-                    "state".to_string() : provider_state.name.clone(),
-                    "params".to_string() : provider_state.params.clone(),
-                    "action".to_string() : if setup {
-                        "setup".to_string()
-                    } else {
-                        "teardown".to_string()
-                    }
-                });
+          let mut json_body = match &self.state_change_body_template {
+            Some(Value::Object(template)) => template.clone(),
+            _ => Map::new()
+          };
+          json_body.insert("state".to_string(), json!(provider_state.name.clone()));
+          json_body.insert("params".to_string(), json!(provider_state.params.clone()));
+          json_body.insert("action".to_string(), json!(if setup { "setup" } else { "teardown" }));
+          let json_body = Value::Object(json_body);
           state_change_request.body = OptionalBody::Present(json_body.to_string().into(), Some(JSON.clone()), None);
           state_change_request.headers = Some(hashmap!{ "Content-Type".to_string() => vec!["application/json".to_string()] });
         } else {
@@ -134,7 +155,11 @@ impl ProviderStateExecutor for HttpRequestProviderStateExecutor {
           state_change_request.query = Some(query);
         }
         make_state_change_request(client.unwrap_or(&reqwest::Client::default()), &state_change_url, &state_change_request).await
-          .map_err(|err| ProviderStateError { description: err.to_string(), interaction_id }.into())
+          .map_err(|err| ProviderStateError {
+            description: err.to_string(),
+            interaction_id,
+            kind: ProviderStateErrorKind::Failed
+          }.into())
       },
       None => {
         if setup {
@@ -0,0 +1,176 @@
+//! Module to load the pact sources and verification options from a config file (`pact.toml` or
+//! `pact.json`). An example TOML config file:
+//!
+//! ```toml
+//! provider = "Pact Provider"
+//! files = ["pacts/consumer-provider.json"]
+//!
+//! [broker]
+//! url = "https://broker.example.com"
+//! token = "abc123"
+//! enable_pending = true
+//! include_wip_pacts_since = "2021-01-01"
+//! consumer_version_selectors = [ { mainBranch = true }, { deployedOrReleased = true } ]
+//!
+//! [verification]
+//! publish = true
+//! provider_version = "1.0.0"
+//! provider_branch = "main"
+//! provider_tags = ["prod"]
+//! ```
+//!
+//! Keys that are not recognised are logged as warnings, and otherwise ignored.
+
+use std::fs;
+use std::path::Path;
+
+use anyhow::{anyhow, Context};
+use log::*;
+use serde_json::{Map, Value};
+
+use pact_models::http_utils::HttpAuth;
+
+use crate::{ConsumerVersionSelector, PactSource, VerificationOptions};
+use crate::callback_executors::RequestFilterExecutor;
+
+const CONFIG_KEYS: [&str; 6] = ["provider", "files", "dirs", "urls", "broker", "verification"];
+const BROKER_KEYS: [&str; 7] = ["url", "token", "username", "password", "enable_pending",
+  "include_wip_pacts_since", "consumer_version_selectors"];
+const VERIFICATION_KEYS: [&str; 8] = ["publish", "provider_version", "provider_branch", "provider_tags",
+  "build_url", "disable_ssl_verification", "request_timeout", "state_change_timeout"];
+
+/// Pact sources and verification options loaded from a config file
+#[derive(Debug, Clone)]
+pub struct PactConfig<F: RequestFilterExecutor> {
+  /// Sources to load the pacts to verify from
+  pub sources: Vec<PactSource>,
+  /// Verification options, with any values not in the config file set to the defaults
+  pub options: VerificationOptions<F>
+}
+
+/// Loads the pact sources and verification options from the config file. The format of the file
+/// is determined from the extension, which must be either `.toml` or `.json`.
+pub fn load_config_file<F: RequestFilterExecutor>(path: &Path) -> anyhow::Result<PactConfig<F>> {
+  let contents = fs::read_to_string(path)
+    .with_context(|| format!("Could not read config file '{}'", path.display()))?;
+  let config: Value = match path.extension().and_then(|ext| ext.to_str()) {
+    Some("toml") => toml::from_str(&contents)
+      .with_context(|| format!("Config file '{}' is not valid TOML", path.display()))?,
+    Some("json") => serde_json::from_str(&contents)
+      .with_context(|| format!("Config file '{}' is not valid JSON", path.display()))?,
+    _ => return Err(anyhow!("Config file '{}' must be either a TOML (.toml) or JSON (.json) file", path.display()))
+  };
+  parse_config(&config)
+}
+
+fn parse_config<F: RequestFilterExecutor>(config: &Value) -> anyhow::Result<PactConfig<F>> {
+  let config = table(config, "config file")?;
+  warn_unknown_keys(config, &CONFIG_KEYS, "config file");
+
+  let empty = Map::new();
+  let verification = match config.get("verification") {
+    Some(value) => table(value, "verification")?,
+    None => &empty
+  };
+  warn_unknown_keys(verification, &VERIFICATION_KEYS, "verification");
+  let defaults = VerificationOptions::<F>::default();
+  let options = VerificationOptions {
+    publish: bool_value(verification, "publish")?.unwrap_or(defaults.publish),
+    provider_version: string_value(verification, "provider_version")?,
+    provider_branch: string_value(verification, "provider_branch")?,
+    provider_tags: string_list(verification, "provider_tags")?,
+    build_url: string_value(verification, "build_url")?,
+    disable_ssl_verification: bool_value(verification, "disable_ssl_verification")?
+      .unwrap_or(defaults.disable_ssl_verification),
+    request_timeout: u64_value(verification, "request_timeout")?.unwrap_or(defaults.request_timeout),
+    state_change_timeout: u64_value(verification, "state_change_timeout")?.unwrap_or(defaults.state_change_timeout),
+    .. defaults
+  };
+
+  let mut sources = vec![];
+  sources.extend(string_list(config, "files")?.into_iter().map(PactSource::File));
+  sources.extend(string_list(config, "dirs")?.into_iter().map(PactSource::Dir));
+  sources.extend(string_list(config, "urls")?.into_iter().map(|url| PactSource::URL(url, None)));
+
+  if let Some(broker) = config.get("broker") {
+    let broker = table(broker, "broker")?;
+    warn_unknown_keys(broker, &BROKER_KEYS, "broker");
+    let broker_url = string_value(broker, "url")?
+      .ok_or_else(|| anyhow!("The Pact Broker 'url' is required in the broker section"))?;
+    let provider_name = string_value(config, "provider")?
+      .ok_or_else(|| anyhow!("The 'provider' name is required to fetch pacts from the Pact Broker"))?;
+    let selectors = match broker.get("consumer_version_selectors") {
+      Some(selectors) => serde_json::from_value::<Vec<ConsumerVersionSelector>>(selectors.clone())
+        .context("'consumer_version_selectors' in the broker section are not valid")?,
+      None => vec![]
+    };
+    sources.push(PactSource::BrokerWithDynamicConfiguration {
+      provider_name,
+      broker_url,
+      enable_pending: bool_value(broker, "enable_pending")?.unwrap_or(false),
+      include_wip_pacts_since: string_value(broker, "include_wip_pacts_since")?,
+      provider_tags: options.provider_tags.clone(),
+      provider_branch: options.provider_branch.clone(),
+      selectors,
+      auth: broker_auth(broker)?,
+      links: vec![]
+    });
+  }
+
+  Ok(PactConfig { sources, options })
+}
+
+fn broker_auth(broker: &Map<String, Value>) -> anyhow::Result<Option<HttpAuth>> {
+  match (string_value(broker, "token")?, string_value(broker, "username")?) {
+    (Some(token), _) => Ok(Some(HttpAuth::Token(token))),
+    (None, Some(username)) => Ok(Some(HttpAuth::User(username, string_value(broker, "password")?))),
+    (None, None) => Ok(None)
+  }
+}
+
+fn warn_unknown_keys(table: &Map<String, Value>, known_keys: &[&str], section: &str) {
+  for key in table.keys().filter(|key| !known_keys.contains(&key.as_str())) {
+    warn!("Ignoring unknown key '{}' in the {} section of the config file", key, section);
+  }
+}
+
+fn table<'a>(value: &'a Value, section: &str) -> anyhow::Result<&'a Map<String, Value>> {
+  value.as_object().ok_or_else(|| anyhow!("Expected the {} to be a table, but got '{}'", section, value))
+}
+
+fn string_value(table: &Map<String, Value>, key: &str) -> anyhow::Result<Option<String>> {
+  match table.get(key) {
+    Some(Value::String(value)) => Ok(Some(value.clone())),
+    Some(value) => Err(anyhow!("Expected '{}' to be a string, but got '{}'", key, value)),
+    None => Ok(None)
+  }
+}
+
+fn bool_value(table: &Map<String, Value>, key: &str) -> anyhow::Result<Option<bool>> {
+  match table.get(key) {
+    Some(Value::Bool(value)) => Ok(Some(*value)),
+    Some(value) => Err(anyhow!("Expected '{}' to be a boolean, but got '{}'", key, value)),
+    None => Ok(None)
+  }
+}
+
+fn u64_value(table: &Map<String, Value>, key: &str) -> anyhow::Result<Option<u64>> {
+  match table.get(key) {
+    Some(value) => value.as_u64()
+      .map(Some)
+      .ok_or_else(|| anyhow!("Expected '{}' to be a positive integer, but got '{}'", key, value)),
+    None => Ok(None)
+  }
+}
+
+fn string_list(table: &Map<String, Value>, key: &str) -> anyhow::Result<Vec<String>> {
+  match table.get(key) {
+    Some(Value::Array(values)) => values.iter()
+      .map(|value| value.as_str()
+        .map(|value| value.to_string())
+        .ok_or_else(|| anyhow!("Expected the values of '{}' to be strings, but got '{}'", key, value)))
+      .collect(),
+    Some(value) => Err(anyhow!("Expected '{}' to be a list of strings, but got '{}'", key, value)),
+    None => Ok(vec![])
+  }
+}
@@ -3,16 +3,20 @@
 //! and V4 Pact specification (https://github.com/pact-foundation/pact-specification/tree/version-4).
 #![warn(missing_docs)]
 
-use std::collections::HashMap;
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::env;
 use std::fmt::{Debug, Display, Formatter};
 use std::fmt;
 use std::fs;
-use std::path::Path;
+use std::panic::RefUnwindSafe;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use std::time::Duration;
 
 use ansi_term::*;
+use anyhow::anyhow;
 use ansi_term::Colour::*;
+use difference::{Changeset, Difference};
 use futures::prelude::*;
 use futures::stream::StreamExt;
 use itertools::Itertools;
@@ -20,38 +24,49 @@ use log::*;
 use maplit::*;
 use pact_plugin_driver::plugin_manager::{load_plugin, shutdown_plugins};
 use regex::Regex;
-use serde_json::Value;
+use reqwest::redirect::Policy;
+use serde_json::{json, Value};
 
 pub use callback_executors::NullRequestFilterExecutor;
 use callback_executors::RequestFilterExecutor;
-use pact_matching::{match_response, Mismatch};
+use pact_matching::{HeaderMatchConfig, match_response_with_config, MatchingConfig, Mismatch};
 use pact_matching::logging::LOG_ID;
 use pact_models::generators::GeneratorTestMode;
 use pact_models::http_utils::HttpAuth;
 use pact_models::interaction::Interaction;
 use pact_models::json_utils::json_to_string;
-use pact_models::pact::{load_pact_from_url, Pact, read_pact};
+use pact_models::pact::{load_pact_from_json, Pact, read_pact};
+use pact_models::PactSpecification;
 use pact_models::prelude::v4::SynchronousHttp;
 use pact_models::provider_states::*;
 use pact_models::v4::interaction::V4Interaction;
 
-use crate::callback_executors::{ProviderStateError, ProviderStateExecutor};
+use crate::body_files::read_pact_file;
+use crate::callback_executors::{ProviderStateError, ProviderStateErrorKind, ProviderStateExecutor};
 use crate::messages::{display_message_result, verify_message_from_provider, verify_sync_message_from_provider};
-use crate::pact_broker::{Link, PactVerificationContext, publish_verification_results, TestResult};
+use crate::pact_broker::{Link, PactVerificationContext, publish_verification_results, TestResult, VerificationTiming};
 pub use crate::pact_broker::{ConsumerVersionSelector, PactsForVerificationRequest};
 use crate::provider_client::make_provider_request;
+use crate::redaction::Redactions;
 use crate::request_response::display_request_response_result;
+use crate::websockets::{is_websocket_upgrade, verify_websocket_handshake};
 use pact_plugin_driver::plugin_models::{PluginDependency, PluginDependencyType};
 use pact_matching::metrics::{MetricEvent, send_metrics};
 use crate::metrics::VerificationMetrics;
 
+mod archive;
+mod body_files;
+#[cfg(feature = "ntlm")] mod ntlm;
 mod provider_client;
 pub mod pact_broker;
 pub mod callback_executors;
 mod request_response;
 mod messages;
+mod redaction;
+mod websockets;
 pub mod selectors;
 pub mod metrics;
+pub mod config;
 
 /// Source for loading pacts
 #[derive(Debug, Clone)]
@@ -62,6 +77,8 @@ pub enum PactSource {
     File(String),
     /// Load all the pacts from a Directory
     Dir(String),
+    /// Load all the pacts from a zip archive
+    Zip(String),
     /// Load the pact from a URL
     URL(String, Option<HttpAuth>),
     /// Load all pacts with the provider name from the pact broker url
@@ -94,15 +111,16 @@ impl Display for PactSource {
     match *self {
       PactSource::File(ref file) => write!(f, "File({})", file),
       PactSource::Dir(ref dir) => write!(f, "Dir({})", dir),
+      PactSource::Zip(ref file) => write!(f, "Zip({})", file),
       PactSource::URL(ref url, _) => write!(f, "URL({})", url),
       PactSource::BrokerUrl(ref provider_name, ref broker_url, _, _) => {
           write!(f, "PactBroker({}, provider_name='{}')", broker_url, provider_name)
       }
       PactSource::BrokerWithDynamicConfiguration { ref provider_name, ref broker_url,ref enable_pending, ref include_wip_pacts_since, ref provider_branch, ref provider_tags, ref selectors, ref auth, links: _ } => {
         if let Some(auth) = auth {
-          write!(f, "PactBrokerWithDynamicConfiguration({}, provider_name='{}', enable_ending={}, include_wip_since={:?}, provider_tags={:?}, provider_branch={:?}, consumer_version_selectors='{:?}, auth={}')", broker_url, provider_name, enable_pending, include_wip_pacts_since, provider_tags, provider_branch, selectors, auth)
+          write!(f, "PactBrokerWithDynamicConfiguration({}, provider_name='{}', enable_ending={}, include_wip_since={:?}, provider_tags={:?}, provider_branch={:?}, consumer_version_selectors='[{}], auth={}')", broker_url, provider_name, enable_pending, include_wip_pacts_since, provider_tags, provider_branch, selectors.iter().join(", "), auth)
         } else {
-          write!(f, "PactBrokerWithDynamicConfiguration({}, provider_name='{}', enable_ending={}, include_wip_since={:?}, provider_tags={:?}, provider_branch={:?}, consumer_version_selectors='{:?}, auth=None')", broker_url, provider_name, enable_pending, include_wip_pacts_since, provider_tags, provider_branch, selectors)
+          write!(f, "PactBrokerWithDynamicConfiguration({}, provider_name='{}', enable_ending={}, include_wip_since={:?}, provider_tags={:?}, provider_branch={:?}, consumer_version_selectors='[{}], auth=None')", broker_url, provider_name, enable_pending, include_wip_pacts_since, provider_tags, provider_branch, selectors.iter().join(", "))
 
         }
       }
@@ -123,7 +141,10 @@ pub struct ProviderInfo {
     /// Port the provider is running on, defaults to 8080
     pub port: Option<u16>,
     /// Base path for the provider, defaults to /
-    pub path: String
+    pub path: String,
+    /// Base paths to use for interactions whose description matches the regex. The first
+    /// matching entry is used, otherwise the base path is used.
+    pub path_mappings: Vec<(String, String)>
 }
 
 impl Default for ProviderInfo {
@@ -134,11 +155,48 @@ impl Default for ProviderInfo {
       protocol: "http".to_string(),
       host: "localhost".to_string(),
       port: Some(8080),
-      path: "/".to_string()
+      path: "/".to_string(),
+      path_mappings: vec![]
     }
   }
 }
 
+impl ProviderInfo {
+  /// Creates the provider info from the base URL of the provider (i.e.
+  /// `https://api.example.com:8443/base`). If the URL has no port, the default port for the
+  /// scheme is used. Only HTTP and HTTPS URLs are supported.
+  pub fn from_url(url: &str) -> anyhow::Result<ProviderInfo> {
+    let url = reqwest::Url::parse(url)
+      .map_err(|err| anyhow!("'{}' is not a valid provider URL - {}", url, err))?;
+    if url.scheme() != "http" && url.scheme() != "https" {
+      return Err(anyhow!("Provider URL '{}' must be an HTTP or HTTPS URL", url));
+    }
+    let host = url.host_str()
+      .ok_or_else(|| anyhow!("Provider URL '{}' does not have a host", url))?;
+    Ok(ProviderInfo {
+      protocol: url.scheme().to_string(),
+      host: host.to_string(),
+      port: url.port_or_known_default(),
+      path: url.path().to_string(),
+      .. ProviderInfo::default()
+    })
+  }
+
+  /// Returns the base path to use for the interaction with the given description
+  pub fn base_path_for(&self, description: &str) -> &str {
+    self.path_mappings.iter()
+      .find(|(pattern, _)| match Regex::new(pattern) {
+        Ok(regex) => regex.is_match(description),
+        Err(err) => {
+          warn!("Ignoring invalid provider path mapping '{}' - {}", pattern, err);
+          false
+        }
+      })
+      .map(|(_, path)| path.as_str())
+      .unwrap_or(&self.path)
+  }
+}
+
 /// Result of performing a match
 pub enum MismatchResult {
     /// Response mismatches
@@ -252,9 +310,12 @@ async fn verify_response_from_provider<F: RequestFilterExecutor>(
 ) -> Result<Option<String>, MismatchResult> {
   let expected_response = &interaction.response;
   let request = pact_matching::generate_request(&interaction.request, &GeneratorTestMode::Provider, &verification_context).await;
-  match make_provider_request(provider, &request, options, client).await {
+  match make_provider_request(provider, &request, &interaction.description, &interaction.provider_states,
+    options, client).await {
     Ok(ref actual_response) => {
-      let mismatches = match_response(expected_response.clone(), actual_response.clone(), pact, &interaction.boxed()).await;
+      let config = MatchingConfig { header_config: options.header_match_config.clone(), .. MatchingConfig::default() };
+      let mismatches = match_response_with_config(expected_response.clone(), actual_response.clone(), pact,
+        &interaction.boxed(), &config).await;
       if mismatches.is_empty() {
         Ok(interaction.id.clone())
       } else {
@@ -278,7 +339,7 @@ async fn execute_state_change<S: ProviderStateExecutor>(
   interaction_id: Option<String>,
   client: &reqwest::Client,
   provider_state_executor: Arc<S>
-) -> Result<HashMap<String, Value>, MismatchResult> {
+) -> Result<HashMap<String, Value>, ProviderStateError> {
     if setup {
         println!("  Given {}", Style::new().bold().paint(provider_state.name.clone()));
     }
@@ -286,29 +347,94 @@ async fn execute_state_change<S: ProviderStateExecutor>(
     debug!("State Change: \"{:?}\" -> {:?}", provider_state, result);
     result.map_err(|err| {
       if let Some(err) = err.downcast_ref::<ProviderStateError>() {
-        MismatchResult::Error(err.description.clone(), err.interaction_id.clone())
+        err.clone()
       } else {
-        MismatchResult::Error(err.to_string(), None)
+        ProviderStateError {
+          description: err.to_string(),
+          interaction_id: None,
+          kind: ProviderStateErrorKind::Failed
+        }
       }
     })
 }
 
+/// Outcome of verifying an interaction that did not result in an error
+enum InteractionOutcome {
+  /// The interaction was verified, with the interaction ID
+  Verified(Option<String>),
+  /// The interaction was skipped, with the reason why
+  Skipped(String)
+}
+
+fn http_client<F: RequestFilterExecutor>(
+  options: &VerificationOptions<F>,
+  timeout: u64,
+  redirect_policy: Policy
+) -> reqwest::Client {
+  let mut builder = reqwest::Client::builder()
+    .danger_accept_invalid_certs(options.disable_ssl_verification)
+    .timeout(Duration::from_millis(timeout))
+    .redirect(redirect_policy)
+    .user_agent(options.user_agent.as_deref().unwrap_or(DEFAULT_USER_AGENT))
+    // Response bodies are decompressed by the provider client, so that the headers can be updated
+    .no_gzip()
+    .no_deflate()
+    .no_brotli();
+  if let Some(pool_size) = options.pool_max_idle_per_host {
+    builder = builder.pool_max_idle_per_host(pool_size);
+  }
+  if let Some(keep_alive) = options.tcp_keep_alive {
+    builder = builder.tcp_keepalive(Duration::from_millis(keep_alive));
+  }
+  builder.build().unwrap_or(reqwest::Client::new())
+}
+
+/// HTTP clients used for the requests to the provider and the provider state change requests.
+/// These are created once for each pact and shared between the interactions, so that the
+/// connections to the provider can be reused.
+#[derive(Debug, Clone)]
+pub(crate) struct ProviderClients {
+  /// Client for the requests to the provider
+  pub(crate) client: Arc<reqwest::Client>,
+  /// Client for the provider state change requests
+  pub(crate) state_change_client: Arc<reqwest::Client>
+}
+
+impl ProviderClients {
+  /// Creates the clients using the timeouts and connection pool settings from the options
+  pub(crate) fn new<F: RequestFilterExecutor>(options: &VerificationOptions<F>) -> Self {
+    let redirect_policy = if options.follow_redirects { Policy::default() } else { Policy::none() };
+    ProviderClients {
+      client: Arc::new(http_client(options, options.request_timeout, redirect_policy)),
+      state_change_client: Arc::new(http_client(options, options.state_change_timeout, Policy::default()))
+    }
+  }
+}
+
 async fn verify_interaction<'a, F: RequestFilterExecutor, S: ProviderStateExecutor>(
   provider: &ProviderInfo,
   interaction: &(dyn Interaction + Send + Sync),
   pact: &Box<dyn Pact + Send + Sync + 'a>,
   options: &VerificationOptions<F>,
+  clients: &ProviderClients,
   provider_state_executor: &Arc<S>
-) -> Result<Option<String>, MismatchResult> {
-  let client = Arc::new(reqwest::Client::builder()
-  .danger_accept_invalid_certs(options.disable_ssl_verification)
-  .timeout(Duration::from_millis(options.request_timeout))
-  .build()
-  .unwrap_or(reqwest::Client::new()));
+) -> Result<InteractionOutcome, MismatchResult> {
+  let client = clients.client.clone();
+  let state_change_client = clients.state_change_client.clone();
+
+  // When the provider states are sent in a header, there are no state changes to execute
+  let state_changes = if options.provider_state_header.is_some() {
+    for state in interaction.provider_states() {
+      println!("  Given {}", Style::new().bold().paint(state.name.clone()));
+    }
+    vec![]
+  } else {
+    interaction.provider_states()
+  };
 
   let mut provider_states_results = hashmap!{};
   let sc_results = futures::stream::iter(
-    interaction.provider_states().iter().map(|state| (state, client.clone())))
+    state_changes.iter().map(|state| (state, state_change_client.clone())))
     .then(|(state, client)| {
       let state_name = state.name.clone();
       info!("Running provider state change handler '{}' for '{}'", state_name, interaction.description());
@@ -320,8 +446,18 @@ async fn verify_interaction<'a, F: RequestFilterExecutor, S: ProviderStateExecut
             err
           }).await
       }
-    }).collect::<Vec<Result<HashMap<String, Value>, MismatchResult>>>().await;
+    }).collect::<Vec<Result<HashMap<String, Value>, ProviderStateError>>>().await;
   if sc_results.iter().any(|result| result.is_err()) {
+    let all_not_found = sc_results.iter()
+      .all(|result| result.as_ref().map(|_| true).unwrap_or_else(|err| err.kind == ProviderStateErrorKind::NotFound));
+    if options.skip_on_missing_state && all_not_found {
+      let reason = sc_results.iter()
+        .filter_map(|result| result.as_ref().err())
+        .map(|err| err.description.clone())
+        .join(", ");
+      warn!("Skipping '{}' as one or more provider states could not be found - {}", interaction.description(), reason);
+      return Ok(InteractionOutcome::Skipped(reason));
+    }
     return Err(MismatchResult::Error("One or more of the state change handlers has failed".to_string(), interaction.id()))
   } else {
     for result in sc_results {
@@ -335,7 +471,9 @@ async fn verify_interaction<'a, F: RequestFilterExecutor, S: ProviderStateExecut
 
   info!("Running provider verification for '{}'", interaction.description());
 
-  let result = futures::future::ready((provider_states_results.iter()
+  // Provider state values are applied last, so they take precedence over the global template values
+  let result = futures::future::ready((options.template_values.iter()
+    .chain(provider_states_results.iter())
     .map(|(k, v)| (k.as_str(), v.clone())).collect(), client.clone()))
     .then(|(context, client)| async move {
     let mut result = Err(MismatchResult::Error("No interaction was verified".into(), interaction.id().clone()));
@@ -344,8 +482,13 @@ async fn verify_interaction<'a, F: RequestFilterExecutor, S: ProviderStateExecut
 
     // Verify an HTTP interaction
     if let Some(interaction) = interaction.as_v4_http() {
-      trace!("Verifying a HTTP interaction");
-      result = verify_response_from_provider(provider, &interaction, &pact.boxed(), options, &client, &context).await;
+      if is_websocket_upgrade(&interaction) {
+        trace!("Verifying the handshake of a WebSocket upgrade interaction");
+        result = verify_websocket_handshake(provider, &interaction, pact.as_ref(), options, &client, &context).await;
+      } else {
+        trace!("Verifying a HTTP interaction");
+        result = verify_response_from_provider(provider, &interaction, &pact.boxed(), options, &client, &context).await;
+      }
     }
     // Verify an asynchronous message (single shot)
     if interaction.is_message() {
@@ -361,9 +504,9 @@ async fn verify_interaction<'a, F: RequestFilterExecutor, S: ProviderStateExecut
     result
   }).await;
 
-  if !interaction.provider_states().is_empty() && provider_state_executor.teardown() {
+  if !state_changes.is_empty() && provider_state_executor.teardown() {
     let sc_teardown_result = futures::stream::iter(
-      interaction.provider_states().iter().map(|state| (state, client.clone())))
+      state_changes.iter().map(|state| (state, state_change_client.clone())))
       .then(|(state, client)| async move {
         let state_name = state.name.clone();
         info!("Running provider state change handler '{}' for '{}'", state_name, interaction.description());
@@ -373,14 +516,19 @@ async fn verify_interaction<'a, F: RequestFilterExecutor, S: ProviderStateExecut
             error!("Provider state change teardown for '{}' has failed - {:?}", state.name, err);
             err
           }).await
-      }).collect::<Vec<Result<HashMap<String, Value>, MismatchResult>>>().await;
+      }).collect::<Vec<Result<HashMap<String, Value>, ProviderStateError>>>().await;
 
     if sc_teardown_result.iter().any(|result| result.is_err()) {
-      return Err(MismatchResult::Error("One or more of the state change handlers has failed during teardown phase".to_string(), interaction.id()))
+      if options.teardown_failures_are_warnings {
+        warn!("One or more of the state change handlers has failed during teardown phase for '{}', ignoring as teardown failures are warnings",
+          interaction.description());
+      } else {
+        return Err(MismatchResult::Error("One or more of the state change handlers has failed during teardown phase".to_string(), interaction.id()))
+      }
     }
   }
 
-  result
+  result.map(InteractionOutcome::Verified)
 }
 
 fn display_result(
@@ -402,30 +550,106 @@ fn display_result(
   println!("      has a matching body ({})", body_result);
 }
 
-fn walkdir(dir: &Path) -> anyhow::Result<Vec<anyhow::Result<Box<dyn Pact + Send + Sync>>>> {
+fn walkdir(dir: &Path, resolve_body_files: bool) -> anyhow::Result<Vec<anyhow::Result<Box<dyn Pact + Send + Sync>>>> {
     let mut pacts = vec![];
     debug!("Scanning {:?}", dir);
     for entry in fs::read_dir(dir)? {
         let entry = entry?;
         let path = entry.path();
         if path.is_dir() {
-            walkdir(&path)?;
-        } else {
-            pacts.push(read_pact(&path))
+            pacts.extend(walkdir(&path, resolve_body_files)?);
+        } else if path.extension().map(|ext| ext == "json").unwrap_or(false) {
+            pacts.push(read_pact_file(&path, resolve_body_files))
         }
     }
     Ok(pacts)
 }
 
+/// Loads the pact file and checks it for structural problems without verifying it against a
+/// provider. Returns a list of warnings, which will be empty if no problems were found. An error
+/// is returned if the file could not be loaded.
+pub fn validate_pact_file(path: &Path) -> anyhow::Result<Vec<String>> {
+  let pact = read_pact(path)?;
+  let mut warnings = vec![];
+
+  if pact.specification_version() == PactSpecification::Unknown {
+    warnings.push("Pact file does not have a known specification version".to_string());
+  }
+
+  let interactions = pact.interactions();
+  if interactions.is_empty() {
+    warnings.push("Pact file has no interactions".to_string());
+  }
+
+  let mut descriptions = HashMap::new();
+  for (index, interaction) in interactions.iter().enumerate() {
+    let description = interaction.description();
+    if description.is_empty() {
+      warnings.push(format!("Interaction {} has no description", index + 1));
+      continue;
+    }
+
+    if let Some(message) = interaction.as_v4_sync_message() {
+      if message.response.is_empty() {
+        warnings.push(format!("Interaction '{}' has no response", description));
+      }
+    }
+
+    let key = (description.clone(), interaction.provider_states().iter()
+      .map(|state| state.name.clone()).collect_vec());
+    let count = descriptions.entry(key).or_insert(0);
+    *count += 1;
+    if *count == 2 {
+      warnings.push(format!("Duplicate interaction description '{}'", description));
+    }
+  }
+
+  Ok(warnings)
+}
+
 fn display_body_mismatch(expected: &Box<dyn Interaction>, actual: &Box<dyn Interaction>, path: &str) {
-  if expected.contents_for_verification().content_type().unwrap_or_default().is_json() {
-    println!("{}", pact_matching::json::display_diff(
-      &expected.contents_for_verification().str_value().to_string(),
-      &actual.contents_for_verification().str_value().to_string(),
-      path, "    "));
+  let diff = body_mismatch_diff(expected, actual, path);
+  if !diff.is_empty() {
+    println!("{}", diff);
+  }
+}
+
+fn body_mismatch_diff(expected: &Box<dyn Interaction>, actual: &Box<dyn Interaction>, path: &str) -> String {
+  let expected_body = expected.contents_for_verification().str_value().to_string();
+  let actual_body = actual.contents_for_verification().str_value().to_string();
+  let content_type = expected.contents_for_verification().content_type().unwrap_or_default();
+  if content_type.is_json() {
+    pact_matching::json::display_diff(&expected_body, &actual_body, path, "    ")
+  } else if content_type.is_xml() {
+    // Put each element on its own line so the diff shows the elements that differ
+    display_text_diff(&expected_body.replace("><", ">\n<"), &actual_body.replace("><", ">\n<"), "    ")
+  } else if content_type.is_text() {
+    display_text_diff(&expected_body, &actual_body, "    ")
+  } else {
+    String::default()
   }
 }
 
+fn display_text_diff(expected: &str, actual: &str, indent: &str) -> String {
+  let changeset = Changeset::new(expected, actual, "\n");
+  let mut output = String::new();
+  for change in changeset.diffs {
+    // Consecutive lines are grouped into a single change, so each line needs to be prefixed
+    match change {
+      Difference::Same(ref x) => for line in x.split('\n') {
+        output.push_str(&format!("{}{}\n", indent, line));
+      },
+      Difference::Add(ref x) => for line in x.split('\n') {
+        output.push_str(&Green.paint(format!("{}+{}\n", indent, line)).to_string());
+      },
+      Difference::Rem(ref x) => for line in x.split('\n') {
+        output.push_str(&Red.paint(format!("{}-{}\n", indent, line)).to_string());
+      }
+    }
+  }
+  output
+}
+
 /// Filter information used to filter the interactions that are verified
 #[derive(Debug, Clone)]
 pub enum FilterInfo {
@@ -436,7 +660,11 @@ pub enum FilterInfo {
     /// Filter on the interaction provider state
     State(String),
     /// Filter on both the interaction description and provider state
-    DescriptionAndState(String, String)
+    DescriptionAndState(String, String),
+    /// Only verify the interactions that are different from the equivalent interaction (with the
+    /// same description and provider states) in the baseline pact file. Interactions that are not
+    /// in the baseline pact are treated as changed.
+    ChangedSince(PathBuf)
 }
 
 impl FilterInfo {
@@ -517,12 +745,66 @@ fn filter_interaction(interaction: &dyn Interaction, filter: &FilterInfo) -> boo
   }
 }
 
+/// Returns the keys of the interactions in the pact that are different from the equivalent
+/// interaction in the baseline pact, or are not in the baseline pact
+fn changed_interactions(
+  pact: &(dyn Pact + Send + Sync),
+  baseline_path: &Path
+) -> anyhow::Result<HashSet<String>> {
+  let baseline = read_pact(baseline_path)
+    .map_err(|err| anyhow!("Failed to load the baseline pact '{}' - {}", baseline_path.display(), err))?;
+
+  let mut changed = HashSet::new();
+  for (key, baseline_interaction, interaction) in pact_matching::pair_interactions(baseline.as_ref(), pact) {
+    if let Some(interaction) = interaction {
+      let unchanged = baseline_interaction
+        .map(|baseline_interaction| interaction_contents(baseline_interaction.as_ref()) == interaction_contents(interaction.as_ref()))
+        .unwrap_or(false);
+      if !unchanged {
+        debug!("Interaction '{}' has changed since the baseline pact", interaction.description());
+        changed.insert(key);
+      }
+    }
+  }
+  Ok(changed)
+}
+
+/// The contents of the interaction that are compared to see if it has changed. This is the V4
+/// form of the interaction (including any matching rules and generators), without the fields that
+/// do not affect the verification (key, comments, pending flag and markup). HTTP bodies have their
+/// content type resolved first, as it is not stored with the body for V3 and earlier pacts.
+fn interaction_contents(interaction: &(dyn Interaction + Send + Sync)) -> Option<Value> {
+  let interaction = match interaction.as_v4_http() {
+    Some(mut http) => {
+      if let Some(content_type) = http.request.content_type() {
+        http.request.body.set_content_type(&content_type);
+      }
+      if let Some(content_type) = http.response.content_type() {
+        http.response.body.set_content_type(&content_type);
+      }
+      http.boxed_v4()
+    }
+    None => interaction.as_v4()?
+  };
+  let mut json = interaction.to_json();
+  if let Some(map) = json.as_object_mut() {
+    for field in ["_id", "key", "comments", "pending", "interactionMarkup"] {
+      map.remove(field);
+    }
+  }
+  Some(json)
+}
+
 fn filter_consumers(consumers: &[String], res: &Result<(Box<dyn Pact + Send + Sync>, Option<PactVerificationContext>, PactSource), String>) -> bool {
   consumers.is_empty() || res.is_err() || consumers.contains(&res.as_ref().unwrap().0.consumer().name)
 }
 
+/// Callback invoked with the result of each interaction as it is verified. This needs to be
+/// `RefUnwindSafe`, as the options are passed across the FFI panic boundary.
+pub type VerificationProgressCallback = Arc<dyn Fn(VerificationInteractionResult) + Send + Sync + RefUnwindSafe>;
+
 /// Options to use when running the verification
-#[derive(Debug, Clone)]
+#[derive(Clone)]
 pub struct VerificationOptions<F> where F: RequestFilterExecutor {
   /// If results should be published back to the broker
   pub publish: bool,
@@ -536,10 +818,90 @@ pub struct VerificationOptions<F> where F: RequestFilterExecutor {
   pub provider_tags: Vec<String>,
   /// Ignore invalid/self-signed SSL certificates
   pub disable_ssl_verification: bool,
-  /// Timeout in ms for verification requests and state callbacks
+  /// Timeout in ms for verification requests
   pub request_timeout: u64,
+  /// Timeout in ms for state change callbacks
+  pub state_change_timeout: u64,
+  /// Number of times to retry a provider request on a connection error or 5xx response. The
+  /// retries use a jittered exponential backoff, and will stop once the request timeout elapses.
+  pub request_retries: u8,
+  /// If non-idempotent provider requests (i.e. POST and PATCH) can also be retried
+  pub retry_non_idempotent_requests: bool,
+  /// Skip interactions whose provider states could not be found, instead of failing them
+  pub skip_on_missing_state: bool,
+  /// Global values available to generators when building requests. Values returned from
+  /// provider state callbacks take precedence over these.
+  pub template_values: HashMap<String, Value>,
   /// Provider branch used when publishing results
   pub provider_branch: Option<String>,
+  /// If the provider branch is not set, detect it from the common CI environment variables
+  /// (i.e. `GIT_BRANCH` or `CI_COMMIT_REF_NAME`) when publishing results
+  pub auto_detect_branch: bool,
+  /// Callback to invoke as each interaction is verified
+  pub progress_callback: Option<VerificationProgressCallback>,
+  /// Header (or message metadata) names and JSON body paths (starting with `$`) of sensitive
+  /// values. These values will be replaced with `***` in the displayed output, progress callbacks
+  /// and published results. Matching is not affected.
+  pub redacted_fields: Vec<String>,
+  /// If the provider client should follow redirects. By default, a redirect response is returned
+  /// as-is and matched against the expected response.
+  pub follow_redirects: bool,
+  /// Only list the interactions that would be verified, without executing any provider state
+  /// changes or requests. All the interactions will be marked as skipped.
+  pub dry_run: bool,
+  /// If set, the provider states are not set up with the provider state executor. Instead, they
+  /// are sent as a JSON array (of `name` and `params`) in this header on the request to the provider.
+  pub provider_state_header: Option<String>,
+  /// User agent to send with the requests to the provider. Defaults to `pact-rust-verifier/<version>`.
+  pub user_agent: Option<String>,
+  /// Maximum number of idle connections to the provider to keep in the connection pool. The
+  /// connections are reused between interactions. Defaults to no limit, and `0` disables reuse.
+  pub pool_max_idle_per_host: Option<usize>,
+  /// Interval in ms for TCP keep-alive probes on the connections to the provider. Defaults to
+  /// not sending keep-alive probes.
+  pub tcp_keep_alive: Option<u64>,
+  /// If a failure of a provider state teardown should only be logged as a warning, instead of
+  /// failing the interaction. The result of the verification is then kept.
+  pub teardown_failures_are_warnings: bool,
+  /// How headers returned by the provider that are not in the expected response are treated.
+  /// Defaults to ignoring them.
+  pub header_match_config: HeaderMatchConfig,
+  /// Load the request and response bodies (or message contents) given as a `file://` reference
+  /// (i.e. `"body": "file://bodies/user.json"`) from the file, relative to the directory of the
+  /// pact file. Only applies to pacts loaded from files or directories.
+  pub resolve_body_files: bool
+}
+
+impl <F: RequestFilterExecutor + Debug> Debug for VerificationOptions<F> {
+  fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+    f.debug_struct("VerificationOptions")
+      .field("publish", &self.publish)
+      .field("provider_version", &self.provider_version)
+      .field("build_url", &self.build_url)
+      .field("request_filter", &self.request_filter)
+      .field("provider_tags", &self.provider_tags)
+      .field("disable_ssl_verification", &self.disable_ssl_verification)
+      .field("request_timeout", &self.request_timeout)
+      .field("state_change_timeout", &self.state_change_timeout)
+      .field("request_retries", &self.request_retries)
+      .field("retry_non_idempotent_requests", &self.retry_non_idempotent_requests)
+      .field("skip_on_missing_state", &self.skip_on_missing_state)
+      .field("template_values", &self.template_values)
+      .field("provider_branch", &self.provider_branch)
+      .field("auto_detect_branch", &self.auto_detect_branch)
+      .field("progress_callback", &self.progress_callback.as_ref().map(|_| "<callback>"))
+      .field("redacted_fields", &self.redacted_fields)
+      .field("follow_redirects", &self.follow_redirects)
+      .field("dry_run", &self.dry_run)
+      .field("provider_state_header", &self.provider_state_header)
+      .field("user_agent", &self.user_agent)
+      .field("pool_max_idle_per_host", &self.pool_max_idle_per_host)
+      .field("tcp_keep_alive", &self.tcp_keep_alive)
+      .field("teardown_failures_are_warnings", &self.teardown_failures_are_warnings)
+      .field("header_match_config", &self.header_match_config)
+      .field("resolve_body_files", &self.resolve_body_files)
+      .finish()
+  }
 }
 
 impl <F: RequestFilterExecutor> Default for VerificationOptions<F> {
@@ -551,12 +913,32 @@ impl <F: RequestFilterExecutor> Default for VerificationOptions<F> {
       request_filter: None,
       provider_tags: vec![],
       provider_branch: None,
+      auto_detect_branch: false,
       disable_ssl_verification: false,
-      request_timeout: 5000
+      request_timeout: 5000,
+      state_change_timeout: 5000,
+      request_retries: 0,
+      retry_non_idempotent_requests: false,
+      skip_on_missing_state: false,
+      template_values: hashmap!{},
+      progress_callback: None,
+      redacted_fields: vec![],
+      follow_redirects: false,
+      dry_run: false,
+      provider_state_header: None,
+      user_agent: None,
+      pool_max_idle_per_host: None,
+      tcp_keep_alive: None,
+      teardown_failures_are_warnings: false,
+      header_match_config: HeaderMatchConfig::AllowUnexpectedHeaders,
+      resolve_body_files: false
     }
   }
 }
 
+/// User agent sent with the requests to the provider and Pact Broker, unless overridden
+pub const DEFAULT_USER_AGENT: &str = concat!("pact-rust-verifier/", env!("CARGO_PKG_VERSION"));
+
 const VERIFICATION_NOTICE_BEFORE: &str = "before_verification";
 const VERIFICATION_NOTICE_AFTER_SUCCESSFUL_RESULT_AND_PUBLISH: &str = "after_verification:success_true_published_true";
 const VERIFICATION_NOTICE_AFTER_SUCCESSFUL_RESULT_AND_NO_PUBLISH: &str = "after_verification:success_true_published_false";
@@ -608,7 +990,8 @@ pub async fn verify_provider_async<F: RequestFilterExecutor, S: ProviderStateExe
   pact_matching::matchers::configure_core_catalogue();
 
   LOG_ID.scope(format!("verify:{}", provider_info.name), async {
-    let pact_results = fetch_pacts(source, consumers).await;
+    let pact_results = fetch_pacts(source, consumers, options.request_timeout, options.user_agent.clone(),
+      options.resolve_body_files).await;
 
     let mut results: Vec<(Option<String>, Result<(), MismatchResult>)> = vec![];
     let mut pending_errors: Vec<(String, MismatchResult)> = vec![];
@@ -640,9 +1023,13 @@ pub async fn verify_provider_async<F: RequestFilterExecutor, S: ProviderStateExe
               Some(context) => context.verification_properties.pending,
               None => false
             };
-            match verify_pact_internal(&provider_info, &filter, pact, &options,
-                                       &provider_state_executor.clone(), pending).await {
-              Ok(result) => for result in &result.results {
+            let verification_date = chrono::Utc::now();
+            let start = std::time::Instant::now();
+            let verification_result = verify_pact_internal(&provider_info, &filter, pact, &options,
+                                       &provider_state_executor.clone(), pending).await;
+            let timing = VerificationTiming { verification_date, execution_time: start.elapsed() };
+            match verification_result {
+              Ok(result) => for result in result.results.iter().filter(|result| !result.skipped) {
                 results.push((result.interaction_id.clone(), result.result.clone()));
                 if let Err(error) = &result.result {
                   if result.pending {
@@ -663,8 +1050,8 @@ pub async fn verify_provider_async<F: RequestFilterExecutor, S: ProviderStateExe
               }
             }
 
-            if options.publish {
-              publish_result(&results, &pact_source, &options).await;
+            if options.publish && !options.dry_run {
+              publish_result(&results, &pact_source, &options, Some(timing)).await;
 
               if !errors.is_empty() || !pending_errors.is_empty() {
                 display_notices(&context, VERIFICATION_NOTICE_AFTER_ERROR_RESULT_AND_PUBLISH);
@@ -756,14 +1143,70 @@ fn print_errors(errors: &Vec<(String, MismatchResult)>) {
   }
 }
 
-async fn fetch_pact(source: PactSource) -> Vec<Result<(Box<dyn Pact + Send + Sync>, Option<PactVerificationContext>, PactSource), String>> {
-  trace!("fetch_pact(source={})", source);
+/// Maximum size of a pact file that will be loaded from a URL (50MB)
+const MAX_PACT_URL_RESPONSE_SIZE: usize = 50 * 1024 * 1024;
+
+/// Loads a pact from a URL, failing if the request takes longer than the timeout (in
+/// milliseconds) or the response is larger than the maximum size (in bytes)
+async fn load_pact_from_url(
+  url: &str,
+  auth: &Option<HttpAuth>,
+  timeout: u64,
+  max_size: usize
+) -> anyhow::Result<Box<dyn Pact + Send + Sync>> {
+  let client = reqwest::Client::builder()
+    .timeout(Duration::from_millis(timeout))
+    .build()?;
+  let request = match auth {
+    Some(HttpAuth::User(username, password)) => client.get(url).basic_auth(username, password.clone()),
+    Some(auth @ HttpAuth::Token(_)) | Some(auth @ HttpAuth::TokenFromEnv(_)) => match auth.token() {
+      Some(token) => client.get(url).bearer_auth(token),
+      None => client.get(url)
+    },
+    _ => client.get(url)
+  };
+
+  let request_error = |err: reqwest::Error| if err.is_timeout() {
+    anyhow!("Request timed out after {}ms", timeout)
+  } else {
+    anyhow!("Request failed - {}", err)
+  };
+  let mut response = request.send().await.map_err(request_error)?;
+  if !response.status().is_success() {
+    return Err(anyhow!("Request failed with status - {}", response.status()));
+  }
+
+  let too_large = || anyhow!("Response is larger than the maximum pact size of {} bytes", max_size);
+  if response.content_length().unwrap_or_default() > max_size as u64 {
+    return Err(too_large());
+  }
+  let mut body = vec![];
+  while let Some(chunk) = response.chunk().await.map_err(request_error)? {
+    body.extend_from_slice(&chunk);
+    if body.len() > max_size {
+      return Err(too_large());
+    }
+  }
+
+  let pact_json = serde_json::from_slice(&body)
+    .map_err(|err| anyhow!("Failed to parse JSON - {}", err))?;
+  load_pact_from_json(url, &pact_json)
+}
+
+async fn fetch_pact(
+  source: PactSource,
+  consumers: &[String],
+  request_timeout: u64,
+  user_agent: Option<&str>,
+  resolve_body_files: bool
+) -> Vec<Result<(Box<dyn Pact + Send + Sync>, Option<PactVerificationContext>, PactSource), String>> {
+  trace!("fetch_pact(source={}, consumers={:?}, request_timeout={})", source, consumers, request_timeout);
 
   match source {
-    PactSource::File(ref file) => vec![read_pact(Path::new(&file))
+    PactSource::File(ref file) => vec![read_pact_file(Path::new(&file), resolve_body_files)
       .map_err(|err| format!("Failed to load pact '{}' - {}", file, err))
       .map(|pact| (pact, None, source))],
-    PactSource::Dir(ref dir) => match walkdir(Path::new(dir)) {
+    PactSource::Dir(ref dir) => match walkdir(Path::new(dir), resolve_body_files) {
       Ok(pact_results) => pact_results.into_iter().map(|pact_result| {
           match pact_result {
               Ok(pact) => Ok((pact, None, source.clone())),
@@ -772,14 +1215,24 @@ async fn fetch_pact(source: PactSource) -> Vec<Result<(Box<dyn Pact + Send + Syn
       }).collect(),
       Err(err) => vec![Err(format!("Could not load pacts from directory '{}' - {}", dir, err))]
     },
-    PactSource::URL(ref url, ref auth) => vec![load_pact_from_url(url, auth)
+    PactSource::Zip(ref file) => match archive::read_pacts_from_zip(Path::new(file)) {
+      Ok(pact_results) => pact_results.into_iter().map(|(entry, pact_result)| {
+        pact_result
+          .map(|pact| (pact, None, source.clone()))
+          .map_err(|err| format!("Failed to load pact '{}' from '{}' - {}", entry, file, err))
+      }).collect(),
+      Err(err) => vec![Err(format!("Could not load pacts from zip archive '{}' - {}", file, err))]
+    },
+    PactSource::URL(ref url, ref auth) => vec![load_pact_from_url(url, auth, request_timeout, MAX_PACT_URL_RESPONSE_SIZE).await
       .map_err(|err| format!("Failed to load pact '{}' - {}", url, err))
       .map(|pact| (pact, None, source))],
     PactSource::BrokerUrl(ref provider_name, ref broker_url, ref auth, _) => {
       let result = pact_broker::fetch_pacts_from_broker(
         broker_url.as_str(),
         provider_name.as_str(),
-        auth.clone()
+        auth.clone(),
+        consumers,
+        user_agent
       ).await;
 
       match result {
@@ -808,7 +1261,8 @@ async fn fetch_pact(source: PactSource) -> Vec<Result<(Box<dyn Pact + Send + Syn
         provider_tags,
         provider_branch,
         selectors,
-        auth.clone()
+        auth.clone(),
+        user_agent
       ).await;
 
       match result {
@@ -832,13 +1286,20 @@ async fn fetch_pact(source: PactSource) -> Vec<Result<(Box<dyn Pact + Send + Syn
   }
 }
 
-async fn fetch_pacts(source: Vec<PactSource>, consumers: Vec<String>)
+async fn fetch_pacts(
+  source: Vec<PactSource>,
+  consumers: Vec<String>,
+  request_timeout: u64,
+  user_agent: Option<String>,
+  resolve_body_files: bool
+)
   -> Vec<Result<(Box<dyn Pact + Send + Sync>, Option<PactVerificationContext>, PactSource), String>> {
-  trace!("fetch_pacts(source={}, consumers={:?})", source.iter().map(|s| s.to_string()).join(", "), consumers);
+  trace!("fetch_pacts(source={}, consumers={:?}, request_timeout={})", source.iter().map(|s| s.to_string()).join(", "),
+    consumers, request_timeout);
 
   futures::stream::iter(source)
     .then(|pact_source| async {
-      futures::stream::iter(fetch_pact(pact_source).await)
+      futures::stream::iter(fetch_pact(pact_source, &consumers, request_timeout, user_agent.as_deref(), resolve_body_files).await)
     })
     .flatten()
     .filter(|res| futures::future::ready(filter_consumers(&consumers, res)))
@@ -847,6 +1308,7 @@ async fn fetch_pacts(source: Vec<PactSource>, consumers: Vec<String>)
 }
 
 /// /// Result of verifying a Pact interaction
+#[derive(Debug, Clone)]
 pub struct VerificationInteractionResult {
   /// Interaction ID
   pub interaction_id: Option<String>,
@@ -855,7 +1317,39 @@ pub struct VerificationInteractionResult {
   /// Result of the verification
   pub result: Result<(), MismatchResult>,
   /// If the Pact or interaction is pending
-  pub pending: bool
+  pub pending: bool,
+  /// If the interaction was skipped (i.e. its provider state could not be found)
+  pub skipped: bool
+}
+
+impl VerificationInteractionResult {
+  /// If the interaction failed verification (skipped interactions are not failures)
+  pub fn is_failure(&self) -> bool {
+    !self.skipped && self.result.is_err()
+  }
+
+  /// Converts this result into a JSON structure for use in reports
+  pub fn to_json(&self) -> Value {
+    let mut json = json!({
+      "description": self.description,
+      "success": self.result.is_ok(),
+      "pending": self.pending,
+      "skipped": self.skipped
+    });
+    if let Some(id) = &self.interaction_id {
+      json["interactionId"] = json!(id);
+    }
+    match &self.result {
+      Err(MismatchResult::Mismatches { mismatches, .. }) => {
+        json["mismatches"] = Value::Array(mismatches.iter().map(|mismatch| mismatch.to_json()).collect());
+      }
+      Err(MismatchResult::Error(error, _)) => {
+        json["error"] = json!(error);
+      }
+      Ok(_) => {}
+    }
+    json
+  }
 }
 
 /// Result of verifying a Pact
@@ -864,6 +1358,67 @@ pub struct VerificationResult {
   pub results: Vec<VerificationInteractionResult>
 }
 
+impl VerificationResult {
+  /// Number of interactions that failed and are not pending
+  pub fn failures(&self) -> usize {
+    self.results.iter().filter(|result| result.is_failure() && !result.pending).count()
+  }
+
+  /// Number of interactions that failed but are pending, and so should not fail the build
+  pub fn pending_failures(&self) -> usize {
+    self.results.iter().filter(|result| result.is_failure() && result.pending).count()
+  }
+
+  /// Converts the results into a machine-readable JSON report. Each entry is tagged with its
+  /// pending flag, and the failures and pending failures are counted separately so that CI can
+  /// gate on the real failures only.
+  pub fn to_json(&self) -> Value {
+    json!({
+      "failures": self.failures(),
+      "pendingFailures": self.pending_failures(),
+      "results": self.results.iter().map(|result| result.to_json()).collect::<Vec<Value>>()
+    })
+  }
+}
+
+/// Changes in the interaction results between two verification runs. Interactions are identified
+/// by their ID if they have one, otherwise by their description.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct VerificationResultsDiff {
+  /// Interactions that passed in the previous run, but fail in the current one
+  pub regressed: BTreeSet<String>,
+  /// Interactions that failed in the previous run, but pass in the current one
+  pub fixed: BTreeSet<String>,
+  /// Interactions that failed in both runs
+  pub still_failing: BTreeSet<String>
+}
+
+/// Compares the interaction results from two verification runs. Skipped interactions, and
+/// interactions that are only in one of the runs, are ignored.
+pub fn diff_verification_results(
+  previous: &[VerificationInteractionResult],
+  current: &[VerificationInteractionResult]
+) -> VerificationResultsDiff {
+  let key = |result: &VerificationInteractionResult| result.interaction_id.clone()
+    .unwrap_or_else(|| result.description.clone());
+  let previous: HashMap<String, bool> = previous.iter()
+    .filter(|result| !result.skipped)
+    .map(|result| (key(result), result.is_failure()))
+    .collect();
+
+  let mut diff = VerificationResultsDiff::default();
+  for result in current.iter().filter(|result| !result.skipped) {
+    let key = key(result);
+    match (previous.get(&key), result.is_failure()) {
+      (Some(false), true) => { diff.regressed.insert(key); },
+      (Some(true), false) => { diff.fixed.insert(key); },
+      (Some(true), true) => { diff.still_failing.insert(key); },
+      _ => {}
+    }
+  }
+  diff
+}
+
 /// Internal function, public for testing purposes
 pub async fn verify_pact_internal<'a, F: RequestFilterExecutor, S: ProviderStateExecutor>(
   provider_info: &ProviderInfo,
@@ -874,29 +1429,43 @@ pub async fn verify_pact_internal<'a, F: RequestFilterExecutor, S: ProviderState
   pending: bool
 ) -> anyhow::Result<VerificationResult> {
   let interactions = pact.interactions();
+  let redactions = Redactions::new(&options.redacted_fields);
+  let redactions = &redactions;
+  let clients = &ProviderClients::new(options);
+  let changed = match filter {
+    FilterInfo::ChangedSince(baseline_path) => Some(changed_interactions(pact.as_ref(), baseline_path)?),
+    _ => None
+  };
+  let changed = &changed;
 
-  let results: Vec<(Box<dyn Interaction + Send + Sync>, Result<Option<String>, MismatchResult>)> =
+  let results: Vec<(Box<dyn Interaction + Send + Sync>, Result<InteractionOutcome, MismatchResult>, VerificationInteractionResult)> =
     futures::stream::iter(interactions.iter().map(|i| (&pact, i)))
-    .filter(|(_, interaction)| futures::future::ready(filter_interaction(interaction.as_ref(), filter)))
+    .filter(|(_, interaction)| futures::future::ready(filter_interaction(interaction.as_ref(), filter) &&
+      changed.as_ref().map(|changed| changed.contains(&pact_matching::interaction_key(interaction.as_ref()))).unwrap_or(true)))
     .then( |(pact, interaction)| async move {
-      (interaction.boxed(), verify_interaction(provider_info, interaction.as_ref(), &pact.boxed(), options, provider_state_executor).await)
+      let match_result = if options.dry_run {
+        Ok(InteractionOutcome::Skipped("dry run".to_string()))
+      } else {
+        verify_interaction(provider_info, interaction.as_ref(), &pact.boxed(), options, clients, provider_state_executor).await
+          .map_err(|err| redactions.redact_mismatch_result(&err))
+      };
+      let result = VerificationInteractionResult {
+        interaction_id: interaction.id(),
+        description: interaction_result_description(&pact.consumer().name, &pact.provider().name, interaction.as_ref()),
+        result: match_result.as_ref().map(|_| ()).map_err(|err| err.clone()),
+        pending: pending || interaction.pending(),
+        skipped: matches!(match_result, Ok(InteractionOutcome::Skipped(_)))
+      };
+      if let Some(callback) = &options.progress_callback {
+        callback(result.clone());
+      }
+      (redactions.redact_interaction(interaction.as_ref()), match_result, result)
     })
     .collect()
     .await;
 
   let mut errors: Vec<VerificationInteractionResult> = vec![];
-  for (interaction, match_result) in results {
-    let mut description = format!("Verifying a pact between {} and {}",
-      pact.consumer().name.clone(), pact.provider().name.clone());
-    if let Some((first, elements)) = interaction.provider_states().split_first() {
-      description.push_str(&format!(" Given {}", first.name));
-      for state in elements {
-        description.push_str(&format!(" And {}", state.name));
-      }
-    }
-    description.push_str(" - ");
-    description.push_str(&interaction.description());
-
+  for (interaction, match_result, result) in results {
     println!();
     if interaction.pending() {
       println!("  {} {}", interaction.description(), Yellow.paint("[PENDING]"));
@@ -910,6 +1479,19 @@ pub async fn verify_pact_internal<'a, F: RequestFilterExecutor, S: ProviderState
       }
     }
 
+    let match_result = match match_result {
+      Ok(InteractionOutcome::Skipped(reason)) => {
+        if options.dry_run {
+          display_dry_run_details(interaction.as_ref());
+        }
+        println!("    {} - {}", Yellow.paint("[SKIPPED]"), reason);
+        errors.push(result);
+        continue;
+      }
+      Ok(InteractionOutcome::Verified(id)) => Ok(id),
+      Err(err) => Err(err)
+    };
+
     if let Some(interaction) = interaction.as_request_response() {
       display_request_response_result(&interaction, &match_result)
     }
@@ -917,24 +1499,7 @@ pub async fn verify_pact_internal<'a, F: RequestFilterExecutor, S: ProviderState
       display_message_result(&interaction, &match_result)
     }
 
-    match match_result {
-      Ok(_) => {
-        errors.push(VerificationInteractionResult {
-          interaction_id: interaction.id(),
-          description: description.clone(),
-          result: Ok(()),
-          pending: pending || interaction.pending()
-        });
-      },
-      Err(err) => {
-        errors.push(VerificationInteractionResult {
-          interaction_id: interaction.id(),
-          description: description.clone(),
-          result: Err(err.clone()),
-          pending: pending || interaction.pending()
-        });
-      }
-    }
+    errors.push(result);
   }
 
   println!();
@@ -942,6 +1507,28 @@ pub async fn verify_pact_internal<'a, F: RequestFilterExecutor, S: ProviderState
   Ok(VerificationResult { results: errors })
 }
 
+fn interaction_result_description(consumer: &str, provider: &str, interaction: &dyn Interaction) -> String {
+  let mut description = format!("Verifying a pact between {} and {}", consumer, provider);
+  if let Some((first, elements)) = interaction.provider_states().split_first() {
+    description.push_str(&format!(" Given {}", first.name));
+    for state in elements {
+      description.push_str(&format!(" And {}", state.name));
+    }
+  }
+  description.push_str(" - ");
+  description.push_str(&interaction.description());
+  description
+}
+
+fn display_dry_run_details(interaction: &dyn Interaction) {
+  for state in interaction.provider_states() {
+    println!("    Given {}", state.name);
+  }
+  if let Some(interaction) = interaction.as_v4_http() {
+    println!("    {} {}", interaction.request.method, interaction.request.path);
+  }
+}
+
 fn display_comments(interaction: Box<dyn V4Interaction>) {
   let comments = interaction.comments();
   if !comments.is_empty() {
@@ -971,10 +1558,56 @@ fn display_comments(interaction: Box<dyn V4Interaction>) {
   }
 }
 
+/// Environment variables that CI systems use for the branch being built, in the order they are checked
+const BRANCH_ENV_VARS: [&str; 9] = [
+  "GIT_BRANCH",
+  "CI_COMMIT_REF_NAME",
+  "GITHUB_HEAD_REF",
+  "GITHUB_REF_NAME",
+  "BRANCH_NAME",
+  "CIRCLE_BRANCH",
+  "TRAVIS_BRANCH",
+  "BUILDKITE_BRANCH",
+  "BITBUCKET_BRANCH"
+];
+
+/// Returns the provider branch to publish the results with. An explicitly set branch always
+/// takes precedence over one detected from the environment.
+fn resolve_provider_branch<F: RequestFilterExecutor>(options: &VerificationOptions<F>) -> Option<String> {
+  resolve_provider_branch_with_lookup(options, |name| env::var(name).ok())
+}
+
+/// Returns the provider branch to publish the results with, reading the environment variables
+/// with the given lookup function
+fn resolve_provider_branch_with_lookup<F: RequestFilterExecutor, L: Fn(&str) -> Option<String>>(
+  options: &VerificationOptions<F>,
+  lookup: L
+) -> Option<String> {
+  if options.provider_branch.is_some() || !options.auto_detect_branch {
+    return options.provider_branch.clone();
+  }
+
+  let branch = BRANCH_ENV_VARS.iter()
+    .find_map(|name| lookup(name)
+      .filter(|value| !value.trim().is_empty())
+      .map(|value| (name, value)));
+  match branch {
+    Some((name, value)) => {
+      debug!("Detected provider branch '{}' from environment variable {}", value, name);
+      Some(value)
+    }
+    None => {
+      warn!("Could not detect the provider branch from the environment, results will be published without one");
+      None
+    }
+  }
+}
+
 async fn publish_result<F: RequestFilterExecutor>(
   results: &[(Option<String>, Result<(), MismatchResult>)],
   source: &PactSource,
-  options: &VerificationOptions<F>
+  options: &VerificationOptions<F>,
+  timing: Option<VerificationTiming>
 ) {
   if let PactSource::BrokerUrl(_, broker_url, auth, links) = source.clone() {
     info!("Publishing verification results back to the Pact Broker");
@@ -998,7 +1631,9 @@ async fn publish_result<F: RequestFilterExecutor>(
       provider_version,
       options.build_url.clone(),
       options.provider_tags.clone(),
-      options.provider_branch.clone()
+      resolve_provider_branch(options),
+      timing,
+      options.user_agent.as_deref()
     ).await;
 
     match &publish_result {
@@ -50,7 +50,8 @@ pub(crate) async fn verify_message_from_provider<'a, F: RequestFilterExecutor>(
     .. HttpRequest::default()
   };
 
-  match make_provider_request(provider, &message_request, options, client).await {
+  match make_provider_request(provider, &message_request, &interaction.description(),
+    &interaction.provider_states(), options, client).await {
     Ok(ref actual_response) => {
       let metadata = extract_metadata(actual_response);
       let actual = AsynchronousMessage {
@@ -193,7 +194,8 @@ pub(crate) async fn verify_sync_message_from_provider<'a, F: RequestFilterExecut
     .. HttpRequest::default()
   };
 
-  match make_provider_request(provider, &message_request, options, client).await {
+  match make_provider_request(provider, &message_request, &message.description(),
+    &message.provider_states, options, client).await {
     Ok(ref actual_response) => {
       if actual_response.is_success() {
         let metadata = extract_metadata(actual_response);
@@ -252,7 +254,8 @@ mod tests {
         },
         matching_rules: MatchingRules {
           rules: hashmap!()
-        }
+        },
+        trailers: None
       };
       let expected = hashmap! {
         "contentType".to_string() => Value::String("application/json".to_string())
@@ -276,7 +279,8 @@ mod tests {
         },
         matching_rules: MatchingRules {
           rules: hashmap!()
-        }
+        },
+        trailers: None
       };
       let expected = hashmap! {
         "contentType".to_string() => Value::String("application/json".to_string()), // From actual HTTP response header
@@ -0,0 +1,349 @@
+//! Support for NTLM (NTLMv2) authentication, as used by servers like IIS.
+//!
+//! NTLM is a connection based handshake. The client sends a negotiate (type 1) message, the server
+//! responds with a 401 status and a challenge (type 2) message, and the client then sends an
+//! authenticate (type 3) message computed from the challenge and the user's credentials. The
+//! authenticate message must be sent on the same (kept-alive) connection as the negotiate message.
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use anyhow::anyhow;
+use hmac::{Hmac, Mac};
+use md4::{Digest, Md4};
+use md5::Md5;
+use rand::RngCore;
+
+type HmacMd5 = Hmac<Md5>;
+
+const SIGNATURE: &[u8] = b"NTLMSSP\0";
+const AUTH_SCHEME: &str = "NTLM ";
+
+const NEGOTIATE_UNICODE: u32 = 0x0000_0001;
+const NEGOTIATE_OEM: u32 = 0x0000_0002;
+const REQUEST_TARGET: u32 = 0x0000_0004;
+const NEGOTIATE_NTLM: u32 = 0x0000_0200;
+const NEGOTIATE_DOMAIN_SUPPLIED: u32 = 0x0000_1000;
+const NEGOTIATE_ALWAYS_SIGN: u32 = 0x0000_8000;
+const NEGOTIATE_EXTENDED_SESSION_SECURITY: u32 = 0x0008_0000;
+const NEGOTIATE_TARGET_INFO: u32 = 0x0080_0000;
+
+/// Flags sent in the negotiate message
+const NEGOTIATE_FLAGS: u32 = NEGOTIATE_UNICODE | NEGOTIATE_OEM | REQUEST_TARGET | NEGOTIATE_NTLM |
+  NEGOTIATE_ALWAYS_SIGN | NEGOTIATE_EXTENDED_SESSION_SECURITY;
+/// Flags from the challenge that are kept in the authenticate message. As no session key is sent,
+/// key exchange, signing and sealing are never negotiated.
+const AUTHENTICATE_FLAGS: u32 = NEGOTIATE_UNICODE | REQUEST_TARGET | NEGOTIATE_NTLM |
+  NEGOTIATE_ALWAYS_SIGN | NEGOTIATE_EXTENDED_SESSION_SECURITY | NEGOTIATE_TARGET_INFO;
+
+/// AV pair ID of the server timestamp in the target info
+const MSV_AV_TIMESTAMP: u16 = 7;
+/// AV pair ID that terminates the target info
+const MSV_AV_EOL: u16 = 0;
+
+/// Number of seconds between the Windows epoch (1601-01-01) and the Unix epoch
+const WINDOWS_EPOCH_OFFSET_SECONDS: u64 = 11_644_473_600;
+
+/// Challenge (type 2) message sent by the server
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct ChallengeMessage {
+  /// Flags negotiated by the server
+  pub flags: u32,
+  /// Random challenge from the server
+  pub server_challenge: [u8; 8],
+  /// Target info (AV pairs) from the server
+  pub target_info: Vec<u8>
+}
+
+/// Returns the `Authorization` header value with the negotiate (type 1) message
+pub(crate) fn negotiate_header(domain: &Option<String>) -> String {
+  let domain = domain.as_ref().map(|d| d.to_uppercase().into_bytes()).unwrap_or_default();
+  let flags = if domain.is_empty() {
+    NEGOTIATE_FLAGS
+  } else {
+    NEGOTIATE_FLAGS | NEGOTIATE_DOMAIN_SUPPLIED
+  };
+
+  let mut message = SIGNATURE.to_vec();
+  message.extend_from_slice(&1u32.to_le_bytes());
+  message.extend_from_slice(&flags.to_le_bytes());
+  // The domain follows the 32 byte header, and the workstation is empty
+  write_security_buffer(&mut message, domain.len(), 32);
+  write_security_buffer(&mut message, 0, 32 + domain.len());
+  message.extend_from_slice(&domain);
+
+  format!("{}{}", AUTH_SCHEME, base64::encode(message))
+}
+
+/// Finds the NTLM challenge in the `WWW-Authenticate` header values of a response. Returns `None`
+/// if the server did not respond with a challenge.
+pub(crate) fn find_challenge<'a, I: IntoIterator<Item = &'a str>>(www_authenticate: I) -> Option<&'a str> {
+  www_authenticate.into_iter().find(|value| value.starts_with(AUTH_SCHEME))
+}
+
+/// Parses the challenge (type 2) message from the `WWW-Authenticate` header value
+pub(crate) fn parse_challenge_header(header: &str) -> anyhow::Result<ChallengeMessage> {
+  let encoded = header.strip_prefix(AUTH_SCHEME)
+    .ok_or_else(|| anyhow!("'{}' is not an NTLM challenge", header))?;
+  let data = base64::decode(encoded.trim())
+    .map_err(|err| anyhow!("NTLM challenge is not valid base64 - {}", err))?;
+  parse_challenge(&data)
+}
+
+fn parse_challenge(data: &[u8]) -> anyhow::Result<ChallengeMessage> {
+  if data.len() < 32 || &data[0..8] != SIGNATURE || read_u32(data, 8) != Some(2) {
+    return Err(anyhow!("NTLM challenge is not a valid challenge (type 2) message"));
+  }
+
+  let flags = read_u32(data, 20).unwrap_or_default();
+  let mut server_challenge = [0; 8];
+  server_challenge.copy_from_slice(&data[24..32]);
+  let target_info = if data.len() >= 48 {
+    let length = read_u16(data, 40).unwrap_or_default() as usize;
+    let offset = read_u32(data, 44).unwrap_or_default() as usize;
+    data.get(offset..offset.saturating_add(length))
+      .ok_or_else(|| anyhow!("NTLM challenge target info is outside the message"))?
+      .to_vec()
+  } else {
+    vec![]
+  };
+
+  Ok(ChallengeMessage { flags, server_challenge, target_info })
+}
+
+/// Returns the `Authorization` header value with the authenticate (type 3) message, in response
+/// to the challenge in the `WWW-Authenticate` header value
+pub(crate) fn authenticate_header(
+  user: &str,
+  password: &str,
+  domain: &Option<String>,
+  challenge_header: &str
+) -> anyhow::Result<String> {
+  let challenge = parse_challenge_header(challenge_header)?;
+  let mut client_challenge = [0; 8];
+  rand::thread_rng().fill_bytes(&mut client_challenge);
+  let timestamp = SystemTime::now().duration_since(UNIX_EPOCH)
+    .map(|duration| (duration.as_secs() + WINDOWS_EPOCH_OFFSET_SECONDS) * 10_000_000 +
+      duration.subsec_nanos() as u64 / 100)
+    .unwrap_or_default();
+  let message = authenticate_message(user, password, domain.as_deref().unwrap_or_default(),
+    &challenge, client_challenge, timestamp);
+  Ok(format!("{}{}", AUTH_SCHEME, base64::encode(message)))
+}
+
+fn authenticate_message(
+  user: &str,
+  password: &str,
+  domain: &str,
+  challenge: &ChallengeMessage,
+  client_challenge: [u8; 8],
+  timestamp: u64
+) -> Vec<u8> {
+  let response_key = ntowf_v2(user, password, domain);
+  // If the server sent a timestamp, it must be used and the LM response is not sent
+  let (timestamp, lm_response) = match server_timestamp(&challenge.target_info) {
+    Some(server_timestamp) => (server_timestamp, vec![0; 24]),
+    None => (timestamp, lmv2_response(&response_key, &challenge.server_challenge, &client_challenge))
+  };
+  let nt_response = ntlmv2_response(&response_key, &challenge.server_challenge, &client_challenge,
+    timestamp, &challenge.target_info);
+
+  let domain = utf16_bytes(domain);
+  let user = utf16_bytes(user);
+  let flags = challenge.flags & AUTHENTICATE_FLAGS | NEGOTIATE_UNICODE;
+
+  let mut message = SIGNATURE.to_vec();
+  message.extend_from_slice(&3u32.to_le_bytes());
+  let mut offset = 64;
+  for field in [&lm_response, &nt_response, &domain, &user] {
+    write_security_buffer(&mut message, field.len(), offset);
+    offset += field.len();
+  }
+  // Empty workstation and session key
+  write_security_buffer(&mut message, 0, offset);
+  write_security_buffer(&mut message, 0, offset);
+  message.extend_from_slice(&flags.to_le_bytes());
+  for field in [lm_response, nt_response, domain, user] {
+    message.extend_from_slice(&field);
+  }
+  message
+}
+
+/// Computes the NTLMv2 response key from the credentials
+fn ntowf_v2(user: &str, password: &str, domain: &str) -> Vec<u8> {
+  let password_hash = Md4::digest(utf16_bytes(password));
+  let user_domain = utf16_bytes(&format!("{}{}", user.to_uppercase(), domain));
+  hmac_md5(&password_hash, &[&user_domain])
+}
+
+fn lmv2_response(response_key: &[u8], server_challenge: &[u8], client_challenge: &[u8]) -> Vec<u8> {
+  let mut response = hmac_md5(response_key, &[server_challenge, client_challenge]);
+  response.extend_from_slice(client_challenge);
+  response
+}
+
+fn ntlmv2_response(
+  response_key: &[u8],
+  server_challenge: &[u8],
+  client_challenge: &[u8],
+  timestamp: u64,
+  target_info: &[u8]
+) -> Vec<u8> {
+  let mut temp = vec![1, 1, 0, 0, 0, 0, 0, 0];
+  temp.extend_from_slice(&timestamp.to_le_bytes());
+  temp.extend_from_slice(client_challenge);
+  temp.extend_from_slice(&[0; 4]);
+  temp.extend_from_slice(target_info);
+  temp.extend_from_slice(&[0; 4]);
+
+  let mut response = hmac_md5(response_key, &[server_challenge, &temp]);
+  response.extend_from_slice(&temp);
+  response
+}
+
+/// Finds the server timestamp in the target info AV pairs
+fn server_timestamp(target_info: &[u8]) -> Option<u64> {
+  let mut offset = 0;
+  while let (Some(id), Some(length)) = (read_u16(target_info, offset), read_u16(target_info, offset + 2)) {
+    let value = target_info.get(offset + 4..offset + 4 + length as usize)?;
+    match id {
+      MSV_AV_EOL => return None,
+      MSV_AV_TIMESTAMP if value.len() == 8 => {
+        let mut timestamp = [0; 8];
+        timestamp.copy_from_slice(value);
+        return Some(u64::from_le_bytes(timestamp));
+      }
+      _ => offset += 4 + length as usize
+    }
+  }
+  None
+}
+
+fn hmac_md5(key: &[u8], data: &[&[u8]]) -> Vec<u8> {
+  let mut mac = HmacMd5::new_from_slice(key).expect("HMAC can take a key of any size");
+  for part in data {
+    mac.update(part);
+  }
+  mac.finalize().into_bytes().to_vec()
+}
+
+fn utf16_bytes(value: &str) -> Vec<u8> {
+  value.encode_utf16().flat_map(|ch| ch.to_le_bytes()).collect()
+}
+
+fn write_security_buffer(message: &mut Vec<u8>, length: usize, offset: usize) {
+  message.extend_from_slice(&(length as u16).to_le_bytes());
+  message.extend_from_slice(&(length as u16).to_le_bytes());
+  message.extend_from_slice(&(offset as u32).to_le_bytes());
+}
+
+fn read_u16(data: &[u8], offset: usize) -> Option<u16> {
+  data.get(offset..offset + 2).map(|bytes| u16::from_le_bytes([bytes[0], bytes[1]]))
+}
+
+fn read_u32(data: &[u8], offset: usize) -> Option<u32> {
+  data.get(offset..offset + 4).map(|bytes| u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+}
+
+#[cfg(test)]
+mod tests {
+  use expectest::prelude::*;
+
+  use super::*;
+
+  // Test vectors are from the NTLMv2 authentication example in section 4.2.4 of [MS-NLMP]
+  const SERVER_CHALLENGE: [u8; 8] = [0x01, 0x23, 0x45, 0x67, 0x89, 0xab, 0xcd, 0xef];
+  const CLIENT_CHALLENGE: [u8; 8] = [0xaa; 8];
+
+  fn target_info() -> Vec<u8> {
+    let mut target_info = vec![0x02, 0x00, 0x0c, 0x00];
+    target_info.extend(utf16_bytes("Domain"));
+    target_info.extend([0x01, 0x00, 0x0c, 0x00]);
+    target_info.extend(utf16_bytes("Server"));
+    target_info.extend([0x00, 0x00, 0x00, 0x00]);
+    target_info
+  }
+
+  fn challenge_message(target_info: &[u8]) -> Vec<u8> {
+    let mut message = SIGNATURE.to_vec();
+    message.extend_from_slice(&2u32.to_le_bytes());
+    write_security_buffer(&mut message, 0, 48);
+    message.extend_from_slice(&(NEGOTIATE_FLAGS | NEGOTIATE_TARGET_INFO).to_le_bytes());
+    message.extend_from_slice(&SERVER_CHALLENGE);
+    message.extend_from_slice(&[0; 8]);
+    write_security_buffer(&mut message, target_info.len(), 48);
+    message.extend_from_slice(target_info);
+    message
+  }
+
+  #[test]
+  fn negotiate_header_test() {
+    expect!(negotiate_header(&None)).to(be_equal_to("NTLM TlRMTVNTUAABAAAAB4IIAAAAAAAgAAAAAAAAACAAAAA="));
+    expect!(negotiate_header(&Some("corp".to_string())))
+      .to(be_equal_to("NTLM TlRMTVNTUAABAAAAB5IIAAQABAAgAAAAAAAAACQAAABDT1JQ"));
+  }
+
+  #[test]
+  fn ntlmv2_responses_match_the_specification_example() {
+    let response_key = ntowf_v2("User", "Password", "Domain");
+    expect!(hex::encode(&response_key)).to(be_equal_to("0c868a403bfd7a93a3001ef22ef02e3f"));
+    expect!(hex::encode(lmv2_response(&response_key, &SERVER_CHALLENGE, &CLIENT_CHALLENGE)))
+      .to(be_equal_to("86c35097ac9cec102554764a57cccc19aaaaaaaaaaaaaaaa"));
+    let nt_response = ntlmv2_response(&response_key, &SERVER_CHALLENGE, &CLIENT_CHALLENGE, 0, &target_info());
+    expect!(hex::encode(&nt_response[0..16])).to(be_equal_to("68cd0ab851e51c96aabc927bebef6a1c"));
+  }
+
+  #[test]
+  fn parse_challenge_header_test() {
+    let header = format!("NTLM {}", base64::encode(challenge_message(&target_info())));
+    expect!(parse_challenge_header(&header)).to(be_ok().value(ChallengeMessage {
+      flags: NEGOTIATE_FLAGS | NEGOTIATE_TARGET_INFO,
+      server_challenge: SERVER_CHALLENGE,
+      target_info: target_info()
+    }));
+
+    expect!(parse_challenge_header("Basic realm=\"broker\"")).to(be_err());
+    expect!(parse_challenge_header(&negotiate_header(&None))).to(be_err());
+    let mut truncated = challenge_message(&target_info());
+    truncated.truncate(60);
+    expect!(parse_challenge_header(&format!("NTLM {}", base64::encode(truncated)))).to(be_err());
+  }
+
+  #[test]
+  fn find_challenge_test() {
+    expect!(find_challenge(vec!["Negotiate", "NTLM"])).to(be_none());
+    expect!(find_challenge(vec!["Negotiate", "NTLM TlRMTVNTUAACAAAA"])).to(be_some().value("NTLM TlRMTVNTUAACAAAA"));
+  }
+
+  #[test]
+  fn authenticate_message_test() {
+    let challenge = parse_challenge(&challenge_message(&target_info())).unwrap();
+    let message = authenticate_message("User", "Password", "Domain", &challenge, CLIENT_CHALLENGE, 0);
+
+    expect!(&message[0..12]).to(be_equal_to(b"NTLMSSP\0\x03\0\0\0".as_ref()));
+    let field = |index: usize| {
+      let length = read_u16(&message, 12 + index * 8).unwrap() as usize;
+      let offset = read_u32(&message, 16 + index * 8).unwrap() as usize;
+      message[offset..offset + length].to_vec()
+    };
+    expect!(hex::encode(field(0))).to(be_equal_to("86c35097ac9cec102554764a57cccc19aaaaaaaaaaaaaaaa"));
+    expect!(hex::encode(&field(1)[0..16])).to(be_equal_to("68cd0ab851e51c96aabc927bebef6a1c"));
+    expect!(field(2)).to(be_equal_to(utf16_bytes("Domain")));
+    expect!(field(3)).to(be_equal_to(utf16_bytes("User")));
+    expect!(field(4).iter()).to(be_empty());
+    expect!(read_u32(&message, 60)).to(be_some().value(challenge.flags & AUTHENTICATE_FLAGS | NEGOTIATE_UNICODE));
+  }
+
+  #[test]
+  fn authenticate_message_uses_the_server_timestamp() {
+    let mut target_info = vec![0x07, 0x00, 0x08, 0x00];
+    target_info.extend(0x01d0_0000_0000_0000u64.to_le_bytes());
+    target_info.extend([0x00, 0x00, 0x00, 0x00]);
+    let challenge = parse_challenge(&challenge_message(&target_info)).unwrap();
+    let message = authenticate_message("User", "Password", "Domain", &challenge, CLIENT_CHALLENGE, 0);
+
+    expect!(read_u16(&message, 12)).to(be_some().value(24));
+    expect!(&message[64..88]).to(be_equal_to([0u8; 24].as_ref()));
+    // The timestamp follows the NT proof (16 bytes) and the 8 byte header of the NTLMv2 response
+    expect!(&message[88 + 24..88 + 32]).to(be_equal_to(0x01d0_0000_0000_0000u64.to_le_bytes().as_ref()));
+  }
+}
@@ -1,7 +1,10 @@
 //! Structs and functions for interacting with a Pact Broker
 
 use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
 
+use anyhow::anyhow;
+use chrono::{DateTime, NaiveDate, NaiveDateTime, Utc};
 use futures::stream::*;
 use itertools::Itertools;
 use log::*;
@@ -11,6 +14,7 @@ use pact_models::pact::{load_pact_from_json, Pact};
 use pact_models::PACT_RUST_VERSION;
 use regex::{Captures, Regex};
 use reqwest::{Method, RequestBuilder};
+#[cfg(feature = "ntlm")] use reqwest::header::{AUTHORIZATION, WWW_AUTHENTICATE};
 use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
 use serde_with::skip_serializing_none;
@@ -18,7 +22,8 @@ use tokio::time::{Duration, sleep};
 
 use pact_matching::Mismatch;
 
-use crate::MismatchResult;
+use crate::{DEFAULT_USER_AGENT, MismatchResult};
+#[cfg(feature = "ntlm")] use crate::ntlm;
 
 use super::provider_client::join_paths;
 
@@ -189,6 +194,25 @@ impl HALClient {
     HALClient { url: url.to_string(), auth, ..HALClient::default() }
   }
 
+  /// Sets the user agent to send with the requests to the Pact Broker. By default, this is
+  /// `pact-rust-verifier/<version>`. Returns an error if the HTTP client could not be created.
+  pub fn with_user_agent(self, user_agent: &str) -> Result<HALClient, PactBrokerError> {
+    let client = reqwest::ClientBuilder::new()
+      .user_agent(user_agent)
+      .build()
+      .map_err(|err| PactBrokerError::IoError(format!("Failed to create the HTTP client - {}", err)))?;
+    Ok(HALClient { client, .. self })
+  }
+
+  /// Initialise a client with the URL and authentication, and the user agent if one is given
+  fn with_url_and_user_agent(url: &str, auth: Option<HttpAuth>, user_agent: Option<&str>) -> Result<HALClient, PactBrokerError> {
+    let client = HALClient::with_url(url, auth);
+    match user_agent {
+      Some(user_agent) => client.with_user_agent(user_agent),
+      None => Ok(client)
+    }
+  }
+
   fn update_path_info(self, path_info: serde_json::Value) -> HALClient {
     HALClient {
       client: self.client.clone(),
@@ -289,13 +313,16 @@ impl HALClient {
     let request_builder = match self.auth {
         Some(ref auth) => match auth {
             HttpAuth::User(username, password) => self.client.get(url).basic_auth(username, password.clone()),
-            HttpAuth::Token(token) => self.client.get(url).bearer_auth(token),
+            HttpAuth::Token(_) | HttpAuth::TokenFromEnv(_) => match auth.token() {
+                Some(token) => self.client.get(url).bearer_auth(token),
+                None => self.client.get(url)
+            },
             _ => self.client.get(url)
         },
         None => self.client.get(url)
     }.header("accept", "application/hal+json, application/json");
 
-    let response = with_retries(self.retries, request_builder).await
+    let response = self.send_request(request_builder).await
       .map_err(|err| {
           PactBrokerError::IoError(format!("Failed to access pact broker path '{}' - {}. URL: '{}'",
               &path,
@@ -429,9 +456,12 @@ impl HALClient {
         HttpAuth::User(username, password) => self.client
           .request(method, url.clone())
           .basic_auth(username, password.clone()),
-        HttpAuth::Token(token) => self.client
-          .request(method, url.clone())
-          .bearer_auth(token),
+        HttpAuth::Token(_) | HttpAuth::TokenFromEnv(_) => match auth.token() {
+          Some(token) => self.client
+            .request(method, url.clone())
+            .bearer_auth(token),
+          None => self.client.request(method, url.clone())
+        },
         _ => self.client.request(method, url.clone())
       },
       None => self.client.request(method, url.clone())
@@ -441,7 +471,7 @@ impl HALClient {
       .header("Accept", "application/json")
       .body(body.to_string());
 
-    let response = with_retries(self.retries, request_builder)
+    let response = self.send_request(request_builder)
       .await
       .map_err(|err| PactBrokerError::IoError(
         format!("Failed to send JSON to the pact broker URL '{}' - {}", url, err)
@@ -460,6 +490,68 @@ impl HALClient {
       }
   }
 
+  /// Sends the request to the broker, using NTLM authentication if it is configured
+  async fn send_request(&self, request_builder: RequestBuilder) -> anyhow::Result<reqwest::Response> {
+    match &self.auth {
+      Some(HttpAuth::Ntlm { user, password, domain }) =>
+        self.send_with_ntlm(request_builder, user, password, domain).await,
+      _ => Ok(with_retries(self.retries, request_builder).await?)
+    }
+  }
+
+  /// Sends the request with NTLM authentication. The negotiate message is sent with the request,
+  /// and if the broker responds with a challenge, the request is sent again with the authenticate
+  /// message. The body of the challenge response is read first, so that the connection is returned
+  /// to the pool and reused for the second request (NTLM authenticates the connection).
+  #[cfg(feature = "ntlm")]
+  async fn send_with_ntlm(
+    &self,
+    request_builder: RequestBuilder,
+    user: &str,
+    password: &str,
+    domain: &Option<String>
+  ) -> anyhow::Result<reqwest::Response> {
+    let authenticate_request = request_builder.try_clone()
+      .ok_or_else(|| anyhow!("Request can not be sent with NTLM authentication as it can not be cloned"))?;
+    let response = with_retries(self.retries,
+      request_builder.header(AUTHORIZATION, ntlm::negotiate_header(domain))).await?;
+    let challenge = ntlm::find_challenge(response.headers().get_all(WWW_AUTHENTICATE).iter()
+      .filter_map(|value| value.to_str().ok()))
+      .map(|challenge| challenge.to_string());
+    match challenge {
+      Some(challenge) => {
+        debug!("Received an NTLM challenge from the pact broker, sending the authenticate message");
+        let _ = response.bytes().await;
+        let header = ntlm::authenticate_header(user, password, domain, &challenge)?;
+        Ok(with_retries(self.retries, authenticate_request.header(AUTHORIZATION, header)).await?)
+      }
+      None => Ok(response)
+    }
+  }
+
+  /// NTLM authentication is only supported if the `ntlm` feature is enabled
+  #[cfg(not(feature = "ntlm"))]
+  async fn send_with_ntlm(
+    &self,
+    _request_builder: RequestBuilder,
+    _user: &str,
+    _password: &str,
+    _domain: &Option<String>
+  ) -> anyhow::Result<reqwest::Response> {
+    Err(anyhow!("NTLM authentication is not supported, as the verifier was built without the 'ntlm' feature"))
+  }
+
+  /// Expands the link URL with the template values, and resolves it against the broker URL (links
+  /// may be relative to the broker)
+  fn resolve_link_url(&self, link: &Link, values: &HashMap<String, String>) -> Result<String, PactBrokerError> {
+    let link_url = self.clone().parse_link_url(link, values)?;
+    let base_url = self.url.parse::<reqwest::Url>()
+      .map_err(|err| PactBrokerError::UrlError(format!("{}", err)))?;
+    base_url.join(&link_url)
+      .map(|url| url.to_string())
+      .map_err(|err| PactBrokerError::UrlError(format!("{}", err)))
+  }
+
   fn with_doc_context(self, doc_attributes: &[Link]) -> Result<HALClient, PactBrokerError> {
     let links: serde_json::Map<String, serde_json::Value> = doc_attributes.iter()
       .map(|link| (link.name.clone(), link.as_json())).collect();
@@ -530,6 +622,7 @@ impl Default for HALClient {
   fn default() -> Self {
     HALClient {
       client: reqwest::ClientBuilder::new()
+        .user_agent(DEFAULT_USER_AGENT)
         .build()
         .unwrap(),
       url: "".to_string(),
@@ -555,16 +648,37 @@ fn links_from_json(json: &serde_json::Value) -> Vec<Link> {
   }
 }
 
-/// Fetches the pacts from the broker that match the provider name
+/// Returns the consumer name for a pact link, using either the consumer segment of the link URL
+/// or the link title (i.e. "Pact between Consumer (1.0.0) and Provider")
+fn consumer_name_from_link(link: &Link) -> Option<String> {
+  link.href.as_ref()
+    .and_then(|href| {
+      let segments = href.split('/').collect_vec();
+      segments.iter().position(|segment| *segment == "consumer")
+        .and_then(|index| segments.get(index + 1))
+        .filter(|name| !name.is_empty())
+        .map(|name| urlencoding::decode(name).map(|name| name.to_string()).unwrap_or_else(|_| name.to_string()))
+    })
+    .or_else(|| link.title.as_ref()
+      .and_then(|title| title.strip_prefix("Pact between "))
+      .and_then(|title| title.split(" (").next())
+      .map(|name| name.to_string()))
+}
+
+/// Fetches the pacts from the broker that match the provider name. If any consumer names are
+/// provided, only the pacts for those consumers will be fetched. Pact links where the consumer
+/// name can not be determined are always fetched.
 pub async fn fetch_pacts_from_broker(
   broker_url: &str,
   provider_name: &str,
-  auth: Option<HttpAuth>
+  auth: Option<HttpAuth>,
+  consumers: &[String],
+  user_agent: Option<&str>
 ) -> anyhow::Result<Vec<anyhow::Result<(Box<dyn Pact + Send + Sync>, Option<PactVerificationContext>, Vec<Link>)>>> {
-  trace!("fetch_pacts_from_broker(broker_url='{}', provider_name='{}', auth={})", broker_url,
-    provider_name, auth.clone().unwrap_or_default());
+  trace!("fetch_pacts_from_broker(broker_url='{}', provider_name='{}', auth={}, consumers={:?})", broker_url,
+    provider_name, auth.clone().unwrap_or_default(), consumers);
 
-    let mut hal_client = HALClient::with_url(broker_url, auth);
+    let mut hal_client = HALClient::with_url_and_user_agent(broker_url, auth, user_agent)?;
     let template_values = hashmap!{ "provider".to_string() => provider_name.to_string() };
 
     hal_client = hal_client.navigate("pb:latest-provider-pacts", &template_values)
@@ -579,7 +693,12 @@ pub async fn fetch_pacts_from_broker(
             }
         })?;
 
-    let pact_links = hal_client.clone().iter_links("pacts")?;
+    let pact_links = hal_client.clone().iter_links("pacts")?
+      .into_iter()
+      .filter(|link| consumers.is_empty() || consumer_name_from_link(link)
+        .map(|name| consumers.contains(&name))
+        .unwrap_or(true))
+      .collect_vec();
 
     let results: Vec<_> = futures::stream::iter(pact_links)
         .map(|ref pact_link| {
@@ -621,6 +740,20 @@ pub async fn fetch_pacts_from_broker(
     Ok(results)
 }
 
+/// Validates that the include WIP pacts since value is an ISO-8601 date (i.e. 2021-01-31) or
+/// date-time (i.e. 2021-01-31T10:00:00Z)
+fn validate_include_wip_pacts_since(since: &str) -> Result<(), PactBrokerError> {
+  if NaiveDate::parse_from_str(since, "%Y-%m-%d").is_ok() ||
+    DateTime::parse_from_rfc3339(since).is_ok() ||
+    NaiveDateTime::parse_from_str(since, "%Y-%m-%dT%H:%M:%S%.f").is_ok() {
+    Ok(())
+  } else {
+    Err(PactBrokerError::ContentError(format!(
+      "Include WIP pacts since value '{}' is not a valid ISO-8601 date (i.e. 2021-01-31 or 2021-01-31T10:00:00Z)",
+      since)))
+  }
+}
+
 /// Fetch Pacts from the broker using the "provider-pacts-for-verification" endpoint
 pub async fn fetch_pacts_dynamically_from_broker(
   broker_url: &str,
@@ -630,14 +763,19 @@ pub async fn fetch_pacts_dynamically_from_broker(
   provider_tags: Vec<String>,
   provider_branch: Option<String>,
   consumer_version_selectors: Vec<ConsumerVersionSelector>,
-  auth: Option<HttpAuth>
+  auth: Option<HttpAuth>,
+  user_agent: Option<&str>
 ) -> Result<Vec<Result<(Box<dyn Pact + Send + Sync>, Option<PactVerificationContext>, Vec<Link>), PactBrokerError>>, PactBrokerError> {
   trace!("fetch_pacts_dynamically_from_broker(broker_url='{}', provider_name='{}', pending={}, \
-    include_wip_pacts_since={:?}, provider_tags: {:?}, consumer_version_selectors: {:?}, auth={})",
+    include_wip_pacts_since={:?}, provider_tags: {:?}, consumer_version_selectors: [{}], auth={})",
     broker_url, provider_name, pending, include_wip_pacts_since, provider_tags,
-    consumer_version_selectors, auth.clone().unwrap_or_default());
+    consumer_version_selectors.iter().join(", "), auth.clone().unwrap_or_default());
+
+    if let Some(since) = &include_wip_pacts_since {
+      validate_include_wip_pacts_since(since)?;
+    }
 
-    let mut hal_client = HALClient::with_url(broker_url, auth);
+    let mut hal_client = HALClient::with_url_and_user_agent(broker_url, auth, user_agent)?;
     let template_values = hashmap!{ "provider".to_string() => provider_name.clone() };
 
     hal_client = hal_client.navigate("pb:provider-pacts-for-verification", &template_values)
@@ -772,6 +910,15 @@ impl TestResult {
   }
 }
 
+/// Timing data captured while verifying a pact
+#[derive(Debug, Clone, PartialEq)]
+pub struct VerificationTiming {
+  /// When the verification was started
+  pub verification_date: DateTime<Utc>,
+  /// How long the verification took to run
+  pub execution_time: Duration
+}
+
 /// Publishes the result to the "pb:publish-verification-results" link in the links associated with the pact
 pub async fn publish_verification_results(
   links: Vec<Link>,
@@ -781,9 +928,11 @@ pub async fn publish_verification_results(
   version: String,
   build_url: Option<String>,
   provider_tags: Vec<String>,
-  branch: Option<String>
+  branch: Option<String>,
+  timing: Option<VerificationTiming>,
+  user_agent: Option<&str>
 ) -> Result<serde_json::Value, PactBrokerError> {
-  let hal_client = HALClient::with_url(broker_url, auth.clone());
+  let hal_client = HALClient::with_url_and_user_agent(broker_url, auth.clone(), user_agent)?;
 
   if branch.is_some() {
     publish_provider_branch(&hal_client, &links, &branch.unwrap(), &version).await?;
@@ -801,11 +950,16 @@ pub async fn publish_verification_results(
           "Response from the pact broker has no 'pb:publish-verification-results' link".into()
       ))?;
 
-  let json = build_payload(result, version, build_url);
+  let json = build_payload(result, version, build_url, timing);
   hal_client.post_json(publish_link.href.unwrap_or_default().as_str(), json.to_string().as_str()).await
 }
 
-fn build_payload(result: TestResult, version: String, build_url: Option<String>) -> serde_json::Value {
+pub(crate) fn build_payload(
+  result: TestResult,
+  version: String,
+  build_url: Option<String>,
+  timing: Option<VerificationTiming>
+) -> serde_json::Value {
   let mut json = json!({
     "success": result.to_bool(),
     "providerApplicationVersion": version,
@@ -820,6 +974,11 @@ fn build_payload(result: TestResult, version: String, build_url: Option<String>)
     json_obj.insert("buildUrl".into(), json!(build_url.unwrap()));
   }
 
+  if let Some(timing) = timing {
+    json_obj.insert("verificationDate".into(), json!(timing.verification_date.to_rfc3339()));
+    json_obj.insert("executionTime".into(), json!(timing.execution_time.as_millis() as u64));
+  }
+
   match result {
     TestResult::Failed(mismatches) => {
       let values = mismatches.iter()
@@ -869,6 +1028,11 @@ fn build_payload(result: TestResult, version: String, build_url: Option<String>)
                         "attribute": "metadata",
                         "identifier": key,
                         "description": mismatch
+                      })),
+                      Mismatch::InteractionMismatch { interaction, mismatch } => acc.0.push(json!({
+                        "attribute": "interaction",
+                        "identifier": interaction,
+                        "description": mismatch
                       }))
                     }
                   }
@@ -964,6 +1128,59 @@ async fn publish_provider_branch(
   }
 }
 
+/// Publishes the pact to the "pb:publish-pact" link from the root of the Pact Broker. If a branch
+/// or any tags are given, they are applied to the consumer version before the pact is published.
+pub async fn publish_pact(
+  broker_url: &str,
+  auth: Option<HttpAuth>,
+  pact: &dyn Pact,
+  consumer_version: &str,
+  tags: Vec<String>,
+  branch: Option<String>,
+  user_agent: Option<&str>
+) -> Result<serde_json::Value, PactBrokerError> {
+  let hal_client = HALClient::with_url_and_user_agent(broker_url, auth, user_agent)?;
+  let index = hal_client.clone().fetch("/").await?;
+  let hal_client = hal_client.update_path_info(index);
+  let consumer = pact.consumer().name;
+
+  if let Some(branch) = branch {
+    let link = hal_client.find_link("pb:pacticipant-branch-version")
+      .map_err(|_| PactBrokerError::LinkError("Can't publish the consumer branch as there is no 'pb:pacticipant-branch-version' link. Please upgrade to Pact Broker version 2.86.0 or later for branch support".to_string()))?;
+    let template_values = hashmap! {
+      "pacticipant".to_string() => consumer.clone(),
+      "branch".to_string() => branch.clone(),
+      "version".to_string() => consumer_version.to_string()
+    };
+    hal_client.put_json(&hal_client.resolve_link_url(&link, &template_values)?, "{}").await?;
+    debug!("Pushed branch {} for consumer version {}", branch, consumer_version);
+  }
+
+  if !tags.is_empty() {
+    let link = hal_client.find_link("pb:pacticipant-version-tag")
+      .map_err(|_| PactBrokerError::LinkError("Can't publish the consumer tags as there is no 'pb:pacticipant-version-tag' link".to_string()))?;
+    for tag in &tags {
+      let template_values = hashmap! {
+        "pacticipant".to_string() => consumer.clone(),
+        "version".to_string() => consumer_version.to_string(),
+        "tag".to_string() => tag.clone()
+      };
+      hal_client.put_json(&hal_client.resolve_link_url(&link, &template_values)?, "{}").await?;
+      debug!("Pushed tag {} for consumer version {}", tag, consumer_version);
+    }
+  }
+
+  let link = hal_client.find_link("pb:publish-pact")?;
+  let template_values = hashmap! {
+    "provider".to_string() => pact.provider().name,
+    "consumer".to_string() => consumer,
+    "consumerApplicationVersion".to_string() => consumer_version.to_string()
+  };
+  let json = pact.to_json(pact.specification_version())
+    .map_err(|err| PactBrokerError::ContentError(format!("Failed to convert the pact to JSON - {}", err)))?;
+  hal_client.put_json(&hal_client.resolve_link_url(&link, &template_values)?, json.to_string().as_str()).await
+}
+
 #[skip_serializing_none]
 #[derive(Serialize, Deserialize, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
@@ -989,10 +1206,39 @@ pub struct ConsumerVersionSelector {
   pub main_branch: Option<bool>,
   /// Applications with the given branch
   pub branch: Option<String>,
+  /// Fallback branch if Branch doesn't exist
+  pub fallback_branch: Option<String>,
   /// Applications that match the the provider version branch sent during verification
   pub matching_branch: Option<bool>,
 }
 
+impl Display for ConsumerVersionSelector {
+  fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+    let strings = [
+      ("consumer", self.consumer.clone()),
+      ("tag", self.tag.clone()),
+      ("fallbackTag", self.fallback_tag.clone()),
+      ("branch", self.branch.clone()),
+      ("fallbackBranch", self.fallback_branch.clone()),
+      ("mainBranch", self.main_branch.map(|v| v.to_string())),
+      ("matchingBranch", self.matching_branch.map(|v| v.to_string())),
+      ("environment", self.environment.clone()),
+      ("latest", self.latest.map(|v| v.to_string())),
+      ("deployed", self.deployed.map(|v| v.to_string())),
+      ("released", self.released.map(|v| v.to_string())),
+      ("deployedOrReleased", self.deployed_or_released.map(|v| v.to_string()))
+    ];
+    let description = strings.iter()
+      .filter_map(|(name, value)| value.as_ref().map(|value| format!("{}={}", name, value)))
+      .join(" ");
+    if description.is_empty() {
+      write!(f, "<all>")
+    } else {
+      write!(f, "{}", description)
+    }
+  }
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
 struct PactsForVerificationResponse {
@@ -1114,6 +1360,47 @@ mod tests {
       pact_broker.url())));
   }
 
+  #[tokio::test]
+  #[cfg(feature = "ntlm")]
+  async fn fetch_completes_the_ntlm_handshake_for_ntlm_auth() {
+    let pact_broker = PactBuilder::new("RustPactVerifier", "PactBrokerStub")
+      .interaction("a request with an NTLM negotiate message", "", |mut i| {
+        i.request
+          .path("/")
+          .header("Authorization", "NTLM TlRMTVNTUAABAAAAB5IIAAQABAAgAAAAAAAAACQAAABDT1JQ");
+        i.response
+          .status(401)
+          .header("WWW-Authenticate", "NTLM TlRMTVNTUAACAAAAAAAAADAAAAAHgogAASNFZ4mrze8AAAAAAAAAACQAJAAwAAAAAgAMAEQAbwBtAGEAaQBuAAEADABTAGUAcgB2AGUAcgAAAAAA");
+        futures::future::ready(i)
+      })
+      .await
+      .interaction("a request with an NTLM authenticate message", "", |mut i| {
+        i.request
+          .path("/")
+          .header("Authorization", term!("^NTLM TlRMTVNTUAADAAAA.+$", "NTLM TlRMTVNTUAADAAAA"));
+        i.response
+          .header("Content-Type", "application/hal+json")
+          .json_body(json_pattern!({ "_links": {} }));
+        futures::future::ready(i)
+      })
+      .await
+      .start_mock_server();
+
+    let auth = HttpAuth::Ntlm { user: "test".to_string(), password: "pass".to_string(), domain: Some("corp".to_string()) };
+    let client = HALClient::with_url(pact_broker.url().as_str(), Some(auth));
+    let result = client.fetch("/").await;
+    expect!(result).to(be_ok());
+  }
+
+  #[tokio::test]
+  #[cfg(not(feature = "ntlm"))]
+  async fn fetch_returns_an_error_for_ntlm_auth_without_the_ntlm_feature() {
+    let auth = HttpAuth::Ntlm { user: "test".to_string(), password: "pass".to_string(), domain: None };
+    let client = HALClient::with_url("http://localhost:1234", Some(auth));
+    let result = client.fetch("/").await;
+    expect!(result.unwrap_err().to_string().contains("built without the 'ntlm' feature")).to(be_true());
+  }
+
     #[test]
     fn content_type_test() {
         let response = reqwest::Response::from(
@@ -1470,7 +1757,7 @@ mod tests {
             .start_mock_server();
 
         let result = fetch_pacts_from_broker(pact_broker.url().as_str(),
-                                             "sad_provider", None).await;
+                                             "sad_provider", None, &[], None).await;
         match result {
           Ok(_) => {
             panic!("Expected an error result, but got OK");
@@ -1482,6 +1769,42 @@ mod tests {
         }
     }
 
+    #[tokio::test]
+    async fn fetch_pacts_from_broker_sends_the_user_agent() {
+      try_init().unwrap_or(());
+      let pact_broker = PactBuilder::new("RustPactVerifier", "PactBroker")
+        .interaction("a request to the pact broker root with a user agent", "", |mut i| async move {
+          i.request
+            .path("/")
+            .header("User-Agent", "my-pipeline/1.0");
+          i.response
+            .header("Content-Type", "application/hal+json")
+            .json_body(json_pattern!({
+              "_links": {
+                "pb:latest-provider-pacts": {
+                  "href": "http://localhost/pacts/provider/{provider}/latest",
+                  "templated": true,
+                }
+              }
+            }));
+          i
+        })
+        .await
+        .interaction("a request for a providers pacts with a user agent", "", |mut i| async move {
+          i.request
+            .path("/pacts/provider/sad_provider/latest")
+            .header("User-Agent", "my-pipeline/1.0");
+          i.response.status(404);
+          i
+        })
+        .await
+        .start_mock_server();
+
+      let result = fetch_pacts_from_broker(pact_broker.url().as_str(), "sad_provider", None, &[],
+        Some("my-pipeline/1.0")).await;
+      expect!(result.unwrap_err().to_string().starts_with("Link/Resource was not found - No pacts for provider 'sad_provider'")).to(be_true());
+    }
+
     #[tokio::test]
     async fn fetch_pacts_from_broker_returns_a_list_of_pacts() {
       try_init().unwrap_or(());
@@ -1559,7 +1882,7 @@ mod tests {
             .start_mock_server();
 
         let result = fetch_pacts_from_broker(pact_broker.url().as_str(),
-          "happy_provider", None).await;
+          "happy_provider", None, &[], None).await;
         match &result {
           Ok(_) => (),
           Err(err) => panic!("Expected an Ok result, got a error {}", err)
@@ -1574,6 +1897,105 @@ mod tests {
         }
     }
 
+    #[tokio::test]
+    async fn fetch_pacts_from_broker_only_fetches_the_pacts_for_the_requested_consumers() {
+      try_init().unwrap_or(());
+      let pact = RequestResponsePact { consumer: Consumer { name: "Consumer2".to_string() },
+        provider: Provider { name: "happy_provider".to_string() },
+        .. RequestResponsePact::default() }
+        .to_json(PactSpecification::V3).unwrap().to_string();
+      let pact_broker = PactBuilder::new("RustPactVerifier", "PactBroker")
+        .interaction("a request to the pact broker root", "", |mut i| async move {
+          i.request
+            .path("/")
+            .header("Accept", "application/hal+json")
+            .header("Accept", "application/json");
+          i.response
+            .header("Content-Type", "application/hal+json")
+            .json_body(json_pattern!({
+              "_links": {
+                "pb:latest-provider-pacts": {
+                  "href": "http://localhost/pacts/provider/{provider}/latest",
+                  "templated": true,
+                }
+              }
+            }));
+          i
+        })
+        .await
+        .interaction("a request for a providers pacts", "", |mut i| async move {
+          i.given("There are three pacts in the pact broker");
+          i.request
+            .path("/pacts/provider/happy_provider/latest")
+            .header("Accept", "application/hal+json")
+            .header("Accept", "application/json");
+          i.response
+            .header("Content-Type", "application/hal+json")
+            .json_body(json_pattern!({
+              "_links":{
+                "pacts":[
+                  {"href":"http://localhost/pacts/provider/happy_provider/consumer/Consumer/version/1.0.0"},
+                  {"href":"http://localhost/pacts/provider/happy_provider/consumer/Consumer2/version/1.0.0"},
+                  {"href":"http://localhost/pacts/provider/happy_provider/consumer/Consumer3/version/1.0.0"}
+                ]
+              }
+            }));
+          i
+        })
+        .await
+        .interaction("a request for the second provider pact", "", |mut i| async move {
+          i.given("There are three pacts in the pact broker");
+          i.request
+            .path("/pacts/provider/happy_provider/consumer/Consumer2/version/1.0.0")
+            .header("Accept", "application/hal+json")
+            .header("Accept", "application/json");
+          i.response
+            .header("Content-Type", "application/json")
+            .body(pact.clone());
+          i
+        })
+        .await
+        .start_mock_server();
+
+      let result = fetch_pacts_from_broker(pact_broker.url().as_str(),
+        "happy_provider", None, &["Consumer2".to_string()], None).await.unwrap();
+      let consumers = result.iter()
+        .map(|pact| pact.as_ref().unwrap().0.consumer().name)
+        .collect_vec();
+      expect!(consumers).to(be_equal_to(vec!["Consumer2".to_string()]));
+    }
+
+    #[test]
+    fn consumer_version_selector_display_test() {
+      let selector: ConsumerVersionSelector = serde_json::from_value(json!({
+        "branch": "main", "latest": true
+      })).unwrap();
+      expect!(selector.to_string()).to(be_equal_to("branch=main latest=true"));
+
+      let selector: ConsumerVersionSelector = serde_json::from_value(json!({
+        "environment": "prod", "deployed": true
+      })).unwrap();
+      expect!(selector.to_string()).to(be_equal_to("environment=prod deployed=true"));
+
+      let selector: ConsumerVersionSelector = serde_json::from_value(json!({
+        "consumer": "Consumer", "tag": "feat-x", "fallbackTag": "master", "latest": true
+      })).unwrap();
+      expect!(selector.to_string()).to(be_equal_to("consumer=Consumer tag=feat-x fallbackTag=master latest=true"));
+
+      let selector: ConsumerVersionSelector = serde_json::from_value(json!({})).unwrap();
+      expect!(selector.to_string()).to(be_equal_to("<all>"));
+    }
+
+    #[test]
+    fn consumer_name_from_link_test() {
+      let link = Link { name: "pacts".to_string(), href: Some("http://localhost/pacts/provider/p/consumer/a%20b/version/1".to_string()), templated: false, title: None };
+      expect!(consumer_name_from_link(&link)).to(be_some().value("a b"));
+      let link = Link { name: "pacts".to_string(), href: Some("http://localhost/pacts/1234".to_string()), templated: false, title: Some("Pact between Consumer (1.0.0) and Provider".to_string()) };
+      expect!(consumer_name_from_link(&link)).to(be_some().value("Consumer"));
+      let link = Link { name: "pacts".to_string(), href: Some("http://localhost/pacts/1234".to_string()), templated: false, title: None };
+      expect!(consumer_name_from_link(&link)).to(be_none());
+    }
+
     #[tokio::test]
     async fn fetch_pacts_for_verification_from_broker_returns_a_list_of_pacts() {
       try_init().unwrap_or(());
@@ -1701,8 +2123,9 @@ mod tests {
         released: None,
         main_branch: None,
         matching_branch: None,
+        fallback_branch: None,
         environment: None,
-      }), None).await;
+      }), None, None).await;
 
       match &result {
         Ok(_) => (),
@@ -1801,8 +2224,9 @@ mod tests {
       released: None,
       main_branch: None,
       matching_branch: None,
+      fallback_branch: None,
       environment: None,
-    }), None).await;
+    }), None, None).await;
 
     match result {
       Ok(_) => {
@@ -1815,10 +2239,30 @@ mod tests {
     }
   }
 
+  #[test]
+  fn validate_include_wip_pacts_since_accepts_iso_8601_dates() {
+    expect!(validate_include_wip_pacts_since("2021-01-31")).to(be_ok());
+    expect!(validate_include_wip_pacts_since("2021-01-31T10:00:00Z")).to(be_ok());
+    expect!(validate_include_wip_pacts_since("2021-01-31T10:00:00+10:00")).to(be_ok());
+    expect!(validate_include_wip_pacts_since("2021-01-31T10:00:00.123")).to(be_ok());
+    expect!(validate_include_wip_pacts_since("2021-02-31")).to(be_err());
+    expect!(validate_include_wip_pacts_since("31/01/2021")).to(be_err());
+  }
+
+  #[tokio::test]
+  async fn fetch_pacts_for_verification_from_broker_rejects_an_invalid_include_wip_pacts_since_date() {
+    let result = fetch_pacts_dynamically_from_broker("http://localhost:1234", "provider".to_string(),
+      false, Some("last tuesday".to_string()), vec![], None, vec![], None, None).await;
+
+    expect!(result.map(|pacts| pacts.len()).map_err(|err| err.to_string())).to(be_err().value(
+      "Error with the content of a HAL resource - Include WIP pacts since value 'last tuesday' is not a valid \
+      ISO-8601 date (i.e. 2021-01-31 or 2021-01-31T10:00:00Z)".to_string()));
+  }
+
   #[test]
   fn test_build_payload_with_success() {
     let result = TestResult::Ok(vec![]);
-    let payload = super::build_payload(result, "1".to_string(), None);
+    let payload = super::build_payload(result, "1".to_string(), None, None);
     expect!(payload).to(be_equal_to(json!({
       "providerApplicationVersion": "1",
       "success": true,
@@ -1833,7 +2277,7 @@ mod tests {
   #[test]
   fn test_build_payload_adds_the_build_url_if_provided() {
     let result = TestResult::Ok(vec![]);
-    let payload = super::build_payload(result, "1".to_string(), Some("http://build-url".to_string()));
+    let payload = super::build_payload(result, "1".to_string(), Some("http://build-url".to_string()), None);
     expect!(payload).to(be_equal_to(json!({
       "providerApplicationVersion": "1",
       "success": true,
@@ -1846,10 +2290,31 @@ mod tests {
     })));
   }
 
+  #[test]
+  fn test_build_payload_adds_the_timing_data_if_provided() {
+    let result = TestResult::Ok(vec![]);
+    let timing = VerificationTiming {
+      verification_date: chrono::DateTime::parse_from_rfc3339("2021-10-12T01:02:03Z").unwrap().with_timezone(&Utc),
+      execution_time: Duration::from_millis(1234)
+    };
+    let payload = super::build_payload(result, "1".to_string(), None, Some(timing));
+    expect!(payload).to(be_equal_to(json!({
+      "providerApplicationVersion": "1",
+      "success": true,
+      "verificationDate": "2021-10-12T01:02:03+00:00",
+      "executionTime": 1234,
+      "testResults": [],
+      "verifiedBy": {
+        "implementation": "Pact-Rust",
+        "version": PACT_RUST_VERSION
+      }
+    })));
+  }
+
   #[test]
   fn test_build_payload_adds_a_result_for_each_interaction() {
     let result = TestResult::Ok(vec![Some("1".to_string()), Some("2".to_string()), Some("3".to_string()), None]);
-    let payload = super::build_payload(result, "1".to_string(), Some("http://build-url".to_string()));
+    let payload = super::build_payload(result, "1".to_string(), Some("http://build-url".to_string()), None);
     expect!(payload).to(be_equal_to(json!({
       "providerApplicationVersion": "1",
       "success": true,
@@ -1869,7 +2334,7 @@ mod tests {
   #[test]
   fn test_build_payload_with_failure() {
     let result = TestResult::Failed(vec![]);
-    let payload = super::build_payload(result, "1".to_string(), None);
+    let payload = super::build_payload(result, "1".to_string(), None, None);
     expect!(payload).to(be_equal_to(json!({
       "providerApplicationVersion": "1",
       "success": false,
@@ -1893,7 +2358,7 @@ mod tests {
         interaction_id: Some("1234abc".to_string())
       }))
     ]);
-    let payload = super::build_payload(result, "1".to_string(), None);
+    let payload = super::build_payload(result, "1".to_string(), None, None);
     expect!(payload).to(be_equal_to(json!({
       "providerApplicationVersion": "1",
       "success": false,
@@ -1920,7 +2385,7 @@ mod tests {
     let result = TestResult::Failed(vec![
       (Some("1234abc".to_string()), Some(MismatchResult::Error("Bang".to_string(), Some("1234abc".to_string()))))
     ]);
-    let payload = super::build_payload(result, "1".to_string(), None);
+    let payload = super::build_payload(result, "1".to_string(), None, None);
     expect!(payload).to(be_equal_to(json!({
       "providerApplicationVersion": "1",
       "success": false,
@@ -1956,7 +2421,7 @@ mod tests {
       (Some("12345678".to_string()), Some(MismatchResult::Error("Bang".to_string(), Some("1234abc".to_string())))),
       (Some("abc123".to_string()), None)
     ]);
-    let payload = super::build_payload(result, "1".to_string(), None);
+    let payload = super::build_payload(result, "1".to_string(), None, None);
     expect!(payload).to(be_equal_to(json!({
       "providerApplicationVersion": "1",
       "success": false,
@@ -2032,4 +2497,111 @@ mod tests {
     expect!(json.to_string()).to(be_equal_to(
       "{\"href\":\"1234\",\"templated\":true,\"title\":\"title\"}"));
   }
+
+  #[test]
+  fn consumer_version_selector_with_a_fallback_branch_serialises_to_json() {
+    let selector = ConsumerVersionSelector {
+      consumer: None,
+      tag: None,
+      fallback_tag: None,
+      latest: None,
+      deployed_or_released: None,
+      deployed: None,
+      released: None,
+      environment: None,
+      main_branch: None,
+      branch: Some("feat/x".to_string()),
+      matching_branch: None,
+      fallback_branch: Some("main".to_string())
+    };
+    let request = PactsForVerificationRequest {
+      provider_version_tags: vec![],
+      include_pending_status: false,
+      include_wip_pacts_since: None,
+      consumer_version_selectors: vec![ selector ],
+      provider_version_branch: None
+    };
+
+    let json = serde_json::to_value(&request).unwrap();
+    expect!(json.clone()).to(be_equal_to(json!({
+      "providerVersionTags": [],
+      "includePendingStatus": false,
+      "consumerVersionSelectors": [
+        { "branch": "feat/x", "fallbackBranch": "main" }
+      ]
+    })));
+
+    let request: PactsForVerificationRequest = serde_json::from_value(json).unwrap();
+    let selector = &request.consumer_version_selectors[0];
+    expect!(selector.branch.clone()).to(be_some().value("feat/x"));
+    expect!(selector.fallback_branch.clone()).to(be_some().value("main"));
+    expect!(selector.to_string()).to(be_equal_to("branch=feat/x fallbackBranch=main"));
+  }
+
+  #[tokio::test]
+  async fn publish_pact_applies_the_branch_and_tags_and_puts_the_pact_to_the_publish_link() {
+    try_init().unwrap_or(());
+    let pact = RequestResponsePact {
+      consumer: Consumer { name: "Consumer".to_string() },
+      provider: Provider { name: "Provider".to_string() },
+      interactions: vec![ RequestResponseInteraction { description: "a request".to_string(), .. RequestResponseInteraction::default() } ],
+      .. RequestResponsePact::default()
+    };
+    let pact_json = pact.to_json(PactSpecification::V3).unwrap();
+
+    let pact_broker = PactBuilder::new("RustPactVerifier", "PactBroker")
+      .interaction("a request to the pact broker root for the publish links", "", |mut i| async move {
+        i.request.path("/");
+        i.response
+          .header("Content-Type", "application/hal+json")
+          .json_body(json_pattern!({
+            "_links": {
+              "pb:publish-pact": {
+                "href": "/pacts/provider/{provider}/consumer/{consumer}/version/{consumerApplicationVersion}",
+                "templated": true
+              },
+              "pb:pacticipant-branch-version": {
+                "href": "/pacticipants/{pacticipant}/branches/{branch}/versions/{version}",
+                "templated": true
+              },
+              "pb:pacticipant-version-tag": {
+                "href": "/pacticipants/{pacticipant}/versions/{version}/tags/{tag}",
+                "templated": true
+              }
+            }
+          }));
+        i
+      })
+      .await
+      .interaction("a request to add the consumer version to a branch", "", |mut i| async move {
+        i.request.put().path("/pacticipants/Consumer/branches/main/versions/1.0.0");
+        i.response.ok();
+        i
+      })
+      .await
+      .interaction("a request to tag the consumer version", "", |mut i| async move {
+        i.request.put().path("/pacticipants/Consumer/versions/1.0.0/tags/prod");
+        i.response.ok();
+        i
+      })
+      .await
+      .interaction("a request to publish the pact", "", |mut i| {
+        let pact_json = pact_json.clone();
+        async move {
+          i.request.put()
+            .path("/pacts/provider/Provider/consumer/Consumer/version/1.0.0")
+            .json_body(pact_json);
+          i.response
+            .header("Content-Type", "application/hal+json")
+            .json_body(json_pattern!({ "_links": {} }));
+          i
+        }
+      })
+      .await
+      .start_mock_server();
+
+    let result = publish_pact(pact_broker.url().as_str(), None, &pact, "1.0.0",
+      vec!["prod".to_string()], Some("main".to_string()), None).await;
+    expect!(result).to(be_ok().value(json!({ "_links": {} })));
+  }
 }
@@ -1,15 +1,18 @@
 use std::collections::hash_map::HashMap;
 use std::convert::TryFrom;
+use std::io::Read;
+use std::time::{Duration, Instant};
 
-use anyhow::anyhow;
-use futures::future::*;
 use http::{HeaderMap, HeaderValue, Method};
 use http::header::{HeaderName, InvalidHeaderName, InvalidHeaderValue};
 use http::header::CONTENT_TYPE;
 use http::method::InvalidMethod;
+use flate2::read::{GzDecoder, ZlibDecoder};
 use itertools::Itertools;
 use log::*;
+use rand::Rng;
 use reqwest::{Client, Error, RequestBuilder};
+use tokio::time::sleep;
 
 use pact_models::bodies::OptionalBody;
 use pact_models::content_types::ContentType;
@@ -156,18 +159,65 @@ async fn native_response_to_pact_response(native_response: reqwest::Response) ->
 
   let body = extract_body(native_response, &response).await?;
 
-  let response = HttpResponse {
+  let response = decompress_body(HttpResponse {
     body, .. response.clone()
-  };
+  });
   info!("Received response: {}", response);
   Ok(response)
 }
 
+/// Decompresses the response body if it has a gzip or deflate content encoding, so that it can be
+/// compared to the expected body. The content length header is updated to be the length of the
+/// decompressed body, otherwise it would not match the expected one.
+fn decompress_body(response: HttpResponse) -> HttpResponse {
+  let encoding = response.headers.as_ref()
+    .and_then(|headers| headers.get("content-encoding"))
+    .and_then(|values| values.first())
+    .map(|value| value.to_lowercase());
+  let compressed = match &response.body {
+    OptionalBody::Present(body, _, _) => body.clone(),
+    _ => return response
+  };
+
+  let mut buffer = vec![];
+  let result = match encoding.as_deref() {
+    Some("gzip") | Some("x-gzip") => GzDecoder::new(compressed.as_ref()).read_to_end(&mut buffer),
+    Some("deflate") => ZlibDecoder::new(compressed.as_ref()).read_to_end(&mut buffer),
+    _ => return response
+  };
+  match result {
+    Ok(length) => {
+      debug!("Decompressed {} response body from {} to {} bytes", encoding.unwrap_or_default(),
+        compressed.len(), length);
+      let headers = response.headers.clone().map(|headers| headers.iter()
+        .map(|(key, values)| if key == "content-length" {
+          (key.clone(), vec![length.to_string()])
+        } else {
+          (key.clone(), values.clone())
+        })
+        .collect());
+      HttpResponse {
+        body: OptionalBody::Present(buffer.into(), response.content_type(), None),
+        headers,
+        .. response
+      }
+    }
+    Err(err) => {
+      warn!("Failed to decompress the {} response body, it will be compared as-is - {}",
+        encoding.unwrap_or_default(), err);
+      response
+    }
+  }
+}
+
 /// This function makes the actual request to the provider, executing any request filter before
-/// executing the request
+/// executing the request. If the `provider_state_header` option is set, the provider states are
+/// added to the request in that header.
 pub async fn make_provider_request<F: RequestFilterExecutor>(
   provider: &ProviderInfo,
   request: &HttpRequest,
+  description: &str,
+  provider_states: &[ProviderState],
   options: &VerificationOptions<F>,
   client: &reqwest::Client
 ) -> anyhow::Result<HttpResponse> {
@@ -179,28 +229,72 @@ pub async fn make_provider_request<F: RequestFilterExecutor>(
   } else {
     request.clone()
   };
+  let mut request = HttpRequest {
+    path: join_paths(provider.base_path_for(description), &request.path),
+    .. request
+  };
+  if let Some(header) = &options.provider_state_header {
+    if !provider_states.is_empty() {
+      let states = Value::Array(provider_states.iter().map(|state| state.to_json()).collect());
+      request.headers.get_or_insert_with(HashMap::new)
+        .insert(header.clone(), vec![states.to_string()]);
+    }
+  }
 
   let base_url = match provider.port {
-    Some(port) => format!("{}://{}:{}{}", provider.protocol, provider.host, port, provider.path),
-    None => format!("{}://{}{}", provider.protocol, provider.host, provider.path),
+    Some(port) => format!("{}://{}:{}", provider.protocol, provider.host, port),
+    None => format!("{}://{}", provider.protocol, provider.host),
   };
 
   info!("Sending request to provider at {}", base_url);
   debug!("Provider details = {:?}", provider);
   debug!("Sending request {}", request);
   trace!("body: {}", request.body.str_value());
-  let request = create_native_request(client, &base_url, &request)?;
 
-  let response = request.send()
-    .map_err(|err| anyhow!(err))
-    .and_then(native_response_to_pact_response)
-    .await?;
+  let retries = if options.retry_non_idempotent_requests || is_idempotent(&request.method) {
+    options.request_retries
+  } else {
+    0
+  };
+  let timeout = Duration::from_millis(options.request_timeout);
+  let start = Instant::now();
+  let mut attempt = 0;
+  let response = loop {
+    let result = create_native_request(client, &base_url, &request)?.send().await;
+    let should_retry = match &result {
+      Ok(response) => response.status().is_server_error(),
+      Err(err) => err.is_connect()
+    };
+    if should_retry && attempt < retries {
+      let delay = retry_delay(attempt);
+      if start.elapsed() + delay < timeout {
+        warn!("Request to provider failed ({}), retrying in {}ms (attempt {}/{})",
+          result.as_ref().map(|res| res.status().to_string()).unwrap_or_else(|err| err.to_string()),
+          delay.as_millis(), attempt + 1, retries);
+        sleep(delay).await;
+        attempt += 1;
+        continue;
+      }
+    }
+    break native_response_to_pact_response(result?).await?;
+  };
 
   debug!("response from call to provider = {:?}", response);
 
   Ok(response)
 }
 
+/// If the HTTP method is idempotent, and the request can be safely retried
+fn is_idempotent(method: &str) -> bool {
+  !matches!(method.to_uppercase().as_str(), "POST" | "PATCH")
+}
+
+/// Exponential backoff (starting at 100ms) with a random jitter of up to the same amount again
+fn retry_delay(attempt: u8) -> Duration {
+  let base = 100_u64 * 2_u64.pow(attempt.min(10) as u32);
+  Duration::from_millis(base + rand::thread_rng().gen_range(0..=base))
+}
+
 /// Make a state change request. If the response returns a JSON body, convert that into a HashMap
 /// and return it
 pub async fn make_state_change_request(
@@ -259,7 +353,11 @@ mod tests {
   use pact_models::bodies::OptionalBody;
   use pact_models::v4::http_parts::HttpRequest;
 
-  use super::{create_native_request, extract_headers, join_paths};
+  use pact_consumer::prelude::*;
+
+  use crate::{NullRequestFilterExecutor, ProviderInfo, VerificationOptions};
+
+  use super::{create_native_request, extract_headers, join_paths, make_provider_request};
 
   #[test]
   fn extract_headers_tests() {
@@ -299,6 +397,115 @@ mod tests {
     expect!(join_paths("/a/b", "/c/d")).to(be_equal_to("/a/b/c/d"));
   }
 
+  async fn provider_request_path(provider_path: &str, request_path: &str, expected_path: &str) -> u16 {
+    let provider_server = PactBuilder::new("RustPactVerifier", "Provider")
+      .interaction(format!("a request to {}", expected_path), String::default(), |mut i| async move {
+        i.request.path(expected_path);
+        i.response.status(204);
+        i
+      })
+      .await
+      .start_mock_server();
+
+    let provider = ProviderInfo {
+      port: provider_server.url().port(),
+      path: provider_path.to_string(),
+      .. ProviderInfo::default()
+    };
+    let request = HttpRequest { path: request_path.to_string(), .. HttpRequest::default() };
+    let options: VerificationOptions<NullRequestFilterExecutor> = VerificationOptions::default();
+    let client = reqwest::Client::new();
+    make_provider_request(&provider, &request, "", &[], &options, &client).await.unwrap().status
+  }
+
+  #[tokio::test]
+  async fn make_provider_request_uses_the_base_path_mapped_to_the_interaction() {
+    let provider_server = PactBuilder::new("RustPactVerifier", "Provider")
+      .interaction("a request to /api/v1/items", "", |mut i| async move {
+        i.request.path("/api/v1/items");
+        i.response.status(204);
+        i
+      })
+      .await
+      .interaction("a request to /api/v2/items", "", |mut i| async move {
+        i.request.path("/api/v2/items");
+        i.response.status(204);
+        i
+      })
+      .await
+      .start_mock_server();
+
+    let provider = ProviderInfo {
+      port: provider_server.url().port(),
+      path: "/api".to_string(),
+      path_mappings: vec![
+        ("^v1 ".to_string(), "/api/v1".to_string()),
+        ("^v2 ".to_string(), "/api/v2".to_string())
+      ],
+      .. ProviderInfo::default()
+    };
+    let request = HttpRequest { path: "/items".to_string(), .. HttpRequest::default() };
+    let options: VerificationOptions<NullRequestFilterExecutor> = VerificationOptions::default();
+    let client = reqwest::Client::new();
+
+    let response = make_provider_request(&provider, &request, "v1 list items", &[], &options, &client).await.unwrap();
+    expect!(response.status).to(be_equal_to(204));
+    let response = make_provider_request(&provider, &request, "v2 list items", &[], &options, &client).await.unwrap();
+    expect!(response.status).to(be_equal_to(204));
+  }
+
+  #[test]
+  fn base_path_for_falls_back_to_the_provider_path() {
+    let provider = ProviderInfo {
+      path: "/api".to_string(),
+      path_mappings: vec![ ("^v1 ".to_string(), "/api/v1".to_string()) ],
+      .. ProviderInfo::default()
+    };
+    expect!(provider.base_path_for("v1 list items")).to(be_equal_to("/api/v1"));
+    expect!(provider.base_path_for("list items")).to(be_equal_to("/api"));
+  }
+
+  #[test]
+  fn provider_info_from_url_test() {
+    let provider = ProviderInfo::from_url("https://api.example.com:8443/base").unwrap();
+    expect!(provider.protocol).to(be_equal_to("https"));
+    expect!(provider.host).to(be_equal_to("api.example.com"));
+    expect!(provider.port).to(be_some().value(8443));
+    expect!(provider.path).to(be_equal_to("/base"));
+
+    let provider = ProviderInfo::from_url("https://api.example.com").unwrap();
+    expect!(provider.port).to(be_some().value(443));
+    expect!(provider.path).to(be_equal_to("/"));
+
+    let provider = ProviderInfo::from_url("http://localhost/api/v1/").unwrap();
+    expect!(provider.protocol).to(be_equal_to("http"));
+    expect!(provider.host).to(be_equal_to("localhost"));
+    expect!(provider.port).to(be_some().value(80));
+    expect!(provider.path).to(be_equal_to("/api/v1/"));
+
+    let provider = ProviderInfo::from_url("http://127.0.0.1:8000").unwrap();
+    expect!(provider.host).to(be_equal_to("127.0.0.1"));
+    expect!(provider.port).to(be_some().value(8000));
+  }
+
+  #[test]
+  fn provider_info_from_url_returns_an_error_for_invalid_urls() {
+    expect!(ProviderInfo::from_url("not a url")).to(be_err());
+    expect!(ProviderInfo::from_url("localhost:8080")).to(be_err());
+    expect!(ProviderInfo::from_url("ftp://example.com/files").unwrap_err().to_string()).to(
+      be_equal_to("Provider URL 'ftp://example.com/files' must be an HTTP or HTTPS URL"));
+  }
+
+  #[tokio::test]
+  async fn make_provider_request_joins_the_provider_path_with_the_request_path() {
+    expect!(provider_request_path("/", "/items", "/items").await).to(be_equal_to(204));
+    expect!(provider_request_path("/", "items", "/items").await).to(be_equal_to(204));
+    expect!(provider_request_path("/api", "/items", "/api/items").await).to(be_equal_to(204));
+    expect!(provider_request_path("/api", "items", "/api/items").await).to(be_equal_to(204));
+    expect!(provider_request_path("/api/", "/items", "/api/items").await).to(be_equal_to(204));
+    expect!(provider_request_path("api", "/items", "/api/items").await).to(be_equal_to(204));
+  }
+
   #[test]
   fn convert_request_to_native_request_test() {
     let client = reqwest::Client::new();
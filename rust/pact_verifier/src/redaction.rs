@@ -0,0 +1,477 @@
+//! Redaction of sensitive values (like tokens or personal data) from the verification output.
+//! Values are only redacted in what is displayed or published, matching is not affected.
+
+use std::collections::HashMap;
+
+use bytes::Bytes;
+use log::*;
+use serde_json::Value;
+
+use pact_matching::Mismatch;
+use pact_models::bodies::OptionalBody;
+use pact_models::interaction::Interaction;
+use pact_models::path_exp::DocPath;
+
+use crate::MismatchResult;
+
+/// Value used in place of redacted values
+pub(crate) const REDACTED: &str = "***";
+
+/// A value from a redacted body path, along with the tokens of the path it was found at
+type SensitiveValue = (Vec<String>, String);
+
+/// Header names and body paths of the values to redact
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Redactions {
+  headers: Vec<String>,
+  paths: Vec<DocPath>
+}
+
+impl Redactions {
+  /// Create the redactions from a list of header names and JSON paths. Any entry starting with
+  /// `$` is treated as a path into the body, and any other entry as a header (or message metadata)
+  /// name.
+  pub fn new(fields: &[String]) -> Self {
+    let mut redactions = Redactions::default();
+    for field in fields {
+      if field.starts_with('$') {
+        match DocPath::new(field) {
+          Ok(path) => redactions.paths.push(path),
+          Err(err) => warn!("Ignoring invalid path '{}' to redact - {}", field, err)
+        }
+      } else {
+        redactions.headers.push(field.to_lowercase());
+      }
+    }
+    redactions
+  }
+
+  /// If there is nothing to redact
+  pub fn is_empty(&self) -> bool {
+    self.headers.is_empty() && self.paths.is_empty()
+  }
+
+  fn is_redacted_header(&self, name: &str) -> bool {
+    self.headers.contains(&name.to_lowercase())
+  }
+
+  fn is_redacted_path(&self, path: &str) -> bool {
+    DocPath::new(path)
+      .map(|path| {
+        let tokens = path.to_vec();
+        let tokens = tokens.iter().map(|t| t.as_str()).collect::<Vec<_>>();
+        self.paths.iter().any(|p| p.matches_path(&tokens))
+      })
+      .unwrap_or(false)
+  }
+
+  /// Returns a copy of the mismatch result with all the redacted values replaced
+  pub fn redact_mismatch_result(&self, result: &MismatchResult) -> MismatchResult {
+    if self.is_empty() {
+      return result.clone();
+    }
+
+    match result {
+      MismatchResult::Mismatches { mismatches, expected, actual, interaction_id } => {
+        let mut values = self.sensitive_values(expected.as_ref());
+        values.extend(self.sensitive_values(actual.as_ref()));
+        MismatchResult::Mismatches {
+          mismatches: mismatches.iter().map(|m| self.redact_mismatch(m, &values)).collect(),
+          expected: self.redact_interaction(expected.as_ref()),
+          actual: self.redact_interaction(actual.as_ref()),
+          interaction_id: interaction_id.clone()
+        }
+      }
+      MismatchResult::Error(_, _) => result.clone()
+    }
+  }
+
+  /// Returns a copy of the interaction with the values of the redacted headers, metadata and
+  /// body paths replaced
+  pub fn redact_interaction(&self, interaction: &dyn Interaction) -> Box<dyn Interaction + Send + Sync> {
+    match interaction.as_v4() {
+      Some(mut v4) if !self.is_empty() => {
+        if let Some(http) = v4.as_v4_http_mut() {
+          self.redact_headers(&mut http.request.headers);
+          self.redact_body(&mut http.request.body);
+          self.redact_headers(&mut http.response.headers);
+          self.redact_body(&mut http.response.body);
+        } else if let Some(message) = v4.as_v4_async_message_mut() {
+          self.redact_metadata(&mut message.contents.metadata);
+          self.redact_body(&mut message.contents.contents);
+        } else if let Some(message) = v4.as_v4_sync_message_mut() {
+          for contents in std::iter::once(&mut message.request).chain(message.response.iter_mut()) {
+            self.redact_metadata(&mut contents.metadata);
+            self.redact_body(&mut contents.contents);
+          }
+        }
+        v4.boxed()
+      }
+      _ => interaction.boxed()
+    }
+  }
+
+  fn redact_headers(&self, headers: &mut Option<HashMap<String, Vec<String>>>) {
+    if let Some(headers) = headers {
+      for (name, values) in headers.iter_mut() {
+        if self.is_redacted_header(name) {
+          *values = values.iter().map(|_| REDACTED.to_string()).collect();
+        }
+      }
+    }
+  }
+
+  fn redact_metadata(&self, metadata: &mut HashMap<String, Value>) {
+    for (key, value) in metadata.iter_mut() {
+      if self.is_redacted_header(key) {
+        *value = Value::String(REDACTED.to_string());
+      }
+    }
+  }
+
+  fn redact_body(&self, body: &mut OptionalBody) {
+    if self.paths.is_empty() {
+      return;
+    }
+    if let OptionalBody::Present(bytes, content_type, hint) = body {
+      if let Ok(mut json) = serde_json::from_slice::<Value>(bytes) {
+        self.redact_json(&DocPath::root(), &mut json);
+        *body = OptionalBody::Present(Bytes::from(json.to_string()), content_type.clone(), *hint);
+      }
+    }
+  }
+
+  fn redact_json(&self, path: &DocPath, json: &mut Value) {
+    if self.is_redacted_json_path(path) {
+      *json = Value::String(REDACTED.to_string());
+      return;
+    }
+    match json {
+      Value::Object(map) => for (key, value) in map.iter_mut() {
+        let mut item_path = path.clone();
+        item_path.push_field(key);
+        self.redact_json(&item_path, value);
+      },
+      Value::Array(items) => for (index, item) in items.iter_mut().enumerate() {
+        let mut item_path = path.clone();
+        item_path.push_index(index);
+        self.redact_json(&item_path, item);
+      },
+      _ => {}
+    }
+  }
+
+  fn is_redacted_json_path(&self, path: &DocPath) -> bool {
+    let tokens = path.to_vec();
+    let tokens = tokens.iter().map(|t| t.as_str()).collect::<Vec<_>>();
+    self.paths.iter().any(|p| p.matches_path_exactly(&tokens))
+  }
+
+  /// Collects the values of the redacted body paths of the interaction, so they can be removed
+  /// from the descriptions of any mismatches for the paths containing them
+  fn sensitive_values(&self, interaction: &dyn Interaction) -> Vec<SensitiveValue> {
+    let mut values = vec![];
+    if !self.paths.is_empty() {
+      if let Ok(json) = serde_json::from_slice::<Value>(&interaction.contents_for_verification().value().unwrap_or_default()) {
+        self.collect_json_values(&DocPath::root(), &json, &mut values);
+      }
+    }
+    values.retain(|(_, value)| !value.is_empty());
+    values
+  }
+
+  fn collect_json_values(&self, path: &DocPath, json: &Value, values: &mut Vec<SensitiveValue>) {
+    if self.is_redacted_json_path(path) {
+      match json {
+        Value::String(s) => values.push((path.to_vec(), s.clone())),
+        Value::Object(_) | Value::Array(_) => values.push((path.to_vec(), json.to_string())),
+        _ => {}
+      }
+      return;
+    }
+    match json {
+      Value::Object(map) => for (key, value) in map {
+        let mut item_path = path.clone();
+        item_path.push_field(key);
+        self.collect_json_values(&item_path, value, values);
+      },
+      Value::Array(items) => for (index, item) in items.iter().enumerate() {
+        let mut item_path = path.clone();
+        item_path.push_index(index);
+        self.collect_json_values(&item_path, item, values);
+      },
+      _ => {}
+    }
+  }
+
+  /// Redacts the mismatch by its header, metadata key or body path. Mismatches for unrelated
+  /// values are returned as is, so the redacted values are not replaced in unrelated text.
+  fn redact_mismatch(&self, mismatch: &Mismatch, values: &[SensitiveValue]) -> Mismatch {
+    match mismatch {
+      Mismatch::HeaderMismatch { key, expected, actual, mismatch } if self.is_redacted_header(key) => {
+        Mismatch::HeaderMismatch {
+          key: key.clone(),
+          expected: REDACTED.to_string(),
+          actual: REDACTED.to_string(),
+          mismatch: redact_text(mismatch, &header_values(expected, actual))
+        }
+      }
+      Mismatch::BodyTypeMismatch { expected, actual, mismatch, expected_body, actual_body } => Mismatch::BodyTypeMismatch {
+        expected: expected.clone(),
+        actual: actual.clone(),
+        mismatch: redact_text(mismatch, &values_under(&DocPath::root(), values)),
+        expected_body: expected_body.as_ref().map(|body| self.redact_json_bytes(&DocPath::root(), body)),
+        actual_body: actual_body.as_ref().map(|body| self.redact_json_bytes(&DocPath::root(), body))
+      },
+      Mismatch::BodyMismatch { path, expected, actual, mismatch, rule } => {
+        let doc_path = DocPath::new(path).unwrap_or_else(|_| DocPath::root());
+        if self.is_redacted_path(path) {
+          let mut path_values = values_under(&doc_path, values);
+          path_values.extend([expected, actual].iter().copied().flatten()
+            .map(|value| String::from_utf8_lossy(value).trim_matches('"').to_string()));
+          Mismatch::BodyMismatch {
+            path: path.clone(),
+            expected: expected.as_ref().map(|_| Bytes::from(REDACTED)),
+            actual: actual.as_ref().map(|_| Bytes::from(REDACTED)),
+            mismatch: redact_text(mismatch, &path_values),
+            rule: rule.clone()
+          }
+        } else {
+          Mismatch::BodyMismatch {
+            path: path.clone(),
+            expected: expected.as_ref().map(|body| self.redact_json_bytes(&doc_path, body)),
+            actual: actual.as_ref().map(|body| self.redact_json_bytes(&doc_path, body)),
+            mismatch: redact_text(mismatch, &values_under(&doc_path, values)),
+            rule: rule.clone()
+          }
+        }
+      }
+      Mismatch::MetadataMismatch { key, expected, actual, mismatch } if self.is_redacted_header(key) => {
+        Mismatch::MetadataMismatch {
+          key: key.clone(),
+          expected: REDACTED.to_string(),
+          actual: REDACTED.to_string(),
+          mismatch: redact_text(mismatch, &[expected.clone(), actual.clone()])
+        }
+      }
+      _ => mismatch.clone()
+    }
+  }
+
+  /// Redacts the body paths in the JSON value found at the given path
+  fn redact_json_bytes(&self, path: &DocPath, bytes: &Bytes) -> Bytes {
+    match serde_json::from_slice::<Value>(bytes) {
+      Ok(mut json) if !self.paths.is_empty() => {
+        self.redact_json(path, &mut json);
+        Bytes::from(json.to_string())
+      }
+      _ => bytes.clone()
+    }
+  }
+}
+
+/// The header values, along with each of the individual values if there are multiple
+fn header_values(expected: &str, actual: &str) -> Vec<String> {
+  [expected, actual].iter()
+    .flat_map(|value| std::iter::once(value.to_string()).chain(value.split(',').map(|v| v.trim().to_string())))
+    .collect()
+}
+
+/// The sensitive values found at or below the path
+fn values_under(path: &DocPath, values: &[SensitiveValue]) -> Vec<String> {
+  let tokens = path.to_vec();
+  values.iter()
+    .filter(|(value_path, _)| value_path.starts_with(&tokens))
+    .map(|(_, value)| value.clone())
+    .collect()
+}
+
+/// Replaces the values in the text. Only whole tokens are replaced, so a value will not be
+/// replaced in the middle of a word or number.
+fn redact_text(text: &str, values: &[String]) -> String {
+  let mut values = values.iter().filter(|value| !value.is_empty()).collect::<Vec<_>>();
+  // Replace the longest values first, so values containing other values are fully redacted
+  values.sort_by_key(|value| std::cmp::Reverse(value.len()));
+  values.dedup();
+  values.iter().fold(text.to_string(), |text, value| replace_token(&text, value))
+}
+
+fn replace_token(text: &str, value: &str) -> String {
+  let is_word_char = |c: Option<char>| c.map(|c| c.is_alphanumeric() || c == '_').unwrap_or(false);
+  let starts_with_word = is_word_char(value.chars().next());
+  let ends_with_word = is_word_char(value.chars().last());
+  let mut result = String::with_capacity(text.len());
+  let mut last = 0;
+  for (index, _) in text.match_indices(value) {
+    let end = index + value.len();
+    let before = text[..index].chars().last();
+    let after = text[end..].chars().next();
+    let part_of_word = (starts_with_word && is_word_char(before)) || (ends_with_word && is_word_char(after));
+    if !part_of_word {
+      result.push_str(&text[last..index]);
+      result.push_str(REDACTED);
+      last = end;
+    }
+  }
+  result.push_str(&text[last..]);
+  result
+}
+
+#[cfg(test)]
+mod tests {
+  use expectest::prelude::*;
+  use maplit::*;
+  use serde_json::json;
+
+  use pact_models::bodies::OptionalBody;
+  use pact_models::v4::http_parts::HttpResponse;
+  use pact_models::v4::synch_http::SynchronousHttp;
+
+  use crate::body_mismatch_diff;
+  use crate::pact_broker::{build_payload, TestResult};
+
+  use super::*;
+
+  fn response(token: &str, name: &str, email: &str) -> Box<dyn Interaction + Send + Sync> {
+    Box::new(SynchronousHttp {
+      response: HttpResponse {
+        headers: Some(hashmap!{ "X-Token".to_string() => vec![token.to_string()] }),
+        body: OptionalBody::from(&json!({ "name": name, "email": email })),
+        .. HttpResponse::default()
+      },
+      .. SynchronousHttp::default()
+    })
+  }
+
+  fn mismatch_result() -> MismatchResult {
+    MismatchResult::Mismatches {
+      mismatches: vec![
+        Mismatch::HeaderMismatch {
+          key: "X-Token".to_string(),
+          expected: "secret-token".to_string(),
+          actual: "other-token".to_string(),
+          mismatch: "Mismatch with header 'X-Token': Expected 'secret-token' to be equal to 'other-token'".to_string()
+        },
+        Mismatch::BodyMismatch {
+          path: "$.email".to_string(),
+          expected: Some(Bytes::from("\"mary@example.com\"")),
+          actual: Some(Bytes::from("\"mary@other.com\"")),
+          mismatch: "Expected 'mary@example.com' (String) to be equal to 'mary@other.com' (String)".to_string(),
+          rule: None
+        },
+        Mismatch::BodyMismatch {
+          path: "$.name".to_string(),
+          expected: Some(Bytes::from("\"Mary\"")),
+          actual: Some(Bytes::from("\"Maria\"")),
+          mismatch: "Expected 'Mary' (String) to be equal to 'Maria' (String)".to_string(),
+          rule: None
+        }
+      ],
+      expected: response("secret-token", "Mary", "mary@example.com"),
+      actual: response("other-token", "Maria", "mary@other.com"),
+      interaction_id: Some("1234".to_string())
+    }
+  }
+
+  #[test]
+  fn redact_mismatch_result_does_nothing_with_no_redactions() {
+    let result = mismatch_result();
+    let redacted = Redactions::new(&[]).redact_mismatch_result(&result);
+    expect!(format!("{:?}", redacted)).to(be_equal_to(format!("{:?}", result)));
+  }
+
+  #[test]
+  fn redact_mismatch_result_replaces_the_redacted_header_and_body_values() {
+    let redactions = Redactions::new(&["x-token".to_string(), "$.email".to_string()]);
+    let redacted = redactions.redact_mismatch_result(&mismatch_result());
+
+    if let MismatchResult::Mismatches { mismatches, expected, actual, .. } = &redacted {
+      let descriptions = mismatches.iter().map(|m| m.description()).collect::<Vec<_>>();
+      expect!(descriptions).to(be_equal_to(vec![
+        "Mismatch with header 'X-Token': Expected '***' to be equal to '***'".to_string(),
+        "$.email -> Expected '***' (String) to be equal to '***' (String)".to_string(),
+        "$.name -> Expected 'Mary' (String) to be equal to 'Maria' (String)".to_string()
+      ]));
+      expect!(mismatches[0].clone()).to(be_equal_to(Mismatch::HeaderMismatch {
+        key: "X-Token".to_string(),
+        expected: REDACTED.to_string(),
+        actual: REDACTED.to_string(),
+        mismatch: "Mismatch with header 'X-Token': Expected '***' to be equal to '***'".to_string()
+      }));
+
+      let diff = body_mismatch_diff(expected, actual, "$");
+      expect!(diff.contains("mary@")).to(be_false());
+      expect!(diff.contains(REDACTED)).to(be_true());
+      expect!(expected.as_v4_http().unwrap().response.headers).to(be_some().value(
+        hashmap!{ "X-Token".to_string() => vec![REDACTED.to_string()] }));
+    } else {
+      panic!("Expected a mismatch result, got {:?}", redacted);
+    }
+
+    let payload = build_payload(TestResult::Failed(vec![(Some("1234".to_string()), Some(redacted))]),
+      "1.0.0".to_string(), None, None).to_string();
+    expect!(payload.contains("secret-token")).to(be_false());
+    expect!(payload.contains("mary@")).to(be_false());
+    expect!(payload.contains("Expected '***' (String) to be equal to '***' (String)")).to(be_true());
+  }
+
+  #[test]
+  fn redact_mismatch_result_does_not_replace_short_values_in_unrelated_text() {
+    let interaction = |code: &str| -> Box<dyn Interaction + Send + Sync> {
+      Box::new(SynchronousHttp {
+        response: HttpResponse {
+          body: OptionalBody::from(&json!({ "code": code, "items": [1] })),
+          .. HttpResponse::default()
+        },
+        .. SynchronousHttp::default()
+      })
+    };
+    let result = MismatchResult::Mismatches {
+      mismatches: vec![
+        Mismatch::BodyMismatch {
+          path: "$.items".to_string(),
+          expected: Some(Bytes::from("[1]")),
+          actual: Some(Bytes::from("[1,2]")),
+          mismatch: "Expected a List with 1 elements but received 2 elements".to_string(),
+          rule: None
+        },
+        Mismatch::BodyMismatch {
+          path: "$.code".to_string(),
+          expected: Some(Bytes::from("\"1\"")),
+          actual: Some(Bytes::from("\"2\"")),
+          mismatch: "Expected '1' (String) to be equal to '2' (String)".to_string(),
+          rule: None
+        },
+        Mismatch::BodyMismatch {
+          path: "$".to_string(),
+          expected: Some(Bytes::from(r#"{"code":"1","items":[1]}"#)),
+          actual: Some(Bytes::from(r#"{"code":"2","items":[1,2],"count":12}"#)),
+          mismatch: "Expected code 1 but got code 2 (with 12 items)".to_string(),
+          rule: None
+        }
+      ],
+      expected: interaction("1"),
+      actual: interaction("2"),
+      interaction_id: None
+    };
+
+    let redacted = Redactions::new(&["$.code".to_string()]).redact_mismatch_result(&result);
+
+    if let MismatchResult::Mismatches { mismatches, .. } = &redacted {
+      expect!(mismatches.iter().map(|m| m.description()).collect::<Vec<_>>()).to(be_equal_to(vec![
+        "$.items -> Expected a List with 1 elements but received 2 elements".to_string(),
+        "$.code -> Expected '***' (String) to be equal to '***' (String)".to_string(),
+        "$ -> Expected code *** but got code *** (with 12 items)".to_string()
+      ]));
+      expect!(mismatches[2].clone()).to(be_equal_to(Mismatch::BodyMismatch {
+        path: "$".to_string(),
+        expected: Some(Bytes::from(r#"{"code":"***","items":[1]}"#)),
+        actual: Some(Bytes::from(r#"{"code":"***","count":12,"items":[1,2]}"#)),
+        mismatch: "Expected code *** but got code *** (with 12 items)".to_string(),
+        rule: None
+      }));
+    } else {
+      panic!("Expected a mismatch result, got {:?}", redacted);
+    }
+  }
+}
@@ -1,6 +1,12 @@
+use std::collections::HashMap;
+use std::{env, fs, process};
 use std::panic::catch_unwind;
+use std::str::FromStr;
 use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
 
+use ansi_term::Colour::{Green, Red};
 use env_logger::*;
 use expectest::expect;
 use expectest::prelude::*;
@@ -9,18 +15,40 @@ use serde_json::json;
 
 use pact_consumer::*;
 use pact_consumer::prelude::*;
-use pact_models::Consumer;
+use pact_matching::{HeaderMatchConfig, Mismatch};
+use pact_models::bodies::OptionalBody;
+use pact_models::{Consumer, Provider};
+use pact_models::content_types::{ContentType, JSON, TEXT, XML};
+use pact_models::interaction::Interaction;
+use pact_models::matchingrules;
+use pact_models::matchingrules::MatchingRule;
+use pact_models::generators;
+use pact_models::generators::Generator;
+use pact_models::http_utils::HttpAuth;
 use pact_models::pact::Pact;
-use pact_models::PACT_RUST_VERSION;
+use pact_models::{PACT_RUST_VERSION, PactSpecification};
 use pact_models::provider_states::*;
+use pact_models::request::Request;
+use pact_models::response::Response;
 use pact_models::sync_interaction::RequestResponseInteraction;
 use pact_models::sync_pact::RequestResponsePact;
+use pact_models::v4::http_parts::{HttpRequest, HttpResponse};
+use pact_models::v4::pact::V4Pact;
+use pact_models::v4::synch_http::SynchronousHttp;
 
-use crate::callback_executors::HttpRequestProviderStateExecutor;
+use crate::callback_executors::{
+  HttpRequestProviderStateExecutor,
+  NullRequestFilterExecutor,
+  ProviderStateError,
+  ProviderStateErrorKind,
+  ProviderStateExecutor
+};
+use crate::archive::read_pacts_from_zip_with_limit;
+use crate::config::load_config_file;
 use crate::pact_broker::Link;
-use crate::PactSource;
+use crate::{diff_verification_results, MismatchResult, PactSource, ProviderClients, ProviderInfo, VerificationInteractionResult, VerificationOptions, VerificationResultsDiff};
 
-use super::{execute_state_change, filter_consumers, filter_interaction, FilterInfo};
+use super::{body_mismatch_diff, fetch_pact, load_pact_from_url, validate_pact_file, verify_pact_internal, execute_state_change, filter_consumers, filter_interaction, FilterInfo, InteractionOutcome, verify_interaction, walkdir};
 
 #[test]
 fn if_no_interaction_filter_is_defined_returns_true() {
@@ -202,6 +230,46 @@ async fn test_state_change_with_parameters_in_query() {
   expect!(result.clone()).to(be_ok());
 }
 
+#[tokio::test]
+async fn test_state_change_with_custom_method_and_body_template() {
+  try_init().unwrap_or(());
+
+  let server = PactBuilder::new("RustPactVerifier", "SomeRunningProvider")
+    .interaction("a state change request using PUT", "", |mut i| async move {
+      i.request.method("PUT");
+      i.request.path("/");
+      i.request.header("Content-Type", "application/json");
+      i.request.json_body(json!({
+        "action": "setup",
+        "state": "TestState",
+        "params": { "A": "1" },
+        "tenant": "test"
+      }));
+      i.response.status(200);
+      i
+    })
+    .await
+    .start_mock_server();
+
+  let provider_state = ProviderState {
+    name: "TestState".to_string(),
+    params: hashmap!{
+        "A".to_string() => json!("1")
+      }
+  };
+
+  let provider_state_executor = Arc::new(HttpRequestProviderStateExecutor {
+    state_change_url: Some(server.url().to_string()),
+    state_change_method: "put".to_string(),
+    state_change_body_template: Some(json!({ "tenant": "test" })),
+    .. HttpRequestProviderStateExecutor::default()
+  });
+  let client = reqwest::Client::new();
+  let result = execute_state_change(&provider_state, true,
+                                    None, &client, provider_state_executor).await;
+  expect!(result.clone()).to(be_ok());
+}
+
 #[tokio::test]
 async fn test_state_change_returning_json_values() {
   try_init().unwrap_or(());
@@ -268,7 +336,7 @@ fn publish_result_does_nothing_if_not_from_broker() {
         disable_ssl_verification: false,
         .. super::VerificationOptions::default()
       };
-      super::publish_result(&vec![], &PactSource::File("/tmp/test".into()), &options).await;
+      super::publish_result(&vec![], &PactSource::File("/tmp/test".into()), &options, None).await;
     })
   });
   expect!(server_response).to(be_err());
@@ -317,5 +385,1246 @@ async fn publish_successful_result_to_broker() {
     }
   ];
   let source = PactSource::BrokerUrl("Test".to_string(), server.url().to_string(), None, links);
-  super::publish_result(&vec![(Some("1".to_string()), Ok(()))], &source, &options).await;
+  super::publish_result(&vec![(Some("1".to_string()), Ok(()))], &source, &options, None).await;
+}
+
+#[test]
+fn resolve_provider_branch_detects_the_branch_from_the_environment() {
+  let options = super::VerificationOptions {
+    auto_detect_branch: true,
+    request_filter: None::<Arc<super::NullRequestFilterExecutor>>,
+    .. super::VerificationOptions::default()
+  };
+  let lookup = |name: &str| match name {
+    "GIT_BRANCH" => Some("feat-ci-branch".to_string()),
+    "BITBUCKET_BRANCH" => Some("other-branch".to_string()),
+    _ => None
+  };
+
+  expect!(super::resolve_provider_branch_with_lookup(&options, lookup)).to(be_some().value("feat-ci-branch".to_string()));
+  expect!(super::resolve_provider_branch_with_lookup(&options, |_| Some(" ".to_string()))).to(be_none());
+
+  let explicit_options = super::VerificationOptions {
+    provider_branch: Some("main".to_string()),
+    .. options.clone()
+  };
+  expect!(super::resolve_provider_branch_with_lookup(&explicit_options, lookup)).to(be_some().value("main".to_string()));
+
+  let disabled_options = super::VerificationOptions {
+    auto_detect_branch: false,
+    .. options.clone()
+  };
+  expect!(super::resolve_provider_branch_with_lookup(&disabled_options, lookup)).to(be_none());
+}
+
+fn interaction_with_response_body(body: &str, content_type: &ContentType) -> Box<dyn Interaction> {
+  Box::new(RequestResponseInteraction {
+    response: Response {
+      body: OptionalBody::Present(body.to_string().into(), Some(content_type.clone()), None),
+      .. Response::default()
+    },
+    .. RequestResponseInteraction::default()
+  })
+}
+
+#[test]
+fn body_mismatch_diff_for_text_bodies() {
+  let expected = interaction_with_response_body("line one\nline two\nline three", &TEXT);
+  let actual = interaction_with_response_body("line one\nline 2\nline three", &TEXT);
+  let diff = body_mismatch_diff(&expected, &actual, "$");
+  expect!(diff.contains("    line one\n")).to(be_true());
+  expect!(diff.contains(&Red.paint("    -line two\n").to_string())).to(be_true());
+  expect!(diff.contains(&Green.paint("    +line 2\n").to_string())).to(be_true());
+}
+
+#[test]
+fn body_mismatch_diff_for_xml_bodies() {
+  let expected = interaction_with_response_body("<foo><bar>one</bar><baz>two</baz></foo>", &XML);
+  let actual = interaction_with_response_body("<foo><bar>one</bar><baz>three</baz></foo>", &XML);
+  let diff = body_mismatch_diff(&expected, &actual, "$");
+  expect!(diff.contains("    <bar>one</bar>\n")).to(be_true());
+  expect!(diff.contains(&Red.paint("    -<baz>two</baz>\n").to_string())).to(be_true());
+  expect!(diff.contains(&Green.paint("    +<baz>three</baz>\n").to_string())).to(be_true());
+}
+
+fn write_message_pact(path: &std::path::Path, consumer: &str) {
+  let pact = json!({
+    "consumer": { "name": consumer },
+    "provider": { "name": "message_provider" },
+    "messages": [
+      {
+        "description": "a test message",
+        "contents": { "id": 1 }
+      }
+    ],
+    "metadata": { "pactSpecification": { "version": "3.0.0" } }
+  });
+  fs::write(path, pact.to_string()).unwrap();
+}
+
+#[test]
+fn walkdir_loads_pacts_from_nested_directories() {
+  let mut dir = env::temp_dir();
+  dir.push(format!("pact_verifier_walkdir_{}", process::id()));
+  let nested = dir.join("nested");
+  fs::create_dir_all(&nested).unwrap();
+  write_message_pact(&dir.join("top.json"), "top_consumer");
+  write_message_pact(&nested.join("nested.json"), "nested_consumer");
+  fs::write(dir.join("README.md"), "not a pact").unwrap();
+
+  let result = walkdir(&dir, false);
+  fs::remove_dir_all(&dir).unwrap_or(());
+
+  let mut consumers: Vec<String> = result.unwrap().into_iter()
+    .map(|pact| pact.unwrap().consumer().name)
+    .collect();
+  consumers.sort();
+  expect!(consumers).to(be_equal_to(vec!["nested_consumer".to_string(), "top_consumer".to_string()]));
+}
+
+fn write_pact_with_body_file(dir: &std::path::Path, body: &str) -> std::path::PathBuf {
+  fs::create_dir_all(dir.join("bodies")).unwrap();
+  let pact = json!({
+    "consumer": { "name": "body_file_consumer" },
+    "provider": { "name": "body_file_provider" },
+    "interactions": [
+      {
+        "description": "a request for a user",
+        "providerStates": [ { "name": "a user exists", "params": { "body": body } } ],
+        "request": { "method": "GET", "path": "/users/1" },
+        "response": {
+          "status": 200,
+          "headers": { "Content-Type": "application/json" },
+          "body": body
+        }
+      }
+    ],
+    "metadata": { "pactSpecification": { "version": "3.0.0" } }
+  });
+  let path = dir.join("pact.json");
+  fs::write(&path, pact.to_string()).unwrap();
+  path
+}
+
+async fn fetch_pact_with_body_file(name: &str, body: &str, resolve_body_files: bool) -> Result<Box<dyn Pact + Send + Sync>, String> {
+  let dir = env::temp_dir().join(format!("pact_verifier_{}_{}", name, process::id()));
+  let path = write_pact_with_body_file(&dir, body);
+  fs::write(dir.join("bodies").join("user.json"), r#"{"id": 1, "name": "Mary"}"#).unwrap();
+
+  let result = fetch_pact(PactSource::File(path.to_string_lossy().to_string()), &[], 10, None,
+    resolve_body_files).await;
+  fs::remove_dir_all(&dir).unwrap_or(());
+  result.into_iter().next().unwrap().map(|(pact, _, _)| pact)
+}
+
+#[tokio::test]
+async fn fetch_pact_loads_bodies_from_files_referenced_in_the_pact() {
+  let pact = fetch_pact_with_body_file("body_file", "file://bodies/user.json", true).await.unwrap();
+
+  let interaction = pact.interactions()[0].as_request_response().unwrap();
+  let body: serde_json::Value = serde_json::from_slice(&interaction.response.body.value().unwrap()).unwrap();
+  expect!(body).to(be_equal_to(json!({ "id": 1, "name": "Mary" })));
+  expect!(interaction.provider_states[0].params.get("body").cloned())
+    .to(be_some().value(json!("file://bodies/user.json")));
+}
+
+#[tokio::test]
+async fn fetch_pact_does_not_load_body_files_unless_enabled() {
+  let pact = fetch_pact_with_body_file("body_file_disabled", "file://bodies/user.json", false).await.unwrap();
+
+  let interaction = pact.interactions()[0].as_request_response().unwrap();
+  let body: serde_json::Value = serde_json::from_slice(&interaction.response.body.value().unwrap()).unwrap();
+  expect!(body).to(be_equal_to(json!("file://bodies/user.json")));
+}
+
+#[tokio::test]
+async fn fetch_pact_returns_an_error_if_a_referenced_body_file_is_missing() {
+  let error = fetch_pact_with_body_file("missing_body_file", "file://bodies/other.json", true).await.err().unwrap();
+
+  expect!(error.contains("Failed to load the body file")).to(be_true());
+  expect!(error.contains("other.json")).to(be_true());
+}
+
+#[tokio::test]
+async fn fetch_pact_returns_an_error_if_a_referenced_body_file_is_outside_the_pact_directory() {
+  let error = fetch_pact_with_body_file("outside_body_file", "file://../bodies/user.json", true).await.err().unwrap();
+  expect!(error.contains("is not within the directory of the pact file")).to(be_true());
+
+  let error = fetch_pact_with_body_file("absolute_body_file", "file:///etc/passwd", true).await.err().unwrap();
+  expect!(error.contains("is not within the directory of the pact file")).to(be_true());
+}
+
+/// Writes a zip archive with the entries, compressing the entries that have the flag set
+fn write_zip(path: &std::path::Path, entries: &[(&str, String, bool)]) {
+  use std::io::Write;
+  use zip::CompressionMethod;
+  use zip::write::FileOptions;
+
+  let mut writer = zip::ZipWriter::new(fs::File::create(path).unwrap());
+  for (name, contents, compress) in entries {
+    let method = if *compress { CompressionMethod::Deflated } else { CompressionMethod::Stored };
+    writer.start_file(*name, FileOptions::default().compression_method(method)).unwrap();
+    writer.write_all(contents.as_bytes()).unwrap();
+  }
+  writer.finish().unwrap();
+}
+
+#[tokio::test]
+async fn fetch_pact_loads_the_pacts_from_a_zip_archive() {
+  let pact = |consumer: &str| json!({
+    "consumer": { "name": consumer },
+    "provider": { "name": "zip_provider" },
+    "interactions": [
+      { "description": "a request", "request": { "method": "GET", "path": "/" }, "response": { "status": 200 } }
+    ],
+    "metadata": { "pactSpecification": { "version": "3.0.0" } }
+  }).to_string();
+  let path = env::temp_dir().join(format!("pact_verifier_pacts_{}.zip", process::id()));
+  write_zip(&path, &[
+    ("pacts/consumer1.json", pact("consumer1"), false),
+    ("README.md", "not a pact".to_string(), false),
+    ("pacts/consumer2.json", pact("consumer2"), true),
+    ("pacts/invalid.json", "not a pact".to_string(), true)
+  ]);
+
+  let result = fetch_pact(PactSource::Zip(path.to_string_lossy().to_string()), &[], 5000, None, false).await;
+  fs::remove_file(&path).unwrap_or(());
+
+  let consumers: Vec<String> = result.iter()
+    .filter_map(|result| result.as_ref().ok())
+    .map(|(pact, _, _)| pact.consumer().name)
+    .collect();
+  expect!(consumers).to(be_equal_to(vec!["consumer1".to_string(), "consumer2".to_string()]));
+  let errors: Vec<&String> = result.iter().filter_map(|result| result.as_ref().err()).collect();
+  expect!(errors.len()).to(be_equal_to(1));
+  expect!(errors[0].starts_with("Failed to load pact 'pacts/invalid.json' from")).to(be_true());
+}
+
+#[tokio::test]
+async fn fetch_pact_returns_an_error_if_the_file_is_not_a_zip_archive() {
+  let path = temp_pact_file("not_a_zip", "not a zip archive");
+
+  let result = fetch_pact(PactSource::Zip(path.to_string_lossy().to_string()), &[], 5000, None, false).await;
+  fs::remove_file(&path).unwrap_or(());
+
+  expect!(result.len()).to(be_equal_to(1));
+  expect!(result[0].as_ref().unwrap_err().contains("File is not a zip archive")).to(be_true());
+}
+
+#[test]
+fn read_pacts_from_zip_fails_entries_larger_than_the_maximum_size() {
+  let path = env::temp_dir().join(format!("pact_verifier_large_pact_{}.zip", process::id()));
+  write_zip(&path, &[
+    ("small.json", "{}".to_string(), true),
+    ("large.json", " ".repeat(1000), true)
+  ]);
+
+  let result = read_pacts_from_zip_with_limit(&path, 100).unwrap();
+  fs::remove_file(&path).unwrap_or(());
+
+  expect!(result.len()).to(be_equal_to(2));
+  expect!(result[1].0.as_str()).to(be_equal_to("large.json"));
+  expect!(result[1].1.as_ref().unwrap_err().to_string()).to(
+    be_equal_to("Entry is larger than the maximum size of 100 bytes"));
+}
+
+/// Response sent by the test provider, after the delay (in milliseconds)
+struct TestResponse {
+  delay: u64,
+  data: Vec<u8>
+}
+
+impl TestResponse {
+  /// Response with the status line (i.e. `200 OK`), headers and body
+  fn new(status: &str, headers: &[(&str, &str)], body: &[u8]) -> TestResponse {
+    let mut data = format!("HTTP/1.1 {}\r\n", status);
+    for (name, value) in headers {
+      data.push_str(&format!("{}: {}\r\n", name, value));
+    }
+    data.push_str(&format!("content-length: {}\r\n\r\n", body.len()));
+    let mut data = data.into_bytes();
+    data.extend_from_slice(body);
+    TestResponse { delay: 0, data }
+  }
+
+  fn ok() -> TestResponse {
+    TestResponse::new("200 OK", &[], &[])
+  }
+
+  fn delayed(self, delay: u64) -> TestResponse {
+    TestResponse { delay, .. self }
+  }
+}
+
+/// Provider for the tests that need behaviour the mock server does not have (delays, server
+/// errors, compressed bodies or connection handling). The handler is called with the index and
+/// text of each request. Connections are kept open, and the request lines and number of
+/// connections are recorded.
+struct TestProvider {
+  port: u16,
+  requests: Arc<std::sync::Mutex<Vec<String>>>,
+  connections: Arc<AtomicUsize>
+}
+
+impl TestProvider {
+  async fn start<H>(handler: H) -> TestProvider
+    where H: Fn(usize, &str) -> TestResponse + Send + Sync + 'static {
+    use tokio::io::AsyncWriteExt;
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let port = listener.local_addr().unwrap().port();
+    let requests = Arc::new(std::sync::Mutex::new(vec![]));
+    let connections = Arc::new(AtomicUsize::new(0));
+    let handler = Arc::new(handler);
+    let (recorded, connection_count) = (requests.clone(), connections.clone());
+    tokio::spawn(async move {
+      while let Ok((mut socket, _)) = listener.accept().await {
+        connection_count.fetch_add(1, Ordering::SeqCst);
+        let (recorded, handler) = (recorded.clone(), handler.clone());
+        tokio::spawn(async move {
+          while let Some(request) = read_request(&mut socket).await {
+            let index = {
+              let mut recorded = recorded.lock().unwrap();
+              recorded.push(request.lines().next().unwrap_or_default().to_string());
+              recorded.len() - 1
+            };
+            let response = handler(index, &request);
+            tokio::time::sleep(Duration::from_millis(response.delay)).await;
+            if socket.write_all(&response.data).await.is_err() {
+              break;
+            }
+          }
+        });
+      }
+    });
+    TestProvider { port, requests, connections }
+  }
+
+  fn provider_info(&self) -> ProviderInfo {
+    ProviderInfo { port: Some(self.port), host: "127.0.0.1".to_string(), .. ProviderInfo::default() }
+  }
+
+  fn url(&self, path: &str) -> String {
+    format!("http://127.0.0.1:{}{}", self.port, path)
+  }
+
+  fn requests(&self) -> Vec<String> {
+    self.requests.lock().unwrap().clone()
+  }
+}
+
+/// Reads the next request (headers and body) from the connection, or None if it is closed
+async fn read_request(socket: &mut tokio::net::TcpStream) -> Option<String> {
+  use tokio::io::AsyncReadExt;
+
+  let mut data = vec![];
+  let mut buffer = [0; 4096];
+  loop {
+    if let Some(end) = data.windows(4).position(|window| window == b"\r\n\r\n") {
+      let content_length = String::from_utf8_lossy(&data[..end]).lines()
+        .find_map(|line| line.to_lowercase().strip_prefix("content-length:").and_then(|value| value.trim().parse::<usize>().ok()))
+        .unwrap_or_default();
+      if data.len() >= end + 4 + content_length {
+        return Some(String::from_utf8_lossy(&data).to_string());
+      }
+    }
+    match socket.read(&mut buffer).await {
+      Ok(0) | Err(_) => return None,
+      Ok(n) => data.extend_from_slice(&buffer[..n])
+    }
+  }
+}
+
+fn mock_provider_info(server: &ValidatingMockServer) -> ProviderInfo {
+  ProviderInfo { port: server.url().port(), .. ProviderInfo::default() }
+}
+
+/// Verifies the interaction against the provider, using a pact with just that interaction
+async fn verify_with<S: ProviderStateExecutor>(
+  provider: &ProviderInfo,
+  interaction: &(dyn Interaction + Send + Sync),
+  options: VerificationOptions<NullRequestFilterExecutor>,
+  provider_state_executor: S
+) -> Result<InteractionOutcome, MismatchResult> {
+  let pact: Box<dyn Pact + Send + Sync> = match interaction.as_request_response() {
+    Some(interaction) => Box::new(RequestResponsePact {
+      interactions: vec![ interaction ],
+      .. RequestResponsePact::default()
+    }),
+    None => Box::new(V4Pact {
+      interactions: interaction.as_v4().into_iter().collect(),
+      .. V4Pact::default()
+    })
+  };
+  verify_interaction(provider, interaction, &pact, &options, &ProviderClients::new(&options),
+    &Arc::new(provider_state_executor)).await
+}
+
+fn request_interaction(description: &str, provider_states: Vec<ProviderState>, request: Request) -> RequestResponseInteraction {
+  RequestResponseInteraction {
+    description: description.to_string(),
+    provider_states,
+    request,
+    .. RequestResponseInteraction::default()
+  }
+}
+
+#[tokio::test]
+async fn fetch_pact_from_a_url_times_out_using_the_request_timeout() {
+  let provider = TestProvider::start(|_, _| TestResponse::new("200 OK", &[], b"{}").delayed(10000)).await;
+  let url = provider.url("/pact.json");
+
+  let start = std::time::Instant::now();
+  let result = fetch_pact(PactSource::URL(url.clone(), None), &[], 200, None, false).await;
+
+  expect!(start.elapsed() < Duration::from_secs(5)).to(be_true());
+  expect!(result.len()).to(be_equal_to(1));
+  expect!(result[0].as_ref().unwrap_err()).to(be_equal_to(
+    &format!("Failed to load pact '{}' - Request timed out after 200ms", url)));
+}
+
+#[tokio::test]
+async fn load_pact_from_url_fails_if_the_response_is_too_large() {
+  let provider = TestProvider::start(|_, _| TestResponse::new("200 OK", &[("content-type", "application/json")],
+    br#"{"consumer":{"name":"c"},"provider":{"name":"p"},"interactions":[]}"#)).await;
+  let url = provider.url("/pact.json");
+
+  expect!(load_pact_from_url(&url, &None, 5000, 1024).await).to(be_ok());
+  expect!(load_pact_from_url(&url, &None, 5000, 10).await.unwrap_err().to_string()).to(
+    be_equal_to("Response is larger than the maximum pact size of 10 bytes"));
+}
+
+fn temp_pact_file(name: &str, contents: &str) -> std::path::PathBuf {
+  let path = env::temp_dir().join(format!("pact_verifier_{}_{}.json", name, process::id()));
+  fs::write(&path, contents).unwrap();
+  path
+}
+
+#[test]
+fn validate_pact_file_returns_no_warnings_for_a_valid_pact() {
+  let path = env::temp_dir().join(format!("pact_verifier_valid_{}.json", process::id()));
+  write_message_pact(&path, "valid_consumer");
+
+  let result = validate_pact_file(&path);
+  fs::remove_file(&path).unwrap_or(());
+
+  expect!(result.unwrap()).to(be_equal_to(Vec::<String>::new()));
+}
+
+#[test]
+fn validate_pact_file_warns_about_duplicate_interaction_descriptions() {
+  let pact = json!({
+    "consumer": { "name": "duplicate_consumer" },
+    "provider": { "name": "duplicate_provider" },
+    "interactions": [
+      { "description": "a request", "request": { "method": "GET", "path": "/one" }, "response": { "status": 200 } },
+      { "description": "a request", "request": { "method": "GET", "path": "/two" }, "response": { "status": 200 } },
+      {
+        "description": "a request",
+        "providerState": "some state",
+        "request": { "method": "GET", "path": "/three" },
+        "response": { "status": 200 }
+      }
+    ],
+    "metadata": { "pactSpecification": { "version": "2.0.0" } }
+  });
+  let path = temp_pact_file("duplicate", &pact.to_string());
+
+  let result = validate_pact_file(&path);
+  fs::remove_file(&path).unwrap_or(());
+
+  expect!(result.unwrap()).to(be_equal_to(vec!["Duplicate interaction description 'a request'".to_string()]));
+}
+
+#[test]
+fn validate_pact_file_returns_an_error_if_the_file_can_not_be_parsed() {
+  let path = temp_pact_file("unparseable", "{ this is not a pact");
+
+  let result = validate_pact_file(&path);
+  fs::remove_file(&path).unwrap_or(());
+
+  expect!(result).to(be_err());
+}
+
+async fn verify_with_timeouts(provider: &TestProvider, request_timeout: u64, state_change_timeout: u64) -> bool {
+  let interaction = request_interaction("a request", vec![ ProviderState::default("a slow state") ], Request::default());
+  let options = VerificationOptions { request_timeout, state_change_timeout, .. VerificationOptions::default() };
+  let provider_state_executor = HttpRequestProviderStateExecutor {
+    state_change_url: Some(provider.url("/_state")),
+    .. HttpRequestProviderStateExecutor::default()
+  };
+  verify_with(&provider.provider_info(), &interaction, options, provider_state_executor).await.is_ok()
+}
+
+#[tokio::test]
+async fn state_change_timeout_governs_state_change_requests() {
+  let provider = TestProvider::start(|_, request| {
+    TestResponse::ok().delayed(if request.contains("/_state") { 500 } else { 0 })
+  }).await;
+
+  expect!(verify_with_timeouts(&provider, 100, 2000).await).to(be_true());
+  expect!(verify_with_timeouts(&provider, 2000, 100).await).to(be_false());
+}
+
+#[tokio::test]
+async fn request_timeout_governs_provider_requests() {
+  let provider = TestProvider::start(|_, request| {
+    TestResponse::ok().delayed(if request.contains("/_state") { 0 } else { 500 })
+  }).await;
+
+  expect!(verify_with_timeouts(&provider, 2000, 100).await).to(be_true());
+  expect!(verify_with_timeouts(&provider, 100, 2000).await).to(be_false());
+}
+
+/// Starts a provider that responds with a 503 to the first two requests, and then 200s
+async fn start_flaky_provider() -> TestProvider {
+  TestProvider::start(|index, _| if index < 2 {
+    TestResponse::new("503 Service Unavailable", &[], &[])
+  } else {
+    TestResponse::ok()
+  }).await
+}
+
+async fn verify_with_retries(provider: &TestProvider, method: &str, request_retries: u8) -> bool {
+  let interaction = request_interaction("a request", vec![],
+    Request { method: method.to_string(), .. Request::default() });
+  let options = VerificationOptions { request_retries, .. VerificationOptions::default() };
+  verify_with(&provider.provider_info(), &interaction, options, HttpRequestProviderStateExecutor::default())
+    .await.is_ok()
+}
+
+#[tokio::test]
+async fn provider_requests_are_retried_on_server_errors() {
+  let provider = start_flaky_provider().await;
+
+  expect!(verify_with_retries(&provider, "GET", 3).await).to(be_true());
+  expect!(provider.requests().len()).to(be_equal_to(3));
+}
+
+#[tokio::test]
+async fn provider_requests_are_not_retried_by_default() {
+  let provider = start_flaky_provider().await;
+
+  expect!(verify_with_retries(&provider, "GET", 0).await).to(be_false());
+  expect!(provider.requests().len()).to(be_equal_to(1));
+}
+
+#[tokio::test]
+async fn non_idempotent_provider_requests_are_not_retried() {
+  let provider = start_flaky_provider().await;
+
+  expect!(verify_with_retries(&provider, "POST", 3).await).to(be_false());
+  expect!(provider.requests().len()).to(be_equal_to(1));
+}
+
+struct MissingStateExecutor;
+
+#[async_trait::async_trait]
+impl ProviderStateExecutor for MissingStateExecutor {
+  async fn call(
+    self: Arc<Self>,
+    interaction_id: Option<String>,
+    provider_state: &ProviderState,
+    _setup: bool,
+    _client: Option<&reqwest::Client>
+  ) -> anyhow::Result<HashMap<String, serde_json::Value>> {
+    Err(ProviderStateError {
+      description: format!("Provider state '{}' was not found", provider_state.name),
+      interaction_id,
+      kind: ProviderStateErrorKind::NotFound
+    }.into())
+  }
+
+  fn teardown(&self) -> bool {
+    false
+  }
+}
+
+#[tokio::test]
+async fn interactions_with_missing_provider_states_are_skipped_if_configured() {
+  let provider = ProviderInfo { port: Some(1), host: "127.0.0.1".to_string(), .. ProviderInfo::default() };
+  let interaction = request_interaction("a request", vec![ ProviderState::default("a state that does not exist") ],
+    Request::default());
+  let options = VerificationOptions { skip_on_missing_state: true, .. VerificationOptions::default() };
+
+  let result = verify_with(&provider, &interaction, options, MissingStateExecutor).await;
+
+  expect!(matches!(result, Ok(InteractionOutcome::Skipped(_)))).to(be_true());
+}
+
+#[tokio::test]
+async fn interactions_with_missing_provider_states_fail_by_default() {
+  let provider = ProviderInfo { port: Some(1), host: "127.0.0.1".to_string(), .. ProviderInfo::default() };
+  let interaction = request_interaction("a request", vec![ ProviderState::default("a state that does not exist") ],
+    Request::default());
+
+  let result = verify_with(&provider, &interaction, VerificationOptions::default(), MissingStateExecutor).await;
+
+  expect!(result.is_err()).to(be_true());
+}
+
+struct FailingTeardownStateExecutor;
+
+#[async_trait::async_trait]
+impl ProviderStateExecutor for FailingTeardownStateExecutor {
+  async fn call(
+    self: Arc<Self>,
+    _interaction_id: Option<String>,
+    provider_state: &ProviderState,
+    setup: bool,
+    _client: Option<&reqwest::Client>
+  ) -> anyhow::Result<HashMap<String, serde_json::Value>> {
+    if setup {
+      Ok(hashmap!{})
+    } else {
+      Err(anyhow::anyhow!("Failed to tear down provider state '{}'", provider_state.name))
+    }
+  }
+
+  fn teardown(&self) -> bool {
+    true
+  }
+}
+
+async fn verify_with_failing_teardown(teardown_failures_are_warnings: bool) -> bool {
+  let server = PactBuilder::new("RustPactVerifier", "SomeRunningProvider")
+    .interaction(format!("a request with a failing teardown ({})", teardown_failures_are_warnings), "".to_string(), |mut i| async move {
+      i.request.path("/");
+      i.response.status(200);
+      i
+    })
+    .await
+    .start_mock_server();
+  let interaction = request_interaction("a request", vec![ ProviderState::default("a state with a failing teardown") ],
+    Request::default());
+  let options = VerificationOptions { teardown_failures_are_warnings, .. VerificationOptions::default() };
+  verify_with(&mock_provider_info(&server), &interaction, options, FailingTeardownStateExecutor).await.is_ok()
+}
+
+#[tokio::test]
+async fn teardown_failures_fail_the_interaction_by_default() {
+  expect!(verify_with_failing_teardown(false).await).to(be_false());
+}
+
+#[tokio::test]
+async fn teardown_failures_are_ignored_if_they_are_configured_as_warnings() {
+  expect!(verify_with_failing_teardown(true).await).to(be_true());
+}
+
+async fn verify_with_header_match_config(header_match_config: HeaderMatchConfig) -> bool {
+  let provider = TestProvider::start(|_, _| TestResponse::ok()).await;
+  let interaction = request_interaction("a request", vec![], Request::default());
+  let options = VerificationOptions { header_match_config, .. VerificationOptions::default() };
+  verify_with(&provider.provider_info(), &interaction, options, HttpRequestProviderStateExecutor::default())
+    .await.is_ok()
+}
+
+#[tokio::test]
+async fn unexpected_response_headers_are_ignored_by_default() {
+  expect!(verify_with_header_match_config(HeaderMatchConfig::AllowUnexpectedHeaders).await).to(be_true());
+  expect!(verify_with_header_match_config(HeaderMatchConfig::strict()).await).to(be_true());
+}
+
+#[tokio::test]
+async fn unexpected_response_headers_fail_the_interaction_if_configured() {
+  expect!(verify_with_header_match_config(HeaderMatchConfig::NoUnexpectedHeaders(vec![])).await).to(be_false());
+}
+
+struct UserIdStateExecutor;
+
+#[async_trait::async_trait]
+impl ProviderStateExecutor for UserIdStateExecutor {
+  async fn call(
+    self: Arc<Self>,
+    _interaction_id: Option<String>,
+    _provider_state: &ProviderState,
+    _setup: bool,
+    _client: Option<&reqwest::Client>
+  ) -> anyhow::Result<HashMap<String, serde_json::Value>> {
+    Ok(hashmap!{ "userId".to_string() => json!(200) })
+  }
+
+  fn teardown(&self) -> bool {
+    false
+  }
+}
+
+fn interaction_with_generated_path(provider_states: Vec<ProviderState>) -> RequestResponseInteraction {
+  RequestResponseInteraction {
+    description: "a request for a user".to_string(),
+    provider_states,
+    request: Request {
+      path: "/users/1".to_string(),
+      generators: generators! {
+        "PATH" => Generator::ProviderStateGenerator("/users/${userId}".to_string(), None)
+      },
+      .. Request::default()
+    },
+    .. RequestResponseInteraction::default()
+  }
+}
+
+/// Starts a mock provider that expects a request for the user
+async fn start_user_provider(user_id: u16) -> ValidatingMockServer {
+  PactBuilder::new("RustPactVerifier", "SomeRunningProvider")
+    .interaction(format!("a request for user {}", user_id), "".to_string(), |mut i| async move {
+      i.request.path(format!("/users/{}", user_id));
+      i.response.status(200);
+      i
+    })
+    .await
+    .start_mock_server()
+}
+
+#[tokio::test]
+async fn global_template_values_are_used_when_generating_requests() {
+  let server = start_user_provider(100).await;
+  let options = VerificationOptions {
+    template_values: hashmap!{ "userId".to_string() => json!(100) },
+    .. VerificationOptions::default()
+  };
+
+  let result = verify_with(&mock_provider_info(&server), &interaction_with_generated_path(vec![]), options,
+    HttpRequestProviderStateExecutor::default()).await;
+
+  expect!(result.is_ok()).to(be_true());
+}
+
+#[tokio::test]
+async fn provider_state_values_take_precedence_over_global_template_values() {
+  let server = start_user_provider(200).await;
+  let interaction = interaction_with_generated_path(vec![ ProviderState::default("a user exists") ]);
+  let options = VerificationOptions {
+    template_values: hashmap!{ "userId".to_string() => json!(100) },
+    .. VerificationOptions::default()
+  };
+
+  let result = verify_with(&mock_provider_info(&server), &interaction, options, UserIdStateExecutor).await;
+
+  expect!(result.is_ok()).to(be_true());
+}
+
+/// Starts a mock provider that redirects `/old` to `/new`, and if `follow` is set, also expects
+/// the request to `/new`
+async fn start_redirecting_provider(follow: bool) -> ValidatingMockServer {
+  let mut builder = PactBuilder::new("RustPactVerifier", "SomeRunningProvider");
+  builder.interaction(format!("a request for a moved resource (follow {})", follow), "".to_string(), |mut i| async move {
+    i.request.path("/old");
+    i.response.status(302).header("Location", "/new");
+    i
+  }).await;
+  if follow {
+    builder.interaction("a request for the new location of a moved resource", "", |mut i| async move {
+      i.request.path("/new");
+      i.response.status(200);
+      i
+    }).await;
+  }
+  builder.start_mock_server()
+}
+
+fn redirect_interaction() -> RequestResponseInteraction {
+  RequestResponseInteraction {
+    description: "a request for a moved resource".to_string(),
+    request: Request { path: "/old".to_string(), .. Request::default() },
+    response: Response {
+      status: 302,
+      headers: Some(hashmap!{ "location".to_string() => vec!["/new".to_string()] }),
+      .. Response::default()
+    },
+    .. RequestResponseInteraction::default()
+  }
+}
+
+#[tokio::test]
+async fn redirect_responses_are_not_followed_by_default() {
+  let server = start_redirecting_provider(false).await;
+
+  let result = verify_with(&mock_provider_info(&server), &redirect_interaction(), VerificationOptions::default(),
+    HttpRequestProviderStateExecutor::default()).await;
+
+  expect!(result.is_ok()).to(be_true());
+}
+
+#[tokio::test]
+async fn redirect_responses_are_followed_if_enabled() {
+  let server = start_redirecting_provider(true).await;
+  let options = VerificationOptions { follow_redirects: true, .. VerificationOptions::default() };
+
+  let result = verify_with(&mock_provider_info(&server), &redirect_interaction(), options,
+    HttpRequestProviderStateExecutor::default()).await;
+
+  match result {
+    Err(MismatchResult::Mismatches { mismatches, .. }) => {
+      expect!(mismatches.iter().any(|mismatch| matches!(mismatch,
+        Mismatch::StatusMismatch { expected: 302, actual: 200, .. }))).to(be_true());
+    }
+    _ => panic!("Expected the followed redirect to result in a status mismatch")
+  }
+}
+
+#[tokio::test]
+async fn dry_run_lists_the_interactions_without_making_any_requests() {
+  // The mock server has no interactions, so it will fail the test if it receives any requests
+  let server = PactBuilder::new("RustPactVerifier", "SomeRunningProvider").start_mock_server();
+  let pact: Box<dyn Pact + Send + Sync> = Box::new(RequestResponsePact {
+    interactions: vec![
+      interaction_with_generated_path(vec![ ProviderState::default("a user exists") ]),
+      redirect_interaction()
+    ],
+    .. RequestResponsePact::default()
+  });
+  let options: VerificationOptions<NullRequestFilterExecutor> = VerificationOptions {
+    dry_run: true,
+    .. VerificationOptions::default()
+  };
+  let provider_state_executor = HttpRequestProviderStateExecutor {
+    state_change_url: Some(server.path("/state").to_string()),
+    state_change_teardown: true,
+    .. HttpRequestProviderStateExecutor::default()
+  };
+
+  let result = verify_pact_internal(&mock_provider_info(&server), &FilterInfo::None, pact, &options,
+    &Arc::new(provider_state_executor), false).await.unwrap();
+
+  expect!(result.results.iter().map(|result| (result.description.clone(), result.skipped)).collect::<Vec<_>>()).to(be_equal_to(vec![
+    ("Verifying a pact between default_consumer and default_provider Given a user exists - a request for a user".to_string(), true),
+    ("Verifying a pact between default_consumer and default_provider - a request for a moved resource".to_string(), true)
+  ]));
+}
+
+#[tokio::test]
+async fn verification_report_counts_pending_failures_separately() {
+  let provider = ProviderInfo { port: Some(1), host: "127.0.0.1".to_string(), .. ProviderInfo::default() };
+  let pact: Box<dyn Pact + Send + Sync> = Box::new(V4Pact {
+    interactions: vec![
+      Box::new(SynchronousHttp {
+        description: "a pending request".to_string(),
+        pending: true,
+        .. SynchronousHttp::default()
+      }),
+      Box::new(SynchronousHttp {
+        description: "a request".to_string(),
+        .. SynchronousHttp::default()
+      })
+    ],
+    consumer: Consumer { name: "consumer".to_string() },
+    provider: Provider { name: "provider".to_string() },
+    .. V4Pact::default()
+  });
+  let options: VerificationOptions<NullRequestFilterExecutor> = VerificationOptions::default();
+
+  let result = verify_pact_internal(&provider, &FilterInfo::None, pact, &options,
+    &Arc::new(HttpRequestProviderStateExecutor::default()), false).await.unwrap();
+
+  expect!(result.failures()).to(be_equal_to(1));
+  expect!(result.pending_failures()).to(be_equal_to(1));
+  let report = result.to_json();
+  expect!(report["failures"].clone()).to(be_equal_to(json!(1)));
+  expect!(report["pendingFailures"].clone()).to(be_equal_to(json!(1)));
+  let entries = report["results"].as_array().unwrap().iter()
+    .map(|entry| (entry["description"].as_str().unwrap().to_string(), entry["success"].clone(), entry["pending"].clone()))
+    .collect::<Vec<_>>();
+  expect!(entries).to(be_equal_to(vec![
+    ("Verifying a pact between consumer and provider - a pending request".to_string(), json!(false), json!(true)),
+    ("Verifying a pact between consumer and provider - a request".to_string(), json!(false), json!(false))
+  ]));
+}
+
+#[tokio::test]
+async fn provider_states_can_be_sent_in_a_header_instead_of_executing_state_changes() {
+  let expected_header = json!([
+    { "name": "a user exists", "params": { "id": 100 } },
+    { "name": "the user is active" }
+  ]).to_string();
+  // Echoes the header back as the body. The mock server can not be used, as it splits the header
+  // value on the commas in the JSON
+  let provider = TestProvider::start(|_, request| {
+    let header = request.lines()
+      .find_map(|line| line.strip_prefix("x-provider-states: "))
+      .unwrap_or_default();
+    TestResponse::new("200 OK", &[("content-type", "text/plain")], header.as_bytes())
+  }).await;
+  let interaction = RequestResponseInteraction {
+    description: "a request for a user".to_string(),
+    provider_states: vec![
+      ProviderState { name: "a user exists".to_string(), params: hashmap!{ "id".to_string() => json!(100) } },
+      ProviderState::default("the user is active")
+    ],
+    request: Request { path: "/users/100".to_string(), .. Request::default() },
+    response: Response {
+      headers: Some(hashmap!{ "Content-Type".to_string() => vec!["text/plain".to_string()] }),
+      body: OptionalBody::Present(expected_header.into(), Some(TEXT.clone()), None),
+      .. Response::default()
+    },
+    .. RequestResponseInteraction::default()
+  };
+  let options = VerificationOptions {
+    provider_state_header: Some("X-Provider-States".to_string()),
+    .. VerificationOptions::default()
+  };
+
+  // The state executor fails for any state, so the verification would fail if it was called
+  let result = verify_with(&provider.provider_info(), &interaction, options, MissingStateExecutor).await;
+
+  expect!(result.is_ok()).to(be_true());
+}
+
+#[tokio::test]
+async fn verify_interaction_compares_the_content_length_of_the_decompressed_body() {
+  use std::io::Write;
+  use flate2::Compression;
+  use flate2::write::GzEncoder;
+
+  let body = r#"{"id":100,"name":"Mary","roles":["admin","user","auditor"]}"#;
+  let mut encoder = GzEncoder::new(vec![], Compression::default());
+  encoder.write_all(body.as_bytes()).unwrap();
+  let compressed = encoder.finish().unwrap();
+  let provider = TestProvider::start(move |_, _| TestResponse::new("200 OK",
+    &[("content-type", "application/json"), ("content-encoding", "gzip")], &compressed)).await;
+  let interaction = RequestResponseInteraction {
+    description: "a request for a user".to_string(),
+    request: Request { path: "/users/100".to_string(), .. Request::default() },
+    response: Response {
+      headers: Some(hashmap!{
+        "Content-Type".to_string() => vec!["application/json".to_string()],
+        "Content-Length".to_string() => vec![body.len().to_string()]
+      }),
+      body: OptionalBody::Present(body.into(), Some(JSON.clone()), None),
+      .. Response::default()
+    },
+    .. RequestResponseInteraction::default()
+  };
+
+  let result = verify_with(&provider.provider_info(), &interaction, VerificationOptions::default(),
+    HttpRequestProviderStateExecutor::default()).await;
+
+  expect!(result.is_ok()).to(be_true());
+}
+
+/// Starts a provider that completes a WebSocket upgrade handshake using the given sub-protocol
+async fn start_websocket_provider(protocol: &'static str) -> TestProvider {
+  TestProvider::start(move |_, request| {
+    let upgrade = request.lines().any(|line| line.eq_ignore_ascii_case("upgrade: websocket"));
+    let key_sent = request.lines().any(|line| line.to_lowercase().starts_with("sec-websocket-key: "));
+    if upgrade && key_sent {
+      TestResponse {
+        delay: 0,
+        data: format!("HTTP/1.1 101 Switching Protocols\r\nupgrade: websocket\r\nconnection: Upgrade\r\nsec-websocket-accept: s3pPLMBiTxaQ9kYGzzhZRbK+xOo=\r\nsec-websocket-protocol: {}\r\n\r\n", protocol).into_bytes()
+      }
+    } else {
+      TestResponse::new("400 Bad Request", &[], &[])
+    }
+  }).await
+}
+
+fn websocket_interaction() -> SynchronousHttp {
+  SynchronousHttp {
+    description: "a request to open a WebSocket".to_string(),
+    request: HttpRequest {
+      path: "/events".to_string(),
+      headers: Some(hashmap!{ "Upgrade".to_string() => vec!["websocket".to_string()] }),
+      .. HttpRequest::default()
+    },
+    response: HttpResponse {
+      status: 101,
+      headers: Some(hashmap!{
+        "Sec-WebSocket-Protocol".to_string() => vec!["graphql-ws".to_string()],
+        "Sec-WebSocket-Accept".to_string() => vec!["dGhlIHNhbXBsZSBub25jZQ==".to_string()]
+      }),
+      .. HttpResponse::default()
+    },
+    .. SynchronousHttp::default()
+  }
+}
+
+#[tokio::test]
+async fn verify_interaction_verifies_the_websocket_handshake() {
+  let provider = start_websocket_provider("graphql-ws").await;
+  let result = verify_with(&provider.provider_info(), &websocket_interaction(), VerificationOptions::default(),
+    HttpRequestProviderStateExecutor::default()).await;
+  expect!(result.is_ok()).to(be_true());
+
+  let provider = start_websocket_provider("chat").await;
+  let result = verify_with(&provider.provider_info(), &websocket_interaction(), VerificationOptions::default(),
+    HttpRequestProviderStateExecutor::default()).await;
+  match result {
+    Err(MismatchResult::Mismatches { mismatches, .. }) => {
+      expect!(mismatches.iter().map(|mismatch| mismatch.description()).collect::<Vec<_>>()).to(be_equal_to(vec![
+        "Mismatch with header 'Sec-WebSocket-Protocol': Expected 'graphql-ws' to be equal to 'chat'".to_string()
+      ]));
+    }
+    _ => panic!("Expected a header mismatch")
+  }
+}
+
+async fn verify_with_user_agent(user_agent: Option<String>, expected_user_agent: &str) -> bool {
+  let server = PactBuilder::new("RustPactVerifier", "SomeRunningProvider")
+    .interaction("a request with a user agent", "", |mut i| async move {
+      i.request.path("/users");
+      i.request.header("User-Agent", expected_user_agent);
+      i.response.status(200);
+      i
+    })
+    .await
+    .start_mock_server();
+  let interaction = request_interaction("a request for users", vec![],
+    Request { path: "/users".to_string(), .. Request::default() });
+  let options = VerificationOptions { user_agent, .. VerificationOptions::default() };
+  verify_with(&mock_provider_info(&server), &interaction, options, HttpRequestProviderStateExecutor::default())
+    .await.is_ok()
+}
+
+#[tokio::test]
+async fn provider_requests_are_sent_with_the_default_user_agent() {
+  expect!(verify_with_user_agent(None, crate::DEFAULT_USER_AGENT).await).to(be_true());
+}
+
+#[tokio::test]
+async fn provider_requests_are_sent_with_the_configured_user_agent() {
+  expect!(verify_with_user_agent(Some("my-pipeline/1.0".to_string()), "my-pipeline/1.0").await).to(be_true());
+}
+
+async fn verify_pact_and_count_connections(pool_max_idle_per_host: Option<usize>) -> usize {
+  let provider = TestProvider::start(|_, _| TestResponse::ok()).await;
+  let pact: Box<dyn Pact + Send + Sync> = Box::new(RequestResponsePact {
+    interactions: (1..=3).map(|i| request_interaction(&format!("request {}", i), vec![],
+      Request { path: format!("/items/{}", i), .. Request::default() })).collect(),
+    .. RequestResponsePact::default()
+  });
+  let options: VerificationOptions<NullRequestFilterExecutor> = VerificationOptions {
+    pool_max_idle_per_host,
+    .. VerificationOptions::default()
+  };
+
+  let result = verify_pact_internal(&provider.provider_info(), &FilterInfo::None, pact, &options,
+    &Arc::new(HttpRequestProviderStateExecutor::default()), false).await.unwrap();
+  expect!(result.results.iter().all(|result| result.result.is_ok())).to(be_true());
+  provider.connections.load(Ordering::SeqCst)
+}
+
+#[tokio::test]
+async fn verify_pact_reuses_the_connection_to_the_provider_between_interactions() {
+  expect!(verify_pact_and_count_connections(None).await).to(be_equal_to(1));
+}
+
+#[tokio::test]
+async fn verify_pact_does_not_reuse_connections_if_the_pool_size_is_zero() {
+  expect!(verify_pact_and_count_connections(Some(0)).await).to(be_equal_to(3));
+}
+
+#[test]
+fn load_config_file_returns_the_pact_sources_and_options_from_the_file() {
+  let path = env::temp_dir().join(format!("pact_verifier_config_{}.toml", process::id()));
+  fs::write(&path, r#"
+provider = "Pact Provider"
+files = ["pacts/consumer-provider.json"]
+unknown = "ignored"
+
+[broker]
+url = "https://broker.example.com"
+token = "abc123"
+enable_pending = true
+include_wip_pacts_since = "2021-01-01"
+consumer_version_selectors = [ { mainBranch = true }, { deployedOrReleased = true } ]
+
+[verification]
+publish = true
+provider_version = "1.0.0"
+provider_branch = "main"
+provider_tags = ["prod"]
+request_timeout = 1000
+"#).unwrap();
+
+  let result = load_config_file::<NullRequestFilterExecutor>(&path);
+  fs::remove_file(&path).unwrap_or(());
+  let config = result.unwrap();
+
+  expect!(config.options.publish).to(be_true());
+  expect!(config.options.provider_version).to(be_some().value("1.0.0"));
+  expect!(config.options.provider_branch).to(be_some().value("main"));
+  expect!(config.options.provider_tags).to(be_equal_to(vec!["prod".to_string()]));
+  expect!(config.options.request_timeout).to(be_equal_to(1000));
+  expect!(config.options.state_change_timeout).to(be_equal_to(5000));
+
+  expect!(config.sources.len()).to(be_equal_to(2));
+  expect!(matches!(&config.sources[0], PactSource::File(file) if file == "pacts/consumer-provider.json")).to(be_true());
+  match &config.sources[1] {
+    PactSource::BrokerWithDynamicConfiguration { provider_name, broker_url, enable_pending,
+      include_wip_pacts_since, provider_tags, provider_branch, selectors, auth, .. } => {
+      expect!(provider_name.as_str()).to(be_equal_to("Pact Provider"));
+      expect!(broker_url.as_str()).to(be_equal_to("https://broker.example.com"));
+      expect!(*enable_pending).to(be_true());
+      expect!(include_wip_pacts_since.clone()).to(be_some().value("2021-01-01"));
+      expect!(provider_tags.clone()).to(be_equal_to(vec!["prod".to_string()]));
+      expect!(provider_branch.clone()).to(be_some().value("main"));
+      expect!(selectors.iter().map(|s| (s.main_branch, s.deployed_or_released)).collect::<Vec<_>>()).to(be_equal_to(vec![
+        (Some(true), None),
+        (None, Some(true))
+      ]));
+      expect!(matches!(auth, Some(HttpAuth::Token(token)) if token == "abc123")).to(be_true());
+    }
+    source => panic!("Expected a broker source, got {}", source)
+  }
+}
+
+#[test]
+fn load_config_file_returns_an_error_for_invalid_values() {
+  let path = env::temp_dir().join(format!("pact_verifier_invalid_config_{}.json", process::id()));
+  fs::write(&path, r#"{ "broker": { "url": "https://broker.example.com" } }"#).unwrap();
+
+  let result = load_config_file::<NullRequestFilterExecutor>(&path);
+  fs::remove_file(&path).unwrap_or(());
+
+  expect!(result.unwrap_err().to_string()).to(
+    be_equal_to("The 'provider' name is required to fetch pacts from the Pact Broker"));
+}
+
+#[tokio::test]
+async fn verify_pact_with_changed_since_filter_only_verifies_the_changed_interactions() {
+  let interaction = |description: &str, body: &str| RequestResponseInteraction {
+    description: description.to_string(),
+    request: Request { path: "/items".to_string(), .. Request::default() },
+    response: Response {
+      body: OptionalBody::Present(body.as_bytes().to_vec().into(), Some(JSON.clone()), None),
+      .. Response::default()
+    },
+    .. RequestResponseInteraction::default()
+  };
+  let baseline = RequestResponsePact {
+    interactions: vec![
+      interaction("an unchanged request", r#"{"id": 1}"#),
+      interaction("a changed request", r#"{"id": 2}"#)
+    ],
+    .. RequestResponsePact::default()
+  };
+  let path = env::temp_dir().join(format!("pact_verifier_baseline_{}.json", process::id()));
+  fs::write(&path, baseline.to_json(PactSpecification::V3).unwrap().to_string()).unwrap();
+
+  let pact: Box<dyn Pact + Send + Sync> = Box::new(RequestResponsePact {
+    interactions: vec![
+      interaction("an unchanged request", r#"{"id": 1}"#),
+      interaction("a changed request", r#"{"id": 3}"#),
+      interaction("a new request", r#"{"id": 4}"#)
+    ],
+    .. RequestResponsePact::default()
+  });
+  let provider = ProviderInfo { port: Some(1), host: "127.0.0.1".to_string(), .. ProviderInfo::default() };
+  let options: VerificationOptions<NullRequestFilterExecutor> = VerificationOptions {
+    dry_run: true,
+    .. VerificationOptions::default()
+  };
+
+  let result = verify_pact_internal(&provider, &FilterInfo::ChangedSince(path.clone()), pact, &options,
+    &Arc::new(HttpRequestProviderStateExecutor::default()), false).await;
+  fs::remove_file(&path).unwrap_or(());
+
+  expect!(result.unwrap().results.iter().map(|result| result.description.clone()).collect::<Vec<_>>()).to(be_equal_to(vec![
+    "Verifying a pact between default_consumer and default_provider - a changed request".to_string(),
+    "Verifying a pact between default_consumer and default_provider - a new request".to_string()
+  ]));
+}
+
+#[tokio::test]
+async fn verify_pact_with_changed_since_filter_compares_the_interactions_structurally() {
+  let interaction = |description: &str, body: &str| RequestResponseInteraction {
+    description: description.to_string(),
+    request: Request { path: "/items".to_string(), .. Request::default() },
+    response: Response {
+      body: OptionalBody::Present(body.as_bytes().to_vec().into(), Some(JSON.clone()), None),
+      matching_rules: matchingrules! { "body" => { "$.id" => [ MatchingRule::Type ] } },
+      .. Response::default()
+    },
+    .. RequestResponseInteraction::default()
+  };
+  let baseline = RequestResponsePact {
+    interactions: vec![
+      interaction("an unchanged request", r#"{"id": 1}"#),
+      interaction("a request with a changed example", r#"{"id": 2}"#),
+      interaction("a request with an added field", r#"{"id": 3}"#)
+    ],
+    .. RequestResponsePact::default()
+  };
+  let path = env::temp_dir().join(format!("pact_verifier_structural_baseline_{}.json", process::id()));
+  fs::write(&path, baseline.to_json(PactSpecification::V3).unwrap().to_string()).unwrap();
+
+  let pact: Box<dyn Pact + Send + Sync> = Box::new(RequestResponsePact {
+    interactions: vec![
+      interaction("an unchanged request", r#"{"id": 1}"#),
+      interaction("a request with a changed example", r#"{"id": 200}"#),
+      interaction("a request with an added field", r#"{"id": 3, "name": "test"}"#)
+    ],
+    .. RequestResponsePact::default()
+  });
+  let provider = ProviderInfo { port: Some(1), host: "127.0.0.1".to_string(), .. ProviderInfo::default() };
+  let options: VerificationOptions<NullRequestFilterExecutor> = VerificationOptions {
+    dry_run: true,
+    .. VerificationOptions::default()
+  };
+
+  let result = verify_pact_internal(&provider, &FilterInfo::ChangedSince(path.clone()), pact, &options,
+    &Arc::new(HttpRequestProviderStateExecutor::default()), false).await;
+  fs::remove_file(&path).unwrap_or(());
+
+  expect!(result.unwrap().results.iter().map(|result| result.description.clone()).collect::<Vec<_>>()).to(be_equal_to(vec![
+    "Verifying a pact between default_consumer and default_provider - a request with a changed example".to_string(),
+    "Verifying a pact between default_consumer and default_provider - a request with an added field".to_string()
+  ]));
+}
+
+#[tokio::test]
+async fn verify_pact_with_changed_since_filter_returns_an_error_if_the_baseline_can_not_be_loaded() {
+  let provider = ProviderInfo { port: Some(1), host: "127.0.0.1".to_string(), .. ProviderInfo::default() };
+  let pact: Box<dyn Pact + Send + Sync> = Box::new(RequestResponsePact::default());
+  let path = env::temp_dir().join(format!("pact_verifier_missing_baseline_{}.json", process::id()));
+  let options: VerificationOptions<NullRequestFilterExecutor> = VerificationOptions::default();
+
+  let result = verify_pact_internal(&provider, &FilterInfo::ChangedSince(path), pact, &options,
+    &Arc::new(HttpRequestProviderStateExecutor::default()), false).await;
+  let error = result.err().map(|err| err.to_string()).unwrap_or_default();
+  expect!(error.starts_with("Failed to load the baseline pact")).to(be_true());
+}
+
+fn interaction_result(description: &str, id: Option<&str>, failed: bool) -> VerificationInteractionResult {
+  VerificationInteractionResult {
+    interaction_id: id.map(|id| id.to_string()),
+    description: description.to_string(),
+    result: if failed { Err(MismatchResult::Error("failed".to_string(), None)) } else { Ok(()) },
+    pending: false,
+    skipped: false
+  }
+}
+
+#[test]
+fn diff_verification_results_returns_the_regressed_fixed_and_still_failing_interactions() {
+  let previous = vec![
+    interaction_result("a request for users", None, false),
+    interaction_result("a request for orders", None, true),
+    interaction_result("a request for items", Some("item-id"), true),
+    interaction_result("a request for stock", None, false)
+  ];
+  let current = vec![
+    interaction_result("a request for users", None, true),
+    interaction_result("a request for orders", None, false),
+    interaction_result("a renamed request for items", Some("item-id"), true),
+    interaction_result("a request for stock", None, false),
+    interaction_result("a new request", None, true)
+  ];
+
+  let diff = diff_verification_results(&previous, &current);
+  expect!(diff).to(be_equal_to(VerificationResultsDiff {
+    regressed: btreeset!{ "a request for users".to_string() },
+    fixed: btreeset!{ "a request for orders".to_string() },
+    still_failing: btreeset!{ "item-id".to_string() }
+  }));
+}
+
+#[test]
+fn diff_verification_results_ignores_skipped_interactions() {
+  let previous = vec![ interaction_result("a request for users", None, false) ];
+  let current = vec![ VerificationInteractionResult {
+    skipped: true,
+    .. interaction_result("a request for users", None, true)
+  } ];
+  expect!(diff_verification_results(&previous, &current)).to(be_equal_to(VerificationResultsDiff::default()));
 }
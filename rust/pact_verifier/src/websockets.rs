@@ -0,0 +1,100 @@
+//! Support for verifying the handshake of WebSocket upgrade interactions. Only the HTTP upgrade
+//! request and the handshake response is verified, the messages sent over the WebSocket are not.
+
+use std::collections::HashMap;
+
+use log::debug;
+use rand::RngCore;
+use serde_json::Value;
+
+use pact_matching::match_response;
+use pact_models::bodies::OptionalBody;
+use pact_models::generators::GeneratorTestMode;
+use pact_models::http_parts::HttpPart;
+use pact_models::interaction::Interaction;
+use pact_models::prelude::Pact;
+use pact_models::v4::synch_http::SynchronousHttp;
+
+use crate::{MismatchResult, ProviderInfo, VerificationOptions};
+use crate::callback_executors::RequestFilterExecutor;
+use crate::provider_client::make_provider_request;
+
+/// Header sent by the provider that is derived from the (random) key sent with the request, so can
+/// not be matched against the value in the pact
+const SEC_WEBSOCKET_ACCEPT: &str = "sec-websocket-accept";
+
+/// If the interaction is a WebSocket upgrade request (the request has an `Upgrade: websocket` header)
+pub(crate) fn is_websocket_upgrade(interaction: &SynchronousHttp) -> bool {
+  interaction.request.lookup_header_value("upgrade")
+    .map(|value| value.eq_ignore_ascii_case("websocket"))
+    .unwrap_or(false)
+}
+
+/// Performs the WebSocket upgrade handshake against the provider, and verifies the handshake
+/// response. The response must have a 101 status and `Upgrade` and `Connection` headers, along
+/// with any other headers that are in the expected response.
+pub(crate) async fn verify_websocket_handshake<F: RequestFilterExecutor>(
+  provider: &ProviderInfo,
+  interaction: &SynchronousHttp,
+  pact: &(dyn Pact + Send + Sync),
+  options: &VerificationOptions<F>,
+  client: &reqwest::Client,
+  verification_context: &HashMap<&str, Value>
+) -> Result<Option<String>, MismatchResult> {
+  let mut request = pact_matching::generate_request(&interaction.request, &GeneratorTestMode::Provider,
+    verification_context).await;
+  add_handshake_headers(request.headers_mut());
+
+  let expected_response = expected_handshake_response(interaction);
+  match make_provider_request(provider, &request, &interaction.description, &interaction.provider_states,
+    options, client).await {
+    Ok(mut actual_response) => {
+      debug!("WebSocket handshake response: {}", actual_response);
+      actual_response.body = OptionalBody::Missing;
+      let mismatches = match_response(expected_response.response.clone(), actual_response.clone(),
+        &pact.boxed(), &expected_response.boxed()).await;
+      if mismatches.is_empty() {
+        Ok(interaction.id.clone())
+      } else {
+        Err(MismatchResult::Mismatches {
+          mismatches,
+          expected: interaction.boxed(),
+          actual: Box::new(SynchronousHttp { response: actual_response, .. SynchronousHttp::default() }),
+          interaction_id: interaction.id.clone()
+        })
+      }
+    }
+    Err(err) => Err(MismatchResult::Error(err.to_string(), interaction.id.clone()))
+  }
+}
+
+/// Adds the headers required for the upgrade handshake that are not already on the request. A new
+/// random key is always used.
+fn add_handshake_headers(headers: &mut HashMap<String, Vec<String>>) {
+  let mut key = [0_u8; 16];
+  rand::thread_rng().fill_bytes(&mut key);
+  headers.retain(|name, _| !name.eq_ignore_ascii_case("sec-websocket-key"));
+  headers.insert("Sec-WebSocket-Key".to_string(), vec![base64::encode(key)]);
+
+  for (name, value) in [("Connection", "Upgrade"), ("Upgrade", "websocket"), ("Sec-WebSocket-Version", "13")] {
+    if !headers.keys().any(|key| key.eq_ignore_ascii_case(name)) {
+      headers.insert(name.to_string(), vec![value.to_string()]);
+    }
+  }
+}
+
+/// The expected handshake response is the response from the pact, but with a 101 status and the
+/// upgrade headers. The accept header can't be matched, and the body is ignored.
+fn expected_handshake_response(interaction: &SynchronousHttp) -> SynchronousHttp {
+  let mut expected = interaction.clone();
+  expected.response.status = 101;
+  expected.response.body = OptionalBody::Missing;
+  let headers = expected.response.headers_mut();
+  headers.retain(|name, _| !name.eq_ignore_ascii_case(SEC_WEBSOCKET_ACCEPT));
+  for (name, value) in [("Connection", "Upgrade"), ("Upgrade", "websocket")] {
+    if !headers.keys().any(|key| key.eq_ignore_ascii_case(name)) {
+      headers.insert(name.to_string(), vec![value.to_string()]);
+    }
+  }
+  expected
+}
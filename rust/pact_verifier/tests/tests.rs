@@ -1,7 +1,7 @@
 use std::collections::HashMap;
 use std::env;
 use std::path::PathBuf;
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
 
 use async_trait::async_trait;
 use env_logger::*;
@@ -14,7 +14,14 @@ use pact_consumer::*;
 use pact_consumer::prelude::*;
 use pact_models::pact::read_pact;
 use pact_models::provider_states::ProviderState;
-use pact_verifier::{FilterInfo, NullRequestFilterExecutor, ProviderInfo, VerificationOptions, verify_pact_internal};
+use pact_verifier::{
+  FilterInfo,
+  NullRequestFilterExecutor,
+  ProviderInfo,
+  VerificationInteractionResult,
+  VerificationOptions,
+  verify_pact_internal
+};
 use pact_verifier::callback_executors::ProviderStateExecutor;
 
 /// Get the path to one of our sample *.json files.
@@ -206,3 +213,64 @@ async fn verifying_a_pact_with_min_type_matcher_and_child_arrays() {
 
   expect!(result.unwrap().results.get(0).unwrap().result.as_ref()).to(be_ok());
 }
+
+#[tokio::test]
+async fn verifying_a_pact_invokes_the_progress_callback_for_each_interaction() {
+  try_init().unwrap_or(());
+
+  let server = PactBuilder::new("consumer", "ProgressProvider")
+    .interaction("a request that will pass", "", |mut i| async move {
+      i.test_name("verifying_a_pact_invokes_the_progress_callback_for_each_interaction");
+      i.request.method("GET");
+      i.request.path("/pass");
+      i.response.ok();
+      i
+    })
+    .await
+    .start_mock_server();
+
+  let pact = PactBuilder::new("consumer", "ProgressProvider")
+    .interaction("a request that will pass", "", |mut i| async move {
+      i.request.method("GET");
+      i.request.path("/pass");
+      i.response.ok();
+      i
+    })
+    .await
+    .interaction("a request that will fail", "", |mut i| async move {
+      i.request.method("GET");
+      i.request.path("/pass");
+      i.response.created();
+      i
+    })
+    .await
+    .build();
+
+  let provider = ProviderInfo {
+    name: "ProgressProvider".to_string(),
+    host: "127.0.0.1".to_string(),
+    port: server.url().port(),
+    .. ProviderInfo::default()
+  };
+
+  let progress: Arc<Mutex<Vec<(String, bool)>>> = Arc::new(Mutex::new(vec![]));
+  let callback_progress = progress.clone();
+  let options: VerificationOptions<NullRequestFilterExecutor> = VerificationOptions {
+    progress_callback: Some(Arc::new(move |result: VerificationInteractionResult| {
+      callback_progress.lock().unwrap().push((result.description, result.result.is_ok()))
+    })),
+    .. VerificationOptions::default()
+  };
+  let provider_states = Arc::new(DummyProviderStateExecutor{});
+
+  let result = verify_pact_internal(&provider, &FilterInfo::None,
+                                    pact, &options, &provider_states, false).await;
+
+  expect!(result.unwrap().results.len()).to(be_equal_to(2));
+  let progress = progress.lock().unwrap();
+  expect!(progress.len()).to(be_equal_to(2));
+  expect!(progress[0].0.ends_with("a request that will pass")).to(be_true());
+  expect!(progress[0].1).to(be_true());
+  expect!(progress[1].0.ends_with("a request that will fail")).to(be_true());
+  expect!(progress[1].1).to(be_false());
+}
@@ -304,9 +304,12 @@ async fn handle_matches(matches: &clap::ArgMatches<'_>) -> Result<(), i32> {
   let provider_state_executor = Arc::new(HttpRequestProviderStateExecutor {
     state_change_url: matches.value_of("state-change-url").map(|s| s.to_string()),
     state_change_body: !matches.is_present("state-change-as-query"),
-    state_change_teardown: matches.is_present("state-change-teardown")
+    state_change_teardown: matches.is_present("state-change-teardown"),
+    .. HttpRequestProviderStateExecutor::default()
   });
 
+  let request_timeout = matches.value_of("request-timeout")
+    .map(|t| t.parse::<u64>().unwrap_or(5000)).unwrap_or(5000);
   let options = VerificationOptions {
     publish: matches.is_present("publish"),
     provider_version: matches.value_of("provider-version").map(|v| v.to_string()),
@@ -315,9 +318,10 @@ async fn handle_matches(matches: &clap::ArgMatches<'_>) -> Result<(), i32> {
     provider_tags: matches.values_of("provider-tags")
       .map_or_else(Vec::new, |tags| tags.map(|tag| tag.to_string()).collect()),
     disable_ssl_verification: matches.is_present("disable-ssl-verification"),
-    request_timeout: matches.value_of("request-timeout")
-      .map(|t| t.parse::<u64>().unwrap_or(5000)).unwrap_or(5000),
-    provider_branch: matches.value_of("provider-branch").map(|v| v.to_string())
+    request_timeout,
+    state_change_timeout: request_timeout,
+    provider_branch: matches.value_of("provider-branch").map(|v| v.to_string()),
+    .. VerificationOptions::default()
   };
 
   for s in &source {